@@ -1,6 +1,9 @@
+use crate::args::SettingsArgs;
 use anyhow::Result;
 use std::io::Write;
-use whis_core::{ApiConfig, Settings};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Mutex, OnceLock};
+use whis_core::{ApiConfig, Settings, VoiceCommand, spill, temp, voice_command};
 
 pub fn ensure_ffmpeg_installed() -> Result<()> {
     if std::process::Command::new("ffmpeg")
@@ -20,31 +23,167 @@ pub fn ensure_ffmpeg_installed() -> Result<()> {
     Ok(())
 }
 
-pub fn load_api_config() -> Result<ApiConfig> {
-    // Priority: settings file > environment variable
-    let settings = Settings::load();
-    if let Some(key) = settings.openai_api_key {
-        return Ok(ApiConfig {
-            openai_api_key: key,
-        });
-    }
-
-    // Fallback to environment
-    match ApiConfig::from_env() {
+/// API configuration for the selected backend (settings file > environment)
+pub fn load_api_config(settings: &Settings) -> Result<ApiConfig> {
+    match ApiConfig::from_settings(settings) {
         Ok(cfg) => Ok(cfg),
-        Err(_) => {
-            eprintln!("Error: No API key configured.");
+        Err(e) => {
+            let missing_key = settings.backend.env_var().filter(|var| {
+                settings.api_key(settings.backend).is_none() && std::env::var(var).is_err()
+            });
+            let Some(var) = missing_key else {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            };
+
+            eprintln!("Error: No API key configured for {}.", settings.backend);
             eprintln!("\nSet your key with:");
             eprintln!("  whis config --api-key YOUR_KEY\n");
-            eprintln!("Or set the OPENAI_API_KEY environment variable.");
+            eprintln!("Or set the {var} environment variable.");
             std::process::exit(1);
         }
     }
 }
 
-pub fn wait_for_enter() -> Result<()> {
-    let mut input = String::new();
-    std::io::stdout().flush()?;
-    std::io::stdin().read_line(&mut input)?;
-    Ok(())
+/// Load settings, applying the named profile and command-line overrides
+pub fn load_settings(profile: Option<&str>, overrides: &SettingsArgs) -> Result<Settings> {
+    let mut settings = Settings::load();
+    if let Some(name) = profile {
+        settings = settings.with_profile(name)?;
+    }
+
+    overrides.apply(&mut settings);
+    settings.chunking.validate()?;
+
+    Ok(settings)
+}
+
+/// Point the user at `whis recover` if a previous run left unfinished recordings
+pub fn notify_orphaned_recordings() {
+    let count = spill::orphaned().len();
+    if count > 0 {
+        eprintln!(
+            "Found {count} unfinished recording(s) from a previous run. \
+            Run 'whis recover' to transcribe them."
+        );
+    }
+}
+
+/// Format seconds as `m:ss` (or `h:mm:ss` for long recordings)
+pub fn format_elapsed(secs: f32) -> String {
+    let secs = secs as u64;
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if h > 0 {
+        format!("{h}:{m:02}:{s:02}")
+    } else {
+        format!("{m}:{s:02}")
+    }
+}
+
+/// Lines typed on stdin, read by a single background thread
+///
+/// Waiting for Enter and later prompts share the reader, so a prompt doesn't
+/// lose its first line to a reader left over from the recording.
+fn stdin_lines() -> &'static Mutex<Receiver<String>> {
+    static LINES: OnceLock<Mutex<Receiver<String>>> = OnceLock::new();
+    LINES.get_or_init(|| {
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in std::io::stdin().lines() {
+                let Ok(line) = line else { break };
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Mutex::new(rx)
+    })
+}
+
+/// Whether Enter was pressed since the last call (non-blocking).
+/// A closed stdin (e.g. in scripts) never counts as Enter.
+pub fn enter_pressed() -> bool {
+    stdin_lines().lock().unwrap().try_recv().is_ok()
+}
+
+/// Wait for a line on stdin; `None` once stdin is closed
+pub fn read_line() -> Option<String> {
+    stdin_lines().lock().unwrap().recv().ok()
+}
+
+/// What to do with a transcript after reviewing it
+pub enum Review {
+    /// Output this (possibly edited) text
+    Accept(String),
+    /// Record again
+    Retry,
+    Discard,
+}
+
+/// Show the transcript and ask whether to accept, edit, retry or discard it
+pub fn review(text: &str) -> Result<Review> {
+    // Drop Enter presses left over from stopping the recording
+    while enter_pressed() {}
+
+    println!("\n{text}\n");
+    loop {
+        print!("[A]ccept, [e]dit, [r]etry or [d]iscard? ");
+        std::io::stdout().flush()?;
+        let Some(input) = read_line() else {
+            return Ok(Review::Discard);
+        };
+        match input.trim().to_lowercase().as_str() {
+            "" | "a" | "accept" => return Ok(Review::Accept(text.to_string())),
+            "e" | "edit" => return edit_text(text).map(Review::Accept),
+            "r" | "retry" => return Ok(Review::Retry),
+            "d" | "discard" => return Ok(Review::Discard),
+            _ => {}
+        }
+    }
+}
+
+/// Let the user edit text in $VISUAL or $EDITOR (vi if neither is set)
+fn edit_text(text: &str) -> Result<String> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let mut file = temp::file("txt")?;
+    file.write_all(text.as_bytes())?;
+    let path = file.path();
+
+    // The editor may come with arguments, e.g. "code --wait"
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+    let status = std::process::Command::new(program)
+        .args(parts)
+        .arg(path)
+        .status();
+    let edited = std::fs::read_to_string(path);
+    drop(file);
+
+    match status {
+        Ok(status) if status.success() => {}
+        Ok(status) => anyhow::bail!("{editor} exited with {status}"),
+        Err(e) => anyhow::bail!("Failed to run {editor}: {e}"),
+    }
+    // Editors usually add a final newline
+    Ok(edited?.trim_end().to_string())
+}
+
+/// Match a transcript against the configured voice commands and run it,
+/// asking on the terminal for commands marked `confirm`. A command that
+/// fails is reported, so the transcript still reaches the history.
+pub fn run_voice_command(text: &str, commands: &[VoiceCommand]) {
+    match voice_command::run_matching(text, commands, confirm) {
+        Ok(outcome) => println!("{outcome}"),
+        Err(e) => eprintln!("Voice command error: {e:#}"),
+    }
+}
+
+/// Ask on the terminal whether to run a voice command's command line
+pub fn confirm(command_line: &str) -> bool {
+    print!("Run `{command_line}`? [y/N] ");
+    let _ = std::io::stdout().flush();
+    let input = read_line().unwrap_or_default();
+    input.trim().eq_ignore_ascii_case("y")
 }
@@ -1,13 +1,364 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+use std::time::Duration;
+use whis_core::{AudioCodec, Backend, ChunkConfig, ResponseFormat, Settings};
 
 #[derive(Parser)]
 #[command(name = "whis")]
 #[command(version)]
 #[command(about = "Voice-to-text CLI using OpenAI Whisper API")]
-#[command(after_help = "Run 'whis' without arguments to record once (press Enter to stop).")]
+#[command(
+    after_help = "Run 'whis' without arguments to record once (press Enter to stop, or use --duration / --until-silence)."
+)]
 pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Commands>,
+
+    /// Apply a named profile from the settings file
+    #[arg(short = 'p', long, global = true)]
+    pub profile: Option<String>,
+
+    #[command(flatten)]
+    pub overrides: SettingsArgs,
+
+    #[command(flatten)]
+    pub record: RecordArgs,
+}
+
+/// Options for one-shot recording (`whis` without a subcommand)
+#[derive(Args, Clone, Default)]
+pub struct RecordArgs {
+    /// Stop recording automatically after this long (e.g. "30s", "2m")
+    #[arg(long, value_parser = parse_duration)]
+    pub duration: Option<Duration>,
+
+    /// Stop recording after this much silence following speech (e.g. "2s")
+    #[arg(long, value_parser = parse_duration)]
+    pub until_silence: Option<Duration>,
+
+    /// Record and encode, print audio stats, but don't call the API
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Show the transcript before it is output: accept, edit in $EDITOR, retry or discard
+    #[arg(long, conflicts_with = "dry_run")]
+    pub review: bool,
+
+    /// Save the encoded audio in dry-run mode (chunks get a "-N" suffix)
+    #[arg(long, value_name = "PATH", requires = "dry_run")]
+    pub save_audio: Option<PathBuf>,
+
+    /// Tag the transcript in the history (repeatable, e.g. "--tag meeting")
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+
+    /// Title of the transcript in the history
+    #[arg(long)]
+    pub title: Option<String>,
+}
+
+/// Command-line overrides for the settings file
+#[derive(Args, Clone, Default)]
+pub struct SettingsArgs {
+    /// Transcription backend (openai, assemblyai, azure, vosk, whisper-server)
+    #[arg(long, global = true)]
+    pub backend: Option<Backend>,
+
+    /// Model directory for local backends (vosk)
+    #[arg(long, global = true, value_name = "DIR")]
+    pub model_path: Option<PathBuf>,
+
+    /// Model of the backend (e.g. gpt-4o-transcribe)
+    #[arg(long, global = true, value_name = "NAME")]
+    pub model: Option<String>,
+
+    /// Spoken language, e.g. "de" or "de-DE" (detected by default)
+    #[arg(long, global = true, value_name = "CODE")]
+    pub language: Option<String>,
+
+    /// Start a recording with "switch to German" to transcribe it in German
+    #[arg(long, global = true)]
+    pub language_hotword: bool,
+
+    /// Keep transcripts and audio off disk (no history, no spill files)
+    #[arg(long, global = true)]
+    pub privacy: bool,
+
+    /// Mask email addresses, phone numbers and card numbers in the transcript
+    #[arg(long, global = true)]
+    pub redact: bool,
+
+    /// Names and jargon that guide spelling (e.g. "Kubernetes, Tauri")
+    #[arg(long, global = true, value_name = "TEXT")]
+    pub prompt: Option<String>,
+
+    /// Sampling temperature from 0 to 1; 0 is the most deterministic
+    #[arg(long, global = true, value_name = "0-1")]
+    pub temperature: Option<f32>,
+
+    /// Response format of Whisper APIs (json, verbose_json)
+    #[arg(long, global = true, value_name = "FORMAT")]
+    pub response_format: Option<ResponseFormat>,
+
+    /// Beam search width (whisper-server backend)
+    #[arg(long, global = true, value_name = "N")]
+    pub beam_size: Option<u32>,
+
+    /// Candidates to sample at non-zero temperature (whisper-server backend)
+    #[arg(long, global = true, value_name = "N")]
+    pub best_of: Option<u32>,
+
+    /// Wrap words recognized with less confidence than this (0-1) in [brackets]
+    #[arg(long, global = true, value_name = "0-1", value_parser = parse_confidence)]
+    pub min_confidence: Option<f32>,
+
+    /// Wrap segments the backend flags as unreliable in [brackets] (Whisper backends)
+    #[arg(long, global = true)]
+    pub mark_unreliable: bool,
+
+    /// Start each segment on a new line with its time in the recording ("[01:23]")
+    #[arg(long, global = true)]
+    pub timestamps: bool,
+
+    /// Keep segments Whisper backends think are silence (dropped by default)
+    #[arg(long, global = true)]
+    pub keep_silent_segments: bool,
+
+    /// Keep the text of chunks that succeeded when others fail
+    #[arg(long, global = true)]
+    pub partial: bool,
+
+    /// Output bullet-point minutes from a chat model instead of the verbatim text
+    #[arg(long, global = true)]
+    pub summarize: bool,
+
+    /// URL of a self-hosted Whisper server (whisper-server backend)
+    #[arg(long, global = true)]
+    pub url: Option<String>,
+
+    /// Connection preset; "slow" uploads small chunks with a longer timeout
+    #[arg(long, global = true, value_enum)]
+    pub network: Option<Network>,
+
+    /// Timeout for each API request in seconds
+    #[arg(long, global = true, value_name = "SECS", value_parser = clap::value_parser!(u64).range(1..))]
+    pub timeout: Option<u64>,
+
+    /// Limit uploads to this many KB/s
+    #[arg(long, global = true, value_name = "KBPS", value_parser = clap::value_parser!(u32).range(1..))]
+    pub upload_limit: Option<u32>,
+
+    /// Upload codec (mp3, opus, wav); backends that can't decode Opus get MP3
+    #[arg(long, global = true)]
+    pub codec: Option<AudioCodec>,
+
+    /// Encoder bitrate, e.g. "64k"
+    #[arg(long, global = true, value_name = "RATE", value_parser = parse_bitrate)]
+    pub bitrate: Option<String>,
+
+    /// Downmix to mono before encoding
+    #[arg(long, global = true)]
+    pub mono: bool,
+
+    #[command(flatten)]
+    pub chunking: ChunkArgs,
+}
+
+/// Connection presets for `--network`
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Network {
+    /// Use the configured chunking and timeout
+    Normal,
+    /// Tethered or hotel connections: 1 MB / 60s chunks and a 10 minute timeout
+    Slow,
+}
+
+/// Request timeout used by `--network slow`
+const SLOW_NETWORK_TIMEOUT_SECS: u64 = 600;
+
+impl SettingsArgs {
+    /// Apply the overrides on top of the loaded settings
+    pub fn apply(&self, settings: &mut Settings) {
+        if let Some(backend) = self.backend {
+            settings.backend = backend;
+        }
+        if let Some(path) = &self.model_path {
+            settings.model_path = Some(path.clone());
+        }
+        if let Some(model) = &self.model {
+            settings.model = Some(model.clone());
+        }
+        if let Some(language) = &self.language {
+            settings.language = Some(language.clone());
+        }
+        if self.language_hotword {
+            settings.language_hotword = true;
+        }
+        if self.privacy {
+            settings.privacy = true;
+        }
+        if self.redact {
+            settings.redact = true;
+        }
+        if let Some(prompt) = &self.prompt {
+            settings.prompt = Some(prompt.clone());
+        }
+        if let Some(temperature) = self.temperature {
+            settings.temperature = Some(temperature);
+        }
+        if let Some(format) = self.response_format {
+            settings.response_format = Some(format);
+        }
+        if let Some(beam_size) = self.beam_size {
+            settings.beam_size = Some(beam_size);
+        }
+        if let Some(best_of) = self.best_of {
+            settings.best_of = Some(best_of);
+        }
+        if let Some(url) = &self.url {
+            settings.server_url = Some(url.clone());
+        }
+        if let Some(confidence) = self.min_confidence {
+            settings.min_confidence = Some(confidence);
+        }
+        if self.mark_unreliable {
+            settings.mark_unreliable = true;
+        }
+        if self.timestamps {
+            settings.timestamps = true;
+        }
+        if self.keep_silent_segments {
+            settings.drop_silent_segments = false;
+        }
+        if self.partial {
+            settings.partial_results = true;
+        }
+        if self.summarize {
+            settings.summarize = true;
+        }
+        // The preset goes first so explicit flags can still adjust it
+        if let Some(Network::Slow) = self.network {
+            settings.chunking = ChunkConfig::slow_network();
+            settings.request_timeout_secs = SLOW_NETWORK_TIMEOUT_SECS;
+        }
+        if let Some(secs) = self.timeout {
+            settings.request_timeout_secs = secs;
+        }
+        if let Some(kbps) = self.upload_limit {
+            settings.upload_limit_kbps = Some(kbps);
+        }
+        if let Some(codec) = self.codec {
+            settings.audio_codec = codec;
+        }
+        if let Some(bitrate) = &self.bitrate {
+            settings.audio_bitrate = Some(bitrate.clone());
+        }
+        if self.mono {
+            settings.mono = true;
+        }
+        self.chunking.apply(&mut settings.chunking);
+    }
+}
+
+/// Overrides for how long recordings are split for upload
+#[derive(Args, Clone, Copy, Default)]
+pub struct ChunkArgs {
+    /// Split recordings larger than this many MB
+    #[arg(long, global = true, value_name = "MB")]
+    pub chunk_threshold: Option<u32>,
+
+    /// Length of each chunk in seconds
+    #[arg(long, global = true, value_name = "SECS")]
+    pub chunk_duration: Option<u32>,
+
+    /// Overlap between chunks in seconds
+    #[arg(long, global = true, value_name = "SECS")]
+    pub chunk_overlap: Option<u32>,
+
+    /// Never split recordings (for backends without an upload limit)
+    #[arg(long, global = true)]
+    pub no_chunking: bool,
+
+    /// End chunks in pauses instead of cutting them every --chunk-duration seconds
+    #[arg(long, global = true)]
+    pub split_on_silence: bool,
+}
+
+impl ChunkArgs {
+    /// Apply the overrides on top of the configured chunking
+    pub fn apply(&self, chunking: &mut ChunkConfig) {
+        if let Some(mb) = self.chunk_threshold {
+            chunking.threshold_mb = mb;
+        }
+        if let Some(secs) = self.chunk_duration {
+            chunking.duration_secs = secs;
+        }
+        if let Some(secs) = self.chunk_overlap {
+            chunking.overlap_secs = secs;
+        }
+        if self.no_chunking {
+            chunking.enabled = false;
+        }
+        if self.split_on_silence {
+            chunking.split_on_silence = true;
+        }
+    }
+}
+
+fn parse_bitrate(s: &str) -> Result<String, String> {
+    whis_core::audio::parse_bitrate(s).map_err(|e| e.to_string())?;
+    Ok(s.to_string())
+}
+
+fn parse_confidence(s: &str) -> Result<f32, String> {
+    let value: f32 = s.parse().map_err(|_| format!("invalid confidence: {s}"))?;
+    if !(0.0..=1.0).contains(&value) {
+        return Err("confidence must be between 0 and 1".to_string());
+    }
+    Ok(value)
+}
+
+/// Parse a duration like "30s", "1.5m", "1h" or a bare number of seconds
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let (number, unit) = match s.find(|c: char| c.is_ascii_alphabetic()) {
+        Some(idx) => s.split_at(idx),
+        None => (s, "s"),
+    };
+    let value: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration: {s}"))?;
+    let secs = match unit {
+        "ms" => value / 1000.0,
+        "s" => value,
+        "m" => value * 60.0,
+        "h" => value * 3600.0,
+        _ => return Err(format!("unknown unit '{unit}' (use ms, s, m or h)")),
+    };
+    Duration::try_from_secs_f64(secs).map_err(|_| format!("invalid duration: {s}"))
+}
+
+/// Parse a position like "1:05:30", "12:30.5" or a duration ("90s", "2m")
+/// as seconds
+fn parse_timestamp(s: &str) -> Result<f32, String> {
+    if !s.contains(':') {
+        return parse_duration(s).map(|d| d.as_secs_f32());
+    }
+    let invalid = || format!("invalid timestamp: {s} (use hh:mm:ss or mm:ss)");
+    let parts: Vec<&str> = s.trim().split(':').collect();
+    if parts.len() > 3 {
+        return Err(invalid());
+    }
+    let (seconds, minutes_hours) = parts.split_last().ok_or_else(invalid)?;
+    let mut secs: f32 = seconds.parse().map_err(|_| invalid())?;
+    for (part, unit) in minutes_hours.iter().rev().zip([60.0, 3600.0]) {
+        let value: u32 = part.parse().map_err(|_| invalid())?;
+        secs += value as f32 * unit;
+    }
+    if !secs.is_finite() || secs < 0.0 {
+        return Err(invalid());
+    }
+    Ok(secs)
 }
 
 #[derive(Subcommand)]
@@ -22,12 +373,268 @@ pub enum Commands {
     /// Stop the background service
     Stop,
 
+    /// Start or stop recording in the background service (e.g. from a desktop shortcut)
+    Toggle {
+        /// Summarize this recording instead of transcribing it verbatim
+        #[arg(long)]
+        summarize: bool,
+
+        /// Append this recording to the last transcript instead of replacing it
+        #[arg(long, conflicts_with = "summarize")]
+        append: bool,
+
+        /// Record for this session: the transcript goes to `whis subscribe
+        /// --session` instead of the output mode
+        #[arg(long, conflicts_with_all = ["summarize", "append"])]
+        session: Option<String>,
+    },
+
+    /// Remove the transcript the service typed last (bind to a shortcut)
+    Undo,
+
     /// Check service status
-    Status,
+    Status {
+        /// Show audio usage and estimated cost for this month instead
+        #[arg(long)]
+        usage: bool,
+
+        /// Also print what the running recording has said so far (needs `live_window_secs`)
+        #[arg(long, conflicts_with = "usage")]
+        partial: bool,
+    },
+
+    /// Run a Model Context Protocol server on stdio
+    Mcp,
+
+    /// Speak line-delimited JSON on stdio for editor plugins
+    Rpc,
+
+    /// Expose the background service over HTTP (toggle, status, last transcript)
+    #[cfg(feature = "http")]
+    Serve {
+        /// Address to bind to
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+
+        /// Port to listen on
+        #[arg(long, default_value_t = 8787)]
+        port: u16,
+
+        /// Bearer token required on every request (overrides settings)
+        #[arg(long)]
+        token: Option<String>,
+    },
+
+    /// Dashboard for the background service: state, level, chunks, transcripts
+    #[cfg(feature = "tui")]
+    Tui,
+
+    /// Transcribe recordings left unfinished by a crash
+    Recover {
+        /// Only list the unfinished recordings
+        #[arg(long)]
+        list: bool,
+
+        /// Delete the unfinished recordings instead of transcribing them
+        #[arg(long, conflicts_with = "list")]
+        discard: bool,
+    },
+
+    /// Transcribe an audio or video file and print the text
+    File {
+        /// Audio or video file
+        path: PathBuf,
+
+        /// Start transcribing here (e.g. "00:05:00", "12:30" or "90s")
+        #[arg(long, value_name = "TIME", value_parser = parse_timestamp)]
+        start: Option<f32>,
+
+        /// Stop transcribing here
+        #[arg(long, value_name = "TIME", value_parser = parse_timestamp)]
+        end: Option<f32>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = FileFormat::Text)]
+        format: FileFormat,
+    },
+
+    /// Read a text or the last transcript aloud
+    Say {
+        /// Text to read
+        #[arg(required_unless_present = "last", conflicts_with = "last")]
+        text: Option<String>,
+
+        /// Read the last transcript in the history
+        #[arg(long)]
+        last: bool,
+    },
+
+    /// Read the text in the clipboard image, a screen selection or an image
+    /// file and output it like a transcript (uses the OpenAI API key)
+    Ocr {
+        /// PNG, JPEG, GIF or WebP file to read instead of the clipboard
+        #[arg(conflicts_with = "select")]
+        image: Option<PathBuf>,
+
+        /// Select a part of the screen with the mouse
+        #[arg(long)]
+        select: bool,
+    },
+
+    /// Record a meeting, transcribing it every few minutes into a Markdown
+    /// file (with --summarize, minutes are added when it ends)
+    Meeting {
+        /// Transcribe what was recorded this often (e.g. "2m")
+        #[arg(long, value_parser = parse_duration, default_value = "2m")]
+        chunk_every: Duration,
+
+        /// Markdown file to write, with strftime patterns and {title}
+        /// (e.g. "meetings/%Y-%m-%d_%H%M_{title}.md"; default: "meeting_file")
+        #[arg(short, long, value_name = "TEMPLATE")]
+        output: Option<String>,
+
+        /// Heading of the file and title in the history (asked for if the
+        /// file name uses {title})
+        #[arg(long)]
+        title: Option<String>,
+    },
+
+    /// Transcribe audio files, each to a .txt file next to it
+    Batch {
+        /// Audio files (the same files again resume an interrupted run)
+        #[arg(required_unless_present = "retry_failed")]
+        files: Vec<PathBuf>,
+
+        /// Transcribe the files that failed again (of the last batch if no files are given)
+        #[arg(long)]
+        retry_failed: bool,
+
+        /// Files to transcribe at once (requests are limited separately)
+        #[arg(short, long, default_value_t = 2, value_parser = clap::value_parser!(u16).range(1..))]
+        jobs: u16,
+    },
+
+    /// Check settings, backends and required tools
+    Doctor,
+
+    /// Record a few seconds, show the device and levels and play it back
+    TestMic {
+        /// Length of the test recording
+        #[arg(long, value_parser = parse_duration, default_value = "3s")]
+        duration: Duration,
+
+        /// Don't play the recording back
+        #[arg(long)]
+        no_playback: bool,
+    },
+
+    /// Transcribe one sample with several backends and compare the results
+    Bench {
+        /// Audio file to use instead of recording a sample
+        audio: Option<PathBuf>,
+
+        /// Backends to compare (default: the backend and its fallbacks)
+        #[arg(long, value_delimiter = ',')]
+        backends: Vec<Backend>,
+
+        /// Length of the recorded sample (press Enter to stop earlier)
+        #[arg(long, value_parser = parse_duration, default_value = "10s")]
+        duration: Duration,
+    },
+
+    /// Word error rate of a transcription against a reference transcript
+    Eval {
+        /// Recording to transcribe with the current settings
+        #[arg(long, value_name = "FILE")]
+        audio: PathBuf,
+
+        /// Text file with what was actually said
+        #[arg(long, value_name = "FILE")]
+        reference: PathBuf,
+    },
+
+    /// Recordings, words and average length per day
+    Stats {
+        /// Number of recent days to show
+        #[arg(long, default_value_t = 14)]
+        days: usize,
+
+        /// Print all stats as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show recent transcripts
+    History {
+        #[command(subcommand)]
+        command: Option<HistoryCommand>,
+
+        /// Number of recent transcripts (or search results) to show
+        #[arg(short = 'n', long, default_value_t = 10, global = true)]
+        limit: usize,
+
+        /// Only transcripts with this tag
+        #[arg(long, global = true)]
+        tag: Option<String>,
+
+        /// Only transcripts dictated into this application (part of its
+        /// name, e.g. "slack")
+        #[arg(long, global = true)]
+        app: Option<String>,
+    },
+
+    /// Export the transcript history to a single file
+    Export {
+        /// Only transcripts from this day on (YYYY-MM-DD)
+        #[arg(long, value_name = "DATE")]
+        since: Option<chrono::NaiveDate>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ExportFormat::Md)]
+        format: ExportFormat,
+
+        /// Only transcripts with this tag
+        #[arg(long)]
+        tag: Option<String>,
+
+        /// Only transcripts dictated into this application
+        #[arg(long)]
+        app: Option<String>,
+
+        /// File to write (stdout if not set)
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+
+    /// Follow the event log of the background service
+    Tail {
+        /// Number of past events to show first
+        #[arg(short = 'n', long, default_value_t = 10)]
+        lines: usize,
+
+        /// Print the past events and exit
+        #[arg(long)]
+        no_follow: bool,
+
+        /// Print raw JSON lines
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Print the transcripts of the background service as they finish
+    Subscribe {
+        /// Only transcripts of recordings started with `whis toggle --session`
+        #[arg(long)]
+        session: Option<String>,
+
+        /// Print JSON lines with the session of each transcript
+        #[arg(long)]
+        json: bool,
+    },
 
     /// Configure settings (API key, etc.)
     Config {
-        /// Set your OpenAI API key
+        /// Set the API key for the backend (see --backend; --url sets the server)
         #[arg(long)]
         api_key: Option<String>,
 
@@ -35,4 +642,65 @@ pub enum Commands {
         #[arg(long)]
         show: bool,
     },
+
+    /// Print the completion script for a shell
+    Completions { shell: clap_complete::Shell },
+
+    /// Print the man page
+    Man,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum FileFormat {
+    /// The post-processed transcript
+    Text,
+    /// JSON array of words with their times in the file and confidence
+    /// (needs a backend that reports word timings)
+    WordsJson,
+    /// Audacity label track, one label per segment (File > Import > Labels)
+    Audacity,
+    /// Reaper region CSV, one region per segment (Region/Marker Manager)
+    Reaper,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ExportFormat {
+    /// Markdown, one heading per day
+    Md,
+    /// JSON array of history entries
+    Json,
+    /// CSV with id, timestamp, duration, title, tags and text
+    Csv,
+}
+
+#[derive(Subcommand)]
+pub enum HistoryCommand {
+    /// Encrypt transcripts stored in plain text (see "encrypt_history")
+    Encrypt,
+
+    /// Find transcripts that mention a word or phrase
+    Search {
+        query: String,
+
+        /// Also match words with a few typos (e.g. "kubernets")
+        #[arg(long)]
+        fuzzy: bool,
+    },
+
+    /// Add tags to a transcript or change its title
+    Tag {
+        /// Number of the transcript, as shown by "whis history"
+        id: u64,
+
+        /// Tags to add
+        tags: Vec<String>,
+
+        /// Remove the given tags instead of adding them
+        #[arg(long)]
+        remove: bool,
+
+        /// New title ("" to clear it)
+        #[arg(long)]
+        title: Option<String>,
+    },
 }
@@ -0,0 +1,174 @@
+//! Transcribe a list of audio files, each to a .txt file next to it
+//!
+//! Files of any format FFmpeg reads are decoded, encoded and chunked like a
+//! recording (see `whis_core::decode`), so long files aren't held back by
+//! the upload limit of the backend. Without FFmpeg, common audio formats are
+//! decoded natively and uploaded as WAV.
+//!
+//! The status of every file is saved as it finishes (see
+//! `whis_core::batch`), so running the same batch again after an
+//! interruption skips the files already done, and `--retry-failed` sends
+//! only the ones that failed.
+//!
+//! Up to `--jobs` files are transcribed at once. Their requests share the
+//! process-wide request limit with the chunks of long files, so more jobs
+//! don't mean more load on the provider than it allows.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::task::JoinSet;
+use whis_core::batch::{self, FileStatus, Job};
+use whis_core::{
+    Provider, RecordingOutput, Settings, decode, parallel_transcribe, postprocess, transcribe,
+    transcribe_audio, usage,
+};
+
+use crate::app;
+use crate::args::SettingsArgs;
+
+pub fn run(
+    profile: Option<String>,
+    overrides: SettingsArgs,
+    files: Vec<PathBuf>,
+    retry_failed: bool,
+    jobs: usize,
+) -> Result<()> {
+    let mut job = if files.is_empty() {
+        batch::load()?.context("No batch to retry")?
+    } else {
+        let paths = files
+            .iter()
+            .map(|path| {
+                fs::canonicalize(path).with_context(|| format!("Cannot open {}", path.display()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Job::resume(batch::load()?, &paths)
+    };
+    if retry_failed {
+        job.retry_failed();
+    }
+
+    let pending = job.pending();
+    let done = job.count(|s| *s == FileStatus::Done);
+    if pending.is_empty() {
+        println!("Nothing to do: {done} of {} file(s) done", job.files.len());
+        return report(&job);
+    }
+    if done > 0 {
+        println!(
+            "Resuming: {done} of {} file(s) already done",
+            job.files.len()
+        );
+    }
+    batch::save(&job)?;
+
+    let settings = Arc::new(app::load_settings(profile.as_deref(), &overrides)?);
+    let config = app::load_api_config(&settings)?;
+    let provider = config.provider();
+    let runtime = tokio::runtime::Runtime::new()?;
+
+    runtime.block_on(async {
+        let total = pending.len();
+        let mut queue = pending.into_iter();
+        let mut running = JoinSet::new();
+        let mut finished = 0;
+        loop {
+            while running.len() < jobs
+                && let Some(index) = queue.next()
+            {
+                let path = job.files[index].path.clone();
+                let (provider, settings) = (provider.clone(), settings.clone());
+                running.spawn(async move {
+                    let result = transcribe_to_file(provider, settings, path).await;
+                    (index, result)
+                });
+            }
+            let Some(joined) = running.join_next().await else {
+                break;
+            };
+            let (index, result) = joined.context("Batch task panicked")?;
+            finished += 1;
+            let path = job.files[index].path.display().to_string();
+            job.files[index].status = match result {
+                Ok((model, duration_secs)) => {
+                    println!(
+                        "[{finished}/{total}] {path} ({})",
+                        usage::describe(&model, duration_secs)
+                    );
+                    match usage::track(&settings, &model, duration_secs) {
+                        Ok(Some(warning)) => eprintln!("{warning}"),
+                        Ok(None) => {}
+                        Err(e) => eprintln!("Usage error: {e:#}"),
+                    }
+                    FileStatus::Done
+                }
+                Err(e) => {
+                    eprintln!("[{finished}/{total}] {path} failed: {e:#}");
+                    FileStatus::Failed {
+                        error: format!("{e:#}"),
+                    }
+                }
+            };
+            batch::save(&job)?;
+        }
+        anyhow::Ok(())
+    })?;
+
+    report(&job)
+}
+
+/// Transcribe one file and write the text next to it. Returns the model
+/// billed and the duration of the file, for usage tracking.
+async fn transcribe_to_file(
+    provider: Arc<dyn Provider>,
+    settings: Arc<Settings>,
+    path: PathBuf,
+) -> Result<(String, f32)> {
+    let encoding = decode::upload_encoding(settings.encoding().negotiate(provider.as_ref()));
+    let codec = encoding.codec;
+    let (output, duration_secs) = tokio::task::spawn_blocking({
+        let (settings, path) = (settings.clone(), path.clone());
+        move || {
+            let decoded = decode::decode(&path, decode::Trim::default())?;
+            let output = decoded.encode(&settings.chunking, &encoding)?;
+            anyhow::Ok((output, decoded.duration_secs))
+        }
+    })
+    .await??;
+
+    let transcription = match output {
+        RecordingOutput::Single(data) => {
+            // Chunks wait for their own request slots, single files take one here
+            let _permit = transcribe::request_permit().await?;
+            let provider = provider.clone();
+            tokio::task::spawn_blocking(move || transcribe_audio(provider.as_ref(), data, codec))
+                .await??
+        }
+        RecordingOutput::Chunked(chunks) => {
+            parallel_transcribe(provider.clone(), chunks, None, None).await?
+        }
+    };
+    let model = transcription.usage_model(provider.as_ref()).to_string();
+    let text = postprocess::to_text(transcription, &settings);
+    let text = postprocess::process(&text, &settings);
+    let output = batch::output_path(&path);
+    fs::write(&output, text + "\n")
+        .with_context(|| format!("Failed to write {}", output.display()))?;
+    Ok((model, duration_secs))
+}
+
+/// Summarize the job, failing if some files could not be transcribed
+fn report(job: &Job) -> Result<()> {
+    let failed = job.count(|s| matches!(s, FileStatus::Failed { .. }));
+    println!(
+        "{} of {} file(s) transcribed",
+        job.count(|s| *s == FileStatus::Done),
+        job.files.len()
+    );
+    if failed > 0 {
+        anyhow::bail!("{failed} file(s) failed; run 'whis batch --retry-failed' to try them again");
+    }
+    Ok(())
+}
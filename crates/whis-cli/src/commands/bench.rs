@@ -0,0 +1,184 @@
+//! Compare backends on the same audio
+//!
+//! Records a short sample (or loads a file) and transcribes it with each
+//! backend in turn, reporting how long each took, what it would cost and how
+//! its text differs from the first backend's. Recordings and WAV files are
+//! encoded once as MP3, which every backend accepts, so all of them get the
+//! same upload.
+
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use whis_core::audio::{self, AudioEncoding};
+use whis_core::{
+    ApiConfig, AudioCodec, Backend, Bytes, ChunkConfig, RecordingOutput, Settings, diff,
+    transcribe_audio, transcribe_file, usage,
+};
+
+use crate::app;
+use crate::args::{RecordArgs, SettingsArgs};
+use crate::commands::record_once;
+
+/// The audio every backend transcribes
+enum Sample {
+    /// MP3 encoded from a recording or a WAV file
+    Encoded { data: Bytes, duration_secs: f32 },
+    /// Compressed file, uploaded as it is; its duration isn't known
+    File(PathBuf),
+}
+
+pub fn run(
+    profile: Option<String>,
+    overrides: SettingsArgs,
+    audio: Option<PathBuf>,
+    backends: Vec<Backend>,
+    duration: Duration,
+) -> Result<()> {
+    let settings = app::load_settings(profile.as_deref(), &overrides)?;
+    let configs = configure(&settings, &backends)?;
+    if configs.is_empty() {
+        anyhow::bail!("None of the backends is configured (see `whis doctor`)");
+    }
+
+    let sample = match audio {
+        Some(path) => load(&path)?,
+        None => {
+            app::ensure_ffmpeg_installed()?;
+            record(&settings, duration)?
+        }
+    };
+    let duration_secs = match &sample {
+        Sample::Encoded { duration_secs, .. } => Some(*duration_secs),
+        Sample::File(_) => None,
+    };
+
+    println!(
+        "\r{:<60}",
+        match duration_secs {
+            Some(secs) => format!("Sample: {}", app::format_elapsed(secs)),
+            None => "Sample: file as it is (cost unknown)".to_string(),
+        }
+    );
+
+    // Text of the first backend that succeeded, which the others are compared with
+    let mut reference: Option<(String, String)> = None;
+    for config in &configs {
+        let provider = config.provider();
+        let started = Instant::now();
+        let result = match &sample {
+            Sample::Encoded { data, .. } => {
+                transcribe_audio(provider.as_ref(), data.clone(), AudioCodec::Mp3)
+            }
+            Sample::File(path) => transcribe_file(provider.as_ref(), path),
+        };
+        let latency = started.elapsed().as_secs_f32();
+
+        println!();
+        let transcription = match result {
+            Ok(transcription) => transcription,
+            Err(e) => {
+                println!("{}  failed after {latency:.2}s", config.backend);
+                println!("  {e:#}");
+                continue;
+            }
+        };
+
+        let model = transcription.usage_model(provider.as_ref()).to_string();
+        let cost = match duration_secs {
+            Some(secs) => format!("  {}", usage::describe(&model, secs)),
+            None => String::new(),
+        };
+        println!("{model}  {latency:.2}s{cost}");
+
+        match &reference {
+            None => {
+                println!("  {}", transcription.text.trim());
+                reference = Some((model.clone(), transcription.text.clone()));
+            }
+            Some((reference_model, reference_text)) => {
+                let changes = diff::words(reference_text, &transcription.text);
+                println!(
+                    "  {:.0}% of words agree with {reference_model}",
+                    diff::agreement(&changes) * 100.0
+                );
+                println!("  {}", diff::render(&changes));
+            }
+        }
+
+        if let Some(secs) = duration_secs {
+            match usage::track(&settings, &model, secs) {
+                Ok(Some(warning)) => eprintln!("{warning}"),
+                Ok(None) => {}
+                Err(e) => eprintln!("Usage error: {e:#}"),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The requested backends, or the configured backend and its fallbacks
+fn configure(settings: &Settings, backends: &[Backend]) -> Result<Vec<ApiConfig>> {
+    if backends.is_empty() {
+        let mut config = app::load_api_config(settings)?;
+        let fallbacks = std::mem::take(&mut config.fallbacks);
+        return Ok(std::iter::once(config).chain(fallbacks).collect());
+    }
+
+    let mut configs: Vec<ApiConfig> = Vec::new();
+    for &backend in backends {
+        if configs.iter().any(|c| c.backend == backend) {
+            continue;
+        }
+        match ApiConfig::for_backend(settings, backend) {
+            Ok(config) => configs.push(config),
+            Err(e) => eprintln!("Skipping backend {backend}: {e:#}"),
+        }
+    }
+    Ok(configs)
+}
+
+/// Record a sample of at most `duration`
+fn record(settings: &Settings, duration: Duration) -> Result<Sample> {
+    let record = RecordArgs {
+        duration: Some(duration),
+        ..Default::default()
+    };
+    let recording = record_once::record_audio(settings, &record)?;
+    let duration_secs = recording.duration_secs();
+    let output = recording.finalize(&unchunked(), &AudioEncoding::default())?;
+    encoded(output, duration_secs)
+}
+
+/// WAV files are encoded like a recording; other formats are sent unchanged
+fn load(path: &Path) -> Result<Sample> {
+    let is_wav = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("wav"));
+    if !is_wav {
+        return Ok(Sample::File(path.to_path_buf()));
+    }
+
+    app::ensure_ffmpeg_installed()?;
+    let duration_secs = audio::wav_duration_secs(path)?;
+    let output = audio::encode_wav_file(path, &unchunked(), &AudioEncoding::default())?;
+    encoded(output, duration_secs)
+}
+
+/// Each backend gets the sample in one upload
+fn unchunked() -> ChunkConfig {
+    ChunkConfig {
+        enabled: false,
+        ..Default::default()
+    }
+}
+
+fn encoded(output: RecordingOutput, duration_secs: f32) -> Result<Sample> {
+    match output {
+        RecordingOutput::Single(data) => Ok(Sample::Encoded {
+            data,
+            duration_secs,
+        }),
+        RecordingOutput::Chunked(_) => anyhow::bail!("The sample is too long for a benchmark"),
+    }
+}
@@ -0,0 +1,16 @@
+//! Shell completions, for packagers and for sourcing from a shell profile:
+//! `whis completions bash > /usr/share/bash-completion/completions/whis`
+
+use anyhow::Result;
+use clap::CommandFactory;
+use clap_complete::Shell;
+use std::io;
+
+use crate::args::Cli;
+
+pub fn run(shell: Shell) -> Result<()> {
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, name, &mut io::stdout());
+    Ok(())
+}
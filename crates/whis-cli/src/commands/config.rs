@@ -1,40 +1,210 @@
 use anyhow::Result;
-use whis_core::Settings;
+use whis_core::{Backend, ClipboardBackend, ClipboardSelection, Settings};
 
-pub fn run(api_key: Option<String>, show: bool) -> Result<()> {
+pub fn run(
+    backend: Option<Backend>,
+    url: Option<String>,
+    api_key: Option<String>,
+    show: bool,
+) -> Result<()> {
     if let Some(key) = api_key {
+        let mut settings = Settings::load();
+        let backend = backend.unwrap_or(settings.backend);
+        if backend.env_var().is_none() {
+            eprintln!("The {backend} backend runs locally and doesn't use an API key");
+            std::process::exit(1);
+        }
+
         // Validate format
-        if !key.starts_with("sk-") {
+        if backend == Backend::OpenAI && !key.starts_with("sk-") {
             eprintln!("Invalid key format. OpenAI keys start with 'sk-'");
             std::process::exit(1);
         }
 
-        let mut settings = Settings::load();
-        settings.openai_api_key = Some(key);
+        settings.set_api_key(backend, key);
         settings.save()?;
         println!("API key saved to {}", Settings::path().display());
         return Ok(());
     }
 
+    if (backend.is_some() || url.is_some()) && !show {
+        let mut settings = Settings::load();
+        if let Some(backend) = backend {
+            settings.backend = backend;
+            println!("Backend set to {backend}");
+        }
+        if let Some(url) = url {
+            println!("Server URL set to {url}");
+            settings.server_url = Some(url);
+        }
+        settings.save()?;
+        return Ok(());
+    }
+
     if show {
         let settings = Settings::load();
         println!("Config file: {}", Settings::path().display());
         println!("Shortcut: {}", settings.shortcut);
-        if let Some(key) = &settings.openai_api_key {
+        println!("Backend: {}", settings.backend);
+        if !settings.backend_fallback.is_empty() {
+            let chain: Vec<&str> = settings
+                .backend_fallback
+                .iter()
+                .map(|b| b.as_str())
+                .collect();
+            println!("Fallback: {}", chain.join(" -> "));
+        }
+        if settings.backend == Backend::Azure {
+            let region = settings.azure_region.as_deref().unwrap_or("(not set)");
+            println!("Azure region: {region}");
+        }
+        if let Some(path) = &settings.model_path {
+            println!("Model path: {}", path.display());
+        }
+        if let Some(url) = &settings.server_url {
+            println!("Server URL: {url}");
+        }
+        if let Some(model) = &settings.model {
+            println!("Model: {model}");
+        }
+        if let Some(language) = &settings.language {
+            println!("Language: {language}");
+        }
+        if settings.language_hotword {
+            println!("Language hotword: on");
+        }
+        if let Some(prompt) = &settings.prompt {
+            println!("Prompt: {prompt:?}");
+        }
+        if let Some(temperature) = settings.temperature {
+            println!("Temperature: {temperature}");
+        }
+        if let Some(format) = settings.response_format {
+            println!("Response format: {format}");
+        }
+        if let Some(beam_size) = settings.beam_size {
+            println!("Beam size: {beam_size}");
+        }
+        if let Some(best_of) = settings.best_of {
+            println!("Best of: {best_of}");
+        }
+        if let Some(key) = settings.api_key(settings.backend) {
             let masked = if key.len() > 10 {
                 format!("{}...{}", &key[..6], &key[key.len() - 4..])
             } else {
                 "***".to_string()
             };
             println!("API key: {masked}");
+        } else if let Some(var) = settings.backend.env_var() {
+            println!("API key: (not set, using ${var})");
+        }
+        if let Some(device) = &settings.input_device {
+            println!("Input device: {device}");
+        } else if settings.follow_default_device {
+            println!("Input device: system default, followed while recording");
+        }
+        println!(
+            "Dictation commands: {}",
+            if settings.dictation_commands {
+                "on"
+            } else {
+                "off"
+            }
+        );
+        if settings.code_mode {
+            println!("Code mode: on");
+        }
+        if settings.redact {
+            match settings.redact_patterns.len() {
+                0 => println!("Redaction: on"),
+                count => println!("Redaction: on, {count} custom pattern(s)"),
+            }
+        }
+        if !settings.terms.is_empty() {
+            println!("Terms: {}", settings.terms.join(", "));
+        }
+        if !settings.macros.is_empty() {
+            let phrases: Vec<&str> = settings.macros.keys().map(String::as_str).collect();
+            println!("Macros: {}", phrases.join(", "));
+        }
+        if settings.profanity_filter {
+            println!("Profanity filter: on");
+        }
+        println!("Output mode: {:?}", settings.output_mode);
+        if settings.clipboard_selection != ClipboardSelection::Clipboard {
+            println!("Clipboard selection: {:?}", settings.clipboard_selection);
+        }
+        if settings.clipboard_backend != ClipboardBackend::Auto {
+            println!("Clipboard backend: {:?}", settings.clipboard_backend);
+        }
+        if let Some(template) = &settings.template {
+            println!("Template: {template:?}");
+        }
+        if let Some(confidence) = settings.min_confidence {
+            println!("Minimum confidence: {confidence}");
+        }
+        if settings.mark_unreliable {
+            println!("Mark unreliable segments: on");
+        }
+        if !settings.drop_silent_segments {
+            println!("Drop silent segments: off");
+        }
+        if let Some(pause) = settings.paragraph_pause_secs {
+            println!("Paragraph breaks: after {pause}s pauses");
+        }
+        let chunking = &settings.chunking;
+        if chunking.enabled {
+            println!(
+                "Chunking: over {} MB, {}s chunks with {}s overlap",
+                chunking.threshold_mb, chunking.duration_secs, chunking.overlap_secs
+            );
         } else {
-            println!("API key: (not set, using $OPENAI_API_KEY)");
+            println!("Chunking: off");
+        }
+        println!("Encoding: {}", settings.encoding());
+        println!("Request timeout: {}s", settings.request_timeout_secs);
+        if let Some(kbps) = settings.upload_limit_kbps {
+            println!("Upload limit: {kbps} KB/s");
+        }
+        if let Some(mins) = settings.idle_exit_mins {
+            println!("Idle exit: after {mins} min");
+        }
+        if settings.privacy {
+            println!("Privacy: on (no history, no spill files)");
+        } else if settings.encrypt_history {
+            println!("History: encrypted");
+        }
+        if let Some(url) = &settings.webhook_url {
+            println!("Webhook: {url}");
+        }
+        if let Some(note) = &settings.daily_note {
+            println!("Daily note: {note}");
+        }
+        if let Some(path) = &settings.pipe_path {
+            println!("Pipe: {path}");
+        }
+        if let Some(target) = &settings.anki_target {
+            println!("Anki: {target}");
+        }
+        if !settings.profiles.is_empty() {
+            let names: Vec<&str> = settings.profiles.keys().map(String::as_str).collect();
+            println!("Profiles: {}", names.join(", "));
+        }
+        for (language, profile) in &settings.language_profiles {
+            println!("Profile for {language}: {profile}");
+        }
+        if !settings.voice_commands.is_empty() {
+            println!("Voice commands:");
+            for vc in &settings.voice_commands {
+                println!("  \"{}\" -> {}", vc.pattern, vc.command);
+            }
         }
         return Ok(());
     }
 
     // No flags - show help
-    eprintln!("Usage: whis config --api-key <KEY>");
+    eprintln!("Usage: whis config [--backend <BACKEND>] --api-key <KEY>");
+    eprintln!("       whis config --backend <BACKEND>");
     eprintln!("       whis config --show");
     std::process::exit(1);
 }
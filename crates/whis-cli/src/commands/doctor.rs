@@ -0,0 +1,85 @@
+//! Check that whis is set up correctly
+//!
+//! Verifies the settings, the configured backends (including whether a
+//! self-hosted server answers) and the external tools whis relies on.
+
+use anyhow::Result;
+use std::process::{Command, Stdio};
+use whis_core::{ApiConfig, Backend, Settings, bluetooth};
+
+use crate::app;
+use crate::args::SettingsArgs;
+
+pub fn run(profile: Option<String>, overrides: SettingsArgs) -> Result<()> {
+    let mut ok = true;
+
+    let path = Settings::path();
+    let detail = if path.exists() {
+        path.display().to_string()
+    } else {
+        "not found, using defaults".to_string()
+    };
+    report(&mut ok, "Settings", Ok(detail));
+
+    match app::load_settings(profile.as_deref(), &overrides) {
+        Ok(settings) => check_backends(&mut ok, &settings),
+        Err(e) => report(&mut ok, "Settings", Err(e)),
+    }
+
+    let ffmpeg = Command::new("ffmpeg")
+        .arg("-version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+    let ffmpeg = match ffmpeg {
+        Ok(status) if status.success() => Ok("installed".to_string()),
+        _ => Err(anyhow::anyhow!("not found (needed to encode recordings)")),
+    };
+    report(&mut ok, "ffmpeg", ffmpeg);
+
+    // Only worth a line with a headset connected
+    if !bluetooth::headsets().is_empty() {
+        let result = match bluetooth::check() {
+            Some(warning) => Err(anyhow::anyhow!(warning)),
+            None => Ok("no profile problems".to_string()),
+        };
+        report(&mut ok, "Bluetooth", result);
+    }
+
+    if !ok {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Check the configuration and reachability of the backend and its fallbacks
+fn check_backends(ok: &mut bool, settings: &Settings) {
+    let config = match ApiConfig::from_settings(settings) {
+        Ok(config) => config,
+        Err(e) => {
+            report(ok, &format!("Backend {}", settings.backend), Err(e));
+            return;
+        }
+    };
+
+    for backend in std::iter::once(&config).chain(&config.fallbacks) {
+        let detail = match &backend.server_url {
+            Some(url) if backend.backend == Backend::WhisperServer => {
+                format!("reachable at {url}")
+            }
+            _ => "configured".to_string(),
+        };
+        let result = backend.provider().health_check().map(|_| detail);
+        report(ok, &format!("Backend {}", backend.backend), result);
+    }
+}
+
+fn report(ok: &mut bool, check: &str, result: Result<String>) {
+    match result {
+        Ok(detail) => println!("✓ {check}: {detail}"),
+        Err(e) => {
+            *ok = false;
+            println!("✗ {check}: {e:#}");
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! Measure transcription accuracy against a known transcript
+//!
+//! Transcribes a recording with the current settings (backend, prompt,
+//! codec, bitrate, post-processing) and reports the word error rate against a
+//! reference text, so the effect of a setting can be measured instead of
+//! guessed. Case and punctuation don't count as errors.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+use whis_core::{
+    RecordingOutput, audio, diff, parallel_transcribe, postprocess, transcribe_audio,
+    transcribe_file, usage,
+};
+
+use crate::app;
+use crate::args::SettingsArgs;
+
+pub fn run(
+    profile: Option<String>,
+    overrides: SettingsArgs,
+    audio_path: &Path,
+    reference_path: &Path,
+) -> Result<()> {
+    let reference = fs::read_to_string(reference_path)
+        .with_context(|| format!("Failed to read {}", reference_path.display()))?;
+    let settings = app::load_settings(profile.as_deref(), &overrides)?;
+    let config = app::load_api_config(&settings)?;
+    let provider = config.provider();
+
+    // WAV goes through the encoder like a recording; other formats as they are
+    let is_wav = audio_path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("wav"));
+    let (transcription, duration_secs) = if is_wav {
+        app::ensure_ffmpeg_installed()?;
+        let duration_secs = audio::wav_duration_secs(audio_path)?;
+        let encoding = settings.encoding().negotiate(provider.as_ref());
+        println!("Encoding: {encoding}");
+        let output = audio::encode_wav_file(audio_path, &settings.chunking, &encoding)?;
+        let transcription = match output {
+            RecordingOutput::Single(data) => {
+                transcribe_audio(provider.as_ref(), data, encoding.codec)?
+            }
+            RecordingOutput::Chunked(chunks) => tokio::runtime::Runtime::new()?
+                .block_on(parallel_transcribe(provider.clone(), chunks, None, None))?,
+        };
+        (transcription, Some(duration_secs))
+    } else {
+        (transcribe_file(provider.as_ref(), audio_path)?, None)
+    };
+
+    let model = transcription.usage_model(provider.as_ref()).to_string();
+    let text = postprocess::process(&transcription.text, &settings);
+    let counts = diff::error_counts(&reference, &text);
+
+    println!("Model: {model}");
+    println!("{}", diff::render(&diff::words(&reference, &text)));
+    println!();
+    println!(
+        "WER: {:.1}% ({} substitutions, {} deletions, {} insertions in {} words)",
+        counts.rate() * 100.0,
+        counts.substitutions,
+        counts.deletions,
+        counts.insertions,
+        counts.reference_words
+    );
+
+    if let Some(secs) = duration_secs {
+        match usage::track(&settings, &model, secs) {
+            Ok(Some(warning)) => eprintln!("{warning}"),
+            Ok(None) => {}
+            Err(e) => eprintln!("Usage error: {e:#}"),
+        }
+    }
+
+    Ok(())
+}
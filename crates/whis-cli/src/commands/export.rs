@@ -0,0 +1,177 @@
+//! Export the transcript history to a single file
+//!
+//! For people who journal by voice and want their archive in a portable
+//! format: Markdown grouped by day, a JSON array, or CSV.
+
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use whis_core::history::{self, Filter, HistoryEntry};
+
+use crate::app;
+use crate::args::ExportFormat;
+
+pub fn run(
+    since: Option<NaiveDate>,
+    format: ExportFormat,
+    filter: Filter,
+    output: Option<&Path>,
+) -> Result<()> {
+    let entries: Vec<HistoryEntry> = history::load()?
+        .into_iter()
+        .filter(|entry| since.is_none_or(|day| entry.timestamp.date_naive() >= day))
+        .filter(|entry| filter.matches(entry))
+        .collect();
+
+    let content = match format {
+        ExportFormat::Md => markdown(&entries),
+        ExportFormat::Json => serde_json::to_string_pretty(&entries)? + "\n",
+        ExportFormat::Csv => csv(&entries),
+    };
+
+    match output {
+        Some(path) => {
+            write_private(path, &content)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            eprintln!(
+                "Exported {} transcript(s) to {}",
+                entries.len(),
+                path.display()
+            );
+        }
+        None => print!("{content}"),
+    }
+    Ok(())
+}
+
+/// Write `content` to a new file only the user can read, as the history may
+/// be encrypted but the export is not
+fn write_private(path: &Path, content: &str) -> std::io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(content.as_bytes())
+}
+
+/// One heading per day, one subheading per transcript
+fn markdown(entries: &[HistoryEntry]) -> String {
+    let mut out = String::from("# Transcripts\n");
+    let mut day = None;
+    for entry in entries {
+        let date = entry.timestamp.date_naive();
+        if day != Some(date) {
+            out.push_str(&format!("\n## {}\n", date.format("%Y-%m-%d")));
+            day = Some(date);
+        }
+        out.push_str(&format!(
+            "\n### {} ({}){}\n",
+            entry.timestamp.format("%H:%M"),
+            app::format_elapsed(entry.duration_secs),
+            entry
+                .title
+                .as_ref()
+                .map(|title| format!(" {title}"))
+                .unwrap_or_default()
+        ));
+        if !entry.tags.is_empty() {
+            out.push_str(&format!("\nTags: {}\n", entry.tags.join(", ")));
+        }
+        out.push_str(&format!("\n{}\n", entry.text.trim()));
+    }
+    out
+}
+
+fn csv(entries: &[HistoryEntry]) -> String {
+    let mut out = String::from("id,timestamp,duration_secs,title,tags,text\n");
+    for entry in entries {
+        out.push_str(&format!(
+            "{},{},{:.1},{},{},{}\n",
+            entry.id,
+            entry.timestamp.to_rfc3339(),
+            entry.duration_secs,
+            csv_field(entry.title.as_deref().unwrap_or_default()),
+            csv_field(&entry.tags.join(";")),
+            csv_field(&entry.text)
+        ));
+    }
+    out
+}
+
+/// Quote a field if it holds a comma, quote or line break
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, TimeZone};
+    use whis_core::metadata::Metadata;
+
+    fn entry(id: u64, day: u32, hour: u32, text: &str) -> HistoryEntry {
+        HistoryEntry {
+            id,
+            timestamp: Local.with_ymd_and_hms(2026, 3, day, hour, 5, 0).unwrap(),
+            duration_secs: 65.0,
+            text: text.to_string(),
+            title: None,
+            tags: Vec::new(),
+            metadata: Metadata::default(),
+        }
+    }
+
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(csv_field("plain text"), "plain text");
+        assert_eq!(csv_field("one, two"), "\"one, two\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("line\nbreak"), "\"line\nbreak\"");
+        assert_eq!(csv_field(""), "");
+    }
+
+    #[test]
+    fn test_markdown_groups_by_day() {
+        let mut first = entry(1, 2, 9, "  Morning notes\n");
+        first.title = Some("Standup".to_string());
+        first.tags = vec!["work".to_string(), "daily".to_string()];
+        let entries = [first, entry(2, 2, 14, "Lunch"), entry(3, 3, 8, "Next day")];
+
+        assert_eq!(
+            markdown(&entries),
+            "# Transcripts\n\
+             \n## 2026-03-02\n\
+             \n### 09:05 (1:05) Standup\n\
+             \nTags: work, daily\n\
+             \nMorning notes\n\
+             \n### 14:05 (1:05)\n\
+             \nLunch\n\
+             \n## 2026-03-03\n\
+             \n### 08:05 (1:05)\n\
+             \nNext day\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!("whis-export-{}.md", std::process::id()));
+        write_private(&path, "secret").unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(content, "secret");
+    }
+}
@@ -0,0 +1,133 @@
+//! Transcribe one audio or video file and print the text
+//!
+//! `--start` and `--end` select a part of a long recording, so only that
+//! part is decoded, uploaded and billed. With `--split-on-silence`, long
+//! files are cut into chunks at pauses rather than every `--chunk-duration`
+//! seconds, so no word is split between two chunks.
+//!
+//! `--format words-json` prints the timed words instead of the text, for
+//! captions and audio editors; `audacity` and `reaper` print the segments as
+//! a label track or region list to import next to the audio. Their times
+//! count from the start of the file, also when only a part was transcribed.
+
+use anyhow::Result;
+use std::io::{self, Write};
+use std::path::Path;
+use whis_core::decode::{self, Trim};
+use whis_core::postprocess::timestamps::{self, Span};
+use whis_core::{
+    RecordingOutput, Transcription, Word, labels, language, parallel_transcribe, postprocess,
+    quality, transcribe_audio, usage,
+};
+
+use crate::app;
+use crate::args::{FileFormat, SettingsArgs};
+
+pub fn run(
+    profile: Option<String>,
+    overrides: SettingsArgs,
+    path: &Path,
+    start_secs: Option<f32>,
+    end_secs: Option<f32>,
+    format: FileFormat,
+) -> Result<()> {
+    let trim = Trim {
+        start_secs: start_secs.unwrap_or(0.0),
+        end_secs,
+    };
+    if let Some(end) = trim.end_secs
+        && end <= trim.start_secs
+    {
+        anyhow::bail!("--end must be after --start");
+    }
+
+    let settings = app::load_settings(profile.as_deref(), &overrides)?;
+    let config = app::load_api_config(&settings)?;
+    let provider = config.provider();
+
+    eprint!("Decoding...");
+    io::stderr().flush()?;
+    let decoded = decode::decode(path, trim)?;
+    let encoding = decode::upload_encoding(settings.encoding().negotiate(provider.as_ref()));
+    let codec = encoding.codec;
+    let output = decoded.encode(&settings.chunking, &encoding)?;
+    eprint!("\r{:<60}\r", "Transcribing...");
+    io::stderr().flush()?;
+
+    let transcription = match output {
+        RecordingOutput::Single(data) => transcribe_audio(provider.as_ref(), data, codec)?,
+        RecordingOutput::Chunked(chunks) => {
+            let runtime = tokio::runtime::Runtime::new()?;
+            runtime.block_on(parallel_transcribe(provider.clone(), chunks, None, None))?
+        }
+    };
+    eprint!("\r{:<60}\r", "");
+
+    let model = transcription.usage_model(provider.as_ref()).to_string();
+    if let Some(report) = quality::report(
+        &transcription,
+        settings.drop_silent_segments,
+        settings.privacy,
+    ) {
+        eprintln!("{report}");
+    }
+    let settings = language::resolve(&settings, transcription.language.as_deref());
+    let transcription = if settings.drop_silent_segments {
+        quality::drop_silence(transcription)
+    } else {
+        transcription
+    };
+    let output = match format {
+        FileFormat::Text => {
+            let text = postprocess::to_text(transcription, &settings);
+            Ok(postprocess::process(&text, &settings))
+        }
+        FileFormat::WordsJson => words_json(transcription.words, trim.start_secs),
+        FileFormat::Audacity => {
+            spans(&transcription, trim.start_secs).map(|s| labels::audacity(&s))
+        }
+        FileFormat::Reaper => spans(&transcription, trim.start_secs).map(|s| labels::reaper(&s)),
+    };
+
+    // The backend bills the audio whether or not the output works out
+    eprintln!("{}", usage::describe(&model, decoded.duration_secs));
+    match usage::track(&settings, &model, decoded.duration_secs) {
+        Ok(Some(warning)) => eprintln!("{warning}"),
+        Ok(None) => {}
+        Err(e) => eprintln!("Usage error: {e:#}"),
+    }
+    println!("{}", output?.trim_end_matches('\n'));
+    Ok(())
+}
+
+/// The segments with their times from the start of the file
+fn spans(transcription: &Transcription, offset_secs: f32) -> Result<Vec<Span>> {
+    let spans = timestamps::spans(transcription);
+    if spans.is_empty() {
+        anyhow::bail!("The backend reported no timings");
+    }
+    Ok(spans
+        .into_iter()
+        .map(|span| Span {
+            start: span.start + offset_secs,
+            end: span.end + offset_secs,
+            ..span
+        })
+        .collect())
+}
+
+/// The words with their times from the start of the file, as JSON
+fn words_json(words: Vec<Word>, offset_secs: f32) -> Result<String> {
+    if words.is_empty() {
+        anyhow::bail!("The backend reported no word timings");
+    }
+    let words: Vec<Word> = words
+        .into_iter()
+        .map(|word| Word {
+            start: word.start + offset_secs,
+            end: word.end + offset_secs,
+            ..word
+        })
+        .collect();
+    Ok(serde_json::to_string_pretty(&words)?)
+}
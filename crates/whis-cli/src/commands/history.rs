@@ -0,0 +1,102 @@
+//! Show and manage the transcript history
+//!
+//! Transcripts encrypted with `encrypt_history` are decrypted for display,
+//! which unlocks the key in the system keyring.
+
+use anyhow::Result;
+use whis_core::history::{self, Filter, HistoryEntry};
+use whis_core::search;
+
+use crate::app;
+use crate::args::HistoryCommand;
+
+pub fn run(command: Option<HistoryCommand>, limit: usize, filter: Filter) -> Result<()> {
+    match command {
+        Some(HistoryCommand::Encrypt) => encrypt(),
+        Some(HistoryCommand::Search { query, fuzzy }) => find(&query, fuzzy, limit, filter),
+        Some(HistoryCommand::Tag {
+            id,
+            tags,
+            remove,
+            title,
+        }) => set_tags(id, &tags, remove, title.as_deref()),
+        None => list(limit, filter),
+    }
+}
+
+fn list(limit: usize, filter: Filter) -> Result<()> {
+    let entries = history::recent_filtered(limit, filter)?;
+    if entries.is_empty() {
+        println!("No transcripts yet");
+        return Ok(());
+    }
+    for entry in entries.iter().rev() {
+        println!("{}", heading(entry));
+        println!("{}\n", entry.text);
+    }
+    Ok(())
+}
+
+/// "#12 2024-05-01 09:30 (2:05) 1:1 with Sam [meeting]"
+fn heading(entry: &HistoryEntry) -> String {
+    let mut line = format!(
+        "#{} {} ({})",
+        entry.id,
+        entry.timestamp.format("%Y-%m-%d %H:%M"),
+        app::format_elapsed(entry.duration_secs)
+    );
+    if let Some(title) = &entry.title {
+        line.push_str(&format!(" {title}"));
+    }
+    if !entry.tags.is_empty() {
+        line.push_str(&format!(" [{}]", entry.tags.join(", ")));
+    }
+    line
+}
+
+fn set_tags(id: u64, tags: &[String], remove: bool, title: Option<&str>) -> Result<()> {
+    if tags.is_empty() && title.is_none() {
+        anyhow::bail!("Nothing to change: give tags or --title");
+    }
+    let entry = history::tag(id, tags, remove, title)?;
+    println!("{}", heading(&entry));
+    Ok(())
+}
+
+fn encrypt() -> Result<()> {
+    match history::encrypt_all()? {
+        0 => println!("History is already encrypted"),
+        count => println!("Encrypted {count} transcript(s)"),
+    }
+    Ok(())
+}
+
+/// Characters of context shown either side of a match
+const SNIPPET_WIDTH: usize = 40;
+
+/// Exact matches first, newest first, then fuzzy ones by number of typos
+fn find(query: &str, fuzzy: bool, limit: usize, filter: Filter) -> Result<()> {
+    let mut hits: Vec<_> = history::load()?
+        .into_iter()
+        .rev()
+        .filter(|entry| filter.matches(entry))
+        .filter_map(|entry| search::find(&entry.text, query, fuzzy).map(|found| (entry, found)))
+        .collect();
+    if hits.is_empty() {
+        println!("No transcripts match \"{query}\"");
+        return Ok(());
+    }
+    hits.sort_by_key(|(_, found)| found.distance);
+    for (entry, found) in hits.iter().take(limit) {
+        println!(
+            "#{} {}  {}",
+            entry.id,
+            entry.timestamp.format("%Y-%m-%d %H:%M"),
+            search::snippet(&entry.text, found.range.clone(), SNIPPET_WIDTH)
+        );
+    }
+    if hits.len() > limit {
+        println!("... and {} more (see --limit)", hits.len() - limit);
+    }
+    Ok(())
+}
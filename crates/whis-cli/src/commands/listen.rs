@@ -1,5 +1,9 @@
 use anyhow::Result;
-use crate::{app, hotkey, ipc, service};
+use std::sync::Arc;
+use whis_daemon::{hotkey, ipc, service};
+
+use crate::app;
+use crate::args::SettingsArgs;
 
 /// Guard to clean up PID and socket files on exit
 struct CleanupGuard;
@@ -10,7 +14,7 @@ impl Drop for CleanupGuard {
     }
 }
 
-pub fn run(hotkey_str: String) -> Result<()> {
+pub fn run(hotkey_str: String, profile: Option<String>, overrides: SettingsArgs) -> Result<()> {
     // Check if FFmpeg is available
     app::ensure_ffmpeg_installed()?;
 
@@ -21,8 +25,12 @@ pub fn run(hotkey_str: String) -> Result<()> {
         std::process::exit(1);
     }
 
-    // Load API configuration
-    let config = app::load_api_config()?;
+    // Load API configuration and settings
+    let settings = app::load_settings(profile.as_deref(), &overrides)?;
+    let config = app::load_api_config(&settings)?;
+    let metrics_addr = settings.metrics_addr.clone();
+
+    app::notify_orphaned_recordings();
 
     // Write PID file
     ipc::write_pid_file()?;
@@ -32,19 +40,49 @@ pub fn run(hotkey_str: String) -> Result<()> {
 
     // Setup hotkey listener
     // This handles platform differences internally
-    println!("Registering hotkey: {}", hotkey_str);
-    let (hotkey_rx, _guard) = hotkey::setup(&hotkey_str)?;
+    let (hotkey_rx, _guard) = match &settings.trigger_device {
+        Some(id) => {
+            println!("Using trigger device: {}", id);
+            hotkey::setup_device(id)?
+        }
+        None => {
+            println!("Registering hotkey: {}", hotkey_str);
+            hotkey::setup(&hotkey_str)?
+        }
+    };
 
     // Create Tokio runtime
     let runtime = tokio::runtime::Runtime::new()?;
-    
+
     runtime.block_on(async {
         // Create service
-        let service = service::Service::new(config)?;
-        
+        let service = Arc::new(service::Service::new(config, settings)?.with_confirm(app::confirm));
+        if let Err(e) = service.start_pre_roll() {
+            eprintln!("Pre-roll unavailable: {e}");
+        }
+
+        // Also expose the service on the session bus (optional, e.g. headless sessions)
+        #[cfg(target_os = "linux")]
+        let _dbus = match whis_daemon::dbus::serve(service.clone()).await {
+            Ok(conn) => Some(conn),
+            Err(e) => {
+                eprintln!("D-Bus interface unavailable: {e}");
+                None
+            }
+        };
+
+        if let Some(addr) = &metrics_addr {
+            #[cfg(feature = "metrics")]
+            whis_daemon::metrics::serve(addr, service.metrics())?;
+            #[cfg(not(feature = "metrics"))]
+            eprintln!(
+                "Ignoring metrics_addr {addr}: whis was built without the \"metrics\" feature"
+            );
+        }
+
         // Run service loop
         tokio::select! {
-            result = service.run(Some(hotkey_rx)) => result,
+            result = service.clone().run(Some(hotkey_rx)) => result,
             _ = tokio::signal::ctrl_c() => {
                 println!("\nShutting down...");
                 Ok(())
@@ -0,0 +1,13 @@
+//! Man page generated from the command line definition:
+//! `whis man > /usr/share/man/man1/whis.1`
+
+use anyhow::Result;
+use clap::CommandFactory;
+use std::io;
+
+use crate::args::Cli;
+
+pub fn run() -> Result<()> {
+    clap_mangen::Man::new(Cli::command()).render(&mut io::stdout())?;
+    Ok(())
+}
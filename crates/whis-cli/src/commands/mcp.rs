@@ -0,0 +1,196 @@
+//! Model Context Protocol server over stdio
+//!
+//! Speaks newline-delimited JSON-RPC 2.0 on stdin/stdout and exposes the
+//! service engine as MCP tools. Diagnostics go to stderr so stdout stays
+//! reserved for protocol messages.
+
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use whis_core::{OutputMode, Provider, Settings, history, postprocess, transcribe_file};
+use whis_daemon::Service;
+
+use crate::app;
+use crate::args::SettingsArgs;
+
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+pub fn run(profile: Option<String>, overrides: SettingsArgs) -> Result<()> {
+    app::ensure_ffmpeg_installed()?;
+
+    let mut settings = app::load_settings(profile.as_deref(), &overrides)?;
+    let config = app::load_api_config(&settings)?;
+    let provider = config.provider();
+
+    // Voice commands may prompt on stdin, which is reserved for the protocol
+    if settings.output_mode == OutputMode::Command {
+        settings.output_mode = OutputMode::Clipboard;
+    }
+
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.block_on(async {
+        let server = McpServer {
+            service: Service::new(config, settings.clone())?,
+            settings,
+            provider,
+        };
+
+        let mut lines = BufReader::new(tokio::io::stdin()).lines();
+        let mut stdout = tokio::io::stdout();
+
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let response = match serde_json::from_str::<Value>(&line) {
+                Ok(request) => server.handle_request(request).await,
+                Err(e) => Some(error_response(Value::Null, PARSE_ERROR, &e.to_string())),
+            };
+
+            if let Some(response) = response {
+                stdout.write_all(format!("{response}\n").as_bytes()).await?;
+                stdout.flush().await?;
+            }
+        }
+
+        Ok(())
+    })
+}
+
+struct McpServer {
+    service: Service,
+    settings: Settings,
+    provider: Arc<dyn Provider>,
+}
+
+impl McpServer {
+    /// Handle a JSON-RPC message. Notifications (no id) get no response.
+    async fn handle_request(&self, request: Value) -> Option<Value> {
+        let id = request.get("id").cloned()?;
+        let method = request.get("method").and_then(Value::as_str).unwrap_or("");
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+        let result = match method {
+            "initialize" => json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": { "tools": {} },
+                "serverInfo": { "name": "whis", "version": env!("CARGO_PKG_VERSION") },
+            }),
+            "ping" => json!({}),
+            "tools/list" => json!({ "tools": tool_definitions() }),
+            "tools/call" => {
+                let Some(name) = params.get("name").and_then(Value::as_str) else {
+                    return Some(error_response(id, INVALID_PARAMS, "Missing tool name"));
+                };
+                let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+                match self.call_tool(name, &arguments).await {
+                    Ok(text) => json!({
+                        "content": [{ "type": "text", "text": text }],
+                        "isError": false,
+                    }),
+                    Err(e) => json!({
+                        "content": [{ "type": "text", "text": format!("{e:#}") }],
+                        "isError": true,
+                    }),
+                }
+            }
+            _ => {
+                let message = format!("Method not found: {method}");
+                return Some(error_response(id, METHOD_NOT_FOUND, &message));
+            }
+        };
+
+        Some(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+    }
+
+    async fn call_tool(&self, name: &str, arguments: &Value) -> Result<String> {
+        match name {
+            "start_recording" => {
+                self.service.start_recording().await?;
+                Ok("Recording started".to_string())
+            }
+            "stop_and_transcribe" => Ok(self.service.finish_recording().await?.text),
+            "transcribe_file" => {
+                let path = arguments
+                    .get("path")
+                    .and_then(Value::as_str)
+                    .map(PathBuf::from)
+                    .context("Missing 'path' argument")?;
+                let provider = self.provider.clone();
+                let transcription =
+                    tokio::task::spawn_blocking(move || transcribe_file(provider.as_ref(), &path))
+                        .await
+                        .context("Failed to join task")??;
+                let text = postprocess::to_text(transcription, &self.settings);
+                Ok(postprocess::process(&text, &self.settings))
+            }
+            "get_history" => {
+                let limit = arguments.get("limit").and_then(Value::as_u64).unwrap_or(10) as usize;
+                let entries = history::recent(limit)?;
+                if entries.is_empty() {
+                    return Ok("No transcripts yet".to_string());
+                }
+                Ok(entries
+                    .iter()
+                    .map(|e| {
+                        format!(
+                            "#{} [{}] {}",
+                            e.id,
+                            e.timestamp.format("%Y-%m-%d %H:%M"),
+                            e.text
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"))
+            }
+            _ => anyhow::bail!("Unknown tool: {name}"),
+        }
+    }
+}
+
+fn tool_definitions() -> Value {
+    json!([
+        {
+            "name": "start_recording",
+            "description": "Start recording from the default microphone",
+            "inputSchema": { "type": "object", "properties": {} },
+        },
+        {
+            "name": "stop_and_transcribe",
+            "description": "Stop the active recording and return its transcript",
+            "inputSchema": { "type": "object", "properties": {} },
+        },
+        {
+            "name": "transcribe_file",
+            "description": "Transcribe an audio file (mp3, wav, m4a, ogg, webm, flac; max 25 MB)",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "path": { "type": "string", "description": "Path to the audio file" } },
+                "required": ["path"],
+            },
+        },
+        {
+            "name": "get_history",
+            "description": "Get the most recent transcripts, newest first",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "limit": { "type": "integer", "description": "Number of entries (default 10)" } },
+            },
+        },
+    ])
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
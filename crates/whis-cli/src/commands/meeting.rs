@@ -0,0 +1,257 @@
+//! Record a meeting, transcribing it every few minutes into Markdown
+//!
+//! The recording is cut every `--chunk-every` and each piece is transcribed
+//! in the background while recording goes on, so the transcript grows in the
+//! file during the meeting and is complete moments after it ends. With
+//! `--summarize`, minutes of the whole meeting are appended when it stops.
+//!
+//! Pieces leave memory once taken, so a meeting of any length never spills
+//! to disk. To transcribe the other side of a call too, record from a
+//! monitor source (`"input_device"`), which captures what the speakers play.
+//!
+//! The file name is a template (`--output` or `"meeting_file"`) with
+//! strftime patterns for the start of the meeting and `{title}`, e.g.
+//! `~/meetings/%Y-%m-%d_%H%M_{title}.md`, so transcripts sort themselves.
+//! Without `--title`, a template using it asks for one.
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use std::fs::OpenOptions;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+use whis_core::{
+    AudioRecorder, Provider, RecordingData, RecordingOutput, Settings, bluetooth, history,
+    parallel_transcribe, postprocess, sinks, stats, summarize, template, transcribe_audio, usage,
+};
+
+use crate::app;
+use crate::args::SettingsArgs;
+
+/// Shortest piece worth a request of its own
+const MIN_CHUNK: Duration = Duration::from_secs(10);
+
+/// Part of the meeting to transcribe
+struct Piece {
+    /// Seconds from the start of the meeting
+    start_secs: f32,
+    recording: RecordingData,
+}
+
+pub fn run(
+    profile: Option<String>,
+    overrides: SettingsArgs,
+    chunk_every: Duration,
+    output: Option<String>,
+    title: Option<String>,
+) -> Result<()> {
+    if chunk_every < MIN_CHUNK {
+        anyhow::bail!("--chunk-every must be at least {}s", MIN_CHUNK.as_secs());
+    }
+    app::ensure_ffmpeg_installed()?;
+    let settings = app::load_settings(profile.as_deref(), &overrides)?;
+    let config = app::load_api_config(&settings)?;
+    let provider = config.provider();
+
+    let started = Local::now();
+    let file = output.unwrap_or_else(|| settings.meeting_file.clone());
+    let title = title.or_else(|| file.contains("{title}").then(ask_title).flatten());
+    let path = template::file_path(&file, started, title.as_deref().unwrap_or("meeting"))?;
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    let title = title.unwrap_or_else(|| format!("Meeting {}", started.format("%Y-%m-%d %H:%M")));
+    append(&path, &format!("# {title}\n"))?;
+
+    let mut recorder = AudioRecorder::new()?;
+    // Pieces are taken out of memory as they go
+    recorder.set_spill_threshold_mb(0);
+    recorder.set_device(settings.input_device.clone());
+    recorder.set_mono(settings.mono);
+    let headset = bluetooth::prepare(settings.bluetooth_switch);
+    recorder.start_recording()?;
+    println!(
+        "Transcribing into {} every {}",
+        path.display(),
+        app::format_elapsed(chunk_every.as_secs_f32())
+    );
+
+    let (pieces, queue) = mpsc::channel();
+    let worker = std::thread::spawn({
+        let (settings, path) = (settings.clone(), path.clone());
+        move || transcribe_pieces(provider, &settings, &path, queue)
+    });
+
+    let mut start_secs = 0.0;
+    loop {
+        let piece_secs = recorder.elapsed_secs();
+        print!(
+            "\rRecording {}... (press Enter to stop)",
+            app::format_elapsed(start_secs + piece_secs)
+        );
+        io::stdout().flush()?;
+
+        if app::enter_pressed() {
+            break;
+        }
+        // Keep what was captured before the microphone went away
+        if let Some(reason) = recorder.stream_failure() {
+            println!("\r{:<60}", format!("{reason}, stopping the meeting"));
+            break;
+        }
+        // The worker only stops with an error (reported below); later pieces
+        // would be lost, so the meeting stops with it
+        if worker.is_finished() {
+            println!("\r{:<60}", "Transcription failed, stopping the meeting");
+            break;
+        }
+        if piece_secs >= chunk_every.as_secs_f32()
+            && let Some(recording) = recorder.take_captured()
+        {
+            let len_secs = recording.duration_secs();
+            let piece = Piece {
+                start_secs,
+                recording,
+            };
+            if pieces.send(piece).is_err() {
+                println!("\r{:<60}", "Transcription failed, stopping the meeting");
+                break;
+            }
+            start_secs += len_secs;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
+    if let Some(warning) = recorder.capture_report().warning() {
+        eprintln!("\r{warning}");
+    }
+    // Nothing left if the last piece was just taken
+    if let Ok(recording) = recorder.stop_recording() {
+        let _ = pieces.send(Piece {
+            start_secs,
+            recording,
+        });
+    }
+    drop(headset);
+    drop(pieces);
+    print!("\r{:<60}\r", "Finishing the transcript...");
+    io::stdout().flush()?;
+    let (transcript, duration_secs) = worker
+        .join()
+        .map_err(|_| anyhow::anyhow!("Transcription thread panicked"))??;
+    println!(
+        "\r{:<60}",
+        format!("Transcript saved to {}", path.display())
+    );
+    if transcript.is_empty() {
+        return Ok(());
+    }
+
+    if settings.summarize {
+        match summarize::summarize(&transcript, &settings) {
+            Ok(summary) => append(&path, &format!("\n## Summary\n\n{}\n", summary.trim()))?,
+            Err(e) => eprintln!("Summary error: {e:#}"),
+        }
+    }
+
+    // Deliver to additional sinks (webhook, etc.) and record in history
+    let payload = sinks::Payload {
+        title: Some(title),
+        tags: vec!["meeting".to_string()],
+        ..sinks::Payload::new(transcript, duration_secs)
+    };
+    for e in sinks::dispatch(&settings, &payload) {
+        eprintln!("Output error: {e:#}");
+    }
+    if !settings.privacy
+        && let Err(e) = history::append(&payload, settings.encrypt_history)
+    {
+        eprintln!("History error: {e:#}");
+    }
+    if let Err(e) = stats::record(&payload) {
+        eprintln!("Stats error: {e:#}");
+    }
+    Ok(())
+}
+
+/// Ask for the title of the meeting; `None` if none is given
+fn ask_title() -> Option<String> {
+    if !io::stdin().is_terminal() {
+        return None;
+    }
+    print!("Title: ");
+    io::stdout().flush().ok()?;
+    let title = app::read_line()?;
+    let title = title.trim();
+    (!title.is_empty()).then(|| title.to_string())
+}
+
+/// Transcribe pieces in order as they come, appending each to the file
+/// under its time in the meeting. Returns the whole transcript and the
+/// length of the meeting in seconds.
+fn transcribe_pieces(
+    provider: Arc<dyn Provider>,
+    settings: &Settings,
+    path: &Path,
+    queue: Receiver<Piece>,
+) -> Result<(String, f32)> {
+    let mut transcript = String::new();
+    let mut duration_secs = 0.0;
+    for piece in queue {
+        let time = app::format_elapsed(piece.start_secs);
+        let len_secs = piece.recording.duration_secs();
+        duration_secs += len_secs;
+        match transcribe(provider.clone(), settings, piece.recording) {
+            Ok((text, model)) => {
+                match usage::track(settings, &model, len_secs) {
+                    Ok(Some(warning)) => eprintln!("\n{warning}"),
+                    Ok(None) => {}
+                    Err(e) => eprintln!("\nUsage error: {e:#}"),
+                }
+                let text = text.trim();
+                if !text.is_empty() {
+                    append(path, &format!("\n**{time}** {text}\n"))?;
+                    transcript = history::join(&transcript, "\n\n", text);
+                }
+            }
+            // A failed piece leaves a gap, not the end of the transcript
+            Err(e) => {
+                eprintln!("\nFailed to transcribe from {time}: {e:#}");
+                append(path, &format!("\n**{time}** _(not transcribed: {e})_\n"))?;
+            }
+        }
+    }
+    Ok((transcript, duration_secs))
+}
+
+/// Transcribe one piece (blocking). Returns the text and the model billed.
+fn transcribe(
+    provider: Arc<dyn Provider>,
+    settings: &Settings,
+    recording: RecordingData,
+) -> Result<(String, String)> {
+    let encoding = settings.encoding().negotiate(provider.as_ref());
+    let codec = encoding.codec;
+    let transcription = match recording.finalize(&settings.chunking, &encoding)? {
+        RecordingOutput::Single(data) => transcribe_audio(provider.as_ref(), data, codec)?,
+        RecordingOutput::Chunked(chunks) => {
+            let runtime = tokio::runtime::Runtime::new()?;
+            runtime.block_on(parallel_transcribe(provider.clone(), chunks, None, None))?
+        }
+    };
+    let model = transcription.usage_model(provider.as_ref()).to_string();
+    let text = postprocess::to_text(transcription, settings);
+    Ok((postprocess::process(&text, settings), model))
+}
+
+fn append(path: &Path, text: &str) -> Result<()> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(text.as_bytes()))
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
@@ -1,5 +1,30 @@
+pub mod batch;
+pub mod bench;
+pub mod completions;
 pub mod config;
+pub mod doctor;
+pub mod eval;
+pub mod export;
+pub mod file;
+pub mod history;
 pub mod listen;
+pub mod man;
+pub mod mcp;
+pub mod meeting;
+pub mod ocr;
 pub mod record_once;
+pub mod recover;
+pub mod rpc;
+pub mod say;
+#[cfg(feature = "http")]
+pub mod serve;
+pub mod stats;
 pub mod status;
 pub mod stop;
+pub mod subscribe;
+pub mod tail;
+pub mod test_mic;
+pub mod toggle;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod undo;
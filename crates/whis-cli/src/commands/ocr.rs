@@ -0,0 +1,83 @@
+//! Read the text in a screenshot
+//!
+//! Takes the image on the clipboard (or, with `--select`, a part of the
+//! screen, or an image file) and outputs its text like a transcript: with
+//! the post-processing, output mode, sinks and history of the profile, so
+//! the dictation hotkey's setup works for screenshots too.
+
+use anyhow::Result;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use whis_core::metadata::Metadata;
+use whis_core::ocr::{self, Source};
+use whis_core::{
+    OutputMode, app_rules, copy_to_clipboard, focus, history, postprocess, sinks, type_text,
+};
+
+use crate::app;
+use crate::args::SettingsArgs;
+
+pub fn run(
+    profile: Option<String>,
+    overrides: SettingsArgs,
+    image: Option<PathBuf>,
+    select: bool,
+) -> Result<()> {
+    let settings = app::load_settings(profile.as_deref(), &overrides)?;
+    let source = match image {
+        Some(path) => Source::File(path),
+        None if select => Source::Selection,
+        None => Source::Clipboard,
+    };
+    let image = ocr::capture(&source)?;
+
+    print!("Reading...");
+    io::stdout().flush()?;
+    let text = ocr::extract_text(&image, &settings)?;
+    print!("\r{:<60}\r", "");
+    if text.is_empty() {
+        anyhow::bail!("No text found in the image");
+    }
+
+    let window = focus::focused_window();
+    let settings = app_rules::resolve(&settings, window.as_ref().map(|w| w.app.as_str()));
+    let metadata = Metadata {
+        model: Some(
+            settings
+                .ocr_model
+                .clone()
+                .unwrap_or_else(|| ocr::DEFAULT_MODEL.to_string()),
+        ),
+        ..Metadata::capture(window, &settings)
+    };
+    let text = postprocess::process(&text, &settings);
+
+    match settings.output_mode {
+        OutputMode::Clipboard => {
+            copy_to_clipboard(
+                &text,
+                settings.clipboard_selection,
+                settings.clipboard_backend,
+            )?;
+            println!("Copied to clipboard");
+        }
+        OutputMode::Command => app::run_voice_command(&text, &settings.voice_commands),
+        OutputMode::Type => type_text(&text, &settings.typing)?,
+        OutputMode::None => println!("{text}"),
+    }
+
+    let payload = sinks::Payload {
+        tags: vec!["ocr".to_string()],
+        metadata,
+        ..sinks::Payload::new(text, 0.0)
+    };
+    for e in sinks::dispatch(&settings, &payload) {
+        eprintln!("Output error: {e:#}");
+    }
+    if !settings.privacy
+        && let Err(e) = history::append(&payload, settings.encrypt_history)
+    {
+        eprintln!("History error: {e:#}");
+    }
+    Ok(())
+}
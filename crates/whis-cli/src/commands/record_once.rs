@@ -1,40 +1,54 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
 use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{UnboundedReceiver, unbounded_channel};
+use whis_core::metadata::Metadata;
 use whis_core::{
-    AudioRecorder, RecordingOutput, copy_to_clipboard, parallel_transcribe,
-    transcribe_audio,
+    AudioRecorder, ChunkEvent, OutputMode, RecordingData, RecordingOutput, Settings, app_rules,
+    bluetooth, copy_to_clipboard, focus, history, language, parallel_transcribe_partial,
+    parallel_transcribe_switching, postprocess, quality, sinks, stats, summarize, template,
+    transcribe_audio, type_text, usage,
 };
+
 use crate::app;
+use crate::args::{RecordArgs, SettingsArgs};
 
-pub fn run() -> Result<()> {
+pub fn run(profile: Option<String>, overrides: SettingsArgs, record: RecordArgs) -> Result<()> {
     // Create Tokio runtime for async operations
     let runtime = tokio::runtime::Runtime::new()?;
 
     // Check if FFmpeg is available
     app::ensure_ffmpeg_installed()?;
 
-    // Load API configuration
-    let config = app::load_api_config()?;
+    let settings = app::load_settings(profile.as_deref(), &overrides)?;
+    if record.dry_run {
+        return dry_run(&settings, &record);
+    }
 
-    // Create recorder and start recording
-    let mut recorder = AudioRecorder::new()?;
-    recorder.start_recording()?;
+    // Load API configuration
+    let config = app::load_api_config(&settings)?;
+    let provider = config.provider();
 
-    print!("Recording... (press Enter to stop)");
-    io::stdout().flush()?;
-    app::wait_for_enter()?;
+    app::notify_orphaned_recordings();
 
-    // Finalize recording and get output
-    let audio_result = recorder.finalize_recording()?;
+    // Record until stopped, then finalize recording and get output
+    let recording = record_audio(&settings, &record)?;
+    let duration_secs = recording.duration_secs();
+    let audio = recording.stats();
+    let encoding = settings.encoding().negotiate(provider.as_ref());
+    let codec = encoding.codec;
+    let audio_result = recording.finalize(&settings.chunking, &encoding)?;
 
     // Transcribe based on output type
     let transcription = match audio_result {
         RecordingOutput::Single(audio_data) => {
             // Small file - simple transcription
-            print!("\rTranscribing...                        \n");
+            print!("\r{:<60}\n", "Transcribing...");
             io::stdout().flush()?;
 
-            match transcribe_audio(&config.openai_api_key, audio_data) {
+            match transcribe_audio(provider.as_ref(), audio_data, codec) {
                 Ok(text) => text,
                 Err(e) => {
                     eprintln!("Transcription error: {e}");
@@ -44,25 +58,317 @@ pub fn run() -> Result<()> {
         }
         RecordingOutput::Chunked(chunks) => {
             // Large file - parallel transcription
-            print!("\rTranscribing...                        \n");
+            print!("\r{:<60}", "Transcribing...");
             io::stdout().flush()?;
 
-            runtime.block_on(async {
-                match parallel_transcribe(&config.openai_api_key, chunks, None).await {
-                    Ok(text) => text,
-                    Err(e) => {
-                        eprintln!("Transcription error: {e}");
-                        std::process::exit(1);
-                    }
+            let (events, progress) = unbounded_channel();
+            let display = runtime.spawn(show_progress(progress, chunks.len()));
+            let result = runtime.block_on(async {
+                if settings.language_hotword {
+                    parallel_transcribe_switching(&config, chunks, Some(events), None).await
+                } else {
+                    parallel_transcribe_partial(provider.clone(), chunks, Some(events), None).await
                 }
-            })
+            });
+            let _ = runtime.block_on(display);
+            println!();
+
+            let result = result.and_then(|partial| {
+                if !settings.partial_results {
+                    return partial.require_complete();
+                }
+                if !partial.failed.is_empty() {
+                    // Machine-readable, so the chunks can be retried later
+                    eprintln!("Failed chunks: {}", serde_json::to_string(&partial.failed)?);
+                }
+                Ok(partial.transcription)
+            });
+            match result {
+                Ok(text) => text,
+                Err(e) => {
+                    eprintln!("Transcription error: {e}");
+                    std::process::exit(1);
+                }
+            }
+        }
+    };
+
+    let model = transcription.usage_model(provider.as_ref()).to_string();
+    if model != provider.model() {
+        eprintln!("Transcribed with fallback backend {model}");
+    }
+    let transcription = if settings.language_hotword {
+        language::apply_switch(transcription)
+    } else {
+        transcription
+    };
+    if let Some(report) = quality::report(
+        &transcription,
+        settings.drop_silent_segments,
+        settings.privacy,
+    ) {
+        eprintln!("{report}");
+    }
+    let settings = language::resolve(&settings, transcription.language.as_deref());
+    let transcription = postprocess::to_text(transcription, &settings);
+
+    // Apply output rules for the focused application, which is also
+    // recorded with the transcript
+    let window = focus::focused_window();
+    let settings = app_rules::resolve(&settings, window.as_ref().map(|w| w.app.as_str()));
+    let metadata = Metadata {
+        model: Some(model.clone()),
+        audio,
+        ..Metadata::capture(window, &settings)
+    };
+
+    // Apply local post-processing (dictation commands, etc.)
+    let transcription = postprocess::process(&transcription, &settings);
+    let transcription = if settings.summarize {
+        match summarize::summarize(&transcription, &settings) {
+            Ok(summary) => summary,
+            Err(e) => {
+                eprintln!("Summary error: {e:#}");
+                transcription
+            }
+        }
+    } else {
+        transcription
+    };
+    let transcription = template::apply(&settings, &transcription, duration_secs);
+
+    let transcription = if record.review {
+        match app::review(&transcription)? {
+            app::Review::Accept(text) => text,
+            app::Review::Retry => {
+                report_usage(&settings, &model, duration_secs);
+                return run(profile, overrides, record);
+            }
+            app::Review::Discard => {
+                println!("Discarded");
+                report_usage(&settings, &model, duration_secs);
+                return Ok(());
+            }
         }
+    } else {
+        transcription
     };
 
-    // Copy to clipboard
-    copy_to_clipboard(&transcription)?;
+    match settings.output_mode {
+        OutputMode::Clipboard => {
+            copy_to_clipboard(
+                &transcription,
+                settings.clipboard_selection,
+                settings.clipboard_backend,
+            )?;
+            println!("Copied to clipboard");
+        }
+        OutputMode::Command => app::run_voice_command(&transcription, &settings.voice_commands),
+        OutputMode::Type => type_text(&transcription, &settings.typing)?,
+        OutputMode::None => println!("{transcription}"),
+    }
 
-    println!("Copied to clipboard");
+    // Deliver to additional sinks (webhook, etc.) and record in history
+    let payload = sinks::Payload {
+        title: record.title.clone(),
+        tags: record.tags.clone(),
+        metadata,
+        ..sinks::Payload::new(transcription, duration_secs)
+    };
+    for e in sinks::dispatch(&settings, &payload) {
+        eprintln!("Output error: {e:#}");
+    }
+    if !settings.privacy
+        && let Err(e) = history::append(&payload, settings.encrypt_history)
+    {
+        eprintln!("History error: {e:#}");
+    }
+    if let Err(e) = stats::record(&payload) {
+        eprintln!("Stats error: {e:#}");
+    }
+
+    report_usage(&settings, &model, duration_secs);
 
     Ok(())
 }
+
+/// Show how many chunks are done and how far the running uploads are
+async fn show_progress(mut events: UnboundedReceiver<ChunkEvent>, chunks: usize) {
+    let mut uploading = BTreeMap::new();
+    let mut done = 0;
+    while let Some(event) = events.recv().await {
+        match event {
+            ChunkEvent::Sending { index, sent, total } if sent < total => {
+                uploading.insert(index, sent * 100 / total);
+            }
+            ChunkEvent::Sending { index, .. } | ChunkEvent::Uploaded { index, .. } => {
+                uploading.remove(&index);
+            }
+            ChunkEvent::Done { index, .. } | ChunkEvent::Failed { index, .. } => {
+                uploading.remove(&index);
+                done += 1;
+            }
+            ChunkEvent::Started { .. } => continue,
+        }
+        let mut line = format!("Transcribing... {done}/{chunks} chunks");
+        for (index, percent) in &uploading {
+            line.push_str(&format!(", #{index} {percent}%"));
+        }
+        print!("\r{line:<60}");
+        let _ = io::stdout().flush();
+    }
+}
+
+/// Track usage and report the estimated cost
+fn report_usage(settings: &Settings, model: &str, duration_secs: f32) {
+    eprintln!("{}", usage::describe(model, duration_secs));
+    match usage::track(settings, model, duration_secs) {
+        Ok(Some(warning)) => eprintln!("{warning}"),
+        Ok(None) => {}
+        Err(e) => eprintln!("Usage error: {e:#}"),
+    }
+}
+
+/// Record until Enter, the duration limit or silence stops it
+pub(crate) fn record_audio(settings: &Settings, record: &RecordArgs) -> Result<RecordingData> {
+    let mut recorder = AudioRecorder::new()?;
+    recorder.set_spill_threshold_mb(settings.spill_limit_mb());
+    recorder.set_device(settings.input_device.clone());
+    recorder.set_mono(settings.mono);
+    let headset = bluetooth::prepare(settings.bluetooth_switch);
+    recorder.start_recording()?;
+
+    wait_for_stop(&recorder, record.duration, record.until_silence);
+
+    if let Some(warning) = recorder.capture_report().warning() {
+        eprintln!("\r{warning}");
+    }
+    let recording = recorder.stop_recording();
+    drop(headset);
+    recording
+}
+
+/// Record and encode without calling the API, then report what would be sent
+fn dry_run(settings: &Settings, record: &RecordArgs) -> Result<()> {
+    let recording = record_audio(settings, record)?;
+    let duration_secs = recording.duration_secs();
+    // No backend to negotiate with: report the configured encoding
+    let encoding = settings.encoding();
+    let output = recording.finalize(&settings.chunking, &encoding)?;
+
+    println!("\r{:<60}", "Dry run: nothing was sent");
+    println!("Duration: {}", app::format_elapsed(duration_secs));
+    println!("Encoding: {encoding}");
+    match &output {
+        RecordingOutput::Single(data) => {
+            println!("Upload: 1 file, {}", format_size(data.len()))
+        }
+        RecordingOutput::Chunked(chunks) => {
+            println!("Upload: {} chunks", chunks.len());
+            for chunk in chunks {
+                if chunk.silent {
+                    println!("  #{}: silent, skipped", chunk.index);
+                } else {
+                    println!("  #{}: {}", chunk.index, format_size(chunk.data.len()));
+                }
+            }
+        }
+    }
+    println!(
+        "Estimated cost: {}",
+        usage::describe(settings.backend.model(), duration_secs)
+    );
+
+    if let Some(path) = &record.save_audio {
+        save_audio(path, &output)?;
+    }
+
+    Ok(())
+}
+
+/// Write the encoded audio; chunks go to `<stem>-<index>.<ext>` next to `path`
+fn save_audio(path: &Path, output: &RecordingOutput) -> Result<()> {
+    let files: Vec<(PathBuf, &[u8])> = match output {
+        RecordingOutput::Single(data) => vec![(path.to_path_buf(), data)],
+        RecordingOutput::Chunked(chunks) => {
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+            let ext = match path.extension() {
+                Some(ext) => ext.to_string_lossy(),
+                None => chunks[0].codec.extension().into(),
+            };
+            chunks
+                .iter()
+                .filter(|c| !c.silent)
+                .map(|c| {
+                    let file = path.with_file_name(format!("{stem}-{}.{ext}", c.index));
+                    (file, &c.data[..])
+                })
+                .collect()
+        }
+    };
+
+    for (file, data) in files {
+        std::fs::write(&file, data)
+            .with_context(|| format!("Failed to write {}", file.display()))?;
+        println!("Saved {}", file.display());
+    }
+    Ok(())
+}
+
+fn format_size(bytes: usize) -> String {
+    format!("{:.2} MB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+/// Block until Enter is pressed, the duration elapses or silence is detected,
+/// showing the elapsed (and remaining) time in place
+fn wait_for_stop(
+    recorder: &AudioRecorder,
+    duration: Option<Duration>,
+    until_silence: Option<Duration>,
+) {
+    let started = Instant::now();
+    let mut detector = recorder.silence_detector();
+    let mut shown_secs = None;
+
+    loop {
+        let elapsed = started.elapsed();
+        if shown_secs != Some(elapsed.as_secs()) {
+            shown_secs = Some(elapsed.as_secs());
+            let time = match duration {
+                Some(limit) => format!(
+                    "{} ({} left)",
+                    app::format_elapsed(elapsed.as_secs_f32()),
+                    app::format_elapsed(limit.saturating_sub(elapsed).as_secs_f32().ceil())
+                ),
+                None => app::format_elapsed(elapsed.as_secs_f32()),
+            };
+            print!("\rRecording {time}... (press Enter to stop)");
+            let _ = io::stdout().flush();
+        }
+
+        if app::enter_pressed() {
+            return;
+        }
+        // Keep what was captured before the microphone went away
+        if let Some(reason) = recorder.stream_failure() {
+            println!(
+                "\r{:<60}",
+                format!("{reason}, transcribing what was recorded")
+            );
+            return;
+        }
+        if duration.is_some_and(|limit| elapsed >= limit) {
+            return;
+        }
+        if let Some(limit) = until_silence {
+            recorder.update_detector(&mut detector);
+            if detector
+                .silence_secs()
+                .is_some_and(|secs| secs >= limit.as_secs_f32())
+            {
+                return;
+            }
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
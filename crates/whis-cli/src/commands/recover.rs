@@ -0,0 +1,131 @@
+//! Recover recordings interrupted by a crash
+//!
+//! Long recordings are spilled to disk while recording. If whis dies before
+//! finishing, the spill file stays behind; this transcribes it and saves the
+//! transcript to history. Files are only deleted once that succeeded.
+
+use anyhow::Result;
+use std::fs;
+use std::path::Path;
+use whis_core::{
+    ApiConfig, RecordingOutput, Settings, audio, history, language, parallel_transcribe,
+    postprocess, quality, sinks, spill, stats, summarize, template, transcribe_audio, usage,
+};
+
+use crate::app;
+use crate::args::SettingsArgs;
+
+pub fn run(
+    profile: Option<String>,
+    overrides: SettingsArgs,
+    list: bool,
+    discard: bool,
+) -> Result<()> {
+    let files = spill::orphaned();
+    if files.is_empty() {
+        println!("No unfinished recordings found");
+        return Ok(());
+    }
+
+    if list {
+        for path in &files {
+            let duration = audio::wav_duration_secs(path).unwrap_or(0.0);
+            println!("{} ({})", path.display(), app::format_elapsed(duration));
+        }
+        return Ok(());
+    }
+
+    if discard {
+        for path in &files {
+            fs::remove_file(path)?;
+        }
+        println!("Discarded {} recording(s)", files.len());
+        return Ok(());
+    }
+
+    app::ensure_ffmpeg_installed()?;
+    let settings = app::load_settings(profile.as_deref(), &overrides)?;
+    let config = app::load_api_config(&settings)?;
+    let runtime = tokio::runtime::Runtime::new()?;
+
+    let mut failed = 0;
+    for path in &files {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        println!("Recovering {name}...");
+        match recover(&runtime, path, &config, &settings) {
+            Ok(text) => {
+                println!("{text}\n");
+                let _ = fs::remove_file(path);
+            }
+            Err(e) => {
+                eprintln!("Failed to recover {name}: {e:#}");
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        anyhow::bail!("{failed} recording(s) could not be recovered and were kept");
+    }
+    Ok(())
+}
+
+/// Transcribe a spill file and save the transcript to history
+fn recover(
+    runtime: &tokio::runtime::Runtime,
+    path: &Path,
+    config: &ApiConfig,
+    settings: &Settings,
+) -> Result<String> {
+    let duration_secs = audio::wav_duration_secs(path)?;
+    let provider = config.provider();
+    let encoding = settings.encoding().negotiate(provider.as_ref());
+    let codec = encoding.codec;
+    let transcription = match audio::encode_wav_file(path, &settings.chunking, &encoding)? {
+        RecordingOutput::Single(data) => transcribe_audio(provider.as_ref(), data, codec)?,
+        RecordingOutput::Chunked(chunks) => {
+            runtime.block_on(parallel_transcribe(provider.clone(), chunks, None, None))?
+        }
+    };
+    let model = transcription.usage_model(provider.as_ref()).to_string();
+    if let Some(report) = quality::report(
+        &transcription,
+        settings.drop_silent_segments,
+        settings.privacy,
+    ) {
+        eprintln!("{report}");
+    }
+    let settings = &language::resolve(settings, transcription.language.as_deref());
+    let text = postprocess::to_text(transcription, settings);
+    let text = postprocess::process(&text, settings);
+    let text = if settings.summarize {
+        match summarize::summarize(&text, settings) {
+            Ok(summary) => summary,
+            Err(e) => {
+                eprintln!("Summary error: {e:#}");
+                text
+            }
+        }
+    } else {
+        text
+    };
+    let text = template::apply(settings, &text, duration_secs);
+
+    let payload = sinks::Payload::new(text.clone(), duration_secs);
+    if !settings.privacy {
+        history::append(&payload, settings.encrypt_history)?;
+    }
+    if let Err(e) = stats::record(&payload) {
+        eprintln!("Stats error: {e:#}");
+    }
+    for e in sinks::dispatch(settings, &payload) {
+        eprintln!("Output error: {e:#}");
+    }
+    match usage::track(settings, &model, duration_secs) {
+        Ok(Some(warning)) => eprintln!("{warning}"),
+        Ok(None) => {}
+        Err(e) => eprintln!("Usage error: {e:#}"),
+    }
+
+    Ok(text)
+}
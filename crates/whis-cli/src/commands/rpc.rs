@@ -0,0 +1,150 @@
+//! Line-based JSON server for editor integrations
+//!
+//! Reads one JSON request per line on stdin and writes responses and events
+//! as JSON lines on stdout, so an editor plugin can spawn `whis rpc` as a
+//! child process and receive transcripts directly instead of via the clipboard.
+//!
+//! Requests: `{"id": 1, "method": "start" | "stop" | "cancel" | "status"}`
+//! Responses: `{"id": 1, "result": ...}` or `{"id": 1, "error": "..."}`
+//! Events:
+//! - `{"event": "state", "state": "recording"}`
+//! - `{"event": "progress", "completed": 2, "total": 5}`
+//! - `{"event": "transcript", "text": "...", "model": "openai/whisper-1", "failed_chunks": []}`
+
+use anyhow::Result;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+use whis_core::OutputMode;
+use whis_daemon::Service;
+
+use crate::app;
+use crate::args::SettingsArgs;
+
+pub fn run(profile: Option<String>, overrides: SettingsArgs) -> Result<()> {
+    app::ensure_ffmpeg_installed()?;
+
+    let mut settings = app::load_settings(profile.as_deref(), &overrides)?;
+    let config = app::load_api_config(&settings)?;
+
+    // Transcripts go to the caller, not the clipboard or a voice command
+    settings.output_mode = OutputMode::None;
+
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.block_on(async {
+        let service = Arc::new(Service::new(config, settings)?);
+        if let Err(e) = service.start_pre_roll() {
+            eprintln!("Pre-roll unavailable: {e}");
+        }
+        service.watch_stream();
+        let (tx, mut rx) = mpsc::unbounded_channel::<Value>();
+
+        forward_events(&service, tx.clone());
+
+        // Single writer so concurrent responses and events never interleave
+        let writer = tokio::spawn(async move {
+            let mut stdout = tokio::io::stdout();
+            while let Some(message) = rx.recv().await {
+                stdout.write_all(format!("{message}\n").as_bytes()).await?;
+                stdout.flush().await?;
+            }
+            anyhow::Ok(())
+        });
+
+        let mut requests = JoinSet::new();
+        let mut lines = BufReader::new(tokio::io::stdin()).lines();
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let request = match serde_json::from_str::<Value>(&line) {
+                Ok(request) => request,
+                Err(e) => {
+                    let _ = tx.send(json!({ "id": null, "error": e.to_string() }));
+                    continue;
+                }
+            };
+
+            // Handle each request in its own task so `status` answers while `stop` transcribes
+            let service = service.clone();
+            let tx = tx.clone();
+            requests.spawn(async move {
+                let id = request.get("id").cloned().unwrap_or(Value::Null);
+                let method = request.get("method").and_then(Value::as_str).unwrap_or("");
+                let response = match handle(&service, method, &tx).await {
+                    Ok(result) => json!({ "id": id, "result": result }),
+                    Err(e) => json!({ "id": id, "error": format!("{e:#}") }),
+                };
+                let _ = tx.send(response);
+            });
+        }
+
+        // stdin closed: answer pending requests, then let the event
+        // forwarders and writer wind down as the channel closes
+        while requests.join_next().await.is_some() {}
+        drop(service);
+        drop(tx);
+        writer.await?
+    })
+}
+
+async fn handle(
+    service: &Service,
+    method: &str,
+    tx: &mpsc::UnboundedSender<Value>,
+) -> Result<Value> {
+    match method {
+        "start" => {
+            service.start_recording().await?;
+            Ok(json!(service.state().as_str()))
+        }
+        "stop" => {
+            let transcript = service.finish_recording().await?;
+            let _ = tx.send(json!({
+                "event": "transcript",
+                "text": transcript.text,
+                "model": transcript.model,
+                "failed_chunks": transcript.failed_chunks,
+            }));
+            Ok(json!(transcript.text))
+        }
+        "cancel" => {
+            service.cancel_recording()?;
+            Ok(json!(service.state().as_str()))
+        }
+        "status" => Ok(json!(service.state().as_str())),
+        _ => anyhow::bail!("Unknown method: {method}"),
+    }
+}
+
+/// Emit state and progress changes as events
+fn forward_events(service: &Service, tx: mpsc::UnboundedSender<Value>) {
+    let mut state_rx = service.subscribe();
+    let state_tx = tx.clone();
+    tokio::spawn(async move {
+        while state_rx.changed().await.is_ok() {
+            let state = *state_rx.borrow_and_update();
+            let event = json!({ "event": "state", "state": state.as_str() });
+            if state_tx.send(event).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut progress_rx = service.subscribe_progress();
+    tokio::spawn(async move {
+        while progress_rx.changed().await.is_ok() {
+            let (completed, total) = *progress_rx.borrow_and_update();
+            if total == 0 {
+                continue;
+            }
+            let event = json!({ "event": "progress", "completed": completed, "total": total });
+            if tx.send(event).is_err() {
+                break;
+            }
+        }
+    });
+}
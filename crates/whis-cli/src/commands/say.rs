@@ -0,0 +1,25 @@
+//! Read a text or the last transcript aloud
+//!
+//! Listening back is a quick check of a long dictation without re-reading
+//! it. The engine and voice come from `"tts_engine"` and `"tts_voice"`.
+
+use anyhow::{Context, Result};
+use whis_core::{history, tts};
+
+use crate::app;
+use crate::args::SettingsArgs;
+
+pub fn run(profile: Option<String>, overrides: SettingsArgs, text: Option<String>) -> Result<()> {
+    let settings = app::load_settings(profile.as_deref(), &overrides)?;
+    let text = match text {
+        Some(text) => text,
+        None => {
+            let entry = history::recent(1)?
+                .into_iter()
+                .next()
+                .context("No transcript in the history yet")?;
+            entry.text
+        }
+    };
+    tts::speak(&text, &settings)
+}
@@ -0,0 +1,128 @@
+//! HTTP control server
+//!
+//! Bridges HTTP requests to the background service's IPC socket so clients
+//! that can't use Unix sockets (browser extensions, phones on the LAN,
+//! Stream Deck plugins) can control it. Without a token, requests from web
+//! pages (with an `Origin` header) and requests for another host name (a
+//! DNS-rebound site) are refused, so a site open in the browser can neither
+//! switch on the microphone nor read transcripts.
+//!
+//! Endpoints:
+//! - `POST /toggle` — start or stop recording
+//! - `POST /toggle-summary` — same, but summarize the recording
+//! - `POST /toggle-append` — same, but append it to the last transcript
+//! - `GET /status` — current service state
+//! - `GET /last-transcript` — most recent transcript from history
+
+use anyhow::Result;
+use serde_json::{Value, json};
+use std::net::IpAddr;
+use tiny_http::{Header, Method, Request, Response, Server};
+use whis_core::{Settings, history};
+use whis_daemon::ipc::{IpcClient, IpcMessage, IpcResponse};
+
+pub fn run(host: &str, port: u16, token: Option<String>) -> Result<()> {
+    let token = token.or_else(|| Settings::load().http_token);
+
+    let is_loopback = host
+        .parse::<IpAddr>()
+        .map(|ip| ip.is_loopback())
+        .unwrap_or(host == "localhost");
+    if token.is_none() && !is_loopback {
+        anyhow::bail!(
+            "Refusing to listen on {host} without a token.\n\
+            Pass --token or set \"http_token\" in {}",
+            Settings::path().display()
+        );
+    }
+
+    let server = Server::http((host, port))
+        .map_err(|e| anyhow::anyhow!("Failed to bind {host}:{port}: {e}"))?;
+    println!("whis HTTP server on http://{host}:{port}. Ctrl+C to stop.");
+
+    for request in server.incoming_requests() {
+        let (status, body) = if is_authorized(&request, token.as_deref(), port) {
+            handle(request.method(), request.url())
+        } else {
+            (401, json!({ "error": "Unauthorized" }))
+        };
+
+        let header = Header::from_bytes("Content-Type", "application/json").unwrap();
+        let response = Response::from_string(body.to_string())
+            .with_status_code(status)
+            .with_header(header);
+        let _ = request.respond(response);
+    }
+
+    Ok(())
+}
+
+fn is_authorized(request: &Request, token: Option<&str>, port: u16) -> bool {
+    let Some(token) = token else {
+        // Browsers send an Origin with cross-site requests, even simple ones,
+        // and the site's own name as Host after DNS rebinding
+        let header = |name: &'static str| request.headers().iter().find(|h| h.field.equiv(name));
+        return header("Origin").is_none()
+            && header("Host").is_some_and(|h| is_local_host(h.value.as_str(), port));
+    };
+    let expected = format!("Bearer {token}");
+    request
+        .headers()
+        .iter()
+        .any(|h| h.field.equiv("Authorization") && constant_time_eq(h.value.as_str(), &expected))
+}
+
+/// Whether a Host header names this machine's loopback address
+fn is_local_host(host: &str, port: u16) -> bool {
+    let name = host
+        .strip_suffix(&format!(":{port}"))
+        .unwrap_or(host)
+        .to_ascii_lowercase();
+    matches!(name.as_str(), "localhost" | "127.0.0.1" | "[::1]")
+}
+
+/// Compare without stopping at the first difference, so response times
+/// don't reveal how much of a guessed token is right
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0u8, |diff, (x, y)| diff | (x ^ y))
+            == 0
+}
+
+fn handle(method: &Method, url: &str) -> (u16, Value) {
+    match (method, url) {
+        (Method::Post, "/toggle") => forward(IpcMessage::Toggle),
+        (Method::Post, "/toggle-summary") => forward(IpcMessage::ToggleSummary),
+        (Method::Post, "/toggle-append") => forward(IpcMessage::ToggleAppend),
+        (Method::Get, "/status") => forward(IpcMessage::Status),
+        (Method::Get, "/last-transcript") => match history::recent(1) {
+            Ok(entries) => match entries.into_iter().next() {
+                Some(entry) => (200, json!(entry)),
+                None => (404, json!({ "error": "No transcripts yet" })),
+            },
+            Err(e) => (500, json!({ "error": e.to_string() })),
+        },
+        _ => (404, json!({ "error": "Not found" })),
+    }
+}
+
+/// Send a message to the background service and translate its response
+fn forward(message: IpcMessage) -> (u16, Value) {
+    let response = IpcClient::connect().and_then(|mut client| client.send_message(message));
+    match response {
+        Ok(IpcResponse::Success) => (200, json!({ "status": "success" })),
+        Ok(IpcResponse::Idle) => (200, json!({ "status": "idle" })),
+        Ok(IpcResponse::Recording { elapsed_secs }) => (
+            200,
+            json!({ "status": "recording", "elapsed_secs": elapsed_secs }),
+        ),
+        Ok(IpcResponse::Transcribing) => (200, json!({ "status": "transcribing" })),
+        Ok(IpcResponse::Snapshot(snapshot)) => (200, json!(snapshot)),
+        Ok(IpcResponse::Partial(text)) => (200, json!({ "partial": text })),
+        Ok(IpcResponse::Transcript(transcript)) => (200, json!(transcript)),
+        Ok(IpcResponse::Error(e)) => (500, json!({ "error": e })),
+        Err(e) => (503, json!({ "error": e.to_string() })),
+    }
+}
@@ -0,0 +1,41 @@
+//! Dictation statistics per day
+//!
+//! Totals are kept in `whis_core::stats` by every recording (one-shot,
+//! service, desktop and recovered ones).
+
+use anyhow::Result;
+use whis_core::stats::{self, DayStats};
+
+use crate::app;
+
+pub fn run(days: usize, json: bool) -> Result<()> {
+    let stats = stats::load()?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+    if stats.days.is_empty() {
+        println!("No recordings yet");
+        return Ok(());
+    }
+
+    println!(
+        "{:<12} {:>10} {:>8} {:>10}",
+        "Day", "Recordings", "Words", "Avg length"
+    );
+    let recent: Vec<(&String, &DayStats)> = stats.days.iter().rev().take(days).collect();
+    for (day, totals) in recent.into_iter().rev() {
+        print_row(day, totals);
+    }
+    print_row("Total", &stats.total());
+    Ok(())
+}
+
+fn print_row(label: &str, totals: &DayStats) {
+    println!(
+        "{label:<12} {:>10} {:>8} {:>10}",
+        totals.recordings,
+        totals.words,
+        app::format_elapsed(totals.average_secs() as f32)
+    );
+}
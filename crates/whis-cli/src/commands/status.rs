@@ -1,7 +1,14 @@
 use anyhow::Result;
-use crate::ipc;
+use whis_core::{Settings, usage};
+use whis_daemon::ipc;
+
+use crate::app;
+
+pub fn run(show_usage: bool, show_partial: bool) -> Result<()> {
+    if show_usage {
+        return print_usage();
+    }
 
-pub fn run() -> Result<()> {
     if !ipc::is_service_running() {
         println!("Status: Not running");
         println!("Start with: whis listen");
@@ -13,7 +20,10 @@ pub fn run() -> Result<()> {
 
     match response {
         ipc::IpcResponse::Idle => println!("Status: Running (idle)"),
-        ipc::IpcResponse::Recording => println!("Status: Running (recording)"),
+        ipc::IpcResponse::Recording { elapsed_secs } => println!(
+            "Status: Running (recording, {})",
+            app::format_elapsed(elapsed_secs)
+        ),
         ipc::IpcResponse::Transcribing => println!("Status: Running (transcribing)"),
         ipc::IpcResponse::Error(e) => {
             eprintln!("Error: {e}");
@@ -22,5 +32,53 @@ pub fn run() -> Result<()> {
         _ => println!("Status: Running"),
     }
 
+    if show_partial {
+        let mut client = ipc::IpcClient::connect()?;
+        match client.send_message(ipc::IpcMessage::Partial)? {
+            ipc::IpcResponse::Partial(text) if text.is_empty() => {
+                println!("Nothing transcribed yet")
+            }
+            ipc::IpcResponse::Partial(text) => println!("{text}"),
+            ipc::IpcResponse::Error(e) => anyhow::bail!(e),
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Print this month's usage per backend/model and totals of earlier months
+fn print_usage() -> Result<()> {
+    let log = usage::load()?;
+    let month = usage::current_month();
+
+    println!("Usage for {month}:");
+    let totals = log.month(&month);
+    if totals.is_empty() {
+        println!("  No transcriptions yet");
+    }
+    for (model, t) in &totals {
+        println!(
+            "  {model}: {:.1} min in {} transcription(s), ≈${:.3}",
+            t.seconds / 60.0,
+            t.requests,
+            usage::estimate_cost(model, t.seconds)
+        );
+    }
+
+    let spent = log.month_cost(&month);
+    match Settings::load().monthly_budget_usd {
+        Some(budget) => println!("Total: ≈${spent:.2} of ${budget:.2} budget"),
+        None => println!("Total: ≈${spent:.2}"),
+    }
+
+    let previous: Vec<&str> = log.months().filter(|m| *m != month).collect();
+    if !previous.is_empty() {
+        println!("Previous months:");
+        for m in previous.iter().rev() {
+            println!("  {m}: ≈${:.2}", log.month_cost(m));
+        }
+    }
+
     Ok(())
 }
@@ -0,0 +1,22 @@
+//! Print the transcripts of the background service as they finish
+//!
+//! Each client can subscribe, so an editor plugin and the tray both get
+//! their results: with `--session`, only the transcripts of recordings
+//! started by `whis toggle --session` with the same id are printed.
+
+use anyhow::Result;
+use whis_daemon::ipc;
+
+pub fn run(session: Option<String>, json: bool) -> Result<()> {
+    let client = ipc::IpcClient::connect()?;
+    for transcript in client.subscribe(session)? {
+        let transcript = transcript?;
+        if json {
+            println!("{}", serde_json::to_string(&transcript)?);
+        } else {
+            println!("{}", transcript.text);
+        }
+    }
+    println!("Service stopped");
+    Ok(())
+}
@@ -0,0 +1,82 @@
+//! Follow the event log of the background service
+
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::Path;
+use std::time::Duration;
+use whis_core::events::{self, EventRecord};
+
+/// How often the log is checked for new events
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+pub fn run(lines: usize, follow: bool, json: bool) -> Result<()> {
+    let path = events::path();
+    if !path.exists() && !follow {
+        println!("No events logged yet");
+        return Ok(());
+    }
+
+    let mut position = 0;
+    if path.exists() {
+        let content = std::fs::read_to_string(&path).context("Failed to read event log")?;
+        let recent: Vec<&str> = content.lines().collect();
+        for line in &recent[recent.len().saturating_sub(lines)..] {
+            print_line(line, json);
+        }
+        position = content.len() as u64;
+    }
+    if !follow {
+        return Ok(());
+    }
+
+    loop {
+        position = print_new(&path, position, json)?;
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Print events appended after `position` and return the new position
+fn print_new(path: &Path, position: u64, json: bool) -> Result<u64> {
+    let Ok(file) = File::open(path) else {
+        // Not created yet, or being rotated
+        return Ok(0);
+    };
+    let len = file.metadata()?.len();
+    // A shorter file means the log was rotated; start from the top
+    let position = if len < position { 0 } else { position };
+    if len == position {
+        return Ok(position);
+    }
+
+    let mut reader = BufReader::new(file);
+    reader.seek(SeekFrom::Start(position))?;
+    let mut position = position;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let read = reader.read_line(&mut line)?;
+        // Stop at the end or at a line that is still being written
+        if read == 0 || !line.ends_with('\n') {
+            break;
+        }
+        position += read as u64;
+        print_line(line.trim_end(), json);
+    }
+    Ok(position)
+}
+
+fn print_line(line: &str, json: bool) {
+    if json {
+        println!("{line}");
+        return;
+    }
+    match serde_json::from_str::<EventRecord>(line) {
+        Ok(record) => println!(
+            "{} {}",
+            record.timestamp.format("%H:%M:%S"),
+            record.event.describe()
+        ),
+        Err(_) => println!("{line}"),
+    }
+}
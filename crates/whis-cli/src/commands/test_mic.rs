@@ -0,0 +1,79 @@
+//! Check the microphone without calling any backend
+//!
+//! Records a few seconds from the configured input device, shows which
+//! device and format were opened and how loud the recording was, then plays
+//! it back. A silent, clipped or distorted recording points at the
+//! microphone rather than the transcription backend.
+
+use anyhow::{Context, Result};
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+use whis_core::{AudioRecorder, bluetooth, playback};
+
+use crate::app;
+use crate::args::SettingsArgs;
+
+/// Peak level below which the microphone is probably muted or the wrong one
+const QUIET_DB: f32 = -40.0;
+/// Peak level at which the recording clips
+const CLIPPING_DB: f32 = -0.5;
+
+pub fn run(
+    profile: Option<String>,
+    overrides: SettingsArgs,
+    duration: Duration,
+    playback: bool,
+) -> Result<()> {
+    let settings = app::load_settings(profile.as_deref(), &overrides)?;
+
+    let mut recorder = AudioRecorder::new()?;
+    recorder.set_device(settings.input_device.clone());
+    recorder.set_mono(settings.mono);
+    let headset = bluetooth::prepare(settings.bluetooth_switch);
+    recorder.start_recording()?;
+    println!("Device: {}", recorder.device_name().unwrap_or("unknown"));
+    println!("Format: {}", recorder.device_format().unwrap_or("unknown"));
+
+    let started = Instant::now();
+    while started.elapsed() < duration {
+        let left = duration.saturating_sub(started.elapsed()).as_secs_f32();
+        print!(
+            "\rRecording, say something... ({})",
+            app::format_elapsed(left.ceil())
+        );
+        io::stdout().flush()?;
+        if let Some(reason) = recorder.stream_failure() {
+            println!();
+            anyhow::bail!("{reason}");
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    println!("\r{:<60}", "Recorded");
+
+    if let Some(warning) = recorder.capture_report().warning() {
+        eprintln!("{warning}");
+    }
+    let recording = recorder.stop_recording()?;
+    drop(headset);
+
+    let stats = recording.stats().context("Nothing was recorded")?;
+    println!(
+        "Recorded: {} Hz, {} channel(s)",
+        stats.sample_rate, stats.channels
+    );
+    println!("Peak: {:.1} dBFS", stats.peak_db);
+    println!("RMS: {:.1} dBFS", stats.rms_db);
+    if stats.peak_db <= -100.0 {
+        println!("No sound at all: the microphone may be muted or the wrong device");
+    } else if stats.peak_db < QUIET_DB {
+        println!("Very quiet: check the input volume or choose another input_device");
+    } else if stats.peak_db >= CLIPPING_DB {
+        println!("Clipping: lower the input volume, or words may be misheard");
+    }
+
+    if playback && let Some(samples) = recording.samples() {
+        println!("Playing it back...");
+        playback::play(samples.to_vec(), stats.sample_rate, stats.channels)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use anyhow::{Context, Result};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+use whis_daemon::ipc;
+
+/// How long to wait for a service started on demand
+const START_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub fn run(summarize: bool, append: bool, session: Option<String>) -> Result<()> {
+    let message = if let Some(session) = session {
+        ipc::IpcMessage::ToggleSession { session }
+    } else if summarize {
+        ipc::IpcMessage::ToggleSummary
+    } else if append {
+        ipc::IpcMessage::ToggleAppend
+    } else {
+        ipc::IpcMessage::Toggle
+    };
+    if !ipc::is_service_running() {
+        start_service()?;
+    }
+    let mut client = ipc::IpcClient::connect()?;
+
+    match client.send_message(message)? {
+        ipc::IpcResponse::Recording { .. } => println!("Recording..."),
+        ipc::IpcResponse::Transcribing => println!("Still transcribing the last recording"),
+        ipc::IpcResponse::Error(e) => {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        }
+        _ => println!("Done"),
+    }
+
+    Ok(())
+}
+
+/// Start `whis listen` in the background and wait until it accepts messages,
+/// so occasional users don't need to keep the service running
+fn start_service() -> Result<()> {
+    let exe = std::env::current_exe().context("Failed to find the whis binary")?;
+    let mut command = Command::new(exe);
+    command
+        .arg("listen")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // Keep it running when the terminal that started it closes
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    let mut child = command.spawn().context("Failed to start whis listen")?;
+    println!("Started whis listen");
+
+    let started = Instant::now();
+    while started.elapsed() < START_TIMEOUT {
+        if let Some(status) = child.try_wait()? {
+            anyhow::bail!("whis listen exited with {status} (run it directly to see why)");
+        }
+        if ipc::is_service_running() {
+            return Ok(());
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+    anyhow::bail!(
+        "whis listen didn't start within {}s",
+        START_TIMEOUT.as_secs()
+    )
+}
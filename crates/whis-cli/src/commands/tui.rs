@@ -0,0 +1,292 @@
+//! Terminal dashboard for the background service
+//!
+//! Polls the service over IPC for its state, microphone level and chunk
+//! progress, and lists recent transcripts from the history file. Toggle and
+//! cancel are sent to the service like `whis toggle` does.
+
+use anyhow::Result;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Gauge, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::{Duration, Instant};
+use whis_core::history::{self, HistoryEntry};
+use whis_daemon::ipc::{IpcClient, IpcMessage, IpcResponse, Snapshot};
+
+use crate::app;
+
+/// How often the service is asked for a snapshot
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// How often the transcript list is reloaded while nothing changes
+const HISTORY_INTERVAL: Duration = Duration::from_secs(5);
+const HISTORY_LEN: usize = 20;
+
+const KEYS: &str = "t toggle  c cancel  y copy  ↑/↓ select  q quit";
+
+pub fn run() -> Result<()> {
+    let snapshots = spawn_poller();
+    let mut terminal = ratatui::init();
+    let result = Dashboard::new(snapshots).run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+struct Dashboard {
+    snapshots: Receiver<Option<Snapshot>>,
+    /// Latest snapshot; `None` while the service isn't running
+    snapshot: Option<Snapshot>,
+    entries: Vec<HistoryEntry>,
+    selected: ListState,
+    history_loaded: Option<Instant>,
+    /// Outcome of the last action, shown instead of the key help
+    message: Option<String>,
+    results_tx: Sender<String>,
+    results: Receiver<String>,
+}
+
+impl Dashboard {
+    fn new(snapshots: Receiver<Option<Snapshot>>) -> Self {
+        let (results_tx, results) = mpsc::channel();
+        Self {
+            snapshots,
+            snapshot: None,
+            entries: Vec::new(),
+            selected: ListState::default(),
+            history_loaded: None,
+            message: None,
+            results_tx,
+            results,
+        }
+    }
+
+    fn run(mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            while let Ok(snapshot) = self.snapshots.try_recv() {
+                self.update(snapshot);
+            }
+            while let Ok(message) = self.results.try_recv() {
+                self.message = Some(message);
+            }
+            if self
+                .history_loaded
+                .is_none_or(|at| at.elapsed() >= HISTORY_INTERVAL)
+            {
+                self.load_history();
+            }
+
+            terminal.draw(|frame| self.draw(frame))?;
+
+            if !event::poll(POLL_INTERVAL)? {
+                continue;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Ok(());
+                }
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Char('t') | KeyCode::Char(' ') => self.send(IpcMessage::Toggle),
+                KeyCode::Char('c') => self.send(IpcMessage::Cancel),
+                KeyCode::Char('y') | KeyCode::Enter => self.copy_selected(),
+                KeyCode::Up | KeyCode::Char('k') => self.selected.select_previous(),
+                KeyCode::Down | KeyCode::Char('j') => self.selected.select_next(),
+                _ => {}
+            }
+        }
+    }
+
+    fn update(&mut self, snapshot: Option<Snapshot>) {
+        let was_busy = self.snapshot.as_ref().is_some_and(|s| s.state != "idle");
+        let is_idle = snapshot.as_ref().is_none_or(|s| s.state == "idle");
+        self.snapshot = snapshot;
+        // A recording just finished: show its transcript right away
+        if was_busy && is_idle {
+            self.load_history();
+        }
+    }
+
+    fn load_history(&mut self) {
+        self.entries = history::recent(HISTORY_LEN).unwrap_or_default();
+        self.history_loaded = Some(Instant::now());
+        if self.selected.selected().is_none() && !self.entries.is_empty() {
+            self.selected.select(Some(0));
+        }
+    }
+
+    /// Send a message on its own thread; stopping a recording waits for the
+    /// transcription
+    fn send(&mut self, message: IpcMessage) {
+        self.message = None;
+        let results = self.results_tx.clone();
+        std::thread::spawn(move || {
+            let error = match request(message) {
+                Ok(IpcResponse::Error(e)) => e,
+                Ok(_) => return,
+                Err(e) => e.to_string(),
+            };
+            let _ = results.send(format!("Error: {}", first_line(&error)));
+        });
+    }
+
+    fn copy_selected(&mut self) {
+        let Some(entry) = self.selected.selected().and_then(|i| self.entries.get(i)) else {
+            self.message = Some("No transcript selected".to_string());
+            return;
+        };
+        let copied = whis_core::copy_to_clipboard(
+            &entry.text,
+            whis_core::ClipboardSelection::Clipboard,
+            whis_core::ClipboardBackend::Auto,
+        );
+        self.message = Some(match copied {
+            Ok(()) => "Copied to clipboard".to_string(),
+            Err(e) => format!("Error: {e}"),
+        });
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [
+            status_area,
+            level_area,
+            chunks_area,
+            history_area,
+            footer_area,
+        ] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Min(3),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let (status, color) = match &self.snapshot {
+            None => (
+                "Not running (start it with `whis listen`)".to_string(),
+                Color::DarkGray,
+            ),
+            Some(s) => match s.state.as_str() {
+                "recording" => (
+                    format!("● Recording  {}", app::format_elapsed(s.elapsed_secs)),
+                    Color::Red,
+                ),
+                "transcribing" => ("Transcribing...".to_string(), Color::Yellow),
+                _ => ("Idle".to_string(), Color::Green),
+            },
+        };
+        frame.render_widget(
+            Paragraph::new(status)
+                .style(Style::new().fg(color).add_modifier(Modifier::BOLD))
+                .block(Block::bordered().title(" whis ")),
+            status_area,
+        );
+
+        let level = self.snapshot.as_ref().map_or(0.0, |s| level_ratio(s.level));
+        frame.render_widget(
+            Gauge::default()
+                .block(Block::bordered().title(" Level "))
+                .gauge_style(Style::new().fg(Color::Green))
+                .ratio(level)
+                .label(""),
+            level_area,
+        );
+
+        let (done, total) = self.snapshot.as_ref().map_or((0, 0), |s| s.chunks);
+        let (ratio, label) = if total > 0 {
+            (done as f64 / total as f64, format!("chunk {done}/{total}"))
+        } else {
+            (0.0, "-".to_string())
+        };
+        frame.render_widget(
+            Gauge::default()
+                .block(Block::bordered().title(" Chunks "))
+                .gauge_style(Style::new().fg(Color::Yellow))
+                .ratio(ratio.clamp(0.0, 1.0))
+                .label(label),
+            chunks_area,
+        );
+
+        let items: Vec<ListItem> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        entry.timestamp.format("%H:%M  ").to_string(),
+                        Style::new().fg(Color::DarkGray),
+                    ),
+                    Span::raw(entry.text.replace('\n', " ")),
+                ]))
+            })
+            .collect();
+        frame.render_stateful_widget(
+            List::new(items)
+                .block(Block::bordered().title(" Recent transcripts "))
+                .highlight_style(Style::new().add_modifier(Modifier::REVERSED)),
+            history_area,
+            &mut self.selected,
+        );
+
+        frame.render_widget(
+            Paragraph::new(self.message.as_deref().unwrap_or(KEYS))
+                .style(Style::new().fg(Color::DarkGray)),
+            footer_area,
+        );
+    }
+}
+
+/// Ask the service for a snapshot every `POLL_INTERVAL` on a background thread
+fn spawn_poller() -> Receiver<Option<Snapshot>> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        loop {
+            let snapshot = match request(IpcMessage::Snapshot) {
+                Ok(IpcResponse::Snapshot(snapshot)) => Some(snapshot),
+                _ => None,
+            };
+            if tx.send(snapshot).is_err() {
+                break;
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    });
+    rx
+}
+
+fn request(message: IpcMessage) -> Result<IpcResponse> {
+    IpcClient::connect()?.send_message(message)
+}
+
+fn first_line(text: &str) -> &str {
+    text.lines().next().unwrap_or_default()
+}
+
+/// Position of an RMS level on a -60..0 dB meter
+fn level_ratio(rms: f32) -> f64 {
+    if rms <= 0.0 {
+        return 0.0;
+    }
+    let db = 20.0 * rms.log10();
+    f64::from((db + 60.0) / 60.0).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_ratio() {
+        assert_eq!(level_ratio(0.0), 0.0);
+        assert_eq!(level_ratio(1.0), 1.0);
+        assert!((level_ratio(0.001) - 0.0).abs() < 1e-6);
+        assert!((level_ratio(0.1) - 2.0 / 3.0).abs() < 1e-3);
+    }
+}
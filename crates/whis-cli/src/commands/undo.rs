@@ -0,0 +1,14 @@
+use anyhow::Result;
+use whis_daemon::ipc;
+
+pub fn run() -> Result<()> {
+    let mut client = ipc::IpcClient::connect()?;
+    match client.send_message(ipc::IpcMessage::UndoTyping)? {
+        ipc::IpcResponse::Error(e) => {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        }
+        _ => println!("Removed the last typed transcript"),
+    }
+    Ok(())
+}
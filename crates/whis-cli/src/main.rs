@@ -1,21 +1,116 @@
 mod app;
 mod args;
 mod commands;
-mod hotkey;
-mod ipc;
-mod service;
 
 use anyhow::Result;
 use clap::Parser;
+use whis_core::history::Filter;
 
 fn main() -> Result<()> {
     let cli = args::Cli::parse();
+    // Audio and text left in the temp dir by a crashed run
+    whis_core::temp::sweep();
 
     match cli.command {
-        Some(args::Commands::Listen { hotkey }) => commands::listen::run(hotkey),
+        Some(args::Commands::Listen { hotkey }) => {
+            commands::listen::run(hotkey, cli.profile, cli.overrides)
+        }
         Some(args::Commands::Stop) => commands::stop::run(),
-        Some(args::Commands::Status) => commands::status::run(),
-        Some(args::Commands::Config { api_key, show }) => commands::config::run(api_key, show),
-        None => commands::record_once::run(),
+        Some(args::Commands::Toggle {
+            summarize,
+            append,
+            session,
+        }) => commands::toggle::run(summarize, append, session),
+        Some(args::Commands::Undo) => commands::undo::run(),
+        Some(args::Commands::Status { usage, partial }) => commands::status::run(usage, partial),
+        Some(args::Commands::Mcp) => commands::mcp::run(cli.profile, cli.overrides),
+        Some(args::Commands::Rpc) => commands::rpc::run(cli.profile, cli.overrides),
+        #[cfg(feature = "http")]
+        Some(args::Commands::Serve { host, port, token }) => {
+            commands::serve::run(&host, port, token)
+        }
+        #[cfg(feature = "tui")]
+        Some(args::Commands::Tui) => commands::tui::run(),
+        Some(args::Commands::Recover { list, discard }) => {
+            commands::recover::run(cli.profile, cli.overrides, list, discard)
+        }
+        Some(args::Commands::File {
+            path,
+            start,
+            end,
+            format,
+        }) => commands::file::run(cli.profile, cli.overrides, &path, start, end, format),
+        Some(args::Commands::Say { text, .. }) => {
+            commands::say::run(cli.profile, cli.overrides, text)
+        }
+        Some(args::Commands::Ocr { image, select }) => {
+            commands::ocr::run(cli.profile, cli.overrides, image, select)
+        }
+        Some(args::Commands::Meeting {
+            chunk_every,
+            output,
+            title,
+        }) => commands::meeting::run(cli.profile, cli.overrides, chunk_every, output, title),
+        Some(args::Commands::Batch {
+            files,
+            retry_failed,
+            jobs,
+        }) => commands::batch::run(cli.profile, cli.overrides, files, retry_failed, jobs.into()),
+        Some(args::Commands::Doctor) => commands::doctor::run(cli.profile, cli.overrides),
+        Some(args::Commands::TestMic {
+            duration,
+            no_playback,
+        }) => commands::test_mic::run(cli.profile, cli.overrides, duration, !no_playback),
+        Some(args::Commands::Bench {
+            audio,
+            backends,
+            duration,
+        }) => commands::bench::run(cli.profile, cli.overrides, audio, backends, duration),
+        Some(args::Commands::Eval { audio, reference }) => {
+            commands::eval::run(cli.profile, cli.overrides, &audio, &reference)
+        }
+        Some(args::Commands::Stats { days, json }) => commands::stats::run(days, json),
+        Some(args::Commands::History {
+            command,
+            limit,
+            tag,
+            app,
+        }) => commands::history::run(
+            command,
+            limit,
+            Filter {
+                tag: tag.as_deref(),
+                app: app.as_deref(),
+            },
+        ),
+        Some(args::Commands::Export {
+            since,
+            format,
+            tag,
+            app,
+            output,
+        }) => commands::export::run(
+            since,
+            format,
+            Filter {
+                tag: tag.as_deref(),
+                app: app.as_deref(),
+            },
+            output.as_deref(),
+        ),
+        Some(args::Commands::Tail {
+            lines,
+            no_follow,
+            json,
+        }) => commands::tail::run(lines, !no_follow, json),
+        Some(args::Commands::Subscribe { session, json }) => {
+            commands::subscribe::run(session, json)
+        }
+        Some(args::Commands::Config { api_key, show }) => {
+            commands::config::run(cli.overrides.backend, cli.overrides.url, api_key, show)
+        }
+        Some(args::Commands::Completions { shell }) => commands::completions::run(shell),
+        Some(args::Commands::Man) => commands::man::run(),
+        None => commands::record_once::run(cli.profile, cli.overrides, cli.record),
     }
 }
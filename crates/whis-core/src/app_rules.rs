@@ -0,0 +1,109 @@
+//! Per-application output rules
+//!
+//! Rules override output settings depending on which application has focus
+//! when a recording stops, e.g. typing into terminals, clipboard-only for
+//! browsers or a trailing newline in chat apps (`"template": "{{text}}\n"`).
+
+use serde::{Deserialize, Serialize};
+
+use crate::settings::{OutputMode, Settings};
+
+/// Overrides applied while a matching application has focus
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppRule {
+    /// Window class or app id, ASCII case-insensitive, `*` as wildcard
+    /// (e.g. "firefox", "*term*")
+    pub app: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_mode: Option<OutputMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dictation_commands: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code_mode: Option<bool>,
+}
+
+impl AppRule {
+    /// Apply this rule's overrides to a copy of the settings
+    pub fn apply(&self, settings: &Settings) -> Settings {
+        let mut effective = settings.clone();
+        if let Some(v) = self.output_mode {
+            effective.output_mode = v;
+        }
+        if let Some(v) = &self.template {
+            effective.template = Some(v.clone());
+        }
+        if let Some(v) = self.dictation_commands {
+            effective.dictation_commands = v;
+        }
+        if let Some(v) = self.code_mode {
+            effective.code_mode = v;
+        }
+        effective
+    }
+}
+
+/// First rule matching the application, in configuration order
+pub fn find<'a>(rules: &'a [AppRule], app: &str) -> Option<&'a AppRule> {
+    rules
+        .iter()
+        .find(|rule| crate::voice_command::match_pattern(rule.app.trim(), app).is_some())
+}
+
+/// Settings with the rule for the focused application `app` (see
+/// `focus::focused_window`) applied
+///
+/// Returns the settings unchanged when no rule matches or the focused
+/// application couldn't be detected.
+pub fn resolve(settings: &Settings, app: Option<&str>) -> Settings {
+    let rule = app.and_then(|app| find(&settings.app_rules, app));
+    match rule {
+        Some(rule) => rule.apply(settings),
+        None => settings.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(app: &str, output_mode: OutputMode) -> AppRule {
+        AppRule {
+            app: app.to_string(),
+            output_mode: Some(output_mode),
+            template: None,
+            dictation_commands: None,
+            code_mode: None,
+        }
+    }
+
+    #[test]
+    fn test_find() {
+        let rules = [
+            rule("firefox", OutputMode::Clipboard),
+            rule("*term*", OutputMode::Type),
+        ];
+        assert_eq!(
+            find(&rules, "Firefox").map(|r| r.output_mode),
+            Some(Some(OutputMode::Clipboard))
+        );
+        assert_eq!(
+            find(&rules, "gnome-terminal-server").map(|r| r.output_mode),
+            Some(Some(OutputMode::Type))
+        );
+        assert!(find(&rules, "firefox-esr").is_none());
+    }
+
+    #[test]
+    fn test_apply() {
+        let chat = AppRule {
+            template: Some("{{text}}\n".to_string()),
+            ..rule("slack", OutputMode::Type)
+        };
+        let settings = chat.apply(&Settings::default());
+        assert_eq!(settings.output_mode, OutputMode::Type);
+        assert_eq!(settings.template.as_deref(), Some("{{text}}\n"));
+        assert!(!settings.dictation_commands);
+    }
+}
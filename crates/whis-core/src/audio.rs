@@ -1,29 +1,284 @@
 use anyhow::{Context, Result};
+use bytes::{Bytes, BytesMut};
+#[cfg(feature = "audio")]
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+#[cfg(feature = "audio")]
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+#[cfg(feature = "audio")]
 use std::sync::{Arc, Mutex};
+#[cfg(feature = "audio")]
+use std::time::{Duration, Instant};
+use zeroize::Zeroize;
 
-/// Threshold for chunking (files larger than this get split)
-const CHUNK_THRESHOLD_BYTES: usize = 20 * 1024 * 1024; // 20 MB
-/// Duration of each chunk in seconds
-const CHUNK_DURATION_SECS: usize = 300; // 5 minutes
-/// Overlap between chunks in seconds (to avoid cutting words)
-const CHUNK_OVERLAP_SECS: usize = 2;
+use crate::metadata::AudioStats;
+use crate::provider::Provider;
+#[cfg(feature = "audio")]
+use crate::spill::{self, SpillWorker};
+use crate::vad;
+#[cfg(feature = "audio")]
+use crate::vad::SilenceDetector;
+
+/// A stream that delivers no audio for this long is considered dead
+#[cfg(feature = "audio")]
+const STALL_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Gap between the end of one buffer and the start of the next that counts
+/// as lost audio (device clocks jitter by less)
+#[cfg(feature = "audio")]
+const GAP_TOLERANCE_SECS: f64 = 0.01;
+
+/// Difference between the audio captured and the wall-clock time of a
+/// recording worth a warning, as a fraction of its length...
+const DRIFT_TOLERANCE: f32 = 0.01;
+/// ...and in seconds, so the start-up latency of the stream doesn't count
+const MIN_DRIFT_SECS: f32 = 1.0;
+
+/// How long recordings are split for upload
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChunkConfig {
+    /// Split recordings at all (local backends have no upload limit)
+    pub enabled: bool,
+    /// Encoded size in MB above which a recording gets split
+    pub threshold_mb: u32,
+    /// Duration of each chunk in seconds
+    pub duration_secs: u32,
+    /// Overlap between chunks in seconds (to avoid cutting words)
+    pub overlap_secs: u32,
+    /// End chunks in a pause in the second half of their duration instead,
+    /// without overlap; chunks without a pause are cut and overlapped as usual
+    pub split_on_silence: bool,
+}
+
+impl Default for ChunkConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold_mb: 20,
+            duration_secs: 300, // 5 minutes
+            overlap_secs: 2,
+            split_on_silence: false,
+        }
+    }
+}
+
+impl ChunkConfig {
+    /// Small chunks that each upload well within the request timeout on a
+    /// slow or tethered connection
+    pub fn slow_network() -> Self {
+        Self {
+            threshold_mb: 1,
+            duration_secs: 60,
+            ..Self::default()
+        }
+    }
+
+    /// Reject combinations that can't produce usable chunks
+    pub fn validate(&self) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+        if self.threshold_mb == 0 {
+            anyhow::bail!("Chunk threshold must be at least 1 MB");
+        }
+        if self.duration_secs == 0 {
+            anyhow::bail!("Chunk duration must be at least 1 second");
+        }
+        if self.overlap_secs >= self.duration_secs {
+            anyhow::bail!(
+                "Chunk overlap ({}s) must be shorter than the chunk duration ({}s)",
+                self.overlap_secs,
+                self.duration_secs
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Codec recordings are uploaded in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioCodec {
+    /// MP3, accepted by every backend
+    #[default]
+    Mp3,
+    /// Opus in an Ogg container, about a quarter of the size for speech
+    Opus,
+    /// Uncompressed 16-bit PCM, written without FFmpeg (several times the
+    /// size of MP3)
+    Wav,
+}
+
+impl AudioCodec {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AudioCodec::Mp3 => "mp3",
+            AudioCodec::Opus => "opus",
+            AudioCodec::Wav => "wav",
+        }
+    }
+
+    /// File extension of the encoded audio
+    pub fn extension(&self) -> &'static str {
+        match self {
+            AudioCodec::Mp3 => "mp3",
+            AudioCodec::Opus => "ogg",
+            AudioCodec::Wav => "wav",
+        }
+    }
+
+    /// MIME type sent with uploads
+    pub fn mime(&self) -> &'static str {
+        match self {
+            AudioCodec::Mp3 => "audio/mpeg",
+            AudioCodec::Opus => "audio/ogg",
+            AudioCodec::Wav => "audio/wav",
+        }
+    }
+
+    /// Bitrate used when none is configured
+    pub fn default_bitrate(&self) -> &'static str {
+        match self {
+            AudioCodec::Mp3 => "128k",
+            AudioCodec::Opus => "32k",
+            // Fixed by the sample rate; shown but not used
+            AudioCodec::Wav => "256k",
+        }
+    }
+
+    /// FFmpeg encoder arguments
+    fn ffmpeg_args(&self) -> &'static [&'static str] {
+        match self {
+            AudioCodec::Mp3 => &["-codec:a", "libmp3lame"],
+            AudioCodec::Opus => &["-codec:a", "libopus", "-application", "voip"],
+            AudioCodec::Wav => &["-codec:a", "pcm_s16le"],
+        }
+    }
+
+    /// FFmpeg container format, needed when writing to a pipe
+    fn ffmpeg_format(&self) -> &'static str {
+        match self {
+            AudioCodec::Mp3 => "mp3",
+            AudioCodec::Opus => "ogg",
+            AudioCodec::Wav => "wav",
+        }
+    }
+}
+
+impl fmt::Display for AudioCodec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AudioCodec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "mp3" => Ok(AudioCodec::Mp3),
+            "opus" | "ogg" => Ok(AudioCodec::Opus),
+            "wav" => Ok(AudioCodec::Wav),
+            _ => Err(format!("unknown codec '{s}' (use mp3, opus or wav)")),
+        }
+    }
+}
+
+/// How recordings are encoded for upload
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AudioEncoding {
+    pub codec: AudioCodec,
+    /// FFmpeg bitrate like "64k"; the codec's default when unset
+    pub bitrate: Option<String>,
+    /// Downmix to a single channel, halving the size of stereo recordings
+    pub mono: bool,
+}
+
+impl AudioEncoding {
+    /// This encoding if `provider` accepts its codec, otherwise MP3 at the
+    /// default bitrate
+    pub fn negotiate(self, provider: &dyn Provider) -> Self {
+        if provider.accepts(self.codec) {
+            self
+        } else {
+            Self {
+                mono: self.mono,
+                ..Self::default()
+            }
+        }
+    }
+
+    pub fn bitrate(&self) -> &str {
+        self.bitrate
+            .as_deref()
+            .unwrap_or(self.codec.default_bitrate())
+    }
+
+    /// Reject bitrates FFmpeg would fail on only after the recording
+    pub fn validate(&self) -> Result<()> {
+        if let Some(bitrate) = &self.bitrate {
+            parse_bitrate(bitrate)?;
+        }
+        Ok(())
+    }
+}
+
+/// E.g. "opus at 32k, mono"
+impl fmt::Display for AudioEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {}", self.codec, self.bitrate())?;
+        if self.mono {
+            f.write_str(", mono")?;
+        }
+        Ok(())
+    }
+}
+
+/// Bits per second of a bitrate like "64k" or "64000"
+pub fn parse_bitrate(bitrate: &str) -> Result<u32> {
+    let (number, scale) = match bitrate.strip_suffix(['k', 'K']) {
+        Some(number) => (number, 1000),
+        None => (bitrate, 1),
+    };
+    let bps = number
+        .parse::<u32>()
+        .ok()
+        .and_then(|n| n.checked_mul(scale))
+        .with_context(|| format!("Invalid bitrate '{bitrate}' (expected e.g. \"64k\")"))?;
+    if !(6_000..=320_000).contains(&bps) {
+        anyhow::bail!("Bitrate must be between 6k and 320k, got {bitrate}");
+    }
+    Ok(bps)
+}
 
 /// A chunk of audio data ready for transcription
 #[derive(Clone)]
 pub struct AudioChunk {
-    /// MP3 audio data
-    pub data: Vec<u8>,
+    /// Encoded audio data; cheap to clone for retries and fallbacks
+    pub data: Bytes,
+    /// Codec of `data`
+    pub codec: AudioCodec,
     /// Chunk index (0-based, for ordering)
     pub index: usize,
+    /// Offset of the chunk in the recording, in seconds
+    pub start_secs: f32,
     /// Whether this chunk has overlap from the previous chunk
     pub has_leading_overlap: bool,
+    /// No speech in the whole chunk; it isn't encoded or sent, since Whisper
+    /// makes up text for silence
+    pub silent: bool,
 }
 
 /// Output of a completed recording - either a single file or multiple chunks
 pub enum RecordingOutput {
     /// Small file that can be transcribed directly
-    Single(Vec<u8>),
+    Single(Bytes),
     /// Large file split into chunks for parallel transcription
     Chunked(Vec<AudioChunk>),
 }
@@ -31,61 +286,344 @@ pub enum RecordingOutput {
 /// Recording data extracted from AudioRecorder after stopping.
 /// This struct is Send-safe (unlike AudioRecorder on macOS where cpal::Stream isn't Send).
 pub struct RecordingData {
+    /// Samples still in memory (the tail of the recording when spilled)
     samples: Vec<f32>,
+    /// WAV file holding the start of the recording, if it was spilled to disk
+    spill: Option<PathBuf>,
+    /// Number of samples in the spill file
+    spilled_samples: usize,
     sample_rate: u32,
     channels: u16,
 }
 
+#[cfg(feature = "audio")]
 pub struct AudioRecorder {
     samples: Arc<Mutex<Vec<f32>>>,
     sample_rate: u32,
+    /// Channels of the captured samples (1 when downmixing)
     channels: u16,
+    /// Average the device's channels into one while capturing
+    mono: bool,
     stream: Option<cpal::Stream>,
+    /// In-memory buffer size in MB before samples move to disk (0 disables)
+    spill_threshold_mb: u32,
+    spill: Option<SpillWorker>,
+    /// Samples moved to disk so far (updated under the `samples` lock)
+    spilled: Arc<AtomicUsize>,
+    /// Samples kept while idle with pre-roll (0 when pre-roll is off)
+    pre_roll: usize,
+    /// Whether captured audio belongs to a recording (otherwise it's pre-roll)
+    capturing: Arc<AtomicBool>,
+    /// Input device name; `None` uses the system default
+    device: Option<String>,
+    /// Name of the device the stream was opened on
+    opened_device: Option<String>,
+    /// Format the device delivers audio in, described for the user
+    opened_format: Option<String>,
+    /// Last error reported by the audio backend for the open stream
+    stream_error: Arc<Mutex<Option<cpal::StreamError>>>,
+    /// When the stream last delivered audio
+    last_data: Arc<Mutex<Instant>>,
+    /// Timing of the buffers of the current recording
+    clock: Arc<Mutex<CaptureClock>>,
 }
 
+/// Timing of the buffers delivered during a recording, to notice audio lost
+/// between them (overruns) and a device clock running off its sample rate.
+/// Either shifts the audio against the timeline chunks and their overlap
+/// are cut on, so words get lost or doubled where chunks are merged.
+#[cfg(feature = "audio")]
+#[derive(Debug, Default)]
+struct CaptureClock {
+    /// When the recording started
+    started: Option<Instant>,
+    /// Device time of the first buffer, which later ones are measured from
+    first_capture: Option<cpal::StreamInstant>,
+    /// Device time (seconds from the first buffer) the next buffer should
+    /// start at
+    next_secs: Option<f64>,
+    /// Frames captured
+    frames: u64,
+    dropped_frames: u64,
+    dropouts: u32,
+}
+
+#[cfg(feature = "audio")]
+impl CaptureClock {
+    fn start() -> Self {
+        Self {
+            started: Some(Instant::now()),
+            ..Self::default()
+        }
+    }
+
+    /// A buffer of `frames` captured at `capture` on the device clock
+    fn record(&mut self, capture: cpal::StreamInstant, frames: usize, sample_rate: u32) {
+        let first = *self.first_capture.get_or_insert(capture);
+        // Some backends report no device time; count frames only then
+        let secs = capture
+            .duration_since(&first)
+            .map(|since| since.as_secs_f64());
+        self.record_at(secs, frames as u64, sample_rate);
+    }
+
+    fn record_at(&mut self, secs: Option<f64>, frames: u64, sample_rate: u32) {
+        if let (Some(secs), Some(expected)) = (secs, self.next_secs) {
+            let gap = secs - expected;
+            if gap > GAP_TOLERANCE_SECS {
+                self.dropped_frames += (gap * sample_rate as f64).round() as u64;
+                self.dropouts += 1;
+            }
+        }
+        self.frames += frames;
+        self.next_secs = secs.map(|secs| secs + frames as f64 / sample_rate as f64);
+    }
+
+    fn report(&self, sample_rate: u32) -> CaptureReport {
+        CaptureReport {
+            captured_secs: self.frames as f32 / sample_rate as f32,
+            wall_secs: self
+                .started
+                .map_or(0.0, |started| started.elapsed().as_secs_f32()),
+            dropped_secs: self.dropped_frames as f32 / sample_rate as f32,
+            dropouts: self.dropouts,
+        }
+    }
+}
+
+/// How well the audio of a recording kept up with the clock
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CaptureReport {
+    /// Audio delivered by the device
+    pub captured_secs: f32,
+    /// Time since the recording started
+    pub wall_secs: f32,
+    /// Audio lost between buffers
+    pub dropped_secs: f32,
+    /// Number of gaps audio was lost in
+    pub dropouts: u32,
+}
+
+impl CaptureReport {
+    /// Audio gained (positive) or missing (negative) against wall-clock time
+    /// beyond what dropouts explain: the device clock running fast or slow
+    pub fn drift_secs(&self) -> f32 {
+        self.captured_secs + self.dropped_secs - self.wall_secs
+    }
+
+    /// A warning if audio was lost or the device clock drifted noticeably
+    pub fn warning(&self) -> Option<String> {
+        let mut problems = Vec::new();
+        if self.dropouts > 0 {
+            problems.push(format!(
+                "{} ms of audio lost in {} dropout(s)",
+                (self.dropped_secs * 1000.0).round(),
+                self.dropouts
+            ));
+        }
+        let drift = self.drift_secs();
+        if drift.abs() > MIN_DRIFT_SECS.max(self.wall_secs * DRIFT_TOLERANCE) {
+            problems.push(format!(
+                "the microphone delivered {:.1}s of audio in {:.1}s ({:+.1}%), \
+                 its sample rate is off",
+                self.captured_secs + self.dropped_secs,
+                self.wall_secs,
+                drift / self.wall_secs * 100.0
+            ));
+        }
+        if problems.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "Audio capture problem: {}; chunks may be merged at the wrong place",
+            problems.join(", ")
+        ))
+    }
+}
+
+#[cfg(feature = "audio")]
 impl AudioRecorder {
     pub fn new() -> Result<Self> {
         Ok(AudioRecorder {
             samples: Arc::new(Mutex::new(Vec::new())),
             sample_rate: 44100, // Default sample rate
             channels: 1,        // Default channels
+            mono: false,
             stream: None,
+            spill_threshold_mb: spill::DEFAULT_THRESHOLD_MB,
+            spill: None,
+            spilled: Arc::new(AtomicUsize::new(0)),
+            pre_roll: 0,
+            capturing: Arc::new(AtomicBool::new(false)),
+            device: None,
+            opened_device: None,
+            opened_format: None,
+            stream_error: Arc::new(Mutex::new(None)),
+            last_data: Arc::new(Mutex::new(Instant::now())),
+            clock: Arc::new(Mutex::new(CaptureClock::default())),
         })
     }
 
+    /// Move samples to disk once the in-memory buffer exceeds this many MB
+    /// (0 keeps the whole recording in memory). Applies to the next recording.
+    pub fn set_spill_threshold_mb(&mut self, mb: u32) {
+        self.spill_threshold_mb = mb;
+    }
+
+    /// Record from the input device with this name instead of the system
+    /// default. Applies when the microphone is next opened.
+    pub fn set_device(&mut self, name: Option<String>) {
+        self.device = name;
+    }
+
+    /// Capture a single channel by averaging the channels of the device.
+    /// Applies when the microphone is next opened.
+    pub fn set_mono(&mut self, mono: bool) {
+        self.mono = mono;
+    }
+
+    /// Open the microphone now and keep the last `secs` seconds while idle,
+    /// so the next recording includes what was said just before it started.
+    /// The microphone then stays open between recordings.
+    pub fn start_pre_roll(&mut self, secs: f32) -> Result<()> {
+        self.capturing.store(false, Ordering::SeqCst);
+        self.open_stream(secs)
+    }
+
+    /// Whether the microphone stays open between recordings
+    pub fn is_pre_rolling(&self) -> bool {
+        self.pre_roll > 0 && self.stream.is_some()
+    }
+
     pub fn start_recording(&mut self) -> Result<()> {
-        let host = cpal::default_host();
-        let device = host
-            .default_input_device()
-            .context("No input device available")?;
+        if self.is_pre_rolling() {
+            // The buffered audio becomes the start of the recording
+            let mut samples = self.samples.lock().unwrap();
+            let excess = samples.len().saturating_sub(self.pre_roll);
+            samples.drain(..excess);
+            self.capturing.store(true, Ordering::SeqCst);
+        } else {
+            self.capturing.store(true, Ordering::SeqCst);
+            self.open_stream(0.0)?;
+        }
 
-        let config = device
-            .default_input_config()
-            .context("Failed to get default input config")?;
+        self.spilled.store(0, Ordering::SeqCst);
+        *self.clock.lock().unwrap() = CaptureClock::start();
+        if self.spill_threshold_mb > 0 {
+            self.spill = Some(SpillWorker::start(
+                self.samples.clone(),
+                self.spilled.clone(),
+                wav_spec(self.sample_rate, self.channels),
+                self.spill_threshold_mb,
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn open_stream(&mut self, pre_roll_secs: f32) -> Result<()> {
+        let device = self.find_device()?;
+        let config = choose_config(&device)?;
 
         self.sample_rate = config.sample_rate().0;
-        self.channels = config.channels();
+        self.channels = if self.mono { 1 } else { config.channels() };
+        self.pre_roll =
+            (pre_roll_secs.max(0.0) * self.sample_rate as f32) as usize * self.channels as usize;
+        self.samples.lock().unwrap().clear();
 
-        let samples = self.samples.clone();
-        samples.lock().unwrap().clear();
+        self.play(&device, config)
+    }
 
-        let stream = match config.sample_format() {
-            cpal::SampleFormat::F32 => {
-                self.build_stream::<f32>(&device, &config.into(), samples)?
-            }
-            cpal::SampleFormat::I16 => {
-                self.build_stream::<i16>(&device, &config.into(), samples)?
-            }
-            cpal::SampleFormat::U16 => {
-                self.build_stream::<u16>(&device, &config.into(), samples)?
-            }
-            _ => anyhow::bail!("Unsupported sample format"),
-        };
+    /// Name of the device the microphone is open on
+    pub fn device_name(&self) -> Option<&str> {
+        self.stream.as_ref()?;
+        self.opened_device.as_deref()
+    }
+
+    /// Sample rate, channels and sample format the microphone is open with
+    /// (before downmixing), e.g. "48000 Hz, 2 channel(s), i16"
+    pub fn device_format(&self) -> Option<&str> {
+        self.stream.as_ref()?;
+        self.opened_format.as_deref()
+    }
+
+    /// Whether the recorder follows the system default input and the default
+    /// has changed since the microphone was opened (e.g. a headset was
+    /// plugged in). Enumerates devices, so don't call it too often.
+    pub fn default_device_changed(&self) -> bool {
+        if self.device.is_some() || self.stream.is_none() {
+            return false;
+        }
+        let current = cpal::default_host()
+            .default_input_device()
+            .and_then(|device| device.name().ok());
+        current.is_some() && current != self.opened_device
+    }
+
+    /// Move the active recording to the current default input device,
+    /// keeping the audio captured so far. The new device has to record at
+    /// the same sample rate (and channel count unless downmixing).
+    pub fn switch_device(&mut self) -> Result<()> {
+        let device = self.find_device()?;
+        let channels = (!self.mono).then_some(self.channels);
+        let config = matching_config(&device, self.sample_rate, channels).with_context(|| {
+            format!(
+                "The new input device can't record at {} Hz with {} channel(s)",
+                self.sample_rate, self.channels
+            )
+        })?;
+        self.play(&device, config)
+    }
+
+    fn find_device(&self) -> Result<cpal::Device> {
+        let host = cpal::default_host();
+        match &self.device {
+            Some(name) => host
+                .input_devices()?
+                .find(|d| d.name().is_ok_and(|n| n == *name))
+                .with_context(|| format!("Input device '{name}' not found")),
+            None => host
+                .default_input_device()
+                .context("No input device available"),
+        }
+    }
 
-        stream.play()?;
+    /// Start capturing from `device` into the sample buffer, replacing the
+    /// current stream
+    fn play(&mut self, device: &cpal::Device, config: cpal::SupportedStreamConfig) -> Result<()> {
+        *self.stream_error.lock().unwrap() = None;
+        *self.last_data.lock().unwrap() = Instant::now();
+
+        // Device channels averaged into each stored sample
+        let mix = usize::from(config.channels() / self.channels);
+        let samples = self.samples.clone();
+        let format = config.sample_format();
+        let description = format!(
+            "{} Hz, {} channel(s), {format}",
+            config.sample_rate().0,
+            config.channels()
+        );
+        let config = config.into();
+        let stream = match format {
+            cpal::SampleFormat::I8 => self.build_stream::<i8>(device, &config, samples, mix)?,
+            cpal::SampleFormat::I16 => self.build_stream::<i16>(device, &config, samples, mix)?,
+            cpal::SampleFormat::I32 => self.build_stream::<i32>(device, &config, samples, mix)?,
+            cpal::SampleFormat::I64 => self.build_stream::<i64>(device, &config, samples, mix)?,
+            cpal::SampleFormat::U8 => self.build_stream::<u8>(device, &config, samples, mix)?,
+            cpal::SampleFormat::U16 => self.build_stream::<u16>(device, &config, samples, mix)?,
+            cpal::SampleFormat::U32 => self.build_stream::<u32>(device, &config, samples, mix)?,
+            cpal::SampleFormat::U64 => self.build_stream::<u64>(device, &config, samples, mix)?,
+            cpal::SampleFormat::F32 => self.build_stream::<f32>(device, &config, samples, mix)?,
+            cpal::SampleFormat::F64 => self.build_stream::<f64>(device, &config, samples, mix)?,
+            format => anyhow::bail!("Unsupported sample format {format}"),
+        };
 
         // Store stream to keep it alive; dropping it will release the microphone
+        // (and stops the previous stream before the new one starts)
         self.stream = Some(stream);
+        self.opened_device = device.name().ok();
+        self.opened_format = Some(description);
+        self.stream.as_ref().unwrap().play()?;
 
         Ok(())
     }
@@ -95,19 +633,39 @@ impl AudioRecorder {
         device: &cpal::Device,
         config: &cpal::StreamConfig,
         samples: Arc<Mutex<Vec<f32>>>,
+        mix: usize,
     ) -> Result<cpal::Stream>
     where
         T: cpal::Sample + cpal::SizedSample,
         f32: cpal::FromSample<T>,
     {
-        let err_fn = |err| eprintln!("Error in audio stream: {err}");
+        let stream_error = self.stream_error.clone();
+        let err_fn = move |err| {
+            eprintln!("Error in audio stream: {err}");
+            *stream_error.lock().unwrap() = Some(err);
+        };
+        let capturing = self.capturing.clone();
+        let pre_roll = self.pre_roll;
+        let last_data = self.last_data.clone();
+        let clock = self.clock.clone();
+        let (sample_rate, device_channels) = (config.sample_rate.0, usize::from(config.channels));
 
         let stream = device.build_input_stream(
             config,
-            move |data: &[T], _: &cpal::InputCallbackInfo| {
+            move |data: &[T], info: &cpal::InputCallbackInfo| {
+                *last_data.lock().unwrap() = Instant::now();
+                if capturing.load(Ordering::Relaxed) {
+                    let frames = data.len() / device_channels.max(1);
+                    let capture = info.timestamp().capture;
+                    clock.lock().unwrap().record(capture, frames, sample_rate);
+                }
                 let mut samples = samples.lock().unwrap();
-                for &sample in data {
-                    samples.push(cpal::Sample::from_sample(sample));
+                push_downmixed(&mut samples, data, mix);
+                // While idle, drop old audio once the buffer holds twice the
+                // pre-roll (trimming on every callback would copy too much)
+                if !capturing.load(Ordering::Relaxed) && samples.len() > pre_roll * 2 {
+                    let excess = samples.len() - pre_roll;
+                    samples.drain(..excess);
                 }
             },
             err_fn,
@@ -117,155 +675,945 @@ impl AudioRecorder {
         Ok(stream)
     }
 
+    /// Why the microphone stopped delivering audio, if it did: the device
+    /// went away (unplugged, sound server restarted) or the stream stalled.
+    /// The audio captured before that is kept and can still be stopped and
+    /// transcribed.
+    pub fn stream_failure(&self) -> Option<String> {
+        self.stream.as_ref()?;
+        let stalled = self.last_data.lock().unwrap().elapsed() >= STALL_TIMEOUT;
+        match &*self.stream_error.lock().unwrap() {
+            Some(cpal::StreamError::DeviceNotAvailable) => {
+                Some("The microphone was disconnected".to_string())
+            }
+            Some(err) if stalled => Some(format!("The audio stream failed: {err}")),
+            None if stalled => Some(format!(
+                "No audio from the microphone for {}s",
+                STALL_TIMEOUT.as_secs()
+            )),
+            _ => None,
+        }
+    }
+
+    /// Audio lost and clock drift of the current recording so far
+    pub fn capture_report(&self) -> CaptureReport {
+        self.clock.lock().unwrap().report(self.sample_rate)
+    }
+
+    /// Seconds of audio captured so far
+    pub fn elapsed_secs(&self) -> f32 {
+        let samples = self.samples.lock().unwrap().len() + self.spilled.load(Ordering::SeqCst);
+        samples_to_secs(samples, self.sample_rate, self.channels)
+    }
+
+    /// A copy of `len_secs` of the recording from `start_secs`, for
+    /// transcribing part of it while recording continues. `None` once that
+    /// part has moved to disk.
+    pub fn window(&self, start_secs: f32, len_secs: f32) -> Option<RecordingData> {
+        let index = |secs: f32| (secs * self.sample_rate as f32) as usize * self.channels as usize;
+        let samples = self.samples.lock().unwrap();
+        let spilled = self.spilled.load(Ordering::SeqCst);
+        let start = index(start_secs).checked_sub(spilled)?;
+        let end = (start + index(len_secs)).min(samples.len());
+        if start >= end {
+            return None;
+        }
+        Some(RecordingData {
+            samples: samples[start..end].to_vec(),
+            spill: None,
+            spilled_samples: 0,
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+        })
+    }
+
+    /// Take the audio captured since the last call (or the start of the
+    /// recording) while recording continues, for transcribing a long
+    /// recording piece by piece. Only for recordings without spilling
+    /// (see `set_spill_threshold_mb`); `elapsed_secs` counts from the last
+    /// call afterwards. `None` if nothing was captured.
+    pub fn take_captured(&self) -> Option<RecordingData> {
+        let samples = std::mem::take(&mut *self.samples.lock().unwrap());
+        if samples.is_empty() {
+            return None;
+        }
+        Some(RecordingData {
+            samples,
+            spill: None,
+            spilled_samples: 0,
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+        })
+    }
+
+    /// Loudness of the last 100 ms as RMS (0 to 1), for level meters
+    pub fn level(&self) -> f32 {
+        let samples = self.samples.lock().unwrap();
+        let window = (self.sample_rate as usize * self.channels as usize / 10).max(1);
+        let recent = &samples[samples.len().saturating_sub(window)..];
+        if recent.is_empty() {
+            return 0.0;
+        }
+        (recent.iter().map(|s| s * s).sum::<f32>() / recent.len() as f32).sqrt()
+    }
+
+    /// Create a silence detector matching the format of the active recording
+    pub fn silence_detector(&self) -> SilenceDetector {
+        SilenceDetector::new(self.sample_rate, self.channels)
+    }
+
+    /// Feed samples captured since the detector's last update
+    pub fn update_detector(&self, detector: &mut SilenceDetector) {
+        let samples = self.samples.lock().unwrap();
+        let spilled = self.spilled.load(Ordering::SeqCst);
+        let start = detector
+            .consumed()
+            .saturating_sub(spilled)
+            .min(samples.len());
+        detector.feed(&samples[start..]);
+    }
+
     /// Stop recording and return the recording data.
     /// The stream is dropped here, making the returned RecordingData Send-safe.
     pub fn stop_recording(&mut self) -> Result<RecordingData> {
-        // Drop the stream first to release the microphone
-        self.stream = None;
+        // Drop the stream first to release the microphone (unless pre-rolling)
+        if !self.is_pre_rolling() {
+            self.stream = None;
+        }
 
-        // Take ownership of samples and clear the buffer
+        // Stop spilling; anything not yet on disk stays in the buffer
+        let spill = self.spill.take().and_then(SpillWorker::finish);
+
+        // Take ownership of samples and clear the buffer; with pre-roll, the
+        // stream keeps filling it for the next recording
         let samples: Vec<f32> = {
             let mut guard = self.samples.lock().unwrap();
+            self.capturing.store(false, Ordering::SeqCst);
             std::mem::take(&mut *guard)
         };
 
-        if samples.is_empty() {
-            anyhow::bail!("No audio data recorded");
-        }
-
-        Ok(RecordingData {
+        let data = RecordingData {
             samples,
+            spilled_samples: if spill.is_some() {
+                self.spilled.load(Ordering::SeqCst)
+            } else {
+                0
+            },
+            spill,
             sample_rate: self.sample_rate,
             channels: self.channels,
-        })
+        };
+
+        if data.samples.is_empty() && data.spill.is_none() {
+            anyhow::bail!("No audio data recorded");
+        }
+
+        Ok(data)
     }
 
     /// Stop recording and finalize in one step (convenience method for single-threaded use).
-    pub fn finalize_recording(&mut self) -> Result<RecordingOutput> {
-        self.stop_recording()?.finalize()
+    pub fn finalize_recording(
+        &mut self,
+        chunking: &ChunkConfig,
+        encoding: &AudioEncoding,
+    ) -> Result<RecordingOutput> {
+        self.stop_recording()?.finalize(chunking, encoding)
+    }
+}
+
+#[cfg(feature = "audio")]
+impl Drop for AudioRecorder {
+    /// A recording dropped without stopping (e.g. cancelled) leaves no spill
+    /// file behind, and no audio in freed memory
+    fn drop(&mut self) {
+        if let Some(path) = self.spill.take().and_then(SpillWorker::finish) {
+            let _ = std::fs::remove_file(path);
+        }
+        self.samples.lock().unwrap().zeroize();
     }
 }
 
 impl RecordingData {
-    /// Finalize the recording by converting samples to MP3.
+    /// Duration of the recording in seconds
+    pub fn duration_secs(&self) -> f32 {
+        let samples = self.spilled_samples + self.samples.len();
+        samples_to_secs(samples, self.sample_rate, self.channels)
+    }
+
+    /// Format and levels of the recording; `None` if it was spilled to
+    /// disk, as only its tail is in memory
+    pub fn stats(&self) -> Option<AudioStats> {
+        if self.spill.is_some() {
+            return None;
+        }
+        AudioStats::measure(&self.samples, self.sample_rate, self.channels)
+    }
+
+    /// The samples (interleaved, -1 to 1) at the rate and channels of
+    /// [`stats`](Self::stats); `None` if the recording was spilled to disk
+    pub fn samples(&self) -> Option<&[f32]> {
+        self.spill.is_none().then_some(self.samples.as_slice())
+    }
+
+    /// Finalize the recording by encoding the samples for upload.
     /// This is Send-safe and can be called from spawn_blocking.
-    pub fn finalize(self) -> Result<RecordingOutput> {
-        // Try to convert the entire recording first
-        let mp3_data = self.samples_to_mp3(&self.samples, "main")?;
+    ///
+    /// If encoding a spilled recording fails, the spill file is kept so
+    /// the recording can be recovered later.
+    pub fn finalize(
+        mut self,
+        chunking: &ChunkConfig,
+        encoding: &AudioEncoding,
+    ) -> Result<RecordingOutput> {
+        chunking.validate()?;
+        encoding.validate()?;
 
-        // If at or under threshold, return as single file (fast path)
-        if mp3_data.len() <= CHUNK_THRESHOLD_BYTES {
-            return Ok(RecordingOutput::Single(mp3_data));
+        let spec = wav_spec(self.sample_rate, self.channels);
+        match self.spill.take() {
+            Some(path) => {
+                // Complete the spill file with the in-memory tail
+                let mut writer = hound::WavWriter::append(&path)?;
+                for &sample in &self.samples {
+                    writer.write_sample(to_i16(sample))?;
+                }
+                writer.finalize()?;
+
+                let input = WavInput::File(&path);
+                let output = encode_wav(input, self.duration_secs(), chunking, encoding)
+                    .with_context(|| format!("Recording kept at {}", path.display()))?;
+                let _ = std::fs::remove_file(&path);
+                Ok(output)
+            }
+            None => {
+                let mut wav = write_wav(spec, &self.samples)?;
+                let output = encode_wav(
+                    WavInput::Memory(&wav),
+                    self.duration_secs(),
+                    chunking,
+                    encoding,
+                );
+                wav.zeroize();
+                output
+            }
+        }
+    }
+}
+
+impl Drop for RecordingData {
+    /// Recordings discarded before finalizing leave no spill file behind;
+    /// the samples are wiped either way
+    fn drop(&mut self) {
+        if let Some(path) = self.spill.take() {
+            let _ = std::fs::remove_file(path);
         }
+        self.samples.zeroize();
+    }
+}
 
-        // File is too large - need to chunk it
-        let samples_per_second = self.sample_rate as usize * self.channels as usize;
-        let chunk_samples = CHUNK_DURATION_SECS * samples_per_second;
-        let overlap_samples = CHUNK_OVERLAP_SECS * samples_per_second;
+/// Names of the available input devices
+#[cfg(feature = "audio")]
+pub fn input_devices() -> Result<Vec<String>> {
+    let host = cpal::default_host();
+    Ok(host
+        .input_devices()
+        .context("Failed to list input devices")?
+        .filter_map(|device| device.name().ok())
+        .collect())
+}
 
-        let mut chunks = Vec::new();
-        let mut chunk_start = 0usize;
-        let mut chunk_index = 0usize;
+/// WAV audio to encode: a file on disk or a complete WAV in memory
+#[derive(Clone, Copy)]
+enum WavInput<'a> {
+    File(&'a Path),
+    Memory(&'a [u8]),
+}
 
-        while chunk_start < self.samples.len() {
-            let chunk_end = (chunk_start + chunk_samples).min(self.samples.len());
-            let chunk_slice = &self.samples[chunk_start..chunk_end];
+/// Encode WAV audio holding a whole recording, chunking it if needed
+fn encode_wav(
+    input: WavInput,
+    total_secs: f32,
+    chunking: &ChunkConfig,
+    encoding: &AudioEncoding,
+) -> Result<RecordingOutput> {
+    // Try to convert the entire recording first
+    let data = transcode(input, None, encoding)?;
 
-            // Convert this chunk to MP3
-            let chunk_mp3 = self.samples_to_mp3(chunk_slice, &format!("chunk{chunk_index}"))?;
+    // If at or under threshold, return as single file (fast path)
+    let threshold_bytes = chunking.threshold_mb as usize * 1024 * 1024;
+    if !chunking.enabled || data.len() <= threshold_bytes {
+        return Ok(RecordingOutput::Single(data.into()));
+    }
 
-            chunks.push(AudioChunk {
-                data: chunk_mp3,
-                index: chunk_index,
-                has_leading_overlap: chunk_index > 0,
+    // File is too large - need to chunk it
+    let (frames, frame_secs) = match input {
+        WavInput::File(path) => speech_frames(
+            hound::WavReader::open(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?,
+        )?,
+        WavInput::Memory(wav) => speech_frames(hound::WavReader::new(std::io::Cursor::new(wav))?)?,
+    };
+    let chunk_secs = chunking.duration_secs as f32;
+    let overlap_secs = chunking.overlap_secs as f32;
+    let ranges = if chunking.split_on_silence {
+        split_at_pauses(&frames, frame_secs, total_secs, chunk_secs, overlap_secs)
+    } else {
+        split_evenly(total_secs, chunk_secs, overlap_secs)
+    };
+    let speech = speech_in_ranges(&frames, frame_secs, &ranges);
+
+    // Encode all chunks into one buffer and hand out slices of it
+    let mut buffer = BytesMut::with_capacity(data.len());
+    let mut spans = Vec::with_capacity(ranges.len());
+    for (range, &has_speech) in ranges.iter().zip(&speech) {
+        let start = buffer.len();
+        if has_speech {
+            let range = (range.start_secs, range.len_secs);
+            buffer.extend_from_slice(&transcode(input, Some(range), encoding)?);
+        }
+        spans.push(start..buffer.len());
+    }
+    let buffer = buffer.freeze();
+
+    let chunks = ranges
+        .into_iter()
+        .zip(speech)
+        .zip(spans)
+        .enumerate()
+        .map(|(chunk_index, ((range, has_speech), span))| AudioChunk {
+            data: buffer.slice(span),
+            codec: encoding.codec,
+            index: chunk_index,
+            start_secs: range.start_secs,
+            has_leading_overlap: range.overlaps_previous,
+            silent: !has_speech,
+        })
+        .collect();
+
+    Ok(RecordingOutput::Chunked(chunks))
+}
+
+/// Part of a recording sent as one chunk
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ChunkRange {
+    start_secs: f32,
+    len_secs: f32,
+    /// Starts before the end of the previous chunk
+    overlaps_previous: bool,
+}
+
+/// Chunks of `chunk_secs`, each starting `overlap_secs` before the end of
+/// the previous one
+fn split_evenly(total_secs: f32, chunk_secs: f32, overlap_secs: f32) -> Vec<ChunkRange> {
+    let mut ranges = Vec::new();
+    let mut chunk_start = 0.0f32;
+    while chunk_start < total_secs {
+        let chunk_end = (chunk_start + chunk_secs).min(total_secs);
+        ranges.push(ChunkRange {
+            start_secs: chunk_start,
+            len_secs: chunk_end - chunk_start,
+            overlaps_previous: !ranges.is_empty(),
+        });
+
+        // Check if we've reached the end
+        if chunk_end >= total_secs {
+            break;
+        }
+
+        // Move to next chunk, stepping back by overlap amount
+        chunk_start = chunk_end - overlap_secs;
+    }
+    ranges
+}
+
+/// Chunks of at most `chunk_secs` that end in the last silent frame of
+/// their second half, so no word is cut; where there is no pause, the
+/// chunk is cut and overlapped like [`split_evenly`]
+fn split_at_pauses(
+    frames: &[bool],
+    frame_secs: f32,
+    total_secs: f32,
+    chunk_secs: f32,
+    overlap_secs: f32,
+) -> Vec<ChunkRange> {
+    let mut ranges = Vec::new();
+    let mut chunk_start = 0.0f32;
+    let mut overlaps_previous = false;
+    while chunk_start < total_secs {
+        let chunk_end = chunk_start + chunk_secs;
+        if chunk_end >= total_secs {
+            ranges.push(ChunkRange {
+                start_secs: chunk_start,
+                len_secs: total_secs - chunk_start,
+                overlaps_previous,
             });
+            break;
+        }
 
-            chunk_index += 1;
+        let first = ((chunk_start + chunk_secs / 2.0) / frame_secs) as usize;
+        let last = ((chunk_end / frame_secs) as usize).min(frames.len());
+        let pause = (first..last).rev().find(|&frame| !frames[frame]);
+        let (len_secs, next_start) = match pause {
+            // Cut in the middle of the silent frame
+            Some(frame) => {
+                let cut = (frame as f32 + 0.5) * frame_secs;
+                (cut - chunk_start, cut)
+            }
+            None => (chunk_secs, chunk_end - overlap_secs),
+        };
+        ranges.push(ChunkRange {
+            start_secs: chunk_start,
+            len_secs,
+            overlaps_previous,
+        });
+        overlaps_previous = pause.is_none();
+        chunk_start = next_start;
+    }
+    ranges
+}
 
-            // Check if we've reached the end
-            if chunk_end >= self.samples.len() {
-                break;
+/// Whether each VAD frame of a WAV holds speech, and the length of a frame
+/// in seconds, measured in one pass over the audio
+fn speech_frames<R: std::io::Read>(mut reader: hound::WavReader<R>) -> Result<(Vec<bool>, f32)> {
+    let spec = reader.spec();
+    let samples_per_sec = spec.sample_rate as f32 * spec.channels as f32;
+    let frame_len = vad::frame_len(spec.sample_rate, spec.channels);
+    let samples = float_samples(&mut reader);
+
+    let mut frame = Vec::with_capacity(frame_len);
+    let mut frames = Vec::new();
+    for sample in samples {
+        frame.push(sample.context("Failed to read WAV samples")?);
+        if frame.len() < frame_len {
+            continue;
+        }
+        frames.push(vad::is_speech(&frame));
+        frame.clear();
+    }
+    Ok((frames, frame_len as f32 / samples_per_sec))
+}
+
+/// Which of the ranges hold any speech
+fn speech_in_ranges(frames: &[bool], frame_secs: f32, ranges: &[ChunkRange]) -> Vec<bool> {
+    let mut speech = vec![false; ranges.len()];
+    for (index, _) in frames
+        .iter()
+        .enumerate()
+        .filter(|(_, is_speech)| **is_speech)
+    {
+        let secs = index as f32 * frame_secs;
+        for (has_speech, range) in speech.iter_mut().zip(ranges) {
+            if (range.start_secs..range.start_secs + range.len_secs).contains(&secs) {
+                *has_speech = true;
             }
+        }
+    }
+    speech
+}
 
-            // Move to next chunk, stepping back by overlap amount
-            chunk_start = chunk_end.saturating_sub(overlap_samples);
+/// The samples of a WAV from the current position, scaled to -1..1
+fn float_samples<R: std::io::Read>(
+    reader: &mut hound::WavReader<R>,
+) -> Box<dyn Iterator<Item = hound::Result<f32>> + '_> {
+    let spec = reader.spec();
+    match spec.sample_format {
+        hound::SampleFormat::Float => Box::new(reader.samples::<f32>()),
+        hound::SampleFormat::Int => {
+            let scale = 1.0 / (1u64 << (spec.bits_per_sample - 1)) as f32;
+            Box::new(
+                reader
+                    .samples::<i32>()
+                    .map(move |sample| sample.map(|s| s as f32 * scale)),
+            )
         }
+    }
+}
+
+/// Copy WAV audio (or a `(start, length)` range of it in seconds) as 16-bit
+/// PCM WAV; what `transcode` does for [`AudioCodec::Wav`], without FFmpeg
+fn copy_wav<R: std::io::Read + std::io::Seek>(
+    mut reader: hound::WavReader<R>,
+    range: Option<(f32, f32)>,
+    mono: bool,
+) -> Result<Vec<u8>> {
+    let spec = reader.spec();
+    let (start, length) = range.unwrap_or((0.0, f32::INFINITY));
+    let first_frame = (start * spec.sample_rate as f32) as u32;
+    reader.seek(first_frame.min(reader.duration()))?;
+    let channels = usize::from(spec.channels.max(1));
+    // Saturates for the whole file
+    let frames = (length * spec.sample_rate as f32) as usize;
 
-        Ok(RecordingOutput::Chunked(chunks))
+    let samples = float_samples(&mut reader)
+        .take(frames.saturating_mul(channels))
+        .collect::<hound::Result<Vec<f32>>>()
+        .context("Failed to read WAV samples")?;
+    if mono && channels > 1 {
+        let mixed: Vec<f32> = samples
+            .chunks_exact(channels)
+            .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+            .collect();
+        write_wav(wav_spec(spec.sample_rate, 1), &mixed)
+    } else {
+        write_wav(wav_spec(spec.sample_rate, spec.channels), &samples)
     }
+}
+
+/// Encode a recording saved as WAV (e.g. a spill file left behind by a crash)
+/// without modifying it
+pub fn encode_wav_file(
+    path: &Path,
+    chunking: &ChunkConfig,
+    encoding: &AudioEncoding,
+) -> Result<RecordingOutput> {
+    chunking.validate()?;
+    encoding.validate()?;
+    encode_wav(
+        WavInput::File(path),
+        wav_duration_secs(path)?,
+        chunking,
+        encoding,
+    )
+}
+
+/// Duration of a WAV file in seconds, read from its header
+pub fn wav_duration_secs(path: &Path) -> Result<f32> {
+    let reader = hound::WavReader::open(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    // `duration` counts frames, not interleaved samples
+    Ok(reader.duration() as f32 / reader.spec().sample_rate as f32)
+}
 
-    /// Convert raw f32 samples to MP3 data
-    fn samples_to_mp3(&self, samples: &[f32], suffix: &str) -> Result<Vec<u8>> {
-        // Convert f32 samples to i16 for WAV format
-        let i16_samples: Vec<i16> = samples
+/// Sample formats that can be recorded, best first
+#[cfg(feature = "audio")]
+const SAMPLE_FORMATS: [cpal::SampleFormat; 10] = [
+    cpal::SampleFormat::F32,
+    cpal::SampleFormat::I16,
+    cpal::SampleFormat::I32,
+    cpal::SampleFormat::F64,
+    cpal::SampleFormat::I64,
+    cpal::SampleFormat::U16,
+    cpal::SampleFormat::U32,
+    cpal::SampleFormat::U64,
+    cpal::SampleFormat::I8,
+    cpal::SampleFormat::U8,
+];
+
+/// Pick the input config to record with (see `best_config`), falling back to
+/// the device default when the backend can't list its configs
+#[cfg(feature = "audio")]
+fn choose_config(device: &cpal::Device) -> Result<cpal::SupportedStreamConfig> {
+    let ranges: Vec<_> = match device.supported_input_configs() {
+        Ok(ranges) => ranges.collect(),
+        Err(_) => Vec::new(),
+    };
+    if let Some(config) = best_config(&ranges) {
+        return Ok(config);
+    }
+
+    let offered = if ranges.is_empty() {
+        let config = device
+            .default_input_config()
+            .context("Failed to get default input config")?;
+        if SAMPLE_FORMATS.contains(&config.sample_format()) {
+            return Ok(config);
+        }
+        config.sample_format().to_string()
+    } else {
+        let mut formats: Vec<String> = ranges
             .iter()
-            .map(|&s| {
-                let clamped = s.clamp(-1.0, 1.0);
-                (clamped * i16::MAX as f32) as i16
+            .map(|range| range.sample_format().to_string())
+            .collect();
+        formats.sort();
+        formats.dedup();
+        formats.join(", ")
+    };
+    let supported: Vec<String> = SAMPLE_FORMATS.iter().map(|f| f.to_string()).collect();
+    anyhow::bail!(
+        "The input device only offers unsupported sample formats ({offered}); whis records {}",
+        supported.join(", ")
+    )
+}
+
+/// The best config of `device` at exactly this sample rate (and channel
+/// count, if given), so its audio can continue an existing recording
+#[cfg(feature = "audio")]
+fn matching_config(
+    device: &cpal::Device,
+    sample_rate: u32,
+    channels: Option<u16>,
+) -> Option<cpal::SupportedStreamConfig> {
+    let rate = cpal::SampleRate(sample_rate);
+    let ranges: Vec<_> = device
+        .supported_input_configs()
+        .ok()?
+        .filter(|range| range.min_sample_rate() <= rate && rate <= range.max_sample_rate())
+        .filter(|range| channels.is_none_or(|c| c == range.channels()))
+        .map(|range| {
+            cpal::SupportedStreamConfigRange::new(
+                range.channels(),
+                rate,
+                rate,
+                *range.buffer_size(),
+                range.sample_format(),
+            )
+        })
+        .collect();
+    best_config(&ranges)
+}
+
+/// The config closest to what transcription wants: 16 kHz (else 48 kHz,
+/// else the nearest rate to it), then the fewest channels, then the best
+/// sample format
+#[cfg(feature = "audio")]
+fn best_config(ranges: &[cpal::SupportedStreamConfigRange]) -> Option<cpal::SupportedStreamConfig> {
+    ranges
+        .iter()
+        .filter_map(|range| {
+            let format_rank = SAMPLE_FORMATS
+                .iter()
+                .position(|&f| f == range.sample_format())?;
+            let (min, max) = (range.min_sample_rate().0, range.max_sample_rate().0);
+            let (rate_rank, rate) = if (min..=max).contains(&16_000) {
+                (0, 16_000)
+            } else if (min..=max).contains(&48_000) {
+                (1, 48_000)
+            } else {
+                (2, 48_000u32.clamp(min, max))
+            };
+            let config = range.with_sample_rate(cpal::SampleRate(rate));
+            Some(((rate_rank, range.channels(), format_rank), config))
+        })
+        .min_by_key(|(rank, _)| *rank)
+        .map(|(_, config)| config)
+}
+
+/// Append interleaved device samples to `out`, averaging every `mix`
+/// consecutive samples (one frame when downmixing, otherwise 1) into one
+#[cfg(feature = "audio")]
+fn push_downmixed<T>(out: &mut Vec<f32>, data: &[T], mix: usize)
+where
+    T: cpal::Sample,
+    f32: cpal::FromSample<T>,
+{
+    let mix = mix.max(1);
+    out.extend(data.chunks_exact(mix).map(|frame| {
+        let sum: f32 = frame
+            .iter()
+            .map(|&sample| <f32 as cpal::Sample>::from_sample(sample))
+            .sum();
+        sum / mix as f32
+    }));
+}
+
+/// Duration of interleaved samples: whole frames over the sample rate
+fn samples_to_secs(samples: usize, sample_rate: u32, channels: u16) -> f32 {
+    let frames = samples / usize::from(channels.max(1));
+    frames as f32 / sample_rate as f32
+}
+
+/// 16-bit PCM WAV format used for intermediate files
+pub(crate) fn wav_spec(sample_rate: u32, channels: u16) -> hound::WavSpec {
+    hound::WavSpec {
+        channels,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    }
+}
+
+/// Convert an f32 sample to i16 for WAV format
+pub(crate) fn to_i16(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+}
+
+/// Write samples as a complete WAV file in memory
+fn write_wav(spec: hound::WavSpec, samples: &[f32]) -> Result<Vec<u8>> {
+    let mut wav = std::io::Cursor::new(Vec::with_capacity(44 + samples.len() * 2));
+    let mut writer = hound::WavWriter::new(&mut wav, spec)?;
+    for &sample in samples {
+        writer.write_sample(to_i16(sample))?;
+    }
+    writer.finalize()?;
+    Ok(wav.into_inner())
+}
+
+/// Encode WAV audio (or a `(start, length)` range of it in seconds) using
+/// FFmpeg, piping in-memory audio through stdin and the result through stdout
+///
+/// WAV output is copied natively, so it works without FFmpeg.
+fn transcode(
+    input: WavInput,
+    range: Option<(f32, f32)>,
+    encoding: &AudioEncoding,
+) -> Result<Vec<u8>> {
+    if encoding.codec == AudioCodec::Wav {
+        return match input {
+            WavInput::File(path) => copy_wav(
+                hound::WavReader::open(path)
+                    .with_context(|| format!("Failed to read {}", path.display()))?,
+                range,
+                encoding.mono,
+            ),
+            WavInput::Memory(wav) => copy_wav(
+                hound::WavReader::new(std::io::Cursor::new(wav))?,
+                range,
+                encoding.mono,
+            ),
+        };
+    }
+
+    let mut command = Command::new("ffmpeg");
+    command.args(["-hide_banner", "-loglevel", "error"]);
+    if let Some((start, length)) = range {
+        command.args(["-ss", &format!("{start:.3}"), "-t", &format!("{length:.3}")]);
+    }
+    match input {
+        WavInput::File(path) => command.arg("-i").arg(path).stdin(Stdio::null()),
+        WavInput::Memory(_) => command.args(["-i", "pipe:0"]).stdin(Stdio::piped()),
+    };
+    let mut child = command
+        .args(encoding.codec.ffmpeg_args())
+        .args(["-b:a", encoding.bitrate()])
+        .args(if encoding.mono {
+            &["-ac", "1"][..]
+        } else {
+            &[]
+        })
+        .args(["-f", encoding.codec.ffmpeg_format(), "pipe:1"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to execute ffmpeg. Make sure ffmpeg is installed.")?;
+
+    // Feed stdin while reading stdout, so neither pipe fills up and blocks
+    let output = std::thread::scope(|scope| {
+        if let (WavInput::Memory(wav), Some(mut stdin)) = (input, child.stdin.take()) {
+            // ffmpeg closes stdin early when seeking past the end of a range
+            scope.spawn(move || {
+                let _ = stdin.write_all(wav);
+            });
+        }
+        child.wait_with_output()
+    })
+    .context("Failed to read encoded audio from ffmpeg")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("FFmpeg conversion failed: {stderr}");
+    }
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codec_from_str() {
+        assert_eq!("opus".parse(), Ok(AudioCodec::Opus));
+        assert_eq!("MP3".parse(), Ok(AudioCodec::Mp3));
+        assert_eq!("wav".parse(), Ok(AudioCodec::Wav));
+        assert!("aac".parse::<AudioCodec>().is_err());
+        assert_eq!(AudioEncoding::default().bitrate(), "128k");
+    }
+
+    #[test]
+    #[cfg(feature = "audio")]
+    fn test_downmix_stereo() {
+        // Left and right channels of three frames
+        let stereo = [0.5f32, -0.5, 0.25, 0.75, 1.0, 1.0];
+        let mut mono = Vec::new();
+        push_downmixed(&mut mono, &stereo, 2);
+        assert_eq!(mono, [0.0, 0.5, 1.0]);
+
+        let mut kept = Vec::new();
+        push_downmixed(&mut kept, &stereo, 1);
+        assert_eq!(kept, stereo);
+    }
+
+    #[test]
+    #[cfg(feature = "audio")]
+    fn test_downmix_integer_samples() {
+        let stereo = [i16::MAX, i16::MAX, 0, 0];
+        let mut mono = Vec::new();
+        push_downmixed(&mut mono, &stereo, 2);
+        assert_eq!(mono.len(), 2);
+        assert!((mono[0] - 1.0).abs() < 1e-3);
+        assert_eq!(mono[1], 0.0);
+    }
+
+    #[test]
+    #[cfg(feature = "audio")]
+    fn test_capture_clock_counts_gaps() {
+        let mut clock = CaptureClock::default();
+        // 100 ms buffers at 1 kHz, the third arriving 50 ms late
+        clock.record_at(Some(0.0), 100, 1000);
+        clock.record_at(Some(0.1), 100, 1000);
+        clock.record_at(Some(0.25), 100, 1000);
+        clock.record_at(Some(0.352), 100, 1000);
+        assert_eq!(clock.frames, 400);
+        assert_eq!(clock.dropouts, 1);
+        assert_eq!(clock.dropped_frames, 50);
+    }
+
+    #[test]
+    fn test_capture_report_warning() {
+        let report = |captured_secs, dropped_secs, dropouts| CaptureReport {
+            captured_secs,
+            wall_secs: 300.0,
+            dropped_secs,
+            dropouts,
+        };
+        assert_eq!(report(299.5, 0.0, 0).warning(), None);
+        let lost = report(299.5, 0.25, 2).warning().unwrap();
+        assert!(lost.contains("250 ms of audio lost in 2 dropout(s)"));
+        let drift = report(294.0, 0.0, 0).warning().unwrap();
+        assert!(drift.contains("(-2.0%)"), "{drift}");
+    }
+
+    #[cfg(feature = "audio")]
+    fn range(
+        channels: u16,
+        rates: (u32, u32),
+        format: cpal::SampleFormat,
+    ) -> cpal::SupportedStreamConfigRange {
+        cpal::SupportedStreamConfigRange::new(
+            channels,
+            cpal::SampleRate(rates.0),
+            cpal::SampleRate(rates.1),
+            cpal::SupportedBufferSize::Unknown,
+            format,
+        )
+    }
+
+    #[test]
+    #[cfg(feature = "audio")]
+    fn test_best_config_prefers_16k_mono() {
+        use cpal::SampleFormat::{F32, I16, I24, I32};
+
+        let config = best_config(&[
+            range(2, (44_100, 44_100), F32),
+            range(2, (8_000, 96_000), I16),
+            range(1, (8_000, 96_000), I32),
+        ])
+        .unwrap();
+        assert_eq!(config.sample_rate().0, 16_000);
+        assert_eq!(config.channels(), 1);
+        assert_eq!(config.sample_format(), I32);
+
+        // 48 kHz beats other fixed rates, unsupported formats are skipped
+        let config = best_config(&[
+            range(1, (48_000, 48_000), I24),
+            range(2, (44_100, 44_100), F32),
+            range(2, (48_000, 48_000), I16),
+        ])
+        .unwrap();
+        assert_eq!(config.sample_rate().0, 48_000);
+        assert_eq!(config.sample_format(), I16);
+
+        assert!(best_config(&[range(1, (16_000, 16_000), I24)]).is_none());
+    }
+
+    #[test]
+    fn test_duration_counts_frames() {
+        // One second of 16 kHz stereo
+        assert_eq!(samples_to_secs(32_000, 16_000, 2), 1.0);
+        assert_eq!(samples_to_secs(32_000, 16_000, 1), 2.0);
+        // A trailing half frame doesn't count
+        assert_eq!(samples_to_secs(32_001, 16_000, 2), 1.0);
+    }
+
+    #[test]
+    fn test_in_memory_wav_speech_ranges() {
+        // One second of silence, then one of a loud square wave
+        let samples: Vec<f32> = (0..32_000)
+            .map(|i| match (i < 16_000, i % 2) {
+                (true, _) => 0.0,
+                (false, 0) => 0.2,
+                (false, _) => -0.2,
             })
             .collect();
+        let wav = write_wav(wav_spec(16_000, 1), &samples).unwrap();
+        let reader = hound::WavReader::new(std::io::Cursor::new(&wav[..])).unwrap();
+        assert_eq!(reader.duration(), 32_000);
+        let (frames, frame_secs) = speech_frames(reader).unwrap();
+        let range = |start_secs, len_secs| ChunkRange {
+            start_secs,
+            len_secs,
+            overlaps_previous: false,
+        };
+        let speech = speech_in_ranges(&frames, frame_secs, &[range(0.0, 0.9), range(1.0, 1.0)]);
+        assert_eq!(speech, [false, true]);
+    }
 
-        // Use unique temp file names to support parallel FFmpeg calls
-        let temp_dir = std::env::temp_dir();
-        let unique_id = format!(
-            "{}_{}_{suffix}",
-            std::process::id(),
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_nanos(),
+    #[test]
+    fn test_split_at_pauses() {
+        let range = |start_secs, len_secs, overlaps_previous| ChunkRange {
+            start_secs,
+            len_secs,
+            overlaps_previous,
+        };
+        // One-second frames: speech, then a pause at 2-3s, then only speech
+        let frames = [true, true, false, true, true, true, true, true, true, true];
+        assert_eq!(
+            split_at_pauses(&frames, 1.0, 10.0, 4.0, 1.0),
+            [
+                range(0.0, 2.5, false),
+                range(2.5, 4.0, false),
+                range(5.5, 4.0, true),
+                range(8.5, 1.5, true),
+            ]
         );
-        let wav_path = temp_dir.join(format!("whis_{unique_id}.wav"));
-        let mp3_path = temp_dir.join(format!("whis_{unique_id}.mp3"));
-
-        {
-            let spec = hound::WavSpec {
-                channels: self.channels,
-                sample_rate: self.sample_rate,
-                bits_per_sample: 16,
-                sample_format: hound::SampleFormat::Int,
-            };
+    }
 
-            let mut writer = hound::WavWriter::create(&wav_path, spec)?;
-            for sample in i16_samples {
-                writer.write_sample(sample)?;
-            }
-            writer.finalize()?;
-        }
-
-        // Convert WAV to MP3 using FFmpeg
-        let output = std::process::Command::new("ffmpeg")
-            .args([
-                "-hide_banner",
-                "-loglevel",
-                "error",
-                "-i",
-                wav_path.to_str().unwrap(),
-                "-codec:a",
-                "libmp3lame",
-                "-b:a",
-                "128k",
-                "-y",
-                mp3_path.to_str().unwrap(),
-            ])
-            .output()
-            .context("Failed to execute ffmpeg. Make sure ffmpeg is installed.")?;
-
-        // Clean up the temporary WAV file
-        let _ = std::fs::remove_file(&wav_path);
-
-        if !output.status.success() {
-            let _ = std::fs::remove_file(&mp3_path);
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("FFmpeg conversion failed: {stderr}");
-        }
-
-        // Read the MP3 file
-        let mp3_data = std::fs::read(&mp3_path).context("Failed to read converted MP3 file")?;
-
-        // Clean up the temporary MP3 file
-        let _ = std::fs::remove_file(&mp3_path);
-
-        Ok(mp3_data)
+    #[test]
+    fn test_copy_wav_range_downmixed() {
+        // Two seconds of stereo at 1 kHz; the right channel is silent
+        let samples: Vec<f32> = (0..4_000)
+            .map(|i| if i % 2 == 0 { 0.5 } else { 0.0 })
+            .collect();
+        let wav = write_wav(wav_spec(1_000, 2), &samples).unwrap();
+        let reader = hound::WavReader::new(std::io::Cursor::new(&wav[..])).unwrap();
+
+        let copy = copy_wav(reader, Some((0.5, 1.0)), true).unwrap();
+        let mut reader = hound::WavReader::new(std::io::Cursor::new(&copy[..])).unwrap();
+        assert_eq!(reader.spec().channels, 1);
+        assert_eq!(reader.duration(), 1_000);
+        let first = reader.samples::<i16>().next().unwrap().unwrap();
+        assert_eq!(first, to_i16(0.25));
+    }
+
+    #[test]
+    fn test_parse_bitrate() {
+        assert_eq!(parse_bitrate("64k").unwrap(), 64_000);
+        assert_eq!(parse_bitrate("24000").unwrap(), 24_000);
+        for invalid in ["", "k", "64kbps", "1k", "1000k", "-64k"] {
+            assert!(parse_bitrate(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_default_chunk_config_is_valid() {
+        assert!(ChunkConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_overlap_must_be_shorter_than_chunk() {
+        let config = ChunkConfig {
+            duration_secs: 10,
+            overlap_secs: 10,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_disabled_chunking_skips_validation() {
+        let config = ChunkConfig {
+            enabled: false,
+            duration_secs: 0,
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
     }
 }
@@ -0,0 +1,175 @@
+//! State of a `whis batch` run
+//!
+//! The status of every file is saved to ~/.local/share/whis/batch.json after
+//! each one, so a run that was interrupted picks up where it left off when
+//! started again with the same files, and failures can be retried later
+//! without sending the finished files again.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where a file of a batch stands
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum FileStatus {
+    Pending,
+    Done,
+    Failed { error: String },
+}
+
+/// A file of a batch and its status
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchFile {
+    pub path: PathBuf,
+    #[serde(flatten)]
+    pub status: FileStatus,
+}
+
+/// The files of a batch, in the order they are transcribed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Job {
+    pub files: Vec<BatchFile>,
+}
+
+impl Job {
+    /// A job with every file pending
+    pub fn new(paths: &[PathBuf]) -> Self {
+        Self {
+            files: paths
+                .iter()
+                .map(|path| BatchFile {
+                    path: path.clone(),
+                    status: FileStatus::Pending,
+                })
+                .collect(),
+        }
+    }
+
+    /// Continue `stored` if it was started with the same files, otherwise
+    /// start over. Paths should be absolute so the working directory
+    /// doesn't matter.
+    pub fn resume(stored: Option<Job>, paths: &[PathBuf]) -> Self {
+        match stored {
+            Some(job) if job.files.iter().map(|f| &f.path).eq(paths) => job,
+            _ => Self::new(paths),
+        }
+    }
+
+    /// Mark failed files as pending again; returns how many there were
+    pub fn retry_failed(&mut self) -> usize {
+        let mut count = 0;
+        for file in &mut self.files {
+            if matches!(file.status, FileStatus::Failed { .. }) {
+                file.status = FileStatus::Pending;
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Indices of the files still to transcribe
+    pub fn pending(&self) -> Vec<usize> {
+        self.files
+            .iter()
+            .enumerate()
+            .filter(|(_, file)| file.status == FileStatus::Pending)
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Number of files with the given status
+    pub fn count(&self, status: impl Fn(&FileStatus) -> bool) -> usize {
+        self.files
+            .iter()
+            .filter(|file| status(&file.status))
+            .count()
+    }
+}
+
+/// Get the batch state path (~/.local/share/whis/batch.json)
+pub fn path() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("whis")
+        .join("batch.json")
+}
+
+/// The last batch job, if any
+pub fn load() -> Result<Option<Job>> {
+    let path = path();
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&content)
+        .map(Some)
+        .with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// Save the job so it can be resumed
+pub fn save(job: &Job) -> Result<()> {
+    let path = path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, serde_json::to_string_pretty(job)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Where the transcript of a batch file is written: next to it, as .txt
+pub fn output_path(path: &Path) -> PathBuf {
+    path.with_extension("txt")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(names: &[&str]) -> Vec<PathBuf> {
+        names.iter().map(PathBuf::from).collect()
+    }
+
+    #[test]
+    fn test_resume_same_files() {
+        let mut job = Job::new(&paths(&["/a.mp3", "/b.mp3", "/c.mp3"]));
+        job.files[0].status = FileStatus::Done;
+        job.files[1].status = FileStatus::Failed {
+            error: "timeout".to_string(),
+        };
+
+        let resumed = Job::resume(Some(job.clone()), &paths(&["/a.mp3", "/b.mp3", "/c.mp3"]));
+        assert_eq!(resumed, job);
+        assert_eq!(resumed.pending(), [2]);
+
+        let other = Job::resume(Some(job), &paths(&["/a.mp3", "/b.mp3"]));
+        assert_eq!(other.pending(), [0, 1]);
+    }
+
+    #[test]
+    fn test_retry_failed() {
+        let mut job = Job::new(&paths(&["/a.mp3", "/b.mp3"]));
+        job.files[0].status = FileStatus::Done;
+        job.files[1].status = FileStatus::Failed {
+            error: "timeout".to_string(),
+        };
+        assert_eq!(job.count(|s| matches!(s, FileStatus::Failed { .. })), 1);
+        assert_eq!(job.retry_failed(), 1);
+        assert_eq!(job.pending(), [1]);
+        assert_eq!(job.count(|s| *s == FileStatus::Done), 1);
+    }
+
+    #[test]
+    fn test_state_format() {
+        let mut job = Job::new(&paths(&["/a.mp3"]));
+        job.files[0].status = FileStatus::Failed {
+            error: "timeout".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_string(&job).unwrap(),
+            r#"{"files":[{"path":"/a.mp3","status":"failed","error":"timeout"}]}"#
+        );
+    }
+}
@@ -0,0 +1,342 @@
+//! Bluetooth headset profiles
+//!
+//! A Bluetooth headset has no microphone in its A2DP (playback) profile, and
+//! records narrowband 8 kHz audio in the HFP profile with the CVSD codec, so
+//! recordings through it silently come out empty or garbled. This asks the
+//! sound server (PulseAudio or PipeWire, through `pactl`) for the profile of
+//! connected headsets to warn about both, and can switch a headset to its
+//! best headset profile while recording (`"bluetooth_switch"`). Linux only.
+
+use anyhow::{Context, Result};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// How long to wait for the headset microphone to appear after a switch
+const SWITCH_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// A Bluetooth audio device as the sound server knows it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Headset {
+    /// Card name, e.g. "bluez_card.00_1B_66_AA_BB_CC"
+    pub card: String,
+    /// Name shown to users, e.g. "WH-1000XM4"
+    pub description: String,
+    pub active_profile: String,
+    profiles: Vec<CardProfile>,
+}
+
+/// A profile a card can be switched to
+#[derive(Debug, Clone, PartialEq)]
+struct CardProfile {
+    name: String,
+    description: String,
+    available: bool,
+}
+
+/// What a headset can record in a profile
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    /// A2DP: playback only, no microphone
+    Playback,
+    /// HSP/HFP with the 8 kHz CVSD codec
+    Narrowband,
+    /// HSP/HFP with a wideband codec (mSBC), or one that isn't named
+    Headset,
+    /// Off or a profile whis doesn't know
+    Other,
+}
+
+impl Headset {
+    pub fn mode(&self) -> Mode {
+        let description = self
+            .profiles
+            .iter()
+            .find(|p| p.name == self.active_profile)
+            .map_or("", |p| p.description.as_str());
+        profile_mode(&self.active_profile, description)
+    }
+
+    /// The headset profile recording in the best quality, if it has one
+    fn best_headset_profile(&self) -> Option<&str> {
+        let headset = |p: &&CardProfile| p.available && is_headset_profile(&p.name);
+        let quality = |p: &CardProfile| match profile_mode(&p.name, &p.description) {
+            Mode::Narrowband => 0,
+            _ if p.name.contains("msbc") => 2,
+            _ => 1,
+        };
+        self.profiles
+            .iter()
+            .filter(headset)
+            .max_by_key(|p| quality(p))
+            .map(|p| p.name.as_str())
+    }
+
+    /// Whether a source (microphone) belongs to this headset: both carry
+    /// its address, e.g. "bluez_input.00_1B_66_AA_BB_CC.0"
+    fn owns(&self, source: &str) -> bool {
+        address(&self.card).is_some_and(|card| address(source) == Some(card))
+    }
+}
+
+/// Connected Bluetooth headsets; none if `pactl` isn't there
+pub fn headsets() -> Vec<Headset> {
+    pactl(&["list", "cards"])
+        .map(|output| parse_cards(&output))
+        .unwrap_or_default()
+}
+
+/// A warning if recording from the default microphone is affected by a
+/// Bluetooth headset: one in A2DP mode (its microphone unavailable) or the
+/// default microphone recording in narrowband
+pub fn check() -> Option<String> {
+    let headsets = headsets();
+    if headsets.is_empty() {
+        return None;
+    }
+    let source = default_source();
+    if let Some(headset) = source
+        .as_deref()
+        .and_then(|source| headsets.iter().find(|h| h.owns(source)))
+    {
+        return (headset.mode() == Mode::Narrowband).then(|| {
+            format!(
+                "Bluetooth headset {} records in narrowband (8 kHz CVSD), which hurts accuracy; \
+                 switch it to mSBC or use another microphone",
+                headset.description
+            )
+        });
+    }
+    headsets
+        .iter()
+        .find(|h| h.mode() == Mode::Playback && h.best_headset_profile().is_some())
+        .map(|headset| {
+            format!(
+                "Bluetooth headset {} is in A2DP mode, which has no microphone; set \
+                 \"bluetooth_switch\": true to switch it while recording",
+                headset.description
+            )
+        })
+}
+
+/// A headset switched to its headset profile, switched back when dropped
+#[derive(Debug)]
+pub struct ProfileSwitch {
+    card: String,
+    previous: String,
+}
+
+impl Drop for ProfileSwitch {
+    /// Switch back to the profile before the recording
+    fn drop(&mut self) {
+        if let Err(e) = set_profile(&self.card, &self.previous) {
+            eprintln!("Bluetooth: {e:#}");
+        }
+    }
+}
+
+/// Before recording: switch a headset in A2DP mode if `switch` is set, or
+/// warn about a headset that would spoil the recording. Problems are
+/// printed, as they shouldn't keep anyone from recording. Keep the returned
+/// switch until the recording stops.
+pub fn prepare(switch: bool) -> Option<ProfileSwitch> {
+    if switch {
+        match switch_to_headset() {
+            Ok(Some(switched)) => return Some(switched),
+            Ok(None) => {}
+            Err(e) => eprintln!("Bluetooth: {e:#}"),
+        }
+    }
+    if let Some(warning) = check() {
+        eprintln!("{warning}");
+    }
+    None
+}
+
+/// Switch a headset in A2DP mode to its best headset profile, so its
+/// microphone can be recorded, and wait for the microphone to become the
+/// default. `None` if no headset needs switching.
+pub fn switch_to_headset() -> Result<Option<ProfileSwitch>> {
+    let headsets = headsets();
+    let Some((headset, profile)) = headsets
+        .iter()
+        .filter(|h| h.mode() == Mode::Playback)
+        .find_map(|h| Some((h, h.best_headset_profile()?)))
+    else {
+        return Ok(None);
+    };
+    set_profile(&headset.card, profile)?;
+    let switch = ProfileSwitch {
+        card: headset.card.clone(),
+        previous: headset.active_profile.clone(),
+    };
+
+    let started = Instant::now();
+    while started.elapsed() < SWITCH_TIMEOUT {
+        if default_source().is_some_and(|source| headset.owns(&source)) {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    Ok(Some(switch))
+}
+
+fn set_profile(card: &str, profile: &str) -> Result<()> {
+    pactl(&["set-card-profile", card, profile])
+        .map(|_| ())
+        .with_context(|| format!("Failed to switch {card} to {profile}"))
+}
+
+/// Name of the default source, from `pactl info`
+fn default_source() -> Option<String> {
+    let info = pactl(&["info"]).ok()?;
+    info.lines()
+        .find_map(|line| line.strip_prefix("Default Source:"))
+        .map(|source| source.trim().to_string())
+}
+
+fn pactl(args: &[&str]) -> Result<String> {
+    let output = Command::new("pactl")
+        .args(args)
+        // Stable output whatever the locale
+        .env("LC_ALL", "C")
+        .stdin(Stdio::null())
+        .output()
+        .context("Failed to run pactl")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("pactl failed: {}", stderr.trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Bluetooth cards in `pactl list cards` output
+fn parse_cards(output: &str) -> Vec<Headset> {
+    let mut headsets = Vec::new();
+    for block in output.split("\nCard #").skip_while(|b| b.trim().is_empty()) {
+        let lines = block.lines().map(str::trim);
+        let Some(card) = lines
+            .clone()
+            .find_map(|line| line.strip_prefix("Name:"))
+            .map(str::trim)
+            .filter(|name| name.starts_with("bluez_card."))
+        else {
+            continue;
+        };
+        let description = lines
+            .clone()
+            .find_map(|line| line.strip_prefix("device.description = "))
+            .map_or(card, |d| d.trim_matches('"'));
+        let active_profile = lines
+            .clone()
+            .find_map(|line| line.strip_prefix("Active Profile:"))
+            .map_or("", str::trim);
+        let profiles = lines
+            .skip_while(|line| *line != "Profiles:")
+            .skip(1)
+            .map_while(parse_profile)
+            .collect();
+        headsets.push(Headset {
+            card: card.to_string(),
+            description: description.to_string(),
+            active_profile: active_profile.to_string(),
+            profiles,
+        });
+    }
+    headsets
+}
+
+/// A line like `a2dp-sink: High Fidelity Playback (A2DP Sink) (sinks: 1,
+/// sources: 0, priority: 40, available: yes)`
+fn parse_profile(line: &str) -> Option<CardProfile> {
+    let (name, rest) = line.split_once(": ")?;
+    let (description, details) = rest.rsplit_once(" (sinks:")?;
+    Some(CardProfile {
+        name: name.to_string(),
+        description: description.to_string(),
+        available: !details.contains("available: no"),
+    })
+}
+
+/// Profiles with a microphone: "headset-head-unit" (PipeWire) and
+/// "headset_head_unit" or "handsfree_head_unit" (PulseAudio)
+fn is_headset_profile(name: &str) -> bool {
+    name.contains("head-unit") || name.contains("head_unit")
+}
+
+fn profile_mode(name: &str, description: &str) -> Mode {
+    if name.starts_with("a2dp") {
+        Mode::Playback
+    } else if !is_headset_profile(name) {
+        Mode::Other
+    } else if name.contains("cvsd") || description.to_ascii_lowercase().contains("cvsd") {
+        Mode::Narrowband
+    } else {
+        Mode::Headset
+    }
+}
+
+/// The device address in a card or source name, e.g. "00_1B_66_AA_BB_CC"
+fn address(name: &str) -> Option<&str> {
+    name.split('.').nth(1).filter(|address| !address.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CARDS: &str = "Card #41
+\tName: alsa_card.pci-0000_00_1f.3
+\tDriver: alsa
+\tProfiles:
+\t\toff: Off (sinks: 0, sources: 0, priority: 0, available: yes)
+\tActive Profile: off
+
+Card #42
+\tName: bluez_card.00_1B_66_AA_BB_CC
+\tDriver: module-bluez5-device.c
+\tProperties:
+\t\tdevice.description = \"WH-1000XM4\"
+\tProfiles:
+\t\toff: Off (sinks: 0, sources: 0, priority: 0, available: yes)
+\t\ta2dp-sink: High Fidelity Playback (A2DP Sink) (sinks: 1, sources: 0, priority: 40, available: yes)
+\t\theadset-head-unit-cvsd: Headset Head Unit (HSP/HFP, codec CVSD) (sinks: 1, sources: 1, priority: 20, available: yes)
+\t\theadset-head-unit-msbc: Headset Head Unit (HSP/HFP, codec mSBC) (sinks: 1, sources: 1, priority: 30, available: yes)
+\tActive Profile: a2dp-sink
+\tPorts:
+\t\theadset-output: Headset (type: Headset, priority: 0, available)
+";
+
+    #[test]
+    fn test_parse_cards() {
+        let headsets = parse_cards(CARDS);
+        assert_eq!(headsets.len(), 1);
+        let headset = &headsets[0];
+        assert_eq!(headset.card, "bluez_card.00_1B_66_AA_BB_CC");
+        assert_eq!(headset.description, "WH-1000XM4");
+        assert_eq!(headset.mode(), Mode::Playback);
+        assert_eq!(headset.profiles.len(), 4);
+        assert_eq!(
+            headset.best_headset_profile(),
+            Some("headset-head-unit-msbc")
+        );
+        assert!(headset.owns("bluez_input.00_1B_66_AA_BB_CC.0"));
+        assert!(!headset.owns("alsa_input.pci-0000_00_1f.3.analog-stereo"));
+    }
+
+    #[test]
+    fn test_profile_mode() {
+        assert_eq!(profile_mode("a2dp_sink", ""), Mode::Playback);
+        assert_eq!(
+            profile_mode(
+                "headset-head-unit",
+                "Headset Head Unit (HSP/HFP, codec CVSD)"
+            ),
+            Mode::Narrowband
+        );
+        assert_eq!(
+            profile_mode("handsfree_head_unit", "Handsfree Head Unit (HFP)"),
+            Mode::Headset
+        );
+        assert_eq!(profile_mode("off", "Off"), Mode::Other);
+    }
+}
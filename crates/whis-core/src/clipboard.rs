@@ -1,8 +1,23 @@
+//! Copy transcripts to the clipboard
+//!
+//! The system clipboard is set with arboard (or wl-copy in Flatpak). Over
+//! SSH there is no clipboard of the desktop to set, so an OSC 52 escape
+//! sequence asks the terminal to set the clipboard on its end instead.
+//! Most terminals support it; tmux passes it on with `allow-passthrough`.
+//!
+//! Inside tmux or GNU screen, the text is also loaded into their paste
+//! buffer, to paste with prefix+] (or C-a ]) without leaving the terminal.
+
 use anyhow::{Context, Result};
 use arboard::Clipboard;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use std::fs::OpenOptions;
 use std::io::Write;
 use std::process::{Command, Stdio};
 
+use crate::settings::{ClipboardBackend, ClipboardSelection};
+
 /// Check if running inside a Flatpak sandbox
 fn is_flatpak() -> bool {
     std::path::Path::new("/.flatpak-info").exists()
@@ -13,38 +28,198 @@ fn is_flatpak() -> bool {
 /// In Flatpak, we bundle wl-clipboard and call wl-copy directly.
 /// This is required because GNOME/Mutter does not implement the wlr-data-control
 /// Wayland protocol that arboard's wayland-data-control feature requires.
-fn copy_via_wl_copy(text: &str) -> Result<()> {
-    let mut child = Command::new("wl-copy")
+fn copy_via_wl_copy(text: &str, primary: bool) -> Result<()> {
+    let mut command = Command::new("wl-copy");
+    if primary {
+        command.arg("--primary");
+    }
+    pipe_to(&mut command, text)
+}
+
+/// Run `command` with `text` on its stdin
+fn pipe_to(command: &mut Command, text: &str) -> Result<()> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let mut child = command
         .stdin(Stdio::piped())
         .spawn()
-        .context("Failed to spawn wl-copy")?;
+        .with_context(|| format!("Failed to spawn {program}"))?;
 
     if let Some(mut stdin) = child.stdin.take() {
         stdin
             .write_all(text.as_bytes())
-            .context("Failed to write to wl-copy")?;
+            .with_context(|| format!("Failed to write to {program}"))?;
     }
 
-    let status = child.wait().context("Failed to wait for wl-copy")?;
+    let status = child
+        .wait()
+        .with_context(|| format!("Failed to wait for {program}"))?;
     if !status.success() {
-        anyhow::bail!("wl-copy exited with non-zero status");
+        anyhow::bail!("{program} exited with non-zero status");
     }
 
     Ok(())
 }
 
-pub fn copy_to_clipboard(text: &str) -> Result<()> {
+/// Copy `text` to the clipboard, the primary selection or both
+pub fn copy_to_clipboard(
+    text: &str,
+    selection: ClipboardSelection,
+    backend: ClipboardBackend,
+) -> Result<()> {
+    let osc52 = match backend {
+        ClipboardBackend::Auto => {
+            // The paste buffer comes in addition to the clipboard here
+            if let Some(multiplexer) = Multiplexer::detect()
+                && let Err(e) = multiplexer.load_buffer(text)
+            {
+                eprintln!("Failed to load the {multiplexer:?} paste buffer: {e:#}");
+            }
+            is_remote_session()
+        }
+        ClipboardBackend::System => false,
+        ClipboardBackend::Osc52 => true,
+        ClipboardBackend::Multiplexer => {
+            return Multiplexer::detect()
+                .context("Not running inside tmux or screen")?
+                .load_buffer(text);
+        }
+    };
+    if osc52 {
+        return copy_via_osc52(text, selection);
+    }
+
     // In Flatpak, use bundled wl-copy directly.
     // This is necessary because GNOME doesn't support wlr-data-control protocol.
     if is_flatpak() {
-        return copy_via_wl_copy(text);
+        if selection.includes_clipboard() {
+            copy_via_wl_copy(text, false)?;
+        }
+        if selection.includes_primary() {
+            copy_via_wl_copy(text, true)?;
+        }
+        return Ok(());
     }
 
     // Standard approach for non-Flatpak environments
     let mut clipboard = Clipboard::new().context("Failed to access clipboard")?;
+    if selection.includes_clipboard() {
+        clipboard
+            .set_text(text)
+            .context("Failed to copy text to clipboard")?;
+    }
+    if selection.includes_primary() {
+        set_primary(&mut clipboard, text)?;
+    }
+
+    Ok(())
+}
+
+/// A terminal multiplexer with a paste buffer
+#[derive(Debug, Clone, Copy)]
+enum Multiplexer {
+    Tmux,
+    Screen,
+}
+
+impl Multiplexer {
+    /// The multiplexer whis runs inside, if any
+    fn detect() -> Option<Self> {
+        if std::env::var_os("TMUX").is_some() {
+            Some(Self::Tmux)
+        } else if std::env::var_os("STY").is_some() {
+            Some(Self::Screen)
+        } else {
+            None
+        }
+    }
+
+    /// Replace the paste buffer with `text`
+    fn load_buffer(self, text: &str) -> Result<()> {
+        match self {
+            Self::Tmux => pipe_to(Command::new("tmux").args(["load-buffer", "-"]), text),
+            Self::Screen => {
+                // The "." register is the paste buffer of screen
+                let status = Command::new("screen")
+                    .args(["-X", "register", ".", text])
+                    .status()
+                    .context("Failed to run screen")?;
+                if !status.success() {
+                    anyhow::bail!("screen exited with {status}");
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// An SSH session without a display, where the clipboard to set is on the
+/// other end
+fn is_remote_session() -> bool {
+    let ssh = std::env::var_os("SSH_TTY").is_some() || std::env::var_os("SSH_CONNECTION").is_some();
+    let display =
+        std::env::var_os("DISPLAY").is_some() || std::env::var_os("WAYLAND_DISPLAY").is_some();
+    ssh && !display
+}
+
+/// Send the text to the controlling terminal as an OSC 52 sequence
+fn copy_via_osc52(text: &str, selection: ClipboardSelection) -> Result<()> {
+    let sequence = osc52(text, selection, std::env::var_os("TMUX").is_some());
+    let mut tty = OpenOptions::new()
+        .write(true)
+        .open("/dev/tty")
+        .context("No terminal to send the clipboard to (OSC 52)")?;
+    tty.write_all(sequence.as_bytes())
+        .and_then(|()| tty.flush())
+        .context("Failed to write to the terminal")
+}
+
+/// The OSC 52 sequence setting `selection` to `text`, wrapped for tmux to
+/// pass it on to the outer terminal
+fn osc52(text: &str, selection: ClipboardSelection, tmux: bool) -> String {
+    let target = match selection {
+        ClipboardSelection::Clipboard => "c",
+        ClipboardSelection::Primary => "p",
+        ClipboardSelection::Both => "cp",
+    };
+    let sequence = format!("\x1b]52;{target};{}\x07", BASE64.encode(text));
+    if tmux {
+        // Escapes inside a tmux passthrough sequence are doubled
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn set_primary(clipboard: &mut Clipboard, text: &str) -> Result<()> {
+    use arboard::{LinuxClipboardKind, SetExtLinux};
+
     clipboard
-        .set_text(text)
-        .context("Failed to copy text to clipboard")?;
+        .set()
+        .clipboard(LinuxClipboardKind::Primary)
+        .text(text)
+        .context("Failed to copy text to the primary selection")
+}
 
+/// Only X11 and Wayland have a primary selection
+#[cfg(not(target_os = "linux"))]
+fn set_primary(_clipboard: &mut Clipboard, _text: &str) -> Result<()> {
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_osc52() {
+        assert_eq!(
+            osc52("hi", ClipboardSelection::Clipboard, false),
+            "\x1b]52;c;aGk=\x07"
+        );
+        assert_eq!(
+            osc52("hi", ClipboardSelection::Both, true),
+            "\x1bPtmux;\x1b\x1b]52;cp;aGk=\x07\x1b\\"
+        );
+    }
+}
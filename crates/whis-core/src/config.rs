@@ -1,17 +1,212 @@
 use anyhow::{Context, Result};
 use std::env;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 
+use crate::provider::{
+    Backend, HttpOptions, Provider, assemblyai, azure, fallback, openai, vosk, whisper_server,
+};
+use crate::request::TranscriptionRequest;
+use crate::settings::Settings;
+
+/// Environment variable holding the Azure region
+const AZURE_REGION_VAR: &str = "AZURE_SPEECH_REGION";
+/// Environment variable holding the Whisper server URL
+const SERVER_URL_VAR: &str = "WHISPER_SERVER_URL";
+
+#[derive(Clone)]
 pub struct ApiConfig {
-    pub openai_api_key: String,
+    pub backend: Backend,
+    /// API key (empty for local backends)
+    pub api_key: String,
+    /// Region of the Azure Speech resource
+    pub region: Option<String>,
+    /// Model directory for local backends
+    pub model_path: Option<PathBuf>,
+    /// URL of a self-hosted Whisper server
+    pub server_url: Option<String>,
+    /// Request timeout and upload limit
+    pub http: HttpOptions,
+    /// Model, language, prompt and decoding options
+    pub request: TranscriptionRequest,
+    /// Backends tried in order when this one fails
+    pub fallbacks: Vec<ApiConfig>,
 }
 
 impl ApiConfig {
-    pub fn from_env() -> Result<Self> {
-        dotenvy::dotenv().ok(); // Load .env file if it exists
+    pub fn from_env(backend: Backend) -> Result<Self> {
+        Self::new(backend, env_api_key(backend)?, None, None, None)
+    }
+
+    /// Use the key from the settings file, falling back to the environment
+    ///
+    /// Fallback backends that aren't configured are skipped with a warning.
+    pub fn from_settings(settings: &Settings) -> Result<Self> {
+        let mut config = Self::for_backend(settings, settings.backend)?;
+        for &backend in &settings.backend_fallback {
+            let configured = backend == settings.backend
+                || config.fallbacks.iter().any(|c| c.backend == backend);
+            if configured {
+                continue;
+            }
+            match Self::for_backend(settings, backend) {
+                Ok(fallback) => config.fallbacks.push(fallback),
+                Err(e) => eprintln!("Skipping fallback backend {backend}: {e:#}"),
+            }
+        }
+        Ok(config)
+    }
+
+    /// Configure a single backend, without fallbacks
+    pub fn for_backend(settings: &Settings, backend: Backend) -> Result<Self> {
+        let api_key = match settings.api_key(backend) {
+            Some(key) => key.to_string(),
+            None => env_api_key(backend)?,
+        };
+        let mut config = Self::new(
+            backend,
+            api_key,
+            settings.azure_region.clone(),
+            settings.model_path.clone(),
+            settings.server_url.clone(),
+        )?;
+        config.http = HttpOptions {
+            timeout: Duration::from_secs(settings.request_timeout_secs),
+            upload_limit_kbps: settings.upload_limit_kbps,
+        };
+        let mut request = TranscriptionRequest::builder();
+        // Model names are specific to a backend, so fallbacks keep their default
+        if backend == settings.backend
+            && let Some(model) = &settings.model
+        {
+            request = request.model(model);
+        }
+        if let Some(language) = &settings.language {
+            request = request.language(language);
+        }
+        if let Some(prompt) = &settings.prompt {
+            request = request.prompt(prompt);
+        }
+        if let Some(temperature) = settings.temperature {
+            request = request.temperature(temperature);
+        }
+        if let Some(format) = settings.response_format {
+            request = request.response_format(format);
+        }
+        if let Some(beam_size) = settings.beam_size {
+            request = request.beam_size(beam_size);
+        }
+        if let Some(best_of) = settings.best_of {
+            request = request.best_of(best_of);
+        }
+        request = request.filter_profanity(settings.profanity_filter);
+        config.request = request.build()?;
+        Ok(config)
+    }
 
-        let openai_api_key = env::var("OPENAI_API_KEY")
-            .context("OPENAI_API_KEY not found. Please set it in .env file or environment")?;
+    fn new(
+        backend: Backend,
+        api_key: String,
+        region: Option<String>,
+        model_path: Option<PathBuf>,
+        server_url: Option<String>,
+    ) -> Result<Self> {
+        let region = region.or_else(|| env::var(AZURE_REGION_VAR).ok());
+        if backend == Backend::Azure && region.is_none() {
+            anyhow::bail!(
+                "No Azure region configured. Set azure_region in the settings file or ${AZURE_REGION_VAR}"
+            );
+        }
+        if backend == Backend::Vosk {
+            match &model_path {
+                Some(path) if path.is_dir() => {}
+                Some(path) => anyhow::bail!("Vosk model not found at {}", path.display()),
+                None => anyhow::bail!(
+                    "No Vosk model configured. Use --model-path or set model_path in the settings file"
+                ),
+            }
+        }
+        let server_url = server_url.or_else(|| env::var(SERVER_URL_VAR).ok());
+        if backend == Backend::WhisperServer {
+            match &server_url {
+                Some(url) => {
+                    reqwest::Url::parse(url)
+                        .with_context(|| format!("Invalid Whisper server URL: {url}"))?;
+                }
+                None => anyhow::bail!(
+                    "No Whisper server configured. Use --url, set server_url in the settings file or ${SERVER_URL_VAR}"
+                ),
+            }
+        }
+        Ok(ApiConfig {
+            backend,
+            api_key,
+            region,
+            model_path,
+            server_url,
+            http: HttpOptions::default(),
+            request: TranscriptionRequest::default(),
+            fallbacks: Vec::new(),
+        })
+    }
 
-        Ok(ApiConfig { openai_api_key })
+    /// The same backends, transcribing in `language` (a language code)
+    pub fn with_language(&self, language: &str) -> Self {
+        Self {
+            request: self.request.clone().with_language(language),
+            fallbacks: self
+                .fallbacks
+                .iter()
+                .map(|fallback| fallback.with_language(language))
+                .collect(),
+            ..self.clone()
+        }
     }
+
+    /// Create the provider for the configured backend and its fallbacks
+    pub fn provider(&self) -> Arc<dyn Provider> {
+        if self.fallbacks.is_empty() {
+            return self.backend_provider();
+        }
+        let chain = std::iter::once(self)
+            .chain(&self.fallbacks)
+            .map(ApiConfig::backend_provider)
+            .collect();
+        Arc::new(fallback::Fallback::new(chain))
+    }
+
+    fn backend_provider(&self) -> Arc<dyn Provider> {
+        let api_key = self.api_key.clone();
+        let request = self.request.clone();
+        // Region, model path and server URL are checked when the config is created
+        match self.backend {
+            Backend::OpenAI => Arc::new(openai::OpenAI::new(api_key, self.http, request)),
+            Backend::AssemblyAI => {
+                Arc::new(assemblyai::AssemblyAI::new(api_key, self.http, request))
+            }
+            Backend::Azure => Arc::new(azure::Azure::new(
+                api_key,
+                self.region.clone().unwrap_or_default(),
+                self.http,
+                request,
+            )),
+            Backend::Vosk => Arc::new(vosk::Vosk::new(self.model_path.clone().unwrap_or_default())),
+            Backend::WhisperServer => Arc::new(whisper_server::WhisperServer::new(
+                self.server_url.as_deref().unwrap_or_default(),
+                self.http,
+                request,
+            )),
+        }
+    }
+}
+
+fn env_api_key(backend: Backend) -> Result<String> {
+    let Some(var) = backend.env_var() else {
+        return Ok(String::new());
+    };
+
+    dotenvy::dotenv().ok(); // Load .env file if it exists
+    env::var(var)
+        .with_context(|| format!("{var} not found. Please set it in .env file or environment"))
 }
@@ -0,0 +1,311 @@
+//! Read audio and video files in any format FFmpeg understands
+//!
+//! Input files are decoded to 16 kHz mono WAV, the format recordings are
+//! captured in, in a private temp file. From there they take the same path
+//! as a recording: encoded for the backend and split into chunks if large.
+//!
+//! Without FFmpeg, mp3, ogg, flac and wav files are decoded natively with
+//! symphonia (the `native-decode` feature) and uploaded as WAV, so files can
+//! be transcribed on minimal systems. Other containers still need FFmpeg.
+
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use tempfile::NamedTempFile;
+
+use crate::audio::{self, AudioCodec, AudioEncoding, ChunkConfig, RecordingOutput};
+use crate::temp;
+
+/// Sample rate files are decoded to, the rate Whisper models work at
+pub const SAMPLE_RATE: u32 = 16_000;
+
+/// Part of a file to decode, in seconds from its start
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Trim {
+    pub start_secs: f32,
+    /// Up to the end of the file if unset
+    pub end_secs: Option<f32>,
+}
+
+impl Trim {
+    /// Length of the part, if it has an end
+    fn len_secs(&self) -> Option<f32> {
+        self.end_secs.map(|end| (end - self.start_secs).max(0.0))
+    }
+
+    /// The samples of the part, from samples at `sample_rate`
+    #[cfg_attr(not(feature = "native-decode"), allow(dead_code))]
+    fn apply<'a>(&self, samples: &'a [f32], sample_rate: u32) -> &'a [f32] {
+        let index = |secs: f32| ((secs * sample_rate as f32) as usize).min(samples.len());
+        let start = index(self.start_secs);
+        let end = self.end_secs.map_or(samples.len(), index).max(start);
+        &samples[start..end]
+    }
+}
+
+/// An input file decoded to WAV, deleted when dropped
+pub struct DecodedFile {
+    wav: NamedTempFile,
+    pub duration_secs: f32,
+}
+
+impl DecodedFile {
+    /// The decoded WAV
+    pub fn path(&self) -> &Path {
+        self.wav.path()
+    }
+
+    /// Encode for upload like a recording, chunking it if needed
+    pub fn encode(
+        &self,
+        chunking: &ChunkConfig,
+        encoding: &AudioEncoding,
+    ) -> Result<RecordingOutput> {
+        audio::encode_wav_file(self.path(), chunking, encoding)
+    }
+}
+
+/// Whether the `ffmpeg` binary can be run
+pub fn ffmpeg_available() -> bool {
+    Command::new("ffmpeg")
+        .arg("-version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// The encoding to upload decoded files with: `encoding` if FFmpeg is
+/// there to encode it, otherwise WAV, which every backend reads
+pub fn upload_encoding(encoding: AudioEncoding) -> AudioEncoding {
+    if encoding.codec == AudioCodec::Wav || ffmpeg_available() {
+        encoding
+    } else {
+        AudioEncoding {
+            codec: AudioCodec::Wav,
+            bitrate: None,
+            ..encoding
+        }
+    }
+}
+
+/// Decode the `trim` part of the audio track of `path`, natively if FFmpeg
+/// isn't installed
+pub fn decode(path: &Path, trim: Trim) -> Result<DecodedFile> {
+    #[cfg(feature = "native-decode")]
+    if !ffmpeg_available() && native::supports(path) {
+        return native::decode(path, trim);
+    }
+    decode_with_ffmpeg(path, trim)
+}
+
+fn decode_with_ffmpeg(path: &Path, trim: Trim) -> Result<DecodedFile> {
+    let wav = temp::file("wav")?;
+    let mut command = Command::new("ffmpeg");
+    command.args(["-hide_banner", "-loglevel", "error", "-y"]);
+    // Seeking before the input skips decoding what comes before
+    if trim.start_secs > 0.0 {
+        command.args(["-ss", &trim.start_secs.to_string()]);
+    }
+    command.arg("-i").arg(path);
+    if let Some(len) = trim.len_secs() {
+        command.args(["-t", &len.to_string()]);
+    }
+    let output = command
+        .args(["-vn", "-ac", "1", "-ar", &SAMPLE_RATE.to_string()])
+        .args(["-c:a", "pcm_s16le", "-f", "wav"])
+        .arg(wav.path())
+        .stdin(Stdio::null())
+        .output()
+        .context("Failed to execute ffmpeg. Make sure ffmpeg is installed.")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to decode {}: {}", path.display(), stderr.trim());
+    }
+
+    let duration_secs = audio::wav_duration_secs(wav.path())?;
+    if duration_secs == 0.0 {
+        anyhow::bail!("No audio in the selected part of {}", path.display());
+    }
+    Ok(DecodedFile { wav, duration_secs })
+}
+
+/// Duration of a media file in seconds, read by ffprobe without decoding it
+pub fn probe_duration_secs(path: &Path) -> Result<f32> {
+    let output = Command::new("ffprobe")
+        .args(["-v", "error", "-show_entries", "format=duration"])
+        .args(["-of", "default=noprint_wrappers=1:nokey=1"])
+        .arg(path)
+        .stdin(Stdio::null())
+        .output()
+        .context("Failed to execute ffprobe. Make sure ffmpeg is installed.")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to read {}: {}", path.display(), stderr.trim());
+    }
+    parse_duration(&String::from_utf8_lossy(&output.stdout))
+        .with_context(|| format!("No duration found for {}", path.display()))
+}
+
+/// Decoding with symphonia, for when FFmpeg isn't installed
+#[cfg(feature = "native-decode")]
+mod native {
+    use anyhow::{Context, Result};
+    use std::fs::File;
+    use std::path::Path;
+    use symphonia::core::audio::SampleBuffer;
+    use symphonia::core::codecs::DecoderOptions;
+    use symphonia::core::errors::Error;
+    use symphonia::core::formats::FormatOptions;
+    use symphonia::core::io::MediaSourceStream;
+    use symphonia::core::meta::MetadataOptions;
+    use symphonia::core::probe::Hint;
+
+    use super::{DecodedFile, SAMPLE_RATE, Trim};
+    use crate::audio::{self, to_i16};
+    use crate::temp;
+
+    /// Extensions of the formats decoded without FFmpeg
+    const EXTENSIONS: [&str; 5] = ["mp3", "ogg", "oga", "flac", "wav"];
+
+    pub fn supports(path: &Path) -> bool {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+    }
+
+    pub fn decode(path: &Path, trim: Trim) -> Result<DecodedFile> {
+        let (samples, sample_rate) =
+            decode_mono(path).with_context(|| format!("Failed to decode {}", path.display()))?;
+        let samples = super::resample(&samples, sample_rate, SAMPLE_RATE);
+        let samples = trim.apply(&samples, SAMPLE_RATE);
+        if samples.is_empty() {
+            anyhow::bail!("No audio in the selected part of {}", path.display());
+        }
+
+        let wav = temp::file("wav")?;
+        let mut writer = hound::WavWriter::create(wav.path(), audio::wav_spec(SAMPLE_RATE, 1))?;
+        for &sample in samples {
+            writer.write_sample(to_i16(sample))?;
+        }
+        writer.finalize()?;
+
+        let duration_secs = samples.len() as f32 / SAMPLE_RATE as f32;
+        Ok(DecodedFile { wav, duration_secs })
+    }
+
+    /// The first audio track downmixed to mono, with its sample rate
+    fn decode_mono(path: &Path) -> Result<(Vec<f32>, u32)> {
+        let file = File::open(path)?;
+        let stream = MediaSourceStream::new(Box::new(file), Default::default());
+        let mut hint = Hint::new();
+        if let Some(ext) = path.extension().and_then(|ext| ext.to_str()) {
+            hint.with_extension(ext);
+        }
+        let mut format = symphonia::default::get_probe()
+            .format(
+                &hint,
+                stream,
+                &FormatOptions::default(),
+                &MetadataOptions::default(),
+            )?
+            .format;
+        let track = format.default_track().context("No audio track")?;
+        let track_id = track.id;
+        let mut sample_rate = track.codec_params.sample_rate;
+        let mut decoder = symphonia::default::get_codecs()
+            .make(&track.codec_params, &DecoderOptions::default())?;
+
+        let mut samples = Vec::new();
+        loop {
+            let packet = match format.next_packet() {
+                Ok(packet) => packet,
+                Err(Error::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(e.into()),
+            };
+            if packet.track_id() != track_id {
+                continue;
+            }
+            let decoded = match decoder.decode(&packet) {
+                Ok(decoded) => decoded,
+                // A corrupt frame costs a few milliseconds, not the file
+                Err(Error::DecodeError(_)) => continue,
+                Err(e) => return Err(e.into()),
+            };
+            let spec = *decoded.spec();
+            if sample_rate.is_none() {
+                sample_rate = Some(spec.rate);
+            }
+            let channels = spec.channels.count().max(1);
+            let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+            buffer.copy_interleaved_ref(decoded);
+            samples.extend(
+                buffer
+                    .samples()
+                    .chunks_exact(channels)
+                    .map(|frame| frame.iter().sum::<f32>() / channels as f32),
+            );
+        }
+        Ok((samples, sample_rate.context("Unknown sample rate")?))
+    }
+}
+
+/// Resample by linear interpolation; plenty for speech recognition
+#[cfg_attr(not(feature = "native-decode"), allow(dead_code))]
+fn resample(samples: &[f32], from: u32, to: u32) -> Vec<f32> {
+    if from == to || samples.is_empty() {
+        return samples.to_vec();
+    }
+    let step = from as f64 / to as f64;
+    let len = (samples.len() as f64 / step) as usize;
+    (0..len)
+        .map(|i| {
+            let position = i as f64 * step;
+            let index = position as usize;
+            let fraction = (position - index as f64) as f32;
+            let next = samples.get(index + 1).unwrap_or(&samples[index]);
+            samples[index] + (next - samples[index]) * fraction
+        })
+        .collect()
+}
+
+/// The duration ffprobe printed, e.g. "12.345000"
+fn parse_duration(stdout: &str) -> Option<f32> {
+    stdout
+        .lines()
+        .find_map(|line| line.trim().parse::<f32>().ok())
+        .filter(|secs| secs.is_finite() && *secs >= 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resample() {
+        assert_eq!(resample(&[0.0, 1.0, 0.0, -1.0], 32_000, 16_000), [0.0, 0.0]);
+        assert_eq!(resample(&[0.0, 1.0], 8_000, 16_000), [0.0, 0.5, 1.0, 1.0]);
+        assert_eq!(resample(&[0.5; 3], 16_000, 16_000), [0.5; 3]);
+    }
+
+    #[test]
+    fn test_trim() {
+        let samples = [0.0, 1.0, 2.0, 3.0, 4.0];
+        let trim = |start_secs, end_secs| Trim {
+            start_secs,
+            end_secs,
+        };
+        assert_eq!(trim(0.0, None).apply(&samples, 2), samples);
+        assert_eq!(trim(0.5, Some(2.0)).apply(&samples, 2), [1.0, 2.0, 3.0]);
+        assert_eq!(trim(1.0, Some(9.0)).apply(&samples, 2), [2.0, 3.0, 4.0]);
+        assert!(trim(9.0, None).apply(&samples, 2).is_empty());
+        assert_eq!(trim(1.5, Some(4.0)).len_secs(), Some(2.5));
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("12.500000\n"), Some(12.5));
+        assert_eq!(parse_duration("N/A\n"), None);
+        assert_eq!(parse_duration(""), None);
+    }
+}
@@ -0,0 +1,219 @@
+//! Word-level comparison of two transcripts
+//!
+//! Words are compared without case and punctuation, so "Hello," and "hello"
+//! count as the same word. The alignment is a longest common subsequence,
+//! which is plenty fast for the length of a dictation. [`error_counts`]
+//! measures the word error rate (WER) of a transcript against a reference.
+
+/// One step of the alignment from the old to the new text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Align the words of `old` and `new`
+pub fn words<'a>(old: &'a str, new: &'a str) -> Vec<Change<'a>> {
+    let old: Vec<&str> = old.split_whitespace().collect();
+    let new: Vec<&str> = new.split_whitespace().collect();
+    let old_keys: Vec<String> = old.iter().map(|w| normalize(w)).collect();
+    let new_keys: Vec<String> = new.iter().map(|w| normalize(w)).collect();
+
+    // lcs[i][j]: length of the common subsequence of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old_keys[i] == new_keys[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut changes = Vec::with_capacity(old.len().max(new.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old_keys[i] == new_keys[j] {
+            changes.push(Change::Same(new[j]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            changes.push(Change::Removed(old[i]));
+            i += 1;
+        } else {
+            changes.push(Change::Added(new[j]));
+            j += 1;
+        }
+    }
+    changes.extend(old[i..].iter().map(|w| Change::Removed(w)));
+    changes.extend(new[j..].iter().map(|w| Change::Added(w)));
+    changes
+}
+
+/// Share of words both texts agree on, from 0 to 1 (1 for two empty texts)
+pub fn agreement(changes: &[Change]) -> f32 {
+    let same = changes
+        .iter()
+        .filter(|c| matches!(c, Change::Same(_)))
+        .count();
+    let old = changes
+        .iter()
+        .filter(|c| !matches!(c, Change::Added(_)))
+        .count();
+    let new = changes
+        .iter()
+        .filter(|c| !matches!(c, Change::Removed(_)))
+        .count();
+    match old.max(new) {
+        0 => 1.0,
+        total => same as f32 / total as f32,
+    }
+}
+
+/// Edits that turn a reference into a transcript
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ErrorCounts {
+    pub substitutions: usize,
+    pub deletions: usize,
+    pub insertions: usize,
+    /// Number of words in the reference
+    pub reference_words: usize,
+}
+
+impl ErrorCounts {
+    pub fn errors(&self) -> usize {
+        self.substitutions + self.deletions + self.insertions
+    }
+
+    /// Word error rate: errors per reference word (can exceed 1)
+    pub fn rate(&self) -> f32 {
+        match self.reference_words {
+            0 if self.insertions == 0 => 0.0,
+            0 => 1.0,
+            words => self.errors() as f32 / words as f32,
+        }
+    }
+}
+
+/// Fewest substitutions, deletions and insertions from `reference` to
+/// `hypothesis` (Levenshtein distance over words)
+pub fn error_counts(reference: &str, hypothesis: &str) -> ErrorCounts {
+    let reference: Vec<String> = reference.split_whitespace().map(normalize).collect();
+    let hypothesis: Vec<String> = hypothesis.split_whitespace().map(normalize).collect();
+
+    // row[j]: edits from the reference words so far to hypothesis[..j]
+    let mut row: Vec<ErrorCounts> = (0..=hypothesis.len())
+        .map(|j| ErrorCounts {
+            insertions: j,
+            ..Default::default()
+        })
+        .collect();
+    for (i, word) in reference.iter().enumerate() {
+        let mut next = vec![ErrorCounts {
+            deletions: i + 1,
+            ..Default::default()
+        }];
+        for (j, candidate) in hypothesis.iter().enumerate() {
+            let diagonal = if word == candidate {
+                row[j]
+            } else {
+                ErrorCounts {
+                    substitutions: row[j].substitutions + 1,
+                    ..row[j]
+                }
+            };
+            let deletion = ErrorCounts {
+                deletions: row[j + 1].deletions + 1,
+                ..row[j + 1]
+            };
+            let insertion = ErrorCounts {
+                insertions: next[j].insertions + 1,
+                ..next[j]
+            };
+            let best = [diagonal, deletion, insertion]
+                .into_iter()
+                .min_by_key(ErrorCounts::errors)
+                .unwrap_or(diagonal);
+            next.push(best);
+        }
+        row = next;
+    }
+
+    ErrorCounts {
+        reference_words: reference.len(),
+        ..row[hypothesis.len()]
+    }
+}
+
+/// The changes as text, like `git diff --word-diff`: "[-old-]{+new+}"
+pub fn render(changes: &[Change]) -> String {
+    changes
+        .iter()
+        .map(|change| match change {
+            Change::Same(word) => word.to_string(),
+            Change::Removed(word) => format!("[-{word}-]"),
+            Change::Added(word) => format!("{{+{word}+}}"),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Lowercase letters and digits only
+fn normalize(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identical_ignoring_case_and_punctuation() {
+        let changes = words("Hello, world.", "hello world");
+        assert_eq!(changes, vec![Change::Same("hello"), Change::Same("world")]);
+        assert_eq!(agreement(&changes), 1.0);
+    }
+
+    #[test]
+    fn test_substitution_and_insertion() {
+        let changes = words("send the report today", "send a report today please");
+        assert_eq!(
+            render(&changes),
+            "send [-the-] {+a+} report today {+please+}"
+        );
+        assert_eq!(agreement(&changes), 3.0 / 5.0);
+    }
+
+    #[test]
+    fn test_error_counts() {
+        let counts = error_counts("Send the report today.", "send a report today please");
+        assert_eq!(
+            counts,
+            ErrorCounts {
+                substitutions: 1,
+                deletions: 0,
+                insertions: 1,
+                reference_words: 4,
+            }
+        );
+        assert_eq!(counts.rate(), 0.5);
+
+        let counts = error_counts("one two three", "one three");
+        assert_eq!((counts.deletions, counts.errors()), (1, 1));
+        assert_eq!(error_counts("same words", "Same, words!").rate(), 0.0);
+        assert_eq!(error_counts("", "").rate(), 0.0);
+        assert_eq!(error_counts("", "hello").rate(), 1.0);
+    }
+
+    #[test]
+    fn test_empty() {
+        assert!(words("", "").is_empty());
+        assert_eq!(agreement(&[]), 1.0);
+        assert_eq!(render(&words("one two", "")), "[-one-] [-two-]");
+    }
+}
@@ -0,0 +1,178 @@
+//! Event log of the background service
+//!
+//! The service appends one JSON object per line to
+//! ~/.local/share/whis/events.jsonl whenever something happens, so other
+//! tools (and `whis tail`) can follow along. Transcript text isn't logged;
+//! it's in the history file. The exception are the partial transcripts of
+//! `live_window_secs`, which exist nowhere else (left out with `privacy`).
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// The log is rotated to `events.jsonl.1` once it grows past this size
+const MAX_BYTES: u64 = 1024 * 1024;
+
+/// Something that happened in the service
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    RecordingStarted {
+        recording: u32,
+    },
+    /// A chunk of a long recording was transcribed
+    ChunkUploaded {
+        recording: u32,
+        done: usize,
+        total: usize,
+    },
+    /// A chunk of a long recording could not be transcribed
+    ChunkFailed {
+        recording: u32,
+        index: usize,
+        error: String,
+    },
+    /// A window of a running recording was transcribed (`live_window_secs`)
+    PartialTranscript {
+        recording: u32,
+        /// Number of the window, from 0
+        window: usize,
+        /// Text of the window; left out in privacy mode
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        text: Option<String>,
+    },
+    TranscriptionDone {
+        recording: u32,
+        /// Recording duration in seconds
+        duration_secs: f32,
+        /// Backend/model that produced the text
+        model: String,
+        /// Length of the transcript in characters
+        chars: usize,
+        /// Chunks missing from the transcript
+        failed_chunks: usize,
+    },
+    Error {
+        recording: u32,
+        message: String,
+    },
+}
+
+impl Event {
+    /// Short description for humans, e.g. "#3 chunk 2/5 uploaded"
+    pub fn describe(&self) -> String {
+        match self {
+            Event::RecordingStarted { recording } => format!("#{recording} recording started"),
+            Event::ChunkUploaded {
+                recording,
+                done,
+                total,
+            } => format!("#{recording} chunk {done}/{total} uploaded"),
+            Event::ChunkFailed {
+                recording,
+                index,
+                error,
+            } => format!("#{recording} chunk {} failed: {error}", index + 1),
+            Event::PartialTranscript {
+                recording,
+                window,
+                text,
+            } => match text {
+                Some(text) => format!("#{recording} window {}: {text}", window + 1),
+                None => format!("#{recording} window {} transcribed", window + 1),
+            },
+            Event::TranscriptionDone {
+                recording,
+                duration_secs,
+                model,
+                chars,
+                failed_chunks,
+            } => {
+                let mut text = format!(
+                    "#{recording} transcription done ({duration_secs:.1}s audio, {chars} chars, {model})"
+                );
+                if *failed_chunks > 0 {
+                    text.push_str(&format!(", {failed_chunks} chunk(s) failed"));
+                }
+                text
+            }
+            Event::Error { recording, message } => format!("#{recording} error: {message}"),
+        }
+    }
+}
+
+/// A line of the event log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventRecord {
+    pub timestamp: DateTime<Local>,
+    #[serde(flatten)]
+    pub event: Event,
+}
+
+/// Get the event log path (~/.local/share/whis/events.jsonl)
+pub fn path() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("whis")
+        .join("events.jsonl")
+}
+
+/// Append an event to the log
+pub fn emit(event: Event) -> Result<()> {
+    let path = path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if fs::metadata(&path).is_ok_and(|m| m.len() > MAX_BYTES) {
+        fs::rename(&path, path.with_extension("jsonl.1")).context("Failed to rotate event log")?;
+    }
+
+    let record = EventRecord {
+        timestamp: Local::now(),
+        event,
+    };
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .context("Failed to open event log")?;
+    writeln!(file, "{}", serde_json::to_string(&record)?).context("Failed to write event log")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_format() {
+        let line = r#"{"timestamp":"2025-03-01T10:00:00+01:00","event":"chunk_uploaded","recording":3,"done":2,"total":5}"#;
+        let record: EventRecord = serde_json::from_str(line).unwrap();
+        assert_eq!(
+            record.event,
+            Event::ChunkUploaded {
+                recording: 3,
+                done: 2,
+                total: 5
+            }
+        );
+        assert_eq!(record.event.describe(), "#3 chunk 2/5 uploaded");
+    }
+
+    #[test]
+    fn test_partial_without_text() {
+        let event = Event::PartialTranscript {
+            recording: 2,
+            window: 0,
+            text: None,
+        };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"event":"partial_transcript","recording":2,"window":0}"#
+        );
+        assert_eq!(event.describe(), "#2 window 1 transcribed");
+    }
+}
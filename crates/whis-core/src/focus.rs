@@ -0,0 +1,194 @@
+//! Focused window detection
+//!
+//! Asks the compositor or window system which application has focus, using
+//! its command-line tools: hyprctl (Hyprland), swaymsg (Sway), xprop (X11,
+//! including XWayland sessions) and osascript (macOS). GNOME and KDE on
+//! Wayland don't expose the focused window, so detection returns `None` there.
+
+use serde_json::Value;
+use std::process::{Command, Stdio};
+
+/// The focused window
+#[derive(Debug, Clone, PartialEq)]
+pub struct Window {
+    /// Window class / app id of the application, e.g. "firefox" or "kitty"
+    pub app: String,
+    /// Not available on macOS
+    pub title: Option<String>,
+}
+
+/// Window class / app id of the focused application, e.g. "firefox" or "kitty"
+pub fn focused_app() -> Option<String> {
+    focused_window().map(|window| window.app)
+}
+
+/// The focused window, with its application and title
+pub fn focused_window() -> Option<Window> {
+    #[cfg(target_os = "linux")]
+    {
+        hyprland().or_else(sway).or_else(x11)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        run(
+            "osascript",
+            &[
+                "-e",
+                "tell application \"System Events\" to get name of first application process whose frontmost is true",
+            ],
+        )
+        .and_then(|name| non_empty(name.trim()))
+        .map(|app| Window { app, title: None })
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        None
+    }
+}
+
+/// Run a command and return its stdout if it succeeded
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program)
+        .args(args)
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(target_os = "linux")]
+fn hyprland() -> Option<Window> {
+    std::env::var_os("HYPRLAND_INSTANCE_SIGNATURE")?;
+    let window: Value = serde_json::from_str(&run("hyprctl", &["activewindow", "-j"])?).ok()?;
+    Some(Window {
+        app: non_empty(window.get("class")?.as_str()?)?,
+        title: window
+            .get("title")
+            .and_then(Value::as_str)
+            .and_then(non_empty),
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn sway() -> Option<Window> {
+    std::env::var_os("SWAYSOCK")?;
+    let tree: Value = serde_json::from_str(&run("swaymsg", &["-t", "get_tree"])?).ok()?;
+    sway_focused_window(&tree)
+}
+
+#[cfg(target_os = "linux")]
+fn x11() -> Option<Window> {
+    std::env::var_os("DISPLAY")?;
+    let active = run("xprop", &["-root", "_NET_ACTIVE_WINDOW"])?;
+    let window = active.split_whitespace().last()?;
+    xprop_window(&run("xprop", &["-id", window, "WM_CLASS", "_NET_WM_NAME"])?)
+}
+
+/// App id (native Wayland) or window class (XWayland) and title of the
+/// focused node
+fn sway_focused_window(node: &Value) -> Option<Window> {
+    if node.get("focused").and_then(Value::as_bool) == Some(true) {
+        let app_id = node.get("app_id").and_then(Value::as_str);
+        let class = node
+            .pointer("/window_properties/class")
+            .and_then(Value::as_str);
+        return Some(Window {
+            app: app_id.or(class).and_then(non_empty)?,
+            title: node.get("name").and_then(Value::as_str).and_then(non_empty),
+        });
+    }
+
+    ["nodes", "floating_nodes"]
+        .iter()
+        .filter_map(|key| node.get(key)?.as_array())
+        .flatten()
+        .find_map(sway_focused_window)
+}
+
+/// Class and title from xprop output:
+/// `WM_CLASS(STRING) = "Navigator", "firefox"` and
+/// `_NET_WM_NAME(UTF8_STRING) = "Example - Mozilla Firefox"`
+fn xprop_window(output: &str) -> Option<Window> {
+    let value = |property: &str| {
+        output
+            .lines()
+            .find(|line| line.starts_with(property))?
+            .split_once('=')
+            .map(|(_, value)| value.trim())
+    };
+    let class = value("WM_CLASS")?
+        .rsplit(',')
+        .next()?
+        .trim()
+        .trim_matches('"');
+    let title = value("_NET_WM_NAME").and_then(|title| {
+        let title = title.strip_prefix('"')?.strip_suffix('"')?;
+        non_empty(&title.replace("\\\"", "\"").replace("\\\\", "\\"))
+    });
+    Some(Window {
+        app: non_empty(class)?,
+        title,
+    })
+}
+
+fn non_empty(s: &str) -> Option<String> {
+    (!s.is_empty()).then(|| s.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xprop_window() {
+        assert_eq!(
+            xprop_window(
+                "WM_CLASS(STRING) = \"Navigator\", \"firefox\"\n\
+                 _NET_WM_NAME(UTF8_STRING) = \"Say \\\"hi\\\" - Mozilla Firefox\"\n"
+            ),
+            Some(Window {
+                app: "firefox".to_string(),
+                title: Some("Say \"hi\" - Mozilla Firefox".to_string()),
+            })
+        );
+        assert_eq!(
+            xprop_window("WM_CLASS(STRING) = \"xterm\"\n_NET_WM_NAME:  not found.\n"),
+            Some(Window {
+                app: "xterm".to_string(),
+                title: None,
+            })
+        );
+        assert_eq!(xprop_window("WM_CLASS:  not found.\n"), None);
+    }
+
+    #[test]
+    fn test_sway_focused_window() {
+        let tree = serde_json::json!({
+            "focused": false,
+            "nodes": [{
+                "focused": false,
+                "nodes": [
+                    { "focused": false, "app_id": "firefox" },
+                ],
+                "floating_nodes": [
+                    {
+                        "focused": true,
+                        "app_id": null,
+                        "name": "general | Slack",
+                        "window_properties": { "class": "Slack" },
+                    },
+                ],
+            }],
+        });
+        assert_eq!(
+            sway_focused_window(&tree),
+            Some(Window {
+                app: "Slack".to_string(),
+                title: Some("general | Slack".to_string()),
+            })
+        );
+    }
+}
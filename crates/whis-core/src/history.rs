@@ -0,0 +1,301 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::metadata::Metadata;
+use crate::sinks::Payload;
+use crate::vault;
+
+/// A transcript stored in the history file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: u64,
+    pub timestamp: DateTime<Local>,
+    /// Recording duration in seconds
+    #[serde(rename = "duration")]
+    pub duration_secs: f32,
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Where and how the transcript was dictated
+    #[serde(default, skip_serializing_if = "Metadata::is_empty")]
+    pub metadata: Metadata,
+}
+
+impl HistoryEntry {
+    /// Whether the entry carries `tag` (ignoring case)
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+}
+
+/// Entries to select; every entry matches the default
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Filter<'a> {
+    /// Only entries with this tag
+    pub tag: Option<&'a str>,
+    /// Only entries dictated into this application (see
+    /// `Metadata::is_from_app`)
+    pub app: Option<&'a str>,
+}
+
+impl Filter<'_> {
+    pub fn matches(&self, entry: &HistoryEntry) -> bool {
+        self.tag.is_none_or(|tag| entry.has_tag(tag))
+            && self.app.is_none_or(|app| entry.metadata.is_from_app(app))
+    }
+}
+
+/// Get the history file path (~/.local/share/whis/history.jsonl)
+pub fn path() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("whis")
+        .join("history.jsonl")
+}
+
+/// Load all history entries, oldest first (unreadable lines are skipped)
+///
+/// Encrypted transcripts are decrypted, which unlocks the key in the keyring.
+pub fn load() -> Result<Vec<HistoryEntry>> {
+    load_stored()?.into_iter().map(decrypt).collect()
+}
+
+/// The most recent `limit` entries, newest first
+pub fn recent(limit: usize) -> Result<Vec<HistoryEntry>> {
+    recent_filtered(limit, Filter::default())
+}
+
+/// The most recent `limit` entries matching `filter`, newest first
+pub fn recent_filtered(limit: usize, filter: Filter) -> Result<Vec<HistoryEntry>> {
+    load_stored()?
+        .into_iter()
+        .rev()
+        .filter(|entry| filter.matches(entry))
+        .take(limit)
+        .map(decrypt)
+        .collect()
+}
+
+/// Entries as stored, with encrypted text left sealed
+fn load_stored() -> Result<Vec<HistoryEntry>> {
+    let path = path();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(&path).context("Failed to read history file")?;
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+fn decrypt(mut entry: HistoryEntry) -> Result<HistoryEntry> {
+    let id = entry.id;
+    let open = |text: String| -> Result<String> {
+        if !vault::is_sealed(&text) {
+            return Ok(text);
+        }
+        vault::open(&text).with_context(|| format!("Failed to decrypt history entry #{id}"))
+    };
+    entry.text = open(entry.text)?;
+    entry.title = entry.title.map(open).transpose()?;
+    entry.metadata.window_title = entry.metadata.window_title.map(open).transpose()?;
+    Ok(entry)
+}
+
+/// `entry` with its text, title and window title encrypted; tags and the
+/// rest of the metadata stay readable for filtering
+fn seal(entry: &HistoryEntry) -> Result<HistoryEntry> {
+    let seal = |text: &str| -> Result<String> {
+        if vault::is_sealed(text) {
+            return Ok(text.to_string());
+        }
+        vault::seal(text)
+    };
+    let mut sealed = entry.clone();
+    sealed.text = seal(&entry.text)?;
+    sealed.title = entry.title.as_deref().map(seal).transpose()?;
+    sealed.metadata.window_title = entry
+        .metadata
+        .window_title
+        .as_deref()
+        .map(seal)
+        .transpose()?;
+    Ok(sealed)
+}
+
+/// Whether everything `seal` encrypts is encrypted
+fn is_sealed(entry: &HistoryEntry) -> bool {
+    [
+        Some(&entry.text),
+        entry.title.as_ref(),
+        entry.metadata.window_title.as_ref(),
+    ]
+    .into_iter()
+    .flatten()
+    .all(|text| vault::is_sealed(text))
+}
+
+/// Append a finished transcript to the history file, encrypted if `encrypt` is set (see `vault`)
+pub fn append(payload: &Payload, encrypt: bool) -> Result<HistoryEntry> {
+    let path = path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let id = load_stored()?.last().map_or(1, |entry| entry.id + 1);
+    let entry = HistoryEntry {
+        id,
+        timestamp: payload.timestamp,
+        duration_secs: payload.duration_secs,
+        text: payload.text.clone(),
+        title: payload.title.clone(),
+        tags: payload.tags.clone(),
+        metadata: payload.metadata.clone(),
+    };
+    let stored = if encrypt {
+        seal(&entry)?
+    } else {
+        entry.clone()
+    };
+
+    // Private from the start, so other users can't read it before a chmod
+    let mut options = OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&path).context("Failed to open history file")?;
+    writeln!(file, "{}", serde_json::to_string(&stored)?).context("Failed to write history")?;
+
+    Ok(entry)
+}
+
+/// Continue the last transcript with `payload`, joined by `separator`, e.g.
+/// for a sentence forgotten in it. Starts a new entry if the history is
+/// empty. Returns the updated entry.
+pub fn append_to_last(payload: &Payload, separator: &str, encrypt: bool) -> Result<HistoryEntry> {
+    let mut entries = load_stored()?;
+    let Some(last) = entries.pop() else {
+        return append(payload, encrypt);
+    };
+    let sealed = vault::is_sealed(&last.text);
+    let mut entry = decrypt(last)?;
+    entry.text = join(&entry.text, separator, &payload.text);
+    entry.duration_secs += payload.duration_secs;
+    for tag in &payload.tags {
+        if !entry.has_tag(tag) {
+            entry.tags.push(tag.clone());
+        }
+    }
+    entries.push(if sealed || encrypt {
+        seal(&entry)?
+    } else {
+        entry.clone()
+    });
+    save_stored(&entries)?;
+    Ok(entry)
+}
+
+/// `text` continued with `more`, trimming whitespace where they meet
+pub fn join(text: &str, separator: &str, more: &str) -> String {
+    let (text, more) = (text.trim_end(), more.trim_start());
+    if text.is_empty() || more.is_empty() {
+        return format!("{text}{more}");
+    }
+    format!("{text}{separator}{more}")
+}
+
+/// Encrypt all entries stored in plain text, e.g. after turning on
+/// `encrypt_history`. Returns the number of entries encrypted.
+pub fn encrypt_all() -> Result<usize> {
+    let mut entries = load_stored()?;
+    let mut count = 0;
+    for entry in entries.iter_mut().filter(|e| !is_sealed(e)) {
+        *entry = seal(entry)?;
+        count += 1;
+    }
+    if count == 0 {
+        return Ok(0);
+    }
+    save_stored(&entries)?;
+    Ok(count)
+}
+
+/// Add tags to an entry, or remove them if `remove` is set, and replace its
+/// title if one is given. Returns the updated entry as stored.
+pub fn tag(id: u64, tags: &[String], remove: bool, title: Option<&str>) -> Result<HistoryEntry> {
+    let mut entries = load_stored()?;
+    let entry = entries
+        .iter_mut()
+        .find(|entry| entry.id == id)
+        .with_context(|| format!("No history entry #{id}"))?;
+    if remove {
+        entry
+            .tags
+            .retain(|t| !tags.iter().any(|r| r.eq_ignore_ascii_case(t)));
+    } else {
+        for tag in tags {
+            if !entry.has_tag(tag) {
+                entry.tags.push(tag.clone());
+            }
+        }
+    }
+    if let Some(title) = title {
+        entry.title = Some(title.to_string()).filter(|t| !t.is_empty());
+    }
+    let updated = entry.clone();
+    save_stored(&entries)?;
+    Ok(updated)
+}
+
+/// Replace the history file with `entries`
+fn save_stored(entries: &[HistoryEntry]) -> Result<()> {
+    // Write next to the history and swap it in, so a failure leaves it intact
+    let path = path();
+    let mut content = String::new();
+    for entry in entries {
+        content.push_str(&serde_json::to_string(entry)?);
+        content.push('\n');
+    }
+    let tmp = path.with_extension("jsonl.tmp");
+    // Left over from a failed save
+    let _ = fs::remove_file(&tmp);
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&tmp).context("Failed to write history")?;
+    file.write_all(content.as_bytes())
+        .context("Failed to write history")?;
+    drop(file);
+    fs::rename(&tmp, &path).context("Failed to replace history file")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join() {
+        assert_eq!(
+            join("First take.", " ", " And more."),
+            "First take. And more."
+        );
+        assert_eq!(join("List:\n", "\n", "- item"), "List:\n- item");
+        assert_eq!(join("", " ", "Only this"), "Only this");
+    }
+}
@@ -0,0 +1,40 @@
+//! User commands run when the recording state changes
+//!
+//! `on_state_change` is run through the shell with the new state ("idle",
+//! "recording" or "transcribing") as `$1` and in `$WHIS_STATE`, e.g. to
+//! switch a keyboard LED or a Home Assistant scene.
+
+use std::process::Command;
+
+use crate::settings::Settings;
+
+/// Run the `on_state_change` hook in the background, if one is configured
+///
+/// The hook doesn't delay recording; failures are only reported on stderr.
+pub fn state_changed(settings: &Settings, state: &str) {
+    let Some(command_line) = settings.on_state_change.clone() else {
+        return;
+    };
+    let state = state.to_string();
+
+    std::thread::spawn(move || {
+        #[cfg(unix)]
+        let mut command = {
+            let mut command = Command::new("sh");
+            command.arg("-c").arg(&command_line).arg("sh").arg(&state);
+            command
+        };
+        #[cfg(windows)]
+        let mut command = {
+            let mut command = Command::new("cmd");
+            command.arg("/C").arg(&command_line);
+            command
+        };
+
+        match command.env("WHIS_STATE", &state).status() {
+            Ok(status) if status.success() => {}
+            Ok(status) => eprintln!("on_state_change hook exited with {status}"),
+            Err(e) => eprintln!("Failed to run on_state_change hook: {e}"),
+        }
+    });
+}
@@ -0,0 +1,89 @@
+//! Transcripts as label tracks for audio editors
+//!
+//! Podcasters edit by listening; with the transcript laid over the audio
+//! they can jump to what was said. Audacity imports a label track as
+//! tab-separated seconds (File > Import > Labels), Reaper regions as the CSV
+//! its Region/Marker Manager reads.
+
+use crate::postprocess::timestamps::Span;
+
+/// Audacity label track: "start<TAB>end<TAB>text" per span
+pub fn audacity(spans: &[Span]) -> String {
+    spans
+        .iter()
+        .map(|span| {
+            format!(
+                "{:.6}\t{:.6}\t{}\n",
+                span.start,
+                span.end,
+                span.text.replace(['\t', '\n'], " ")
+            )
+        })
+        .collect()
+}
+
+/// Reaper region list: "#,Name,Start,End,Length" with one region per span
+pub fn reaper(spans: &[Span]) -> String {
+    let mut out = String::from("#,Name,Start,End,Length\n");
+    for (index, span) in spans.iter().enumerate() {
+        out.push_str(&format!(
+            "R{},\"{}\",{},{},{}\n",
+            index + 1,
+            span.text.replace('"', "\"\"").replace('\n', " "),
+            time(span.start),
+            time(span.end),
+            time(span.end - span.start)
+        ));
+    }
+    out
+}
+
+/// "m:ss.fff", the time format of Reaper's region lists
+fn time(secs: f32) -> String {
+    let millis = (secs.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{}:{:02}.{:03}",
+        millis / 60_000,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans() -> Vec<Span> {
+        vec![
+            Span {
+                start: 0.5,
+                end: 4.25,
+                text: "Welcome to the show.".to_string(),
+            },
+            Span {
+                start: 61.0,
+                end: 65.5,
+                text: "He said \"hi\", then left.".to_string(),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_audacity() {
+        assert_eq!(
+            audacity(&spans()),
+            "0.500000\t4.250000\tWelcome to the show.\n\
+             61.000000\t65.500000\tHe said \"hi\", then left.\n"
+        );
+    }
+
+    #[test]
+    fn test_reaper() {
+        assert_eq!(
+            reaper(&spans()),
+            "#,Name,Start,End,Length\n\
+             R1,\"Welcome to the show.\",0:00.500,0:04.250,0:03.750\n\
+             R2,\"He said \"\"hi\"\", then left.\",1:01.000,1:05.500,0:04.500\n"
+        );
+    }
+}
@@ -0,0 +1,314 @@
+//! Profiles chosen by the spoken language
+//!
+//! Whisper backends detect the language when none is set. With
+//! `language_profiles` mapping language codes to profile names, the matching
+//! profile is applied to the post-processing and output of that transcript,
+//! so bilingual users get e.g. German fillers removed in German and English
+//! output templates in English without switching anything by hand.
+//!
+//! With `language_hotword`, a recording can also start with "switch to
+//! German": the phrase is removed, the rest is transcribed in that language
+//! and its profile applies.
+
+use crate::provider::Transcription;
+use crate::settings::Settings;
+
+/// Phrases that, followed by a language name, switch the language
+const SWITCH_PHRASES: &[&[&str]] = &[&["switch", "to"], &["switching", "to"]];
+
+/// Whisper's languages as (ISO 639-1 code, name) pairs; the OpenAI API
+/// reports the detected language by name
+const LANGUAGES: &[(&str, &str)] = &[
+    ("en", "english"),
+    ("zh", "chinese"),
+    ("de", "german"),
+    ("es", "spanish"),
+    ("ru", "russian"),
+    ("ko", "korean"),
+    ("fr", "french"),
+    ("ja", "japanese"),
+    ("pt", "portuguese"),
+    ("tr", "turkish"),
+    ("pl", "polish"),
+    ("ca", "catalan"),
+    ("nl", "dutch"),
+    ("ar", "arabic"),
+    ("sv", "swedish"),
+    ("it", "italian"),
+    ("id", "indonesian"),
+    ("hi", "hindi"),
+    ("fi", "finnish"),
+    ("vi", "vietnamese"),
+    ("he", "hebrew"),
+    ("uk", "ukrainian"),
+    ("el", "greek"),
+    ("ms", "malay"),
+    ("cs", "czech"),
+    ("ro", "romanian"),
+    ("da", "danish"),
+    ("hu", "hungarian"),
+    ("ta", "tamil"),
+    ("no", "norwegian"),
+    ("th", "thai"),
+    ("ur", "urdu"),
+    ("hr", "croatian"),
+    ("bg", "bulgarian"),
+    ("lt", "lithuanian"),
+    ("la", "latin"),
+    ("mi", "maori"),
+    ("ml", "malayalam"),
+    ("cy", "welsh"),
+    ("sk", "slovak"),
+    ("te", "telugu"),
+    ("fa", "persian"),
+    ("lv", "latvian"),
+    ("bn", "bengali"),
+    ("sr", "serbian"),
+    ("az", "azerbaijani"),
+    ("sl", "slovenian"),
+    ("kn", "kannada"),
+    ("et", "estonian"),
+    ("mk", "macedonian"),
+    ("br", "breton"),
+    ("eu", "basque"),
+    ("is", "icelandic"),
+    ("hy", "armenian"),
+    ("ne", "nepali"),
+    ("mn", "mongolian"),
+    ("bs", "bosnian"),
+    ("kk", "kazakh"),
+    ("sq", "albanian"),
+    ("sw", "swahili"),
+    ("gl", "galician"),
+    ("mr", "marathi"),
+    ("pa", "punjabi"),
+    ("si", "sinhala"),
+    ("km", "khmer"),
+    ("sn", "shona"),
+    ("yo", "yoruba"),
+    ("so", "somali"),
+    ("af", "afrikaans"),
+    ("oc", "occitan"),
+    ("ka", "georgian"),
+    ("be", "belarusian"),
+    ("tg", "tajik"),
+    ("sd", "sindhi"),
+    ("gu", "gujarati"),
+    ("am", "amharic"),
+    ("yi", "yiddish"),
+    ("lo", "lao"),
+    ("uz", "uzbek"),
+    ("fo", "faroese"),
+    ("ht", "haitian creole"),
+    ("ps", "pashto"),
+    ("tk", "turkmen"),
+    ("nn", "nynorsk"),
+    ("mt", "maltese"),
+    ("sa", "sanskrit"),
+    ("lb", "luxembourgish"),
+    ("my", "myanmar"),
+    ("bo", "tibetan"),
+    ("tl", "tagalog"),
+    ("mg", "malagasy"),
+    ("as", "assamese"),
+    ("tt", "tatar"),
+    ("haw", "hawaiian"),
+    ("ln", "lingala"),
+    ("ha", "hausa"),
+    ("ba", "bashkir"),
+    ("jw", "javanese"),
+    ("su", "sundanese"),
+    ("yue", "cantonese"),
+];
+
+/// Language code for a detected language given as a name ("german") or a
+/// code or locale ("de", "de-DE")
+pub fn code(language: &str) -> Option<String> {
+    let language = language.trim().to_ascii_lowercase();
+    if let Some((code, _)) = LANGUAGES.iter().find(|(_, name)| *name == language) {
+        return Some(code.to_string());
+    }
+    let code = language.split(['-', '_']).next().unwrap_or_default();
+    LANGUAGES
+        .iter()
+        .any(|(known, _)| *known == code)
+        .then(|| code.to_string())
+}
+
+/// A spoken "switch to German" at the start of a transcript
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Switch {
+    /// Code of the language asked for
+    pub code: String,
+    /// Number of words the phrase takes up
+    pub words: usize,
+}
+
+/// The language switch the text starts with, if any
+pub fn spoken_switch(text: &str) -> Option<Switch> {
+    let words: Vec<String> = text.split_whitespace().take(4).map(normalize).collect();
+    for phrase in SWITCH_PHRASES {
+        let matches = words.len() > phrase.len() && words.iter().zip(*phrase).all(|(w, p)| w == p);
+        if !matches {
+            continue;
+        }
+        let rest = &words[phrase.len()..];
+        // Names have one or two words ("haitian creole")
+        for len in [2, 1] {
+            let Some(name) = rest.get(..len) else {
+                continue;
+            };
+            let name = name.join(" ");
+            if let Some((code, _)) = LANGUAGES.iter().find(|(_, known)| *known == name) {
+                return Some(Switch {
+                    code: code.to_string(),
+                    words: phrase.len() + len,
+                });
+            }
+        }
+    }
+    None
+}
+
+/// Remove a spoken language switch from the start of the transcript and
+/// record its language, so the matching profile applies
+///
+/// Returns the transcription unchanged when it doesn't start with one.
+pub fn apply_switch(mut transcription: Transcription) -> Transcription {
+    let Some(switch) = spoken_switch(&transcription.text) else {
+        return transcription;
+    };
+    transcription.text = capitalize(skip_words(&transcription.text, switch.words));
+    let timed = switch.words.min(transcription.words.len());
+    transcription.words.drain(..timed);
+    let mut remaining = switch.words;
+    for segment in &mut transcription.segments {
+        if remaining == 0 {
+            break;
+        }
+        let count = remaining.min(segment.text.split_whitespace().count());
+        segment.text = capitalize(skip_words(&segment.text, count));
+        remaining -= count;
+    }
+    transcription
+        .segments
+        .retain(|segment| !segment.text.is_empty());
+    transcription.language = Some(switch.code);
+    transcription
+}
+
+/// Settings with the profile for the detected language applied
+///
+/// Returns the settings unchanged when no language was detected or no
+/// profile is set for it.
+pub fn resolve(settings: &Settings, detected: Option<&str>) -> Settings {
+    let profile = detected
+        .and_then(code)
+        .and_then(|code| settings.language_profiles.get(&code));
+    let Some(profile) = profile else {
+        return settings.clone();
+    };
+    match settings.with_profile(profile) {
+        Ok(effective) => effective,
+        Err(e) => {
+            eprintln!("Language profile: {e}");
+            settings.clone()
+        }
+    }
+}
+
+/// Lowercase letters only, so "German." matches "german"
+fn normalize(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphabetic())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// The text after its first `count` words and the punctuation following them
+fn skip_words(text: &str, count: usize) -> &str {
+    let mut rest = text.trim_start();
+    for _ in 0..count {
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        rest = rest[end..].trim_start();
+    }
+    rest.trim_start_matches(|c: char| c.is_ascii_punctuation() || c.is_whitespace())
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile::Profile;
+    use crate::provider::Segment;
+    use crate::settings::OutputMode;
+
+    #[test]
+    fn test_code() {
+        assert_eq!(code("German").as_deref(), Some("de"));
+        assert_eq!(code("de").as_deref(), Some("de"));
+        assert_eq!(code("pt-BR").as_deref(), Some("pt"));
+        assert_eq!(code("klingon"), None);
+    }
+
+    #[test]
+    fn test_spoken_switch() {
+        let switch = spoken_switch("Switch to German. Hallo zusammen").unwrap();
+        assert_eq!((switch.code.as_str(), switch.words), ("de", 3));
+        let switch = spoken_switch("switching to Haitian Creole, bonjou").unwrap();
+        assert_eq!((switch.code.as_str(), switch.words), ("ht", 4));
+        assert_eq!(spoken_switch("Switch to the next slide"), None);
+        assert_eq!(spoken_switch("Please switch to German"), None);
+    }
+
+    #[test]
+    fn test_apply_switch() {
+        let mut transcription = Transcription::from_text("Switch to German. hallo zusammen");
+        transcription.segments = vec![Segment {
+            text: " Switch to German. hallo zusammen".to_string(),
+            start: 0.0,
+            end: 3.0,
+            avg_logprob: -0.2,
+            no_speech_prob: 0.0,
+        }];
+        let switched = apply_switch(transcription);
+        assert_eq!(switched.text, "Hallo zusammen");
+        assert_eq!(switched.segments[0].text, "Hallo zusammen");
+        assert_eq!(switched.language.as_deref(), Some("de"));
+
+        let plain = Transcription::from_text("Nothing to switch.");
+        assert_eq!(apply_switch(plain.clone()), plain);
+    }
+
+    #[test]
+    fn test_resolve() {
+        let mut settings = Settings::default();
+        settings.profiles.insert(
+            "deutsch".to_string(),
+            Profile {
+                output_mode: Some(OutputMode::Type),
+                ..Default::default()
+            },
+        );
+        settings
+            .language_profiles
+            .insert("de".to_string(), "deutsch".to_string());
+
+        assert_eq!(
+            resolve(&settings, Some("german")).output_mode,
+            OutputMode::Type
+        );
+        assert_eq!(
+            resolve(&settings, Some("english")).output_mode,
+            OutputMode::Clipboard
+        );
+        assert_eq!(resolve(&settings, None).output_mode, OutputMode::Clipboard);
+    }
+}
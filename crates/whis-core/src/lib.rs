@@ -1,11 +1,63 @@
+pub mod app_rules;
 pub mod audio;
+pub mod batch;
+pub mod bluetooth;
+#[cfg(feature = "clipboard")]
 pub mod clipboard;
 pub mod config;
+pub mod decode;
+pub mod diff;
+pub mod events;
+pub mod focus;
+pub mod history;
+pub mod hooks;
+pub mod labels;
+pub mod language;
+pub mod metadata;
+pub mod ocr;
+#[cfg(feature = "audio")]
+pub mod playback;
+pub mod postprocess;
+pub mod profile;
+pub mod provider;
+pub mod quality;
+pub mod request;
+pub mod search;
 pub mod settings;
+pub mod sinks;
+pub mod spill;
+pub mod stats;
+pub mod summarize;
+pub mod temp;
+pub mod template;
 pub mod transcribe;
+#[cfg(feature = "audio")]
+pub mod tts;
+pub mod typing;
+pub mod usage;
+pub mod vad;
+pub mod vault;
+pub mod voice_command;
 
-pub use audio::{AudioChunk, AudioRecorder, RecordingData, RecordingOutput};
+#[cfg(feature = "audio")]
+pub use audio::AudioRecorder;
+pub use audio::{
+    AudioChunk, AudioCodec, AudioEncoding, CaptureReport, ChunkConfig, RecordingData,
+    RecordingOutput,
+};
+pub use bytes::Bytes;
+#[cfg(feature = "clipboard")]
 pub use clipboard::copy_to_clipboard;
 pub use config::ApiConfig;
-pub use settings::Settings;
-pub use transcribe::{ChunkTranscription, parallel_transcribe, transcribe_audio};
+pub use profile::Profile;
+pub use provider::{Backend, Provider, Segment, Transcription, Word};
+pub use request::{ResponseFormat, TranscriptionRequest, TranscriptionRequestBuilder};
+pub use settings::{ClipboardBackend, ClipboardSelection, OutputMode, Settings, TtsEngine};
+pub use tokio_util::sync::CancellationToken;
+pub use transcribe::{
+    ChunkEvent, ChunkTranscription, FailedChunk, PartialTranscription, parallel_transcribe,
+    parallel_transcribe_partial, parallel_transcribe_switching, transcribe_audio, transcribe_file,
+};
+pub use typing::{TypingConfig, type_text};
+pub use vad::SilenceDetector;
+pub use voice_command::VoiceCommand;
@@ -0,0 +1,138 @@
+//! Context recorded with each transcript
+//!
+//! Where a transcript was dictated (the window focused when the recording
+//! stopped, the host) and how (profile, model, audio levels) is kept with it
+//! in the history, so it can be found again by application later, e.g.
+//! `whis history --app slack`.
+
+use serde::{Deserialize, Serialize};
+use std::process::{Command, Stdio};
+
+use crate::focus::Window;
+use crate::settings::Settings;
+
+/// Context of a transcript; everything is optional, as not every platform
+/// tells which window has focus
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Metadata {
+    /// Window class / app id of the focused application
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window_title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Model billed for the transcription
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio: Option<AudioStats>,
+}
+
+/// Format and levels of a recording
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AudioStats {
+    pub sample_rate: u32,
+    pub channels: u16,
+    /// Loudest sample in dBFS
+    pub peak_db: f32,
+    /// Average loudness (RMS) in dBFS
+    pub rms_db: f32,
+}
+
+impl Metadata {
+    /// Context from the focused `window`, this host and the profile in
+    /// effect; the caller adds the model and audio once known
+    pub fn capture(window: Option<Window>, settings: &Settings) -> Self {
+        let (app, window_title) = match window {
+            Some(window) => (Some(window.app), window.title),
+            None => (None, None),
+        };
+        Self {
+            app,
+            window_title,
+            hostname: hostname(),
+            profile: settings.active_profile.clone(),
+            model: None,
+            audio: None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether the transcript was dictated into `app` (ASCII
+    /// case-insensitive, part of the name is enough)
+    pub fn is_from_app(&self, app: &str) -> bool {
+        self.app.as_ref().is_some_and(|name| {
+            name.to_ascii_lowercase()
+                .contains(&app.to_ascii_lowercase())
+        })
+    }
+}
+
+impl AudioStats {
+    /// Levels of `samples` (-1 to 1); `None` without samples
+    pub fn measure(samples: &[f32], sample_rate: u32, channels: u16) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        let rms = (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt();
+        Some(Self {
+            sample_rate,
+            channels,
+            peak_db: decibels(peak),
+            rms_db: decibels(rms),
+        })
+    }
+}
+
+/// Level in dBFS to one decimal, floored at -100 for silence
+fn decibels(level: f32) -> f32 {
+    let db = (20.0 * level.log10()).max(-100.0);
+    (db * 10.0).round() / 10.0
+}
+
+/// Name of this machine, from the `hostname` command
+fn hostname() -> Option<String> {
+    let output = Command::new("hostname")
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!name.is_empty()).then_some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audio_stats() {
+        let stats = AudioStats::measure(&[0.5, -0.5, 0.5, -0.5], 16_000, 1).unwrap();
+        assert_eq!(stats.peak_db, -6.0);
+        assert_eq!(stats.rms_db, -6.0);
+        assert_eq!(
+            AudioStats::measure(&[0.0; 4], 16_000, 1).map(|s| s.peak_db),
+            Some(-100.0)
+        );
+        assert!(AudioStats::measure(&[], 16_000, 1).is_none());
+    }
+
+    #[test]
+    fn test_is_from_app() {
+        let metadata = Metadata {
+            app: Some("Slack".to_string()),
+            ..Metadata::default()
+        };
+        assert!(metadata.is_from_app("slack"));
+        assert!(metadata.is_from_app("sla"));
+        assert!(!metadata.is_from_app("firefox"));
+        assert!(!Metadata::default().is_from_app("slack"));
+    }
+}
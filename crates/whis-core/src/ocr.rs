@@ -0,0 +1,175 @@
+//! Text from screenshots
+//!
+//! Reads the image on the clipboard, a part of the screen the user selects
+//! or an image file, and has an OpenAI vision model write out the text in
+//! it. Like summaries, this always uses the OpenAI API key. The text then
+//! takes the way of a transcript: post-processing, output mode and sinks.
+//!
+//! Images are taken with the platform's tools: wl-paste, grim and slurp on
+//! Wayland, xclip and maim on X11, pngpaste and screencapture on macOS.
+
+use anyhow::{Context, Result};
+use base64::Engine;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+
+use crate::config::ApiConfig;
+use crate::provider::Backend;
+use crate::settings::Settings;
+use crate::summarize;
+
+/// Vision model used unless `ocr_model` is set
+pub const DEFAULT_MODEL: &str = "gpt-4o-mini";
+
+const PROMPT: &str = "Transcribe all text in this image exactly as written, \
+keeping its line breaks and reading order. Reply only with the text; \
+reply with nothing if there is none.";
+
+/// Where the image comes from
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    /// The image on the clipboard
+    Clipboard,
+    /// A part of the screen selected with the mouse
+    Selection,
+    File(PathBuf),
+}
+
+/// An image to read, with its MIME type
+pub struct Image {
+    pub data: Vec<u8>,
+    pub mime: &'static str,
+}
+
+/// Get the image from `source`
+pub fn capture(source: &Source) -> Result<Image> {
+    let data = match source {
+        Source::Clipboard => clipboard_png()?,
+        Source::Selection => selection_png()?,
+        Source::File(path) => {
+            let mime = mime_type(path)
+                .with_context(|| format!("{} is not a PNG, JPEG, GIF or WebP", path.display()))?;
+            let data = std::fs::read(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            return Ok(Image { data, mime });
+        }
+    };
+    if data.is_empty() {
+        anyhow::bail!("No image was captured");
+    }
+    Ok(Image {
+        data,
+        mime: "image/png",
+    })
+}
+
+/// The text in `image`, read by the vision model (blocking); empty if
+/// there is none
+pub fn extract_text(image: &Image, settings: &Settings) -> Result<String> {
+    let config = ApiConfig::for_backend(settings, Backend::OpenAI)
+        .context("Reading images needs an OpenAI API key")?;
+    let model = settings.ocr_model.as_deref().unwrap_or(DEFAULT_MODEL);
+    let url = format!(
+        "data:{};base64,{}",
+        image.mime,
+        base64::engine::general_purpose::STANDARD.encode(&image.data)
+    );
+    let messages = json!([{
+        "role": "user",
+        "content": [
+            { "type": "text", "text": PROMPT },
+            { "type": "image_url", "image_url": { "url": url } },
+        ],
+    }]);
+    Ok(summarize::chat(&config, model, messages)?.unwrap_or_default())
+}
+
+/// MIME type of an image file the vision model reads, from its extension
+fn mime_type(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    match ext.as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        _ => None,
+    }
+}
+
+fn clipboard_png() -> Result<Vec<u8>> {
+    #[cfg(target_os = "linux")]
+    {
+        let png = if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            output("wl-paste", &["--no-newline", "--type", "image/png"])
+        } else {
+            output(
+                "xclip",
+                &["-selection", "clipboard", "-target", "image/png", "-out"],
+            )
+        };
+        png.context("No image on the clipboard")
+    }
+    #[cfg(target_os = "macos")]
+    {
+        output("pngpaste", &["-"]).context("No image on the clipboard")
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        anyhow::bail!("Reading the clipboard image is not supported on this platform")
+    }
+}
+
+fn selection_png() -> Result<Vec<u8>> {
+    #[cfg(target_os = "linux")]
+    {
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            let region = output("slurp", &[]).context("No part of the screen selected")?;
+            let region = String::from_utf8_lossy(&region);
+            output("grim", &["-g", region.trim(), "-"])
+        } else {
+            output("maim", &["--select", "--hidecursor"])
+        }
+    }
+    #[cfg(target_os = "macos")]
+    {
+        // screencapture writes files only
+        let file = crate::temp::file("png")?;
+        let path = file.path().to_string_lossy();
+        output("screencapture", &["-i", "-x", &path])?;
+        std::fs::read(file.path()).context("Failed to read the screenshot")
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        anyhow::bail!("Selecting a part of the screen is not supported on this platform")
+    }
+}
+
+/// Run `program` and return what it printed
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn output(program: &str, args: &[&str]) -> Result<Vec<u8>> {
+    use std::process::{Command, Stdio};
+
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .with_context(|| format!("Failed to run {program}. Is it installed?"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("{program} failed: {}", stderr.trim());
+    }
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mime_type() {
+        assert_eq!(mime_type(Path::new("shot.PNG")), Some("image/png"));
+        assert_eq!(mime_type(Path::new("a/photo.jpeg")), Some("image/jpeg"));
+        assert_eq!(mime_type(Path::new("scan.pdf")), None);
+        assert_eq!(mime_type(Path::new("README")), None);
+    }
+}
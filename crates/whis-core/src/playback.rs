@@ -0,0 +1,21 @@
+//! Play audio on the default output device
+//!
+//! The counterpart of the recorder, for checking the microphone
+//! (`whis test-mic`) and reading transcripts aloud (`whis say`).
+
+use anyhow::{Context, Result};
+
+/// Play interleaved samples (-1 to 1) and wait until they're done
+pub fn play(samples: Vec<f32>, sample_rate: u32, channels: u16) -> Result<()> {
+    let mut stream = rodio::OutputStreamBuilder::open_default_stream()
+        .context("Failed to open the default output device")?;
+    stream.log_on_drop(false);
+    let sink = rodio::Sink::connect_new(stream.mixer());
+    sink.append(rodio::buffer::SamplesBuffer::new(
+        channels,
+        sample_rate,
+        samples,
+    ));
+    sink.sleep_until_end();
+    Ok(())
+}
@@ -0,0 +1,171 @@
+//! Verbatim mode for dictating code
+//!
+//! Drops the punctuation and capitalization Whisper adds, lowercases
+//! everything and turns spoken symbols and case formatters into identifiers:
+//! "snake case max retries" becomes `max_retries`, "main dot rs" becomes
+//! `main.rs` and "git dash dash amend" becomes `git --amend`.
+
+/// How a spoken symbol attaches to the word before it
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Join {
+    /// Always joined (`max_retries`, `main.rs`)
+    Always,
+    /// Joined only when continuing a token (`cd /etc/nginx`)
+    InToken,
+    /// Starts a new word (`git --amend --no-edit`)
+    Never,
+}
+
+/// Spoken symbols and their replacements; the next word is always joined
+const SYMBOLS: &[(&[&str], &str, Join)] = &[
+    (&["underscore"], "_", Join::Always),
+    (&["dot"], ".", Join::Always),
+    (&["slash"], "/", Join::InToken),
+    (&["dash"], "-", Join::Never),
+    (&["hyphen"], "-", Join::Never),
+];
+
+/// How the words following a case formatter are joined into one identifier
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Case {
+    Camel,
+    Pascal,
+    Snake,
+    Kebab,
+}
+
+const CASES: &[(&[&str], Case)] = &[
+    (&["camel", "case"], Case::Camel),
+    (&["pascal", "case"], Case::Pascal),
+    (&["snake", "case"], Case::Snake),
+    (&["kebab", "case"], Case::Kebab),
+];
+
+/// Characters Whisper attaches to words that are never part of dictated code
+const PUNCTUATION: &[char] = &['.', ',', '!', '?', ';', ':', '"'];
+
+/// Convert a transcript into verbatim code
+pub fn apply(text: &str) -> String {
+    let words: Vec<String> = text
+        .split_whitespace()
+        .map(|w| w.trim_matches(PUNCTUATION).to_lowercase())
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    let mut out = String::new();
+    // The next word attaches to the previous output without a space
+    let mut glue = false;
+    // The previous word was attached to a symbol
+    let mut in_token = false;
+    let mut i = 0;
+
+    while i < words.len() {
+        if let Some((len, symbol, join)) = match_symbol(&words[i..]) {
+            if join == Join::Always || (join == Join::InToken && in_token) {
+                out.truncate(out.trim_end_matches(' ').len());
+            } else if !glue && !out.is_empty() {
+                out.push(' ');
+            }
+            out.push_str(symbol);
+            glue = true;
+            i += len;
+            continue;
+        }
+
+        let word = if let Some((len, case)) = match_case(&words[i..]) {
+            i += len;
+            // The identifier runs until the next spoken symbol or formatter
+            let start = i;
+            while i < words.len()
+                && match_symbol(&words[i..]).is_none()
+                && match_case(&words[i..]).is_none()
+            {
+                i += 1;
+            }
+            format_case(&words[start..i], case)
+        } else {
+            i += 1;
+            words[i - 1].clone()
+        };
+
+        if !glue && !out.is_empty() {
+            out.push(' ');
+        }
+        out.push_str(&word);
+        in_token = glue;
+        glue = false;
+    }
+
+    out
+}
+
+fn match_symbol(words: &[String]) -> Option<(usize, &'static str, Join)> {
+    SYMBOLS.iter().find_map(|(phrase, symbol, join)| {
+        starts_with(words, phrase).then_some((phrase.len(), *symbol, *join))
+    })
+}
+
+fn match_case(words: &[String]) -> Option<(usize, Case)> {
+    CASES
+        .iter()
+        .find_map(|(phrase, case)| starts_with(words, phrase).then_some((phrase.len(), *case)))
+}
+
+fn starts_with(words: &[String], phrase: &[&str]) -> bool {
+    words.len() >= phrase.len() && phrase.iter().zip(words).all(|(p, w)| p == w)
+}
+
+fn format_case(words: &[String], case: Case) -> String {
+    match case {
+        Case::Snake => words.join("_"),
+        Case::Kebab => words.join("-"),
+        Case::Camel | Case::Pascal => words
+            .iter()
+            .enumerate()
+            .map(|(i, word)| {
+                if i == 0 && case == Case::Camel {
+                    word.clone()
+                } else {
+                    capitalize(word)
+                }
+            })
+            .collect(),
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strips_punctuation_and_case() {
+        assert_eq!(apply("Cargo build, release."), "cargo build release");
+    }
+
+    #[test]
+    fn test_symbols() {
+        assert_eq!(apply("max underscore retries"), "max_retries");
+        assert_eq!(apply("Open main dot RS."), "open main.rs");
+        assert_eq!(
+            apply("git commit dash dash amend dash dash kebab case no edit"),
+            "git commit --amend --no-edit"
+        );
+        assert_eq!(apply("cd slash etc slash nginx"), "cd /etc/nginx");
+    }
+
+    #[test]
+    fn test_case_formatters() {
+        assert_eq!(apply("Camel case, user name."), "userName");
+        assert_eq!(apply("pascal case http client"), "HttpClient");
+        assert_eq!(apply("let snake case retry count"), "let retry_count");
+        assert_eq!(apply("kebab case dry run dot sh"), "dry-run.sh");
+    }
+}
@@ -0,0 +1,177 @@
+//! Spoken dictation commands
+//!
+//! Converts spoken formatting commands ("new line", "comma", "scratch that", ...)
+//! into the text they describe. Whisper usually punctuates around the spoken
+//! command ("Hello, comma, world."), so commands are matched on words with their
+//! surrounding punctuation stripped and any punctuation Whisper attached to the
+//! previous word is replaced by the dictated mark.
+
+/// A dictation command recognized in the transcript
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Command {
+    /// Insert a punctuation mark, attached to the previous word
+    Punctuation(char),
+    /// Insert one or more line breaks
+    Break(&'static str),
+    /// Start upper-casing words until `EndCaps`
+    AllCaps,
+    /// Stop upper-casing words
+    EndCaps,
+    /// Remove the previous phrase
+    ScratchThat,
+}
+
+/// Spoken phrases and the commands they map to (longest phrases first)
+const COMMANDS: &[(&[&str], Command)] = &[
+    (&["exclamation", "mark"], Command::Punctuation('!')),
+    (&["exclamation", "point"], Command::Punctuation('!')),
+    (&["question", "mark"], Command::Punctuation('?')),
+    (&["full", "stop"], Command::Punctuation('.')),
+    (&["new", "paragraph"], Command::Break("\n\n")),
+    (&["new", "line"], Command::Break("\n")),
+    (&["all", "caps"], Command::AllCaps),
+    (&["end", "caps"], Command::EndCaps),
+    (&["scratch", "that"], Command::ScratchThat),
+    (&["delete", "that"], Command::ScratchThat),
+    (&["period"], Command::Punctuation('.')),
+    (&["comma"], Command::Punctuation(',')),
+    (&["colon"], Command::Punctuation(':')),
+    (&["semicolon"], Command::Punctuation(';')),
+];
+
+/// Characters treated as phrase boundaries and stripped when matching commands
+const PUNCTUATION: &[char] = &['.', ',', '!', '?', ';', ':'];
+
+/// Apply spoken dictation commands to a transcript
+pub fn apply_commands(text: &str) -> String {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let normalized: Vec<String> = words.iter().map(|w| normalize(w)).collect();
+
+    let mut out = String::new();
+    let mut all_caps = false;
+    let mut capitalize_next = false;
+    let mut i = 0;
+
+    while i < words.len() {
+        if let Some((len, command)) = match_command(&normalized[i..]) {
+            match command {
+                Command::Punctuation(mark) => {
+                    trim_trailing_punctuation(&mut out);
+                    out.push(mark);
+                    capitalize_next = matches!(mark, '.' | '!' | '?');
+                }
+                Command::Break(breaks) => {
+                    out.truncate(out.trim_end_matches(' ').len());
+                    out.push_str(breaks);
+                    capitalize_next = true;
+                }
+                Command::AllCaps => all_caps = true,
+                Command::EndCaps => all_caps = false,
+                Command::ScratchThat => scratch_phrase(&mut out),
+            }
+            i += len;
+            continue;
+        }
+
+        let word = words[i];
+        if !out.is_empty() && !out.ends_with('\n') {
+            out.push(' ');
+        }
+        if all_caps {
+            out.push_str(&word.to_uppercase());
+        } else if capitalize_next {
+            out.push_str(&capitalize(word));
+        } else {
+            out.push_str(word);
+        }
+        capitalize_next = false;
+        i += 1;
+    }
+
+    out
+}
+
+/// Lowercase a word and strip surrounding punctuation for command matching
+fn normalize(word: &str) -> String {
+    word.trim_matches(|c: char| PUNCTUATION.contains(&c) || c == '"')
+        .to_lowercase()
+}
+
+/// Find the command starting at the beginning of `words`, if any
+fn match_command(words: &[String]) -> Option<(usize, Command)> {
+    COMMANDS.iter().find_map(|(phrase, command)| {
+        let matches = words.len() >= phrase.len() && phrase.iter().zip(words).all(|(p, w)| p == w);
+        matches.then_some((phrase.len(), *command))
+    })
+}
+
+/// Remove trailing spaces and punctuation so a dictated mark replaces Whisper's own
+fn trim_trailing_punctuation(out: &mut String) {
+    let trimmed = out.trim_end_matches(|c: char| c == ' ' || PUNCTUATION.contains(&c));
+    out.truncate(trimmed.len());
+}
+
+/// Remove everything after the last phrase boundary
+fn scratch_phrase(out: &mut String) {
+    trim_trailing_punctuation(out);
+    let keep = out
+        .rfind(|c: char| PUNCTUATION.contains(&c) || c == '\n')
+        .map(|idx| idx + 1)
+        .unwrap_or(0);
+    out.truncate(keep);
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_punctuation_commands() {
+        assert_eq!(apply_commands("hello comma world period"), "hello, world.");
+        assert_eq!(
+            apply_commands("Hello, comma, world. Period. how are you question mark"),
+            "Hello, world. How are you?"
+        );
+    }
+
+    #[test]
+    fn test_line_breaks() {
+        assert_eq!(
+            apply_commands("first line new line second new paragraph third"),
+            "first line\nSecond\n\nThird"
+        );
+    }
+
+    #[test]
+    fn test_all_caps() {
+        assert_eq!(
+            apply_commands("this is all caps very important end caps okay"),
+            "this is VERY IMPORTANT okay"
+        );
+    }
+
+    #[test]
+    fn test_scratch_that() {
+        assert_eq!(
+            apply_commands("I like cats. I like dogs, scratch that."),
+            "I like cats."
+        );
+        assert_eq!(apply_commands("never mind delete that"), "");
+    }
+
+    #[test]
+    fn test_plain_text_unchanged() {
+        assert_eq!(
+            apply_commands("Nothing to see here."),
+            "Nothing to see here."
+        );
+    }
+}
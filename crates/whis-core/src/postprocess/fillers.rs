@@ -0,0 +1,194 @@
+//! Filler word and stutter removal
+//!
+//! Drops hesitations ("um", "uh") and repeated words ("the the", "I- I").
+//! Phrases of several words ("you know") are only removed where they are set
+//! off by a comma, so "Do you know the way?" keeps its words. Whisper's
+//! commas around a removed filler are dropped with it.
+
+/// Fillers removed unless `Settings.fillers` replaces the list
+pub const DEFAULT_FILLERS: &[&str] = &[
+    "um", "umm", "uh", "uhh", "uhm", "er", "erm", "ah", "hmm", "mhm", "you know",
+];
+
+/// Words that are often correctly repeated ("I know that that works")
+const REPEATABLE: &[&str] = &["that", "had", "is"];
+
+/// Characters treated as punctuation around a word
+const PUNCTUATION: &[char] = &['.', ',', '!', '?', ';', ':', '"', '-'];
+
+/// Remove filler words and phrases and stutters from a transcript
+pub fn remove<S: AsRef<str>>(text: &str, fillers: &[S]) -> String {
+    let mut phrases: Vec<Vec<String>> = fillers
+        .iter()
+        .map(|f| {
+            f.as_ref()
+                .split_whitespace()
+                .map(str::to_lowercase)
+                .collect()
+        })
+        .filter(|p: &Vec<String>| !p.is_empty())
+        .collect();
+    // Longest phrases first
+    phrases.sort_by_key(|p| std::cmp::Reverse(p.len()));
+
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let keys: Vec<String> = words.iter().map(|w| normalize(w)).collect();
+
+    let mut out: Vec<String> = Vec::new();
+    let mut capitalize_next = false;
+    let mut i = 0;
+
+    while i < words.len() {
+        let sentence_start = out.last().is_none_or(|w| ends_sentence(w));
+
+        if let Some(len) = match_filler(&phrases, &words[i..], &keys[i..], &out) {
+            let trail = trailing_punctuation(words[i + len - 1]);
+            if sentence_start && starts_uppercase(words[i]) {
+                capitalize_next = true;
+            }
+            if let Some(mark) = trail.chars().find(|c| matches!(c, '.' | '!' | '?')) {
+                // "it was fine, um." keeps its full stop
+                if let Some(prev) = out.last_mut() {
+                    let trimmed = prev.trim_end_matches([',', ';', ':']).len();
+                    prev.truncate(trimmed);
+                    if !ends_sentence(prev) {
+                        prev.push(mark);
+                    }
+                }
+            } else if trail.contains(',')
+                && let Some(prev) = out.last_mut()
+                && prev.ends_with(',')
+            {
+                // "I think, uh, we" → "I think we"
+                prev.pop();
+            }
+            i += len;
+            continue;
+        }
+
+        // Stutter: drop the first of two equal words
+        let word = words[i];
+        let repeated = keys.get(i + 1).is_some_and(|next| *next == keys[i])
+            && !keys[i].is_empty()
+            && !REPEATABLE.contains(&keys[i].as_str())
+            && (trailing_punctuation(word).is_empty() || word.ends_with('-'));
+        if repeated {
+            if sentence_start && starts_uppercase(word) {
+                capitalize_next = true;
+            }
+            i += 1;
+            continue;
+        }
+
+        if capitalize_next {
+            out.push(capitalize(word));
+            capitalize_next = false;
+        } else {
+            out.push(word.to_string());
+        }
+        i += 1;
+    }
+
+    out.join(" ")
+}
+
+/// Length of the filler phrase starting at the beginning of `words`, if any
+fn match_filler(
+    phrases: &[Vec<String>],
+    words: &[&str],
+    keys: &[String],
+    out: &[String],
+) -> Option<usize> {
+    phrases
+        .iter()
+        .find(|phrase| {
+            let len = phrase.len();
+            if keys.len() < len || phrase.iter().zip(keys).any(|(p, k)| p != k) {
+                return false;
+            }
+            // Punctuation inside the phrase means the words belong apart
+            if words[..len - 1]
+                .iter()
+                .any(|w| !trailing_punctuation(w).is_empty())
+            {
+                return false;
+            }
+            len == 1
+                || !trailing_punctuation(words[len - 1]).is_empty()
+                || out.last().is_some_and(|w| w.ends_with(','))
+        })
+        .map(Vec::len)
+}
+
+/// Lowercase a word and strip surrounding punctuation for matching
+fn normalize(word: &str) -> String {
+    word.trim_matches(PUNCTUATION).to_lowercase()
+}
+
+fn trailing_punctuation(word: &str) -> &str {
+    let body = word.trim_end_matches(PUNCTUATION);
+    &word[body.len()..]
+}
+
+fn ends_sentence(word: &str) -> bool {
+    word.ends_with(['.', '!', '?'])
+}
+
+fn starts_uppercase(word: &str) -> bool {
+    word.starts_with(char::is_uppercase)
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clean(text: &str) -> String {
+        remove(text, DEFAULT_FILLERS)
+    }
+
+    #[test]
+    fn test_hesitations() {
+        assert_eq!(clean("Um, I think we should go."), "I think we should go.");
+        assert_eq!(clean("I think, uh, we should go."), "I think we should go.");
+        assert_eq!(clean("so um the plan"), "so the plan");
+        assert_eq!(clean("It was fine, um."), "It was fine.");
+        assert_eq!(clean("Uh. Okay then."), "Okay then.");
+    }
+
+    #[test]
+    fn test_phrases_need_commas() {
+        assert_eq!(clean("You know, it works."), "It works.");
+        assert_eq!(clean("It was, you know, fine."), "It was fine.");
+        assert_eq!(clean("Do you know the way?"), "Do you know the way?");
+    }
+
+    #[test]
+    fn test_stutters() {
+        assert_eq!(clean("the the plan"), "the plan");
+        assert_eq!(clean("I I I think so"), "I think so");
+        assert_eq!(clean("I- I think so"), "I think so");
+        assert_eq!(clean("The the plan works."), "The plan works.");
+        assert_eq!(clean("I know that that works"), "I know that that works");
+        // Separated by punctuation on purpose
+        assert_eq!(clean("Go, go, go!"), "Go, go, go!");
+    }
+
+    #[test]
+    fn test_custom_list() {
+        assert_eq!(remove("so like, I mean it", &["like"]), "so I mean it");
+        assert_eq!(remove("um okay", &["like"]), "um okay");
+    }
+
+    #[test]
+    fn test_plain_text_unchanged() {
+        assert_eq!(clean("Nothing to see here."), "Nothing to see here.");
+    }
+}
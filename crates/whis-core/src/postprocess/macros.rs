@@ -0,0 +1,65 @@
+//! Spoken text snippets
+//!
+//! A macro maps a spoken phrase to the text it stands for, e.g. "sign off"
+//! to "Best regards,\nFrank". Phrases are matched as whole words in any case,
+//! with the punctuation Whisper attached to their end ("Sign off.").
+
+use regex::{NoExpand, RegexBuilder};
+use std::collections::BTreeMap;
+
+/// Replace every spoken phrase of `macros` with its text
+///
+/// Longer phrases go first, so "sign off formally" wins over "sign off".
+pub fn apply(text: &str, macros: &BTreeMap<String, String>) -> String {
+    let mut phrases: Vec<(&String, &String)> = macros.iter().collect();
+    phrases.sort_by_key(|(phrase, _)| std::cmp::Reverse(phrase.split_whitespace().count()));
+
+    let mut text = text.to_string();
+    for (phrase, expansion) in phrases {
+        let words: Vec<String> = phrase.split_whitespace().map(regex::escape).collect();
+        if words.is_empty() {
+            continue;
+        }
+        let pattern = RegexBuilder::new(&format!(r"\b{}\b[.,!?;:]?", words.join(r"\s+")))
+            .case_insensitive(true)
+            .build();
+        if let Ok(pattern) = pattern {
+            text = pattern.replace_all(&text, NoExpand(expansion)).into_owned();
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn macros(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(phrase, text)| (phrase.to_string(), text.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_expands_phrases() {
+        let macros = macros(&[("sign off", "Best regards,\nFrank")]);
+        assert_eq!(
+            apply("Thanks for the update. Sign off.", &macros),
+            "Thanks for the update. Best regards,\nFrank"
+        );
+        assert_eq!(apply("We signed off on it", &macros), "We signed off on it");
+    }
+
+    #[test]
+    fn test_longest_phrase_first() {
+        let macros = macros(&[
+            ("my address", "Main St 1"),
+            ("my address in full", "Main St 1, Berlin, $0"),
+        ]);
+        assert_eq!(
+            apply("Send it to my address in full", &macros),
+            "Send it to Main St 1, Berlin, $0"
+        );
+    }
+}
@@ -0,0 +1,95 @@
+//! Local post-processing applied to transcripts before output
+//!
+//! Each stage is optional and controlled by `Settings`.
+
+pub mod code;
+pub mod dictation;
+pub mod fillers;
+pub mod macros;
+pub mod normalize;
+pub mod paragraphs;
+pub mod profanity;
+pub mod redact;
+pub mod terms;
+pub mod timestamps;
+
+use crate::provider::Transcription;
+use crate::quality;
+use crate::settings::Settings;
+
+/// The text of a transcription, formatted with its word details where enabled
+/// (silent segments, low-confidence highlighting, unreliable segments,
+/// paragraph breaks at pauses, time stamps)
+pub fn to_text(transcription: Transcription, settings: &Settings) -> String {
+    let transcription = if settings.drop_silent_segments {
+        quality::drop_silence(transcription)
+    } else {
+        transcription
+    };
+    if settings.timestamps
+        && let Some(text) = timestamps::apply(&transcription)
+    {
+        return text;
+    }
+    let words = transcription.words.clone();
+    let text = if settings.mark_unreliable && !transcription.segments.is_empty() {
+        quality::mark(&transcription)
+    } else {
+        transcription.into_text(settings.min_confidence)
+    };
+    let Some(pause) = settings.paragraph_pause_secs else {
+        return text;
+    };
+    paragraphs::apply(&text, &words, pause)
+}
+
+/// Run all enabled post-processing stages over a transcript
+///
+/// Word-based stages run line by line, so line breaks are kept.
+pub fn process(text: &str, settings: &Settings) -> String {
+    let mut text = text.to_string();
+
+    if settings.remove_fillers {
+        text = match &settings.fillers {
+            Some(list) => by_line(&text, |line| fillers::remove(line, list)),
+            None => by_line(&text, |line| {
+                fillers::remove(line, fillers::DEFAULT_FILLERS)
+            }),
+        };
+    }
+
+    // Code mode replaces prose formatting entirely
+    if settings.code_mode {
+        text = by_line(&text, code::apply);
+    } else if settings.dictation_commands {
+        text = by_line(&text, dictation::apply_commands);
+    }
+    if settings.normalize && !settings.code_mode {
+        text = by_line(&text, |line| normalize::apply(line, settings.locale));
+    }
+    if !settings.terms.is_empty() {
+        text = terms::apply(&text, &settings.terms);
+    }
+    if settings.profanity_filter {
+        text = match &settings.profanity_words {
+            Some(list) => profanity::apply(&text, list),
+            None => profanity::apply(&text, profanity::DEFAULT_WORDS),
+        };
+    }
+    // After normalizing, so spoken numbers and addresses are masked once
+    // written out
+    if settings.redact {
+        text = redact::apply(&text, &settings.redact_patterns);
+    }
+    // Last, so snippets come out exactly as written
+    if !settings.macros.is_empty() {
+        text = macros::apply(&text, &settings.macros);
+    }
+
+    text
+}
+
+/// Apply a stage to each line of the text
+fn by_line(text: &str, stage: impl Fn(&str) -> String) -> String {
+    text.split('\n').map(stage).collect::<Vec<_>>().join("\n")
+}
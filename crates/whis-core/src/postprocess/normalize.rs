@@ -0,0 +1,894 @@
+//! Spoken forms to written forms
+//!
+//! Rewrites numbers, amounts, percentages, times and email addresses the way
+//! they are usually written: "twenty three dollars" → "$23", "five pm" →
+//! "5 PM", "john dot doe at example dot com" → "john.doe@example.com".
+//!
+//! The vocabulary and the conventions (currency placement, separators, time
+//! format) follow the configured `Locale`. Numbers below ten stay words unless
+//! they carry a unit, as most style guides spell them out, and a number never
+//! continues past punctuation Whisper put after a word.
+
+use serde::{Deserialize, Serialize};
+
+/// Language and writing conventions used by `apply`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum Locale {
+    /// "$1500.50", "12,000", "5:30 PM"
+    #[default]
+    #[serde(rename = "en-US")]
+    EnUs,
+    /// "£1500.50", "12,000", "5.30pm"
+    #[serde(rename = "en-GB")]
+    EnGb,
+    /// "1500,50 €", "12.000", "17:30 Uhr"
+    #[serde(rename = "de")]
+    De,
+}
+
+impl Locale {
+    fn is_english(self) -> bool {
+        matches!(self, Locale::EnUs | Locale::EnGb)
+    }
+}
+
+/// Numbers from this value on get thousands separators
+const GROUPING_FROM: u64 = 10_000;
+
+/// Characters split off the end of a word and kept as they are
+const TRAILING: &[char] = &['.', ',', '!', '?', ';', ':', '"', ')'];
+
+/// A transcribed word with its trailing punctuation split off
+#[derive(Debug)]
+struct Token<'a> {
+    text: &'a str,
+    /// Lowercase form used for matching
+    key: String,
+    trail: &'a str,
+}
+
+impl Token<'_> {
+    /// Whether nothing separates this word from the next one
+    fn open(&self) -> bool {
+        self.trail.is_empty()
+    }
+}
+
+/// A number read from one or more tokens
+#[derive(Debug)]
+struct Number {
+    int: u64,
+    /// Digits after the decimal separator
+    frac: String,
+    /// Tokens the number was read from
+    len: usize,
+    /// Written with digits in the transcript already
+    digits: bool,
+    /// A single group below 100, without "hundred" or a scale word
+    simple: bool,
+}
+
+/// Rewrite spoken numbers, amounts, times and email addresses in written form
+pub fn apply(text: &str, locale: Locale) -> String {
+    let tokens = tokenize(text, locale);
+    let mut out: Vec<String> = Vec::new();
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let rest = &tokens[i..];
+
+        if let Some((len, email)) = email(rest, locale) {
+            out.push(format!("{email}{}", rest[len - 1].trail));
+            i += len;
+            continue;
+        }
+
+        if let Some(number) = parse_number(rest, locale) {
+            let after = &rest[number.len..];
+            if rest[number.len - 1].open() {
+                let unit = time(&number, after, locale)
+                    .or_else(|| currency(&number, after, locale))
+                    .or_else(|| percent(&number, after, locale))
+                    .or_else(|| year(&number, after, locale));
+                if let Some((len, written)) = unit {
+                    out.push(written);
+                    i += number.len + len;
+                    continue;
+                }
+            }
+            if !number.digits && (number.int >= 10 || !number.frac.is_empty()) {
+                let written = format_number(number.int, &number.frac, locale);
+                out.push(format!("{written}{}", rest[number.len - 1].trail));
+                i += number.len;
+                continue;
+            }
+        }
+
+        out.push(format!("{}{}", rest[0].text, rest[0].trail));
+        i += 1;
+    }
+
+    out.join(" ")
+}
+
+fn tokenize(text: &str, locale: Locale) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    for word in text.split_whitespace() {
+        let body = word.trim_end_matches(TRAILING);
+        let trail = &word[body.len()..];
+
+        // "twenty-three" is read as two words
+        let parts: Vec<&str> = body.split('-').collect();
+        let split = locale.is_english()
+            && parts.len() > 1
+            && parts
+                .iter()
+                .all(|p| english_word(&p.to_lowercase()).is_some());
+        if split {
+            let last = parts.len() - 1;
+            for (idx, part) in parts.into_iter().enumerate() {
+                tokens.push(Token {
+                    text: part,
+                    key: part.to_lowercase(),
+                    trail: if idx == last { trail } else { "" },
+                });
+            }
+        } else {
+            tokens.push(Token {
+                text: body,
+                key: body.to_lowercase(),
+                trail,
+            });
+        }
+    }
+    tokens
+}
+
+// --- Email addresses ---
+
+/// Read "name dot name at domain dot com" from the start of `tokens`
+fn email(tokens: &[Token], locale: Locale) -> Option<(usize, String)> {
+    let is_word = |t: &Token| {
+        !t.key.is_empty()
+            && t.key.chars().all(char::is_alphanumeric)
+            && email_symbol(&t.key, locale).is_none()
+    };
+
+    // Local part: words joined by dots, underscores and dashes, up to "at"
+    let mut out = String::new();
+    let mut i = 0;
+    loop {
+        let word = tokens.get(i).filter(|t| is_word(t) && t.open())?;
+        out.push_str(&word.key);
+        i += 1;
+        let next = tokens.get(i).filter(|t| t.open())?;
+        match email_symbol(&next.key, locale)? {
+            '@' => {
+                out.push('@');
+                i += 1;
+                break;
+            }
+            symbol => out.push(symbol),
+        }
+        i += 1;
+    }
+
+    // Domain: words joined by dots and dashes, with at least one dot
+    let mut dots = 0;
+    loop {
+        let word = tokens.get(i).filter(|t| is_word(t))?;
+        out.push_str(&word.key);
+        i += 1;
+        if !word.open() {
+            break;
+        }
+        let symbol = tokens
+            .get(i)
+            .filter(|t| t.open())
+            .and_then(|t| email_symbol(&t.key, locale));
+        let symbol = match symbol {
+            Some(dot @ '.') => {
+                dots += 1;
+                dot
+            }
+            Some(dash @ '-') => dash,
+            _ => break,
+        };
+        out.push(symbol);
+        i += 1;
+    }
+
+    (dots > 0).then_some((i, out))
+}
+
+/// The character a spoken word stands for in an email address
+fn email_symbol(key: &str, locale: Locale) -> Option<char> {
+    let symbol = match (locale, key) {
+        (_, "at") => '@',
+        (Locale::De, "ät") => '@',
+        (Locale::EnUs | Locale::EnGb, "dot") => '.',
+        (Locale::EnUs | Locale::EnGb, "underscore") => '_',
+        (Locale::EnUs | Locale::EnGb, "dash" | "hyphen") => '-',
+        (Locale::De, "punkt") => '.',
+        (Locale::De, "unterstrich") => '_',
+        (Locale::De, "bindestrich" | "minus") => '-',
+        _ => return None,
+    };
+    Some(symbol)
+}
+
+// --- Units following a number ---
+
+/// "five pm" → "5 PM" (en-US) / "5pm" (en-GB), "fünf uhr dreißig" → "5:30 Uhr"
+fn time(number: &Number, after: &[Token], locale: Locale) -> Option<(usize, String)> {
+    if !number.frac.is_empty() {
+        return None;
+    }
+    let hour = number.int;
+
+    if locale == Locale::De {
+        let uhr = after.first().filter(|t| t.key == "uhr")?;
+        if hour > 24 {
+            return None;
+        }
+        let minutes = parse_number(&after[1..], locale)
+            .filter(|_| uhr.open())
+            .filter(|m| m.frac.is_empty() && m.int < 60);
+        return Some(match minutes {
+            Some(m) => (
+                1 + m.len,
+                format!("{hour}:{:02} Uhr{}", m.int, after[m.len].trail),
+            ),
+            None => (1, format!("{hour} Uhr{}", uhr.trail)),
+        });
+    }
+
+    if !(1..=12).contains(&hour) {
+        return None;
+    }
+    let (minutes, used) = english_minutes(after)
+        .filter(|&(_, used)| after.get(used).and_then(|t| meridiem(&t.key)).is_some())
+        .map_or((None, 0), |(m, used)| (Some(m), used));
+    let suffix = after.get(used)?;
+    let meridiem = meridiem(&suffix.key)?;
+
+    // "p.m." already ends with a dot; keep it only where it ends the sentence
+    let mut trail = suffix.trail;
+    let next_is_lowercase = after
+        .get(used + 1)
+        .is_some_and(|t| t.text.starts_with(|c: char| c.is_lowercase()));
+    if suffix.key.contains('.') && next_is_lowercase {
+        trail = trail.strip_prefix('.').unwrap_or(trail);
+    }
+
+    let written = match (locale, minutes) {
+        (Locale::EnGb, Some(m)) => format!("{hour}.{m:02}{}", meridiem.to_lowercase()),
+        (Locale::EnGb, None) => format!("{hour}{}", meridiem.to_lowercase()),
+        (_, Some(m)) => format!("{hour}:{m:02} {meridiem}"),
+        (_, None) => format!("{hour} {meridiem}"),
+    };
+    Some((used + 1, format!("{written}{trail}")))
+}
+
+/// Minutes after an hour: "thirty", "oh five", "45"
+fn english_minutes(tokens: &[Token]) -> Option<(u64, usize)> {
+    if let [oh, unit, ..] = tokens
+        && oh.key == "oh"
+        && oh.open()
+        && let Some(Word::Unit(n @ 1..)) = english_word(&unit.key)
+    {
+        return unit.open().then_some((n, 2));
+    }
+    let number = parse_number(tokens, Locale::EnUs)?;
+    let valid =
+        number.frac.is_empty() && (10..60).contains(&number.int) && tokens[number.len - 1].open();
+    valid.then_some((number.int, number.len))
+}
+
+fn meridiem(key: &str) -> Option<&'static str> {
+    match key {
+        "am" | "a.m" => Some("AM"),
+        "pm" | "p.m" => Some("PM"),
+        _ => None,
+    }
+}
+
+/// "twenty three dollars" → "$23", "drei euro fünfzig" → "3,50 €"
+fn currency(number: &Number, after: &[Token], locale: Locale) -> Option<(usize, String)> {
+    let word = after.first()?;
+    let symbol = currency_symbol(&word.key, locale)?;
+    let mut used = 1;
+    let mut frac = number.frac.clone();
+    let mut trail = word.trail;
+
+    // "... and fifty cents" / "... euro fünfzig (cent)"
+    if frac.is_empty() && word.open() {
+        let cents_word = |t: &Token| matches!(t.key.as_str(), "cent" | "cents");
+        let cents = if locale.is_english() {
+            after
+                .get(1)
+                .filter(|t| t.key == "and" && t.open())
+                .and_then(|_| parse_number(&after[2..], locale))
+                .map(|c| (c, 2))
+                .filter(|(c, start)| {
+                    after[start + c.len - 1].open()
+                        && after.get(start + c.len).is_some_and(cents_word)
+                })
+                .map(|(c, start)| (c.int, start + c.len + 1))
+        } else {
+            parse_number(&after[1..], locale).map(|c| {
+                let end = 1 + c.len;
+                let with_cent = after[end - 1].open() && after.get(end).is_some_and(cents_word);
+                (c.int, if with_cent { end + 1 } else { end })
+            })
+        };
+        if let Some((cents, end)) = cents.filter(|&(c, _)| (1..100).contains(&c)) {
+            frac = format!("{cents:02}");
+            used = end;
+            trail = after[end - 1].trail;
+        }
+    }
+    if frac.len() == 1 {
+        frac.push('0');
+    }
+
+    let amount = format_number(number.int, &frac, locale);
+    let written = match locale {
+        Locale::De => format!("{amount} {symbol}"),
+        Locale::EnUs | Locale::EnGb => format!("{symbol}{amount}"),
+    };
+    Some((used, format!("{written}{trail}")))
+}
+
+fn currency_symbol(key: &str, locale: Locale) -> Option<&'static str> {
+    let symbol = match (locale, key) {
+        (Locale::EnUs | Locale::EnGb, "dollar" | "dollars" | "bucks") => "$",
+        (Locale::EnUs | Locale::EnGb, "euro" | "euros") => "€",
+        // "pounds" is a weight in the US
+        (Locale::EnGb, "pound" | "pounds" | "quid") => "£",
+        (Locale::De, "euro") => "€",
+        (Locale::De, "dollar") => "$",
+        _ => return None,
+    };
+    Some(symbol)
+}
+
+/// "fifteen percent" → "15%", "fünfzehn prozent" → "15 %"
+fn percent(number: &Number, after: &[Token], locale: Locale) -> Option<(usize, String)> {
+    let amount = format_number(number.int, &number.frac, locale);
+    match (locale, after) {
+        (Locale::De, [word, ..]) if word.key == "prozent" => {
+            Some((1, format!("{amount} %{}", word.trail)))
+        }
+        (Locale::EnUs | Locale::EnGb, [word, ..]) if word.key == "percent" => {
+            Some((1, format!("{amount}%{}", word.trail)))
+        }
+        (Locale::EnUs | Locale::EnGb, [per, cent, ..])
+            if per.key == "per" && per.open() && cent.key == "cent" =>
+        {
+            Some((2, format!("{amount}%{}", cent.trail)))
+        }
+        _ => None,
+    }
+}
+
+/// "nineteen eighty four" → "1984", "twenty oh five" → "2005"
+fn year(number: &Number, after: &[Token], locale: Locale) -> Option<(usize, String)> {
+    if !locale.is_english() || !number.simple || !(16..=20).contains(&number.int) {
+        return None;
+    }
+    let (rest, used, trail) = match after {
+        [oh, unit, ..] if oh.key == "oh" && oh.open() => match english_word(&unit.key) {
+            Some(Word::Unit(n @ 1..)) => (n, 2, unit.trail),
+            _ => return None,
+        },
+        _ => {
+            let second = parse_number(after, locale).filter(|n| n.simple && n.int >= 10)?;
+            (second.int, second.len, after[second.len - 1].trail)
+        }
+    };
+    Some((used, format!("{}{trail}", number.int * 100 + rest)))
+}
+
+/// Digits with the locale's thousands and decimal separators
+fn format_number(int: u64, frac: &str, locale: Locale) -> String {
+    let (group, decimal) = match locale {
+        Locale::De => ('.', ','),
+        Locale::EnUs | Locale::EnGb => (',', '.'),
+    };
+    let digits = int.to_string();
+    let mut out = String::new();
+    for (idx, c) in digits.chars().enumerate() {
+        let remaining = digits.len() - idx;
+        if idx > 0 && remaining.is_multiple_of(3) && int >= GROUPING_FROM {
+            out.push(group);
+        }
+        out.push(c);
+    }
+    if !frac.is_empty() {
+        out.push(decimal);
+        out.push_str(frac);
+    }
+    out
+}
+
+// --- Numbers ---
+
+fn parse_number(tokens: &[Token], locale: Locale) -> Option<Number> {
+    if let Some(number) = tokens.first().and_then(|t| parse_digits(&t.key, locale)) {
+        return Some(number);
+    }
+    match locale {
+        Locale::EnUs | Locale::EnGb => parse_english(tokens),
+        Locale::De => parse_german(tokens),
+    }
+}
+
+/// A number Whisper already wrote with digits, e.g. "23" or "1,500.5"
+fn parse_digits(key: &str, locale: Locale) -> Option<Number> {
+    let (group, decimal) = match locale {
+        Locale::De => ('.', ','),
+        Locale::EnUs | Locale::EnGb => (',', '.'),
+    };
+    let key = key.replace(group, "");
+    let (int, frac) = key.split_once(decimal).unwrap_or((&key, ""));
+    let all_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    if int.is_empty() || !all_digits(int) || !all_digits(frac) {
+        return None;
+    }
+    Some(Number {
+        int: int.parse().ok()?,
+        frac: frac.to_string(),
+        len: 1,
+        digits: true,
+        simple: false,
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Word {
+    /// zero to nine
+    Unit(u64),
+    /// ten to nineteen
+    Teen(u64),
+    /// twenty, thirty, ... ninety
+    Tens(u64),
+    Hundred,
+    /// thousand, million, billion
+    Scale(u64),
+}
+
+fn english_word(key: &str) -> Option<Word> {
+    const UNITS: [&str; 10] = [
+        "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+    ];
+    const TEENS: [&str; 10] = [
+        "ten",
+        "eleven",
+        "twelve",
+        "thirteen",
+        "fourteen",
+        "fifteen",
+        "sixteen",
+        "seventeen",
+        "eighteen",
+        "nineteen",
+    ];
+    const TENS: [&str; 8] = [
+        "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+    ];
+
+    let position = |list: &[&str]| list.iter().position(|w| *w == key).map(|i| i as u64);
+    if let Some(n) = position(&UNITS) {
+        return Some(Word::Unit(n));
+    }
+    if let Some(n) = position(&TEENS) {
+        return Some(Word::Teen(10 + n));
+    }
+    if let Some(n) = position(&TENS) {
+        return Some(Word::Tens(20 + n * 10));
+    }
+    match key {
+        "hundred" => Some(Word::Hundred),
+        "thousand" => Some(Word::Scale(1_000)),
+        "million" => Some(Word::Scale(1_000_000)),
+        "billion" => Some(Word::Scale(1_000_000_000)),
+        _ => None,
+    }
+}
+
+/// "two thousand three hundred and five point seven"
+fn parse_english(tokens: &[Token]) -> Option<Number> {
+    let mut total = 0;
+    let mut current = 0;
+    let mut last: Option<Word> = None;
+    let mut last_scale = u64::MAX;
+    let mut simple = true;
+    let mut i = 0;
+
+    while let Some(token) = tokens.get(i) {
+        if i > 0 && !tokens[i - 1].open() {
+            break;
+        }
+        // "one hundred and five"
+        if token.key == "and"
+            && token.open()
+            && matches!(last, Some(Word::Hundred | Word::Scale(_)))
+        {
+            let next = tokens.get(i + 1).and_then(|t| english_word(&t.key));
+            if matches!(next, Some(Word::Unit(1..) | Word::Teen(_) | Word::Tens(_))) {
+                i += 1;
+                continue;
+            }
+            break;
+        }
+        let Some(word) = english_word(&token.key) else {
+            break;
+        };
+        let after_group = matches!(last, None | Some(Word::Hundred | Word::Scale(_)));
+        match word {
+            Word::Unit(0) if last.is_none() => {}
+            Word::Unit(n @ 1..) if after_group || matches!(last, Some(Word::Tens(_))) => {
+                current += n
+            }
+            Word::Teen(n) | Word::Tens(n) if after_group => current += n,
+            Word::Hundred
+                if matches!(last, Some(Word::Unit(1..) | Word::Teen(_))) && current < 100 =>
+            {
+                current *= 100;
+                simple = false;
+            }
+            Word::Scale(scale) if current > 0 && scale < last_scale => {
+                total += current * scale;
+                current = 0;
+                last_scale = scale;
+                simple = false;
+            }
+            _ => break,
+        }
+        last = Some(word);
+        i += 1;
+    }
+    if i == 0 {
+        return None;
+    }
+
+    // "three point one four"
+    let mut frac = String::new();
+    if tokens[i - 1].open() && tokens.get(i).is_some_and(|t| t.key == "point" && t.open()) {
+        let mut j = i + 1;
+        while let Some(Word::Unit(digit)) = tokens.get(j).and_then(|t| english_word(&t.key)) {
+            frac.push_str(&digit.to_string());
+            j += 1;
+            if !tokens[j - 1].open() {
+                break;
+            }
+        }
+        if !frac.is_empty() {
+            i = j;
+        }
+    }
+
+    let int = total + current;
+    Some(Number {
+        int,
+        simple: simple && frac.is_empty() && int < 100,
+        frac,
+        len: i,
+        digits: false,
+    })
+}
+
+/// "zwei millionen dreihunderttausend", "dreiundzwanzig komma fünf"
+fn parse_german(tokens: &[Token]) -> Option<Number> {
+    let mut total = 0;
+    let mut current = 0;
+    let mut last_scale = u64::MAX;
+    let mut simple = true;
+    let mut i = 0;
+
+    while let Some(token) = tokens.get(i) {
+        if i > 0 && !tokens[i - 1].open() {
+            break;
+        }
+        let scale = match token.key.as_str() {
+            "million" | "millionen" => Some(1_000_000),
+            "milliarde" | "milliarden" => Some(1_000_000_000),
+            _ => None,
+        };
+        if let Some(scale) = scale {
+            if current == 0 || current >= 1000 || scale >= last_scale {
+                break;
+            }
+            total += current * scale;
+            current = 0;
+            last_scale = scale;
+            simple = false;
+        } else if let Some(n) = german_compound(&token.key).filter(|_| current == 0) {
+            current = n;
+            simple &= n < 100 && i == 0;
+        } else {
+            break;
+        }
+        i += 1;
+    }
+    if i == 0 {
+        return None;
+    }
+
+    let mut frac = String::new();
+    if tokens[i - 1].open() && tokens.get(i).is_some_and(|t| t.key == "komma" && t.open()) {
+        let mut j = i + 1;
+        while let Some(digit) = tokens
+            .get(j)
+            .and_then(|t| german_below_hundred(&t.key))
+            .filter(|d| *d < 10)
+        {
+            frac.push_str(&digit.to_string());
+            j += 1;
+            if !tokens[j - 1].open() {
+                break;
+            }
+        }
+        if !frac.is_empty() {
+            i = j;
+        }
+    }
+
+    let int = total + current;
+    Some(Number {
+        int,
+        simple: simple && frac.is_empty() && int < 100,
+        frac,
+        len: i,
+        digits: false,
+    })
+}
+
+/// A German number written as one word, below a million
+fn german_compound(word: &str) -> Option<u64> {
+    if let Some((left, right)) = word.split_once("tausend") {
+        let thousands = if left.is_empty() {
+            1
+        } else {
+            german_below_thousand(left)?
+        };
+        let rest = if right.is_empty() {
+            0
+        } else {
+            german_below_thousand(right)?
+        };
+        return Some(thousands * 1000 + rest);
+    }
+    german_below_thousand(word)
+}
+
+fn german_below_thousand(word: &str) -> Option<u64> {
+    if let Some((left, right)) = word.split_once("hundert") {
+        // "neunzehnhundert" is read as 1900
+        let hundreds = if left.is_empty() {
+            1
+        } else {
+            german_below_hundred(left)?
+        };
+        let rest = if right.is_empty() {
+            0
+        } else {
+            german_below_hundred(right)?
+        };
+        return Some(hundreds * 100 + rest);
+    }
+    german_below_hundred(word)
+}
+
+fn german_below_hundred(word: &str) -> Option<u64> {
+    const SMALL: [(&str, u64); 22] = [
+        ("null", 0),
+        ("eins", 1),
+        ("ein", 1),
+        ("eine", 1),
+        ("zwei", 2),
+        ("drei", 3),
+        ("vier", 4),
+        ("fünf", 5),
+        ("sechs", 6),
+        ("sieben", 7),
+        ("acht", 8),
+        ("neun", 9),
+        ("zehn", 10),
+        ("elf", 11),
+        ("zwölf", 12),
+        ("dreizehn", 13),
+        ("vierzehn", 14),
+        ("fünfzehn", 15),
+        ("sechzehn", 16),
+        ("siebzehn", 17),
+        ("achtzehn", 18),
+        ("neunzehn", 19),
+    ];
+    const TENS: [(&str, u64); 9] = [
+        ("zwanzig", 20),
+        ("dreißig", 30),
+        ("dreissig", 30),
+        ("vierzig", 40),
+        ("fünfzig", 50),
+        ("sechzig", 60),
+        ("siebzig", 70),
+        ("achtzig", 80),
+        ("neunzig", 90),
+    ];
+    let lookup =
+        |list: &[(&str, u64)], key: &str| list.iter().find(|(w, _)| *w == key).map(|(_, n)| *n);
+
+    if let Some(n) = lookup(&SMALL, word).or_else(|| lookup(&TENS, word)) {
+        return Some(n);
+    }
+    // "dreiundzwanzig"
+    let (unit, tens) = word.split_once("und")?;
+    let unit = lookup(&SMALL, unit).filter(|n| (1..10).contains(n))?;
+    Some(lookup(&TENS, tens)? + unit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn us(text: &str) -> String {
+        apply(text, Locale::EnUs)
+    }
+
+    fn gb(text: &str) -> String {
+        apply(text, Locale::EnGb)
+    }
+
+    fn de(text: &str) -> String {
+        apply(text, Locale::De)
+    }
+
+    #[test]
+    fn test_plain_numbers() {
+        assert_eq!(us("I have twenty three apples"), "I have 23 apples");
+        assert_eq!(us("twenty-three people came."), "23 people came.");
+        assert_eq!(us("one hundred and five"), "105");
+        assert_eq!(us("two thousand three hundred"), "2300");
+        assert_eq!(us("twelve thousand five hundred"), "12,500");
+        assert_eq!(us("three million"), "3,000,000");
+        assert_eq!(us("Fifteen"), "15");
+        assert_eq!(us("three point one four"), "3.14");
+        assert_eq!(us("zero point five"), "0.5");
+    }
+
+    #[test]
+    fn test_small_numbers_stay_words() {
+        assert_eq!(
+            us("I have two cats and one dog"),
+            "I have two cats and one dog"
+        );
+        assert_eq!(us("one two three"), "one two three");
+        assert_eq!(us("zero"), "zero");
+    }
+
+    #[test]
+    fn test_numbers_stop_at_punctuation() {
+        assert_eq!(us("twenty, thirty, forty"), "20, 30, 40");
+        assert_eq!(
+            us("It was eighty. Four of them left"),
+            "It was 80. Four of them left"
+        );
+    }
+
+    #[test]
+    fn test_digits_left_alone() {
+        assert_eq!(us("Call 911 now"), "Call 911 now");
+        assert_eq!(us("version 2.5 is out"), "version 2.5 is out");
+    }
+
+    #[test]
+    fn test_currency_us() {
+        assert_eq!(us("twenty three dollars"), "$23");
+        assert_eq!(us("It costs twenty three dollars."), "It costs $23.");
+        assert_eq!(us("five bucks"), "$5");
+        assert_eq!(us("twenty three dollars and fifty cents"), "$23.50");
+        assert_eq!(us("twenty three dollars and fifty"), "$23 and 50");
+        assert_eq!(us("one point five dollars"), "$1.50");
+        assert_eq!(us("twelve thousand euros"), "€12,000");
+        assert_eq!(us("23 dollars"), "$23");
+        assert_eq!(us("1,500 dollars"), "$1500");
+    }
+
+    #[test]
+    fn test_currency_gb() {
+        assert_eq!(gb("ten pounds"), "£10");
+        assert_eq!(gb("twenty quid"), "£20");
+        // A weight in the US
+        assert_eq!(us("ten pounds"), "10 pounds");
+    }
+
+    #[test]
+    fn test_percent() {
+        assert_eq!(us("fifteen percent"), "15%");
+        assert_eq!(us("five per cent."), "5%.");
+        assert_eq!(us("two point five percent"), "2.5%");
+        assert_eq!(de("fünfzehn prozent"), "15 %");
+    }
+
+    #[test]
+    fn test_time_us() {
+        assert_eq!(us("five pm"), "5 PM");
+        assert_eq!(us("Meet at five pm"), "Meet at 5 PM");
+        assert_eq!(us("five thirty pm"), "5:30 PM");
+        assert_eq!(us("ten oh five am"), "10:05 AM");
+        assert_eq!(us("twelve fifteen PM."), "12:15 PM.");
+        assert_eq!(us("at 7 p.m. tomorrow"), "at 7 PM tomorrow");
+        assert_eq!(us("at 7 p.m."), "at 7 PM.");
+        assert_eq!(us("thirteen pm"), "13 pm");
+    }
+
+    #[test]
+    fn test_time_gb() {
+        assert_eq!(gb("five pm"), "5pm");
+        assert_eq!(gb("half past, five thirty pm"), "half past, 5.30pm");
+    }
+
+    #[test]
+    fn test_years() {
+        assert_eq!(us("in nineteen eighty four"), "in 1984");
+        assert_eq!(us("twenty twenty five"), "2025");
+        assert_eq!(us("twenty oh five."), "2005.");
+        assert_eq!(us("nineteen hundred"), "1900");
+        // Only English reads years in pairs
+        assert_eq!(de("neunzehnhundertvierundachtzig"), "1984");
+    }
+
+    #[test]
+    fn test_email() {
+        assert_eq!(
+            us("john dot doe at example dot com"),
+            "john.doe@example.com"
+        );
+        assert_eq!(
+            us("Write to Jane underscore Roe at mail dash host dot co dot uk."),
+            "Write to jane_roe@mail-host.co.uk."
+        );
+        assert_eq!(us("email me at work"), "email me at work");
+        assert_eq!(us("look at this dot"), "look at this dot");
+        assert_eq!(
+            de("max punkt mustermann ät beispiel punkt de"),
+            "max.mustermann@beispiel.de"
+        );
+    }
+
+    #[test]
+    fn test_german_numbers() {
+        assert_eq!(de("dreiundzwanzig Leute"), "23 Leute");
+        assert_eq!(de("zweitausenddreihundert"), "2300");
+        assert_eq!(de("zwölftausendfünfhundert"), "12.500");
+        assert_eq!(de("zwei millionen"), "2.000.000");
+        assert_eq!(de("drei komma fünf"), "3,5");
+        assert_eq!(de("ein Haus und drei Bäume"), "ein Haus und drei Bäume");
+        assert_eq!(de("Jahrhundert"), "Jahrhundert");
+    }
+
+    #[test]
+    fn test_german_currency_and_time() {
+        assert_eq!(de("dreiundzwanzig euro"), "23 €");
+        assert_eq!(de("drei euro fünfzig"), "3,50 €");
+        assert_eq!(de("drei euro fünfzig cent."), "3,50 €.");
+        assert_eq!(de("fünf uhr"), "5 Uhr");
+        assert_eq!(de("siebzehn uhr dreißig"), "17:30 Uhr");
+    }
+
+    #[test]
+    fn test_plain_text_unchanged() {
+        let text = "Nothing to see here, really.";
+        assert_eq!(us(text), text);
+        assert_eq!(de(text), text);
+    }
+
+    #[test]
+    fn test_locale_names() {
+        let locale: Locale = serde_json::from_str("\"en-GB\"").unwrap();
+        assert_eq!(locale, Locale::EnGb);
+        assert_eq!(serde_json::to_string(&Locale::De).unwrap(), "\"de\"");
+    }
+}
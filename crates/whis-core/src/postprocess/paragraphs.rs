@@ -0,0 +1,141 @@
+//! Paragraph breaks at long pauses
+//!
+//! Uses the word timings of a transcription to start a new paragraph where
+//! the speaker paused, so long dictations don't come out as one block. Breaks
+//! only go between sentences: a pause in the middle of a sentence is a
+//! hesitation, not a new thought.
+
+use crate::provider::Word;
+
+/// How many words of the text to look ahead for a timed word
+///
+/// The text and the word list can differ slightly (punctuation, words the
+/// backend merged or split), so words are matched within a small window.
+const LOOKAHEAD: usize = 5;
+
+/// Insert a blank line before sentences that follow a pause of at least
+/// `pause_secs`
+pub fn apply(text: &str, words: &[Word], pause_secs: f32) -> String {
+    let spans = word_spans(text);
+    let mut breaks = vec![false; spans.len()];
+
+    let mut next = 0;
+    for (idx, word) in words.iter().enumerate() {
+        let key = normalize(&word.text);
+        let window = next..(next + LOOKAHEAD).min(spans.len());
+        let Some(pos) = window
+            .into_iter()
+            .find(|&pos| normalize(&text[spans[pos].0..spans[pos].1]) == key)
+        else {
+            continue;
+        };
+
+        let paused = idx > 0 && word.start - words[idx - 1].end >= pause_secs;
+        let after_sentence = pos > 0 && text[..spans[pos - 1].1].ends_with(['.', '!', '?']);
+        breaks[pos] = paused && after_sentence;
+        next = pos + 1;
+    }
+
+    let mut out = String::new();
+    let mut last = 0;
+    for (&(start, end), &paragraph) in spans.iter().zip(&breaks) {
+        if paragraph {
+            out.push_str("\n\n");
+        } else {
+            out.push_str(&text[last..start]);
+        }
+        out.push_str(&text[start..end]);
+        last = end;
+    }
+    out.push_str(&text[last..]);
+    out
+}
+
+/// Byte ranges of the whitespace-separated words of `text`
+fn word_spans(text: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = None;
+    for (idx, c) in text.char_indices() {
+        match (c.is_whitespace(), start) {
+            (true, Some(s)) => {
+                spans.push((s, idx));
+                start = None;
+            }
+            (false, None) => start = Some(idx),
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        spans.push((s, text.len()));
+    }
+    spans
+}
+
+/// Lowercase letters and digits only, for matching text against timed words
+fn normalize(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timed(words: &[(&str, f32, f32)]) -> Vec<Word> {
+        words
+            .iter()
+            .map(|&(text, start, end)| Word {
+                text: text.to_string(),
+                start,
+                end,
+                confidence: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_breaks_after_long_pause() {
+        let words = timed(&[
+            ("First", 0.0, 0.4),
+            ("point", 0.5, 0.9),
+            ("Second", 4.0, 4.4),
+            ("point", 4.5, 4.9),
+        ]);
+        assert_eq!(
+            apply("First point. Second point.", &words, 2.0),
+            "First point.\n\nSecond point."
+        );
+        assert_eq!(
+            apply("First point. Second point.", &words, 5.0),
+            "First point. Second point."
+        );
+    }
+
+    #[test]
+    fn test_no_break_inside_sentence() {
+        let words = timed(&[("I", 0.0, 0.2), ("think", 3.0, 3.4), ("so", 3.5, 3.7)]);
+        assert_eq!(apply("I think so.", &words, 2.0), "I think so.");
+    }
+
+    #[test]
+    fn test_unmatched_words_are_skipped() {
+        // The text says "23" where the backend timed "twenty-three"
+        let words = timed(&[
+            ("It", 0.0, 0.2),
+            ("costs", 0.3, 0.6),
+            ("twenty-three", 0.7, 1.2),
+            ("Okay", 5.0, 5.3),
+        ]);
+        assert_eq!(
+            apply("It costs 23. Okay.", &words, 2.0),
+            "It costs 23.\n\nOkay."
+        );
+    }
+
+    #[test]
+    fn test_without_words() {
+        assert_eq!(apply("Plain text.", &[], 1.0), "Plain text.");
+    }
+}
@@ -0,0 +1,96 @@
+//! Masking of swear words
+//!
+//! Keeps the first letter of a listed word and replaces the rest with
+//! asterisks ("f***"), the way AssemblyAI masks them, so the transcript can
+//! go to professional channels and still read naturally. Inflected forms
+//! ("fucking", "bitches") count as the word. Backends that can censor
+//! themselves are asked to as well (see `TranscriptionRequest`).
+
+/// Words masked unless `Settings.profanity_words` replaces the list
+pub const DEFAULT_WORDS: &[&str] = &[
+    "arsehole",
+    "asshole",
+    "bastard",
+    "bitch",
+    "bollocks",
+    "bullshit",
+    "cock",
+    "cunt",
+    "dick",
+    "fuck",
+    "motherfucker",
+    "piss",
+    "prick",
+    "shit",
+    "slut",
+    "twat",
+    "wanker",
+    "whore",
+];
+
+/// Endings of inflected forms of a listed word
+const SUFFIXES: &[&str] = &["s", "es", "ed", "er", "ers", "ing", "in", "y"];
+
+/// Mask the words of `words` (case-insensitive) in a transcript
+pub fn apply<S: AsRef<str>>(text: &str, words: &[S]) -> String {
+    let words: Vec<String> = words
+        .iter()
+        .map(|w| w.as_ref().trim().to_lowercase())
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(char::is_alphabetic) {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest
+            .find(|c: char| !c.is_alphabetic())
+            .unwrap_or(rest.len());
+        let word = &rest[..end];
+        if is_listed(&word.to_lowercase(), &words) {
+            let mut chars = word.chars();
+            out.extend(chars.next());
+            out.extend(chars.map(|_| '*'));
+        } else {
+            out.push_str(word);
+        }
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Whether `word` (lowercase) is one of `words` or an inflection of one
+fn is_listed(word: &str, words: &[String]) -> bool {
+    words.iter().any(|listed| {
+        word.strip_prefix(listed.as_str())
+            .is_some_and(|ending| ending.is_empty() || SUFFIXES.contains(&ending))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_masks_words_and_inflections() {
+        assert_eq!(
+            apply("Well, shit. This fucking build!", DEFAULT_WORDS),
+            "Well, s***. This f****** build!"
+        );
+        assert_eq!(apply("Bitches, Shit", DEFAULT_WORDS), "B******, S***");
+    }
+
+    #[test]
+    fn test_keeps_other_words() {
+        let text = "Dickens wrote a cocktail of classics in Scunthorpe.";
+        assert_eq!(apply(text, DEFAULT_WORDS), text);
+    }
+
+    #[test]
+    fn test_custom_words() {
+        assert_eq!(apply("Darn it, heck", &["darn", "heck"]), "D*** it, h***");
+        assert_eq!(apply("shit", &[" "]), "shit");
+    }
+}
@@ -0,0 +1,128 @@
+//! Masking of personal data
+//!
+//! Replaces email addresses, phone numbers and card numbers with
+//! placeholders ("[email]", "[phone]", "[card]") before the transcript is
+//! output, so it can be pasted into shared documents. Card numbers must pass
+//! the Luhn check; digit runs that read as dates or grouped amounts are not
+//! taken for phone numbers. `redact_patterns` adds regexes whose matches
+//! become "[redacted]".
+
+use regex::Regex;
+use std::sync::LazyLock;
+
+static EMAIL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[\w.+-]+@[\w-]+(?:\.[\w-]+)*\.[A-Za-z]{2,}").unwrap());
+static CARD: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b(?:\d[ -]?){12,18}\d\b").unwrap());
+static PHONE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:\+|\b)\(?\d[\d ().-]{5,}\d\b").unwrap());
+static DATE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\d{4}-\d{2}-\d{2}$|^\d{1,2}[./]\d{1,2}[./]\d{4}$").unwrap());
+static GROUPED: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\d{1,3}(?:[.,]\d{3})+$").unwrap());
+
+/// Phone numbers have between this many digits and `MAX_PHONE_DIGITS`
+const MIN_PHONE_DIGITS: usize = 7;
+const MAX_PHONE_DIGITS: usize = 15;
+
+/// Mask personal data and the matches of `patterns` in a transcript
+///
+/// Patterns that aren't valid regexes are reported and skipped.
+pub fn apply(text: &str, patterns: &[String]) -> String {
+    let text = CARD.replace_all(text, |caps: &regex::Captures| {
+        let number = &caps[0];
+        if luhn_valid(number) {
+            "[card]".to_string()
+        } else {
+            number.to_string()
+        }
+    });
+    let text = EMAIL.replace_all(&text, "[email]");
+    let text = PHONE.replace_all(&text, |caps: &regex::Captures| {
+        let number = &caps[0];
+        if is_phone(number) {
+            "[phone]".to_string()
+        } else {
+            number.to_string()
+        }
+    });
+
+    let mut text = text.into_owned();
+    for pattern in patterns {
+        match Regex::new(pattern) {
+            Ok(regex) => text = regex.replace_all(&text, "[redacted]").into_owned(),
+            Err(e) => eprintln!("Invalid redact pattern {pattern:?}: {e}"),
+        }
+    }
+    text
+}
+
+/// Whether a digit run has the length of a phone number and doesn't read as
+/// a date or an amount with thousands separators
+fn is_phone(number: &str) -> bool {
+    let digits = number.chars().filter(char::is_ascii_digit).count();
+    (MIN_PHONE_DIGITS..=MAX_PHONE_DIGITS).contains(&digits)
+        && !DATE.is_match(number)
+        && !GROUPED.is_match(number)
+}
+
+/// Luhn checksum used by payment card numbers
+fn luhn_valid(number: &str) -> bool {
+    let digits: Vec<u32> = number.chars().filter_map(|c| c.to_digit(10)).collect();
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| match (i % 2, d * 2) {
+            (0, _) => d,
+            (_, doubled) if doubled > 9 => doubled - 9,
+            (_, doubled) => doubled,
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_email() {
+        assert_eq!(
+            apply("Mail john.doe+work@example.co.uk today.", &[]),
+            "Mail [email] today."
+        );
+    }
+
+    #[test]
+    fn test_card() {
+        assert_eq!(apply("Card 4111 1111 1111 1111.", &[]), "Card [card].");
+        // Fails the Luhn check, and is too long for a phone number
+        assert_eq!(
+            apply("Order 1234 5678 9012 3456.", &[]),
+            "Order 1234 5678 9012 3456."
+        );
+    }
+
+    #[test]
+    fn test_phone() {
+        assert_eq!(apply("Call +1 (555) 123-4567 now", &[]), "Call [phone] now");
+        assert_eq!(apply("Call 030 1234567.", &[]), "Call [phone].");
+        assert_eq!(
+            apply("Due 2025-01-31 or 31.01.2025", &[]),
+            "Due 2025-01-31 or 31.01.2025"
+        );
+        assert_eq!(
+            apply("Raised 1,500,000 and 12.000.000", &[]),
+            "Raised 1,500,000 and 12.000.000"
+        );
+        assert_eq!(apply("Room 101, year 2025", &[]), "Room 101, year 2025");
+    }
+
+    #[test]
+    fn test_custom_patterns() {
+        let patterns = vec![r"Project \w+".to_string(), "(".to_string()];
+        assert_eq!(
+            apply("Status of Project Falcon is green", &patterns),
+            "Status of [redacted] is green"
+        );
+    }
+}
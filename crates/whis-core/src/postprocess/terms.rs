@@ -0,0 +1,80 @@
+//! Spelling of acronyms and product names
+//!
+//! Whisper cases names as it pleases ("Postgresql", "GRPC", "oauth2"). Every
+//! occurrence of a term from `Settings.terms` is written the way it is listed,
+//! whatever its case in the transcript. Only whole words are matched, so
+//! "gRPC" doesn't touch "grpcurl", and spaces in a term match any spacing.
+
+use regex::{Regex, RegexBuilder};
+
+/// Write every term of `terms` in a transcript with its listed casing
+pub fn apply<S: AsRef<str>>(text: &str, terms: &[S]) -> String {
+    let mut text = text.to_string();
+    for term in terms {
+        let term = term.as_ref().trim();
+        if term.is_empty() {
+            continue;
+        }
+        let words: Vec<String> = term.split_whitespace().map(regex::escape).collect();
+        let pattern = RegexBuilder::new(&words.join(r"\s+"))
+            .case_insensitive(true)
+            .build();
+        if let Ok(pattern) = pattern {
+            text = replace_words(&text, &pattern, term);
+        }
+    }
+    text
+}
+
+/// Replace the matches of `pattern` that aren't part of a longer word
+fn replace_words(text: &str, pattern: &Regex, term: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for found in pattern.find_iter(text) {
+        let before = text[..found.start()].chars().next_back();
+        let after = text[found.end()..].chars().next();
+        if before.is_some_and(is_word_char) || after.is_some_and(is_word_char) {
+            continue;
+        }
+        out.push_str(&text[last..found.start()]);
+        out.push_str(term);
+        last = found.end();
+    }
+    out.push_str(&text[last..]);
+    out
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_casing() {
+        let terms = ["PostgreSQL", "gRPC", "OAuth2"];
+        assert_eq!(
+            apply("Postgresql talks GRPC, oauth2 and grpc.", &terms),
+            "PostgreSQL talks gRPC, OAuth2 and gRPC."
+        );
+    }
+
+    #[test]
+    fn test_whole_words_only() {
+        assert_eq!(
+            apply("grpcurl and my_grpc", &["gRPC"]),
+            "grpcurl and my_grpc"
+        );
+        assert_eq!(apply("Use c++ or C#", &["C++", "C#"]), "Use C++ or C#");
+    }
+
+    #[test]
+    fn test_phrases() {
+        assert_eq!(
+            apply("open visual  studio code", &["Visual Studio Code"]),
+            "open Visual Studio Code"
+        );
+    }
+}
@@ -0,0 +1,138 @@
+//! Time stamps in front of each segment
+//!
+//! Puts the time a segment starts in the recording ("[01:23]") in front of
+//! it, one segment per line, so a long transcript can be checked against the
+//! saved audio. Chunks are merged into recording time before this, so the
+//! times run on across chunks. Backends without segments but with word
+//! timings get a line per sentence instead.
+
+use crate::provider::{Transcription, Word};
+
+/// A stretch of the transcript with its time in the recording
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    pub start: f32,
+    pub end: f32,
+    pub text: String,
+}
+
+/// The transcript as time-stamped lines; `None` if the backend reported
+/// no timings
+pub fn apply(transcription: &Transcription) -> Option<String> {
+    let lines: Vec<String> = spans(transcription)
+        .iter()
+        .map(|span| format!("[{}] {}", timestamp(span.start), span.text))
+        .collect();
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// The segments of the transcript, or its sentences for backends that only
+/// time words; empty without timings
+pub fn spans(transcription: &Transcription) -> Vec<Span> {
+    if transcription.segments.is_empty() {
+        return sentences(&transcription.words);
+    }
+    transcription
+        .segments
+        .iter()
+        .filter(|segment| !segment.text.trim().is_empty())
+        .map(|segment| Span {
+            start: segment.start,
+            end: segment.end,
+            text: segment.text.trim().to_string(),
+        })
+        .collect()
+}
+
+/// A span per sentence of the timed words
+fn sentences(words: &[Word]) -> Vec<Span> {
+    let mut spans = Vec::new();
+    let mut sentence: Vec<&Word> = Vec::new();
+    for word in words {
+        sentence.push(word);
+        if word.text.trim_end().ends_with(['.', '!', '?']) {
+            spans.extend(span(&sentence));
+            sentence.clear();
+        }
+    }
+    spans.extend(span(&sentence));
+    spans
+}
+
+fn span(words: &[&Word]) -> Option<Span> {
+    let text: Vec<&str> = words.iter().map(|word| word.text.trim()).collect();
+    Some(Span {
+        start: words.first()?.start,
+        end: words.last()?.end,
+        text: text.join(" "),
+    })
+}
+
+/// "mm:ss", or "h:mm:ss" from an hour on
+fn timestamp(secs: f32) -> String {
+    let secs = secs.max(0.0) as u64;
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes:02}:{seconds:02}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::{Segment, Word};
+
+    fn segment(start: f32, text: &str) -> Segment {
+        Segment {
+            text: text.to_string(),
+            start,
+            end: start + 5.0,
+            avg_logprob: -0.2,
+            no_speech_prob: 0.0,
+        }
+    }
+
+    fn word(start: f32, text: &str) -> Word {
+        Word {
+            text: text.to_string(),
+            start,
+            end: start + 0.4,
+            confidence: None,
+        }
+    }
+
+    #[test]
+    fn test_segments() {
+        let transcription = Transcription {
+            segments: vec![segment(0.0, " Hello there."), segment(83.4, " Next topic.")],
+            ..Transcription::from_text("Hello there. Next topic.")
+        };
+        assert_eq!(
+            apply(&transcription).unwrap(),
+            "[00:00] Hello there.\n[01:23] Next topic."
+        );
+    }
+
+    #[test]
+    fn test_sentences_from_words() {
+        let transcription = Transcription {
+            words: vec![
+                word(1.0, "Hi."),
+                word(3725.0, "Still"),
+                word(3726.0, "here"),
+            ],
+            ..Transcription::from_text("Hi. Still here")
+        };
+        assert_eq!(
+            apply(&transcription).unwrap(),
+            "[00:01] Hi.\n[1:02:05] Still here"
+        );
+    }
+
+    #[test]
+    fn test_without_timings() {
+        assert_eq!(apply(&Transcription::from_text("Hello")), None);
+    }
+}
@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::settings::{ClipboardSelection, OutputMode, Settings};
+
+/// Named set of overrides applied on top of the global settings.
+///
+/// Every field is optional; unset fields keep the global value.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dictation_commands: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code_mode: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remove_fillers: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fillers: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub terms: Option<Vec<String>>,
+    /// Added to the global macros, replacing those with the same phrase
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub macros: Option<BTreeMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redact: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profanity_filter: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summarize: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_mode: Option<OutputMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clipboard_selection: Option<ClipboardSelection>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_note: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anki_target: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anki_deck: Option<String>,
+}
+
+impl Profile {
+    /// Apply this profile's overrides to a copy of the settings
+    pub fn apply(&self, settings: &Settings) -> Settings {
+        let mut effective = settings.clone();
+        if let Some(v) = self.dictation_commands {
+            effective.dictation_commands = v;
+        }
+        if let Some(v) = self.code_mode {
+            effective.code_mode = v;
+        }
+        if let Some(v) = self.remove_fillers {
+            effective.remove_fillers = v;
+        }
+        if let Some(v) = &self.fillers {
+            effective.fillers = Some(v.clone());
+        }
+        if let Some(v) = &self.terms {
+            effective.terms = v.clone();
+        }
+        if let Some(v) = &self.macros {
+            effective.macros.extend(v.clone());
+        }
+        if let Some(v) = self.redact {
+            effective.redact = v;
+        }
+        if let Some(v) = self.profanity_filter {
+            effective.profanity_filter = v;
+        }
+        if let Some(v) = self.summarize {
+            effective.summarize = v;
+        }
+        if let Some(v) = self.output_mode {
+            effective.output_mode = v;
+        }
+        if let Some(v) = self.clipboard_selection {
+            effective.clipboard_selection = v;
+        }
+        if let Some(v) = &self.template {
+            effective.template = Some(v.clone());
+        }
+        if let Some(v) = &self.webhook_url {
+            effective.webhook_url = Some(v.clone());
+        }
+        if let Some(v) = &self.daily_note {
+            effective.daily_note = Some(v.clone());
+        }
+        if let Some(v) = &self.anki_target {
+            effective.anki_target = Some(v.clone());
+        }
+        if let Some(v) = &self.anki_deck {
+            effective.anki_deck = Some(v.clone());
+        }
+        effective
+    }
+}
@@ -0,0 +1,163 @@
+//! AssemblyAI API
+//!
+//! Audio is uploaded first, then a transcript job is created and polled
+//! until it completes. Words come back with per-word confidence.
+
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use serde::Deserialize;
+use serde_json::json;
+use std::time::{Duration, Instant};
+
+use super::{HttpOptions, Provider, Transcription, Word};
+use crate::audio::AudioCodec;
+use crate::request::TranscriptionRequest;
+
+/// Default AssemblyAI speech model
+pub const MODEL: &str = "universal";
+const API_URL: &str = "https://api.assemblyai.com/v2";
+/// How often a pending transcript is checked
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Give up on a transcript job after this long
+const JOB_TIMEOUT: Duration = Duration::from_secs(600);
+
+#[derive(Deserialize)]
+struct UploadResponse {
+    upload_url: String,
+}
+
+#[derive(Deserialize)]
+struct TranscriptResponse {
+    id: String,
+    status: String,
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    words: Option<Vec<WordResponse>>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct WordResponse {
+    text: String,
+    /// Milliseconds
+    start: u64,
+    /// Milliseconds
+    end: u64,
+    confidence: f32,
+}
+
+pub struct AssemblyAI {
+    api_key: String,
+    http: HttpOptions,
+    request: TranscriptionRequest,
+    /// Backend/model identifier for usage tracking
+    usage_model: String,
+}
+
+impl AssemblyAI {
+    pub fn new(api_key: String, http: HttpOptions, request: TranscriptionRequest) -> Self {
+        let usage_model = format!("assemblyai/{}", request.model().unwrap_or(MODEL));
+        Self {
+            api_key,
+            http,
+            request,
+            usage_model,
+        }
+    }
+}
+
+impl Provider for AssemblyAI {
+    fn model(&self) -> &str {
+        &self.usage_model
+    }
+
+    fn max_upload_bytes(&self) -> Option<usize> {
+        None
+    }
+
+    /// Transcodes any common format on upload
+    fn accepts(&self, _codec: AudioCodec) -> bool {
+        true
+    }
+
+    fn transcribe(&self, audio: Bytes, _file_name: &str, _mime: &str) -> Result<Transcription> {
+        let client = self.http.client()?;
+        let (body, len) = self.http.body(audio);
+
+        let response = client
+            .post(format!("{API_URL}/upload"))
+            .header("Authorization", &self.api_key)
+            .body(reqwest::blocking::Body::sized(body, len))
+            .send()
+            .context("Failed to upload audio to AssemblyAI")?;
+        let upload: UploadResponse = super::check_status(response, "AssemblyAI")?
+            .json()
+            .context("Failed to parse AssemblyAI upload response")?;
+
+        let mut job = json!({
+            "audio_url": upload.upload_url,
+            "speech_model": self.request.model().unwrap_or(MODEL),
+        });
+        if let Some(language) = self.request.language_code() {
+            job["language_code"] = json!(language);
+        }
+        if self.request.filter_profanity() {
+            job["filter_profanity"] = json!(true);
+        }
+        let response = client
+            .post(format!("{API_URL}/transcript"))
+            .header("Authorization", &self.api_key)
+            .json(&job)
+            .send()
+            .context("Failed to send request to AssemblyAI API")?;
+        let mut transcript: TranscriptResponse = super::check_status(response, "AssemblyAI")?
+            .json()
+            .context("Failed to parse AssemblyAI API response")?;
+
+        let started = Instant::now();
+        loop {
+            match transcript.status.as_str() {
+                "completed" => break,
+                "error" => anyhow::bail!(
+                    "AssemblyAI transcription failed: {}",
+                    transcript
+                        .error
+                        .unwrap_or_else(|| "Unknown error".to_string())
+                ),
+                _ if started.elapsed() > JOB_TIMEOUT => {
+                    anyhow::bail!("AssemblyAI transcription timed out")
+                }
+                _ => std::thread::sleep(POLL_INTERVAL),
+            }
+
+            let response = client
+                .get(format!("{API_URL}/transcript/{}", transcript.id))
+                .header("Authorization", &self.api_key)
+                .send()
+                .context("Failed to poll AssemblyAI API")?;
+            transcript = super::check_status(response, "AssemblyAI")?
+                .json()
+                .context("Failed to parse AssemblyAI API response")?;
+        }
+
+        Ok(Transcription {
+            text: transcript.text.unwrap_or_default(),
+            words: transcript
+                .words
+                .unwrap_or_default()
+                .into_iter()
+                .map(|w| Word {
+                    text: w.text,
+                    start: w.start as f32 / 1000.0,
+                    end: w.end as f32 / 1000.0,
+                    confidence: Some(w.confidence),
+                })
+                .collect(),
+            segments: Vec::new(),
+            language: None,
+            model: None,
+        })
+    }
+}
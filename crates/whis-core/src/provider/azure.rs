@@ -0,0 +1,150 @@
+//! Azure AI Speech fast transcription API
+//!
+//! Uses the synchronous REST endpoint of the Speech resource in the
+//! configured region, so audio stays within the customer's Azure tenant.
+
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use reqwest::blocking::multipart;
+use serde::Deserialize;
+
+use super::{HttpOptions, Provider, Transcription, Word};
+use crate::audio::AudioCodec;
+use crate::request::TranscriptionRequest;
+
+const API_VERSION: &str = "2024-11-15";
+/// Maximum upload size accepted by fast transcription
+const MAX_UPLOAD_BYTES: usize = 300 * 1024 * 1024;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TranscribeResponse {
+    #[serde(default)]
+    combined_phrases: Vec<CombinedPhrase>,
+    #[serde(default)]
+    phrases: Vec<Phrase>,
+}
+
+#[derive(Deserialize)]
+struct CombinedPhrase {
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct Phrase {
+    #[serde(default)]
+    confidence: Option<f32>,
+    #[serde(default)]
+    words: Vec<WordResponse>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WordResponse {
+    text: String,
+    offset_milliseconds: u64,
+    duration_milliseconds: u64,
+}
+
+pub struct Azure {
+    api_key: String,
+    region: String,
+    http: HttpOptions,
+    request: TranscriptionRequest,
+}
+
+impl Azure {
+    pub fn new(
+        api_key: String,
+        region: String,
+        http: HttpOptions,
+        request: TranscriptionRequest,
+    ) -> Self {
+        Self {
+            api_key,
+            region,
+            http,
+            request,
+        }
+    }
+}
+
+impl Provider for Azure {
+    fn model(&self) -> &str {
+        super::Backend::Azure.model()
+    }
+
+    fn max_upload_bytes(&self) -> Option<usize> {
+        Some(MAX_UPLOAD_BYTES)
+    }
+
+    /// Fast transcription decodes Ogg/Opus as well as MP3
+    fn accepts(&self, _codec: AudioCodec) -> bool {
+        true
+    }
+
+    fn transcribe(&self, audio: Bytes, file_name: &str, mime: &str) -> Result<Transcription> {
+        let url = format!(
+            "https://{}.api.cognitive.microsoft.com/speechtotext/transcriptions:transcribe?api-version={API_VERSION}",
+            self.region
+        );
+        let (body, len) = self.http.body(audio);
+        // Without locales the language is detected
+        let mut definition = serde_json::json!({});
+        if let Some(locale) = self.request.language() {
+            definition["locales"] = serde_json::json!([locale]);
+        }
+        if self.request.filter_profanity() {
+            definition["profanityFilterMode"] = serde_json::json!("Masked");
+        }
+        let form = multipart::Form::new()
+            .text("definition", definition.to_string())
+            .part(
+                "audio",
+                multipart::Part::reader_with_length(body, len)
+                    .file_name(file_name.to_string())
+                    .mime_str(mime)?,
+            );
+
+        let response = self
+            .http
+            .client()?
+            .post(url)
+            .header("Ocp-Apim-Subscription-Key", &self.api_key)
+            .multipart(form)
+            .send()
+            .context("Failed to send request to Azure Speech API")?;
+        let response: TranscribeResponse = super::check_status(response, "Azure Speech")?
+            .json()
+            .context("Failed to parse Azure Speech API response")?;
+
+        let text = response
+            .combined_phrases
+            .into_iter()
+            .map(|p| p.text)
+            .collect::<Vec<_>>()
+            .join(" ");
+        // Azure reports confidence per phrase; every word inherits it
+        let words = response
+            .phrases
+            .into_iter()
+            .flat_map(|phrase| {
+                let confidence = phrase.confidence;
+                phrase.words.into_iter().map(move |w| Word {
+                    text: w.text,
+                    start: w.offset_milliseconds as f32 / 1000.0,
+                    end: (w.offset_milliseconds + w.duration_milliseconds) as f32 / 1000.0,
+                    confidence,
+                })
+            })
+            .collect();
+
+        Ok(Transcription {
+            text,
+            words,
+            segments: Vec::new(),
+            language: None,
+            model: None,
+        })
+    }
+}
@@ -0,0 +1,128 @@
+//! Backend fallback chain
+//!
+//! Tries each provider in order until one succeeds, so a rate limit or
+//! network outage at the primary backend doesn't lose the recording.
+
+use anyhow::Result;
+use bytes::Bytes;
+use std::sync::Arc;
+
+use super::{Provider, Transcription};
+use crate::audio::AudioCodec;
+
+pub struct Fallback {
+    /// Primary provider first
+    providers: Vec<Arc<dyn Provider>>,
+}
+
+impl Fallback {
+    pub fn new(providers: Vec<Arc<dyn Provider>>) -> Self {
+        assert!(!providers.is_empty(), "fallback chain needs a provider");
+        Self { providers }
+    }
+}
+
+impl Provider for Fallback {
+    fn model(&self) -> &str {
+        self.providers[0].model()
+    }
+
+    /// The smallest limit in the chain, so every backend can take the audio
+    fn max_upload_bytes(&self) -> Option<usize> {
+        self.providers
+            .iter()
+            .filter_map(|p| p.max_upload_bytes())
+            .min()
+    }
+
+    /// Only codecs every backend in the chain can decode
+    fn accepts(&self, codec: AudioCodec) -> bool {
+        self.providers.iter().all(|p| p.accepts(codec))
+    }
+
+    fn transcribe(&self, audio: Bytes, file_name: &str, mime: &str) -> Result<Transcription> {
+        let (last, rest) = self.providers.split_last().unwrap();
+        for (i, provider) in rest.iter().enumerate() {
+            match provider.transcribe(audio.clone(), file_name, mime) {
+                Ok(transcription) => return Ok(tag(transcription, provider.as_ref(), i)),
+                Err(e) => eprintln!(
+                    "{} failed, trying {}: {e:#}",
+                    provider.model(),
+                    self.providers[i + 1].model()
+                ),
+            }
+        }
+        let transcription = last.transcribe(audio, file_name, mime)?;
+        Ok(tag(transcription, last.as_ref(), rest.len()))
+    }
+}
+
+/// Record which backend produced a transcription if it wasn't the primary
+fn tag(mut transcription: Transcription, provider: &dyn Provider, index: usize) -> Transcription {
+    if index > 0 {
+        transcription.model = Some(provider.model().to_string());
+    }
+    transcription
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Stub {
+        model: &'static str,
+        result: Option<&'static str>,
+    }
+
+    impl Provider for Stub {
+        fn model(&self) -> &str {
+            self.model
+        }
+
+        fn max_upload_bytes(&self) -> Option<usize> {
+            None
+        }
+
+        fn transcribe(&self, _: Bytes, _: &str, _: &str) -> Result<Transcription> {
+            match self.result {
+                Some(text) => Ok(Transcription::from_text(text)),
+                None => anyhow::bail!("{} is down", self.model),
+            }
+        }
+    }
+
+    fn chain(results: &[Option<&'static str>]) -> Fallback {
+        let models = ["a/1", "b/1", "c/1"];
+        Fallback::new(
+            results
+                .iter()
+                .zip(models)
+                .map(|(&result, model)| Arc::new(Stub { model, result }) as Arc<dyn Provider>)
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_primary_succeeds() {
+        let result =
+            chain(&[Some("hi"), Some("ho")]).transcribe(Bytes::new(), "a.mp3", "audio/mpeg");
+        let transcription = result.unwrap();
+        assert_eq!(transcription.text, "hi");
+        assert_eq!(transcription.model, None);
+    }
+
+    #[test]
+    fn test_falls_back_in_order() {
+        let result =
+            chain(&[None, Some("ho"), Some("hu")]).transcribe(Bytes::new(), "a.mp3", "audio/mpeg");
+        let transcription = result.unwrap();
+        assert_eq!(transcription.text, "ho");
+        assert_eq!(transcription.model.as_deref(), Some("b/1"));
+
+        assert!(
+            chain(&[None, None])
+                .transcribe(Bytes::new(), "a.mp3", "audio/mpeg")
+                .is_err()
+        );
+    }
+}
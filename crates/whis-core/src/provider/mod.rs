@@ -0,0 +1,481 @@
+//! Transcription backends
+//!
+//! Every backend implements [`Provider`] and returns a structured
+//! [`Transcription`]; chunking, merging and output don't depend on which
+//! service produced it.
+
+pub mod assemblyai;
+pub mod azure;
+pub mod fallback;
+pub mod openai;
+pub mod vosk;
+pub mod whisper_server;
+
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use reqwest::blocking::multipart;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::fmt;
+use std::io::{Cursor, Read};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::audio::AudioCodec;
+use crate::request::TranscriptionRequest;
+
+/// Default API request timeout in seconds
+pub const DEFAULT_TIMEOUT_SECS: u64 = 300;
+
+/// Available transcription backends
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    /// OpenAI Whisper API
+    #[default]
+    OpenAI,
+    /// AssemblyAI (word-level confidence)
+    AssemblyAI,
+    /// Azure AI Speech (needs a region)
+    Azure,
+    /// Vosk, offline on the CPU (needs a model path)
+    Vosk,
+    /// Self-hosted faster-whisper / whisper.cpp server (needs a URL)
+    #[serde(rename = "whisper-server")]
+    WhisperServer,
+}
+
+impl Backend {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Backend::OpenAI => "openai",
+            Backend::AssemblyAI => "assemblyai",
+            Backend::Azure => "azure",
+            Backend::Vosk => "vosk",
+            Backend::WhisperServer => "whisper-server",
+        }
+    }
+
+    /// Environment variable holding the API key (`None` for local backends)
+    pub fn env_var(&self) -> Option<&'static str> {
+        match self {
+            Backend::OpenAI => Some("OPENAI_API_KEY"),
+            Backend::AssemblyAI => Some("ASSEMBLYAI_API_KEY"),
+            Backend::Azure => Some("AZURE_SPEECH_KEY"),
+            Backend::Vosk | Backend::WhisperServer => None,
+        }
+    }
+
+    /// Backend/model identifier of the default model, used for usage tracking
+    pub fn model(&self) -> &'static str {
+        match self {
+            Backend::OpenAI => "openai/whisper-1",
+            Backend::AssemblyAI => "assemblyai/universal",
+            Backend::Azure => "azure/fast",
+            Backend::Vosk => "local/vosk",
+            Backend::WhisperServer => "self-hosted/whisper",
+        }
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "openai" => Ok(Backend::OpenAI),
+            "assemblyai" => Ok(Backend::AssemblyAI),
+            "azure" => Ok(Backend::Azure),
+            "vosk" => Ok(Backend::Vosk),
+            "whisper-server" => Ok(Backend::WhisperServer),
+            _ => Err(format!(
+                "unknown backend '{s}' (use openai, assemblyai, azure, vosk or whisper-server)"
+            )),
+        }
+    }
+}
+
+/// A transcription service
+pub trait Provider: Send + Sync {
+    /// Backend/model identifier used for usage tracking, e.g. "openai/whisper-1"
+    fn model(&self) -> &str;
+
+    /// Largest file the API accepts, if it has a limit
+    fn max_upload_bytes(&self) -> Option<usize>;
+
+    /// Whether the backend decodes audio in this codec; MP3 always works
+    fn accepts(&self, codec: AudioCodec) -> bool {
+        codec == AudioCodec::Mp3
+    }
+
+    /// Transcribe encoded audio (blocking)
+    fn transcribe(&self, audio: Bytes, file_name: &str, mime: &str) -> Result<Transcription>;
+
+    /// Check that the backend is reachable (used by `whis doctor`)
+    fn health_check(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A recognized word with its position in the audio
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Word {
+    pub text: String,
+    /// Start time in seconds
+    pub start: f32,
+    /// End time in seconds
+    pub end: f32,
+    /// Recognition confidence from 0 to 1, if the backend reports it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+}
+
+/// A stretch of the transcript with the decoder's quality signals
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Segment {
+    pub text: String,
+    /// Start time in seconds
+    pub start: f32,
+    /// End time in seconds
+    pub end: f32,
+    /// Average log probability of the decoded tokens
+    pub avg_logprob: f32,
+    /// Probability that the segment holds no speech
+    pub no_speech_prob: f32,
+}
+
+/// Result of a transcription: the text plus word details when available
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Transcription {
+    pub text: String,
+    /// Words with timings (empty if the backend doesn't return them)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub words: Vec<Word>,
+    /// Segments with quality signals (Whisper backends only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub segments: Vec<Segment>,
+    /// Language code detected by the backend, e.g. "de"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Backend/model that produced the text when a fallback was used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+impl Transcription {
+    pub fn from_text(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            words: Vec::new(),
+            segments: Vec::new(),
+            language: None,
+            model: None,
+        }
+    }
+
+    /// Backend/model to bill this transcription to
+    pub fn usage_model<'a>(&'a self, provider: &'a dyn Provider) -> &'a str {
+        self.model.as_deref().unwrap_or(provider.model())
+    }
+
+    /// The final text, highlighting low-confidence words if a minimum is set
+    pub fn into_text(self, min_confidence: Option<f32>) -> String {
+        match min_confidence {
+            Some(min) => self.highlight(min),
+            None => self.text,
+        }
+    }
+
+    /// The text with words below `min_confidence` wrapped in [brackets]
+    ///
+    /// Falls back to the plain text if the backend reported no confidences.
+    pub fn highlight(&self, min_confidence: f32) -> String {
+        if !self.words.iter().any(|w| w.confidence.is_some()) {
+            return self.text.clone();
+        }
+
+        self.words
+            .iter()
+            .map(|word| match word.confidence {
+                Some(confidence) if confidence < min_confidence => format!("[{}]", word.text),
+                _ => word.text.clone(),
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Network settings shared by the HTTP backends
+#[derive(Debug, Clone, Copy)]
+pub struct HttpOptions {
+    /// Timeout for each API request
+    pub timeout: Duration,
+    /// Maximum upload rate in KB/s
+    pub upload_limit_kbps: Option<u32>,
+}
+
+impl Default for HttpOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+            upload_limit_kbps: None,
+        }
+    }
+}
+
+impl HttpOptions {
+    /// Blocking HTTP client with the request timeout
+    pub(crate) fn client(&self) -> Result<reqwest::blocking::Client> {
+        reqwest::blocking::Client::builder()
+            .timeout(self.timeout)
+            .build()
+            .context("Failed to create HTTP client")
+    }
+
+    /// Request body for the audio, throttled to the upload limit
+    ///
+    /// Reports to the [`UploadProgress`] set with [`with_progress`], if any.
+    pub(crate) fn body(&self, audio: Bytes) -> (Throttled, u64) {
+        let len = audio.len() as u64;
+        let progress = PROGRESS.with(|p| p.borrow().clone());
+        if let Some(progress) = &progress {
+            progress.start(len);
+        }
+        let reader = Throttled {
+            inner: Cursor::new(audio),
+            bytes_per_sec: self.upload_limit_kbps.map(|kbps| kbps as u64 * 1024),
+            started: None,
+            progress,
+        };
+        (reader, len)
+    }
+}
+
+thread_local! {
+    static PROGRESS: RefCell<Option<Arc<UploadProgress>>> = const { RefCell::new(None) };
+}
+
+/// Run `f` with uploads started on this thread reporting to `progress`
+///
+/// The body is read on the HTTP client's own thread, so the progress is
+/// picked up when the body is created rather than passed through every
+/// [`Provider`].
+pub fn with_progress<T>(progress: Arc<UploadProgress>, f: impl FnOnce() -> T) -> T {
+    let previous = PROGRESS.with(|p| p.replace(Some(progress)));
+    let result = f();
+    PROGRESS.with(|p| *p.borrow_mut() = previous);
+    result
+}
+
+/// How much of an upload has been sent, and when it last moved
+#[derive(Debug)]
+pub struct UploadProgress {
+    state: Mutex<ProgressState>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ProgressState {
+    sent: u64,
+    total: u64,
+    moved: Instant,
+}
+
+impl Default for UploadProgress {
+    fn default() -> Self {
+        Self {
+            state: Mutex::new(ProgressState {
+                sent: 0,
+                total: 0,
+                moved: Instant::now(),
+            }),
+        }
+    }
+}
+
+impl UploadProgress {
+    /// Bytes sent and bytes to send (0 until the upload starts)
+    pub fn get(&self) -> (u64, u64) {
+        let state = self.lock();
+        (state.sent, state.total)
+    }
+
+    /// Time since bytes were last sent, while the upload is unfinished
+    ///
+    /// `None` before the upload starts and once all bytes are sent, when the
+    /// wait is for the backend to transcribe.
+    pub fn stalled_for(&self) -> Option<Duration> {
+        let state = self.lock();
+        (state.total > 0 && state.sent < state.total).then(|| state.moved.elapsed())
+    }
+
+    fn start(&self, total: u64) {
+        *self.lock() = ProgressState {
+            sent: 0,
+            total,
+            moved: Instant::now(),
+        };
+    }
+
+    fn add(&self, bytes: u64) {
+        let mut state = self.lock();
+        state.sent += bytes;
+        state.moved = Instant::now();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ProgressState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Reader that sleeps as needed to stay below a byte rate
+pub(crate) struct Throttled {
+    inner: Cursor<Bytes>,
+    bytes_per_sec: Option<u64>,
+    /// Set on the first read, when the upload actually starts
+    started: Option<Instant>,
+    progress: Option<Arc<UploadProgress>>,
+}
+
+impl Read for Throttled {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.read_throttled(buf)?;
+        if let Some(progress) = &self.progress
+            && read > 0
+        {
+            progress.add(read as u64);
+        }
+        Ok(read)
+    }
+}
+
+impl Throttled {
+    fn read_throttled(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let Some(rate) = self.bytes_per_sec.filter(|r| *r > 0) else {
+            return self.inner.read(buf);
+        };
+
+        let started = *self.started.get_or_insert_with(Instant::now);
+        // Send at most ~100ms worth of data per read to keep the rate smooth
+        let max = ((rate / 10).max(1) as usize).min(buf.len());
+        let read = self.inner.read(&mut buf[..max])?;
+
+        let due = Duration::from_secs_f64(self.inner.position() as f64 / rate as f64);
+        if let Some(wait) = due.checked_sub(started.elapsed()) {
+            std::thread::sleep(wait);
+        }
+        Ok(read)
+    }
+}
+
+/// Fail with the response body if the request was unsuccessful
+pub(crate) fn check_status(
+    response: reqwest::blocking::Response,
+    service: &str,
+) -> Result<reqwest::blocking::Response> {
+    if response.status().is_success() {
+        return Ok(response);
+    }
+    let status = response.status();
+    let error_text = response
+        .text()
+        .unwrap_or_else(|_| "Unknown error".to_string());
+    anyhow::bail!("{service} API error ({status}): {error_text}");
+}
+
+/// Add the language, prompt and temperature of a request to an
+/// OpenAI-style form
+pub(crate) fn request_fields(
+    mut form: multipart::Form,
+    request: &TranscriptionRequest,
+) -> multipart::Form {
+    if let Some(language) = request.language_code() {
+        form = form.text("language", language.to_string());
+    }
+    if let Some(prompt) = request.prompt() {
+        form = form.text("prompt", prompt.to_string());
+    }
+    if let Some(temperature) = request.temperature() {
+        form = form.text("temperature", temperature.to_string());
+    }
+    form
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upload_progress() {
+        let progress = Arc::new(UploadProgress::default());
+        assert_eq!(progress.stalled_for(), None);
+        let (mut body, len) = with_progress(progress.clone(), || {
+            HttpOptions::default().body(Bytes::from_static(&[0; 10]))
+        });
+        assert_eq!(progress.get(), (0, len));
+        assert!(progress.stalled_for().is_some());
+
+        let mut buf = [0; 4];
+        body.read_exact(&mut buf).unwrap();
+        assert_eq!(progress.get(), (4, 10));
+        std::io::copy(&mut body, &mut std::io::sink()).unwrap();
+        assert_eq!(progress.get(), (10, 10));
+        assert_eq!(progress.stalled_for(), None);
+
+        // Bodies created outside `with_progress` don't report
+        HttpOptions::default().body(Bytes::from_static(&[0; 3]));
+        assert_eq!(progress.get(), (10, 10));
+    }
+
+    fn word(text: &str, confidence: Option<f32>) -> Word {
+        Word {
+            text: text.to_string(),
+            start: 0.0,
+            end: 0.0,
+            confidence,
+        }
+    }
+
+    #[test]
+    fn test_highlight() {
+        let transcription = Transcription {
+            text: "Meet Siobhan at noon.".to_string(),
+            words: vec![
+                word("Meet", Some(0.98)),
+                word("Siobhan", Some(0.41)),
+                word("at", Some(0.95)),
+                word("noon.", Some(0.9)),
+            ],
+            segments: Vec::new(),
+            language: None,
+            model: None,
+        };
+        assert_eq!(transcription.highlight(0.6), "Meet [Siobhan] at noon.");
+        assert_eq!(transcription.highlight(0.0), "Meet Siobhan at noon.");
+    }
+
+    #[test]
+    fn test_highlight_without_confidence() {
+        let transcription = Transcription {
+            text: "Hello world.".to_string(),
+            words: vec![word("Hello", None), word("world", None)],
+            segments: Vec::new(),
+            language: None,
+            model: None,
+        };
+        assert_eq!(transcription.highlight(0.6), "Hello world.");
+    }
+
+    #[test]
+    fn test_backend_from_str() {
+        assert_eq!("AssemblyAI".parse(), Ok(Backend::AssemblyAI));
+        assert_eq!("whisper-server".parse(), Ok(Backend::WhisperServer));
+        assert!("whisper".parse::<Backend>().is_err());
+    }
+}
@@ -0,0 +1,148 @@
+//! OpenAI Whisper API
+
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use reqwest::blocking::multipart;
+use serde::Deserialize;
+
+use super::{HttpOptions, Provider, Segment, Transcription, Word};
+use crate::audio::AudioCodec;
+use crate::language;
+use crate::request::{ResponseFormat, TranscriptionRequest};
+
+/// Default OpenAI transcription model
+pub const MODEL: &str = "whisper-1";
+/// Maximum upload size accepted by the OpenAI transcription API
+const MAX_UPLOAD_BYTES: usize = 25 * 1024 * 1024;
+const API_URL: &str = "https://api.openai.com/v1/audio/transcriptions";
+
+#[derive(Deserialize)]
+struct TranscriptionResponse {
+    text: String,
+    #[serde(default)]
+    words: Vec<WordResponse>,
+    #[serde(default)]
+    segments: Vec<SegmentResponse>,
+    /// Detected language by name, e.g. "german"
+    #[serde(default)]
+    language: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct WordResponse {
+    word: String,
+    start: f32,
+    end: f32,
+}
+
+#[derive(Deserialize)]
+struct SegmentResponse {
+    text: String,
+    start: f32,
+    end: f32,
+    avg_logprob: f32,
+    no_speech_prob: f32,
+}
+
+pub struct OpenAI {
+    api_key: String,
+    http: HttpOptions,
+    request: TranscriptionRequest,
+    /// Backend/model identifier for usage tracking
+    usage_model: String,
+}
+
+impl OpenAI {
+    pub fn new(api_key: String, http: HttpOptions, request: TranscriptionRequest) -> Self {
+        let usage_model = format!("openai/{}", request.model().unwrap_or(MODEL));
+        Self {
+            api_key,
+            http,
+            request,
+            usage_model,
+        }
+    }
+}
+
+impl Provider for OpenAI {
+    fn model(&self) -> &str {
+        &self.usage_model
+    }
+
+    fn max_upload_bytes(&self) -> Option<usize> {
+        Some(MAX_UPLOAD_BYTES)
+    }
+
+    /// Ogg is one of the accepted upload formats
+    fn accepts(&self, _codec: AudioCodec) -> bool {
+        true
+    }
+
+    fn transcribe(&self, audio: Bytes, file_name: &str, mime: &str) -> Result<Transcription> {
+        let (body, len) = self.http.body(audio);
+        let model = self.request.model().unwrap_or(MODEL);
+        let mut form = multipart::Form::new().text("model", model.to_string());
+        // Only whisper-1 returns word timings and segment quality; the GPT-4o
+        // models answer in plain JSON
+        let format = self.request.response_format().unwrap_or(if model == MODEL {
+            ResponseFormat::VerboseJson
+        } else {
+            ResponseFormat::Json
+        });
+        form = if format == ResponseFormat::VerboseJson {
+            form.text("response_format", "verbose_json")
+                .text("timestamp_granularities[]", "word")
+                .text("timestamp_granularities[]", "segment")
+        } else {
+            form.text("response_format", "json")
+        };
+        form = super::request_fields(form, &self.request);
+        let form = form.part(
+            "file",
+            multipart::Part::reader_with_length(body, len)
+                .file_name(file_name.to_string())
+                .mime_str(mime)?,
+        );
+
+        let response = self
+            .http
+            .client()?
+            .post(API_URL)
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .multipart(form)
+            .send()
+            .context("Failed to send request to OpenAI API")?;
+        let response = super::check_status(response, "OpenAI")?;
+
+        let text = response.text().context("Failed to get response text")?;
+        let transcription: TranscriptionResponse =
+            serde_json::from_str(&text).context("Failed to parse OpenAI API response")?;
+
+        Ok(Transcription {
+            text: transcription.text,
+            words: transcription
+                .words
+                .into_iter()
+                .map(|w| Word {
+                    text: w.word,
+                    start: w.start,
+                    end: w.end,
+                    confidence: None,
+                })
+                .collect(),
+            segments: transcription
+                .segments
+                .into_iter()
+                .map(|s| Segment {
+                    text: s.text.trim().to_string(),
+                    start: s.start,
+                    end: s.end,
+                    avg_logprob: s.avg_logprob,
+                    no_speech_prob: s.no_speech_prob,
+                })
+                .collect(),
+            language: transcription.language.as_deref().and_then(language::code),
+            model: None,
+        })
+    }
+}
@@ -0,0 +1,81 @@
+//! Vosk offline recognition
+//!
+//! Runs `vosk-transcriber` (from the vosk Python package) on a local model.
+//! Small Vosk models run on any CPU, so this works on machines that can't
+//! run Whisper locally and without a network connection.
+
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use super::{Provider, Transcription};
+use crate::audio::AudioCodec;
+
+pub struct Vosk {
+    model_path: PathBuf,
+}
+
+impl Vosk {
+    pub fn new(model_path: PathBuf) -> Self {
+        Self { model_path }
+    }
+}
+
+impl Provider for Vosk {
+    fn model(&self) -> &str {
+        super::Backend::Vosk.model()
+    }
+
+    fn max_upload_bytes(&self) -> Option<usize> {
+        None
+    }
+
+    /// vosk-transcriber decodes with FFmpeg
+    fn accepts(&self, _codec: AudioCodec) -> bool {
+        true
+    }
+
+    fn transcribe(&self, audio: Bytes, file_name: &str, _mime: &str) -> Result<Transcription> {
+        // vosk-transcriber decodes with ffmpeg and picks the format by extension
+        let extension = file_name.rsplit_once('.').map_or("mp3", |(_, ext)| ext);
+        let mut input = crate::temp::file(extension)?;
+        input
+            .write_all(&audio)
+            .with_context(|| format!("Failed to write {}", input.path().display()))?;
+
+        let output = Command::new("vosk-transcriber")
+            .arg("--model")
+            .arg(&self.model_path)
+            .arg("--input")
+            .arg(input.path())
+            .stderr(Stdio::piped())
+            .output();
+        drop(input);
+
+        let output =
+            output.context("Failed to run vosk-transcriber. Install it with 'pip install vosk'")?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "vosk-transcriber failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        let text = String::from_utf8_lossy(&output.stdout);
+        Ok(Transcription::from_text(
+            text.split_whitespace().collect::<Vec<_>>().join(" "),
+        ))
+    }
+
+    fn health_check(&self) -> Result<()> {
+        Command::new("vosk-transcriber")
+            .arg("--help")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .context("vosk-transcriber not found. Install it with 'pip install vosk'")?;
+        Ok(())
+    }
+}
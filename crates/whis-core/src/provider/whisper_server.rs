@@ -0,0 +1,386 @@
+//! Self-hosted Whisper servers
+//!
+//! Talks to the common server APIs: OpenAI-compatible servers
+//! (faster-whisper-server, speaches, LocalAI), the whisper.cpp server
+//! (`/inference`) and whisper-asr-webservice (`/asr`). When the URL has no
+//! path, the endpoints are tried in that order and the first one that exists
+//! is remembered.
+
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use reqwest::StatusCode;
+use reqwest::blocking::multipart;
+use serde::Deserialize;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use super::{HttpOptions, Provider, Segment, Transcription, Word};
+use crate::language;
+use crate::request::{ResponseFormat, TranscriptionRequest};
+
+/// Timeout for the health check in `whis doctor`
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Transcription APIs spoken by Whisper servers
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Api {
+    /// `/v1/audio/transcriptions`
+    OpenAI,
+    /// whisper.cpp `/inference`
+    WhisperCpp,
+    /// whisper-asr-webservice `/asr`
+    AsrWebservice,
+}
+
+impl Api {
+    const ALL: [Api; 3] = [Api::OpenAI, Api::WhisperCpp, Api::AsrWebservice];
+
+    fn path(&self) -> &'static str {
+        match self {
+            Api::OpenAI => "/v1/audio/transcriptions",
+            Api::WhisperCpp => "/inference",
+            Api::AsrWebservice => "/asr",
+        }
+    }
+
+    /// The API of an endpoint URL given by the user
+    fn from_path(path: &str) -> Self {
+        if path.ends_with("/inference") {
+            Api::WhisperCpp
+        } else if path.ends_with("/asr") {
+            Api::AsrWebservice
+        } else {
+            Api::OpenAI
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ServerResponse {
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    segments: Vec<SegmentResponse>,
+    #[serde(default)]
+    words: Vec<WordResponse>,
+    /// Detected language, as a code or a name depending on the server
+    #[serde(default)]
+    language: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SegmentResponse {
+    text: String,
+    #[serde(default)]
+    words: Vec<WordResponse>,
+    // Quality signals, returned by faster-whisper based servers
+    #[serde(default)]
+    start: f32,
+    #[serde(default)]
+    end: f32,
+    #[serde(default)]
+    avg_logprob: Option<f32>,
+    #[serde(default)]
+    no_speech_prob: Option<f32>,
+}
+
+#[derive(Deserialize)]
+struct WordResponse {
+    #[serde(alias = "text")]
+    word: String,
+    start: f32,
+    end: f32,
+    #[serde(default)]
+    probability: Option<f32>,
+}
+
+pub struct WhisperServer {
+    /// Server URL without a trailing slash
+    url: String,
+    /// The URL is the server root and the endpoint still has to be found
+    detect: bool,
+    api: OnceLock<Api>,
+    http: HttpOptions,
+    request: TranscriptionRequest,
+}
+
+impl WhisperServer {
+    /// `url` is either the server root or the full transcription endpoint
+    pub fn new(url: &str, http: HttpOptions, request: TranscriptionRequest) -> Self {
+        // The URL is validated when the config is created
+        let path = reqwest::Url::parse(url)
+            .map(|u| u.path().to_string())
+            .unwrap_or_default();
+        let detect = path.is_empty() || path == "/";
+        let api = OnceLock::new();
+        if !detect {
+            let _ = api.set(Api::from_path(&path));
+        }
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            detect,
+            api,
+            http,
+            request,
+        }
+    }
+
+    fn endpoint(&self, api: Api) -> String {
+        if self.detect {
+            format!("{}{}", self.url, api.path())
+        } else {
+            self.url.clone()
+        }
+    }
+
+    fn send(
+        &self,
+        api: Api,
+        audio: Bytes,
+        file_name: &str,
+        mime: &str,
+    ) -> Result<reqwest::blocking::Response> {
+        let (body, len) = self.http.body(audio);
+        let part = multipart::Part::reader_with_length(body, len)
+            .file_name(file_name.to_string())
+            .mime_str(mime)?;
+
+        let client = self.http.client()?;
+        let format = self.response_format();
+        let request = match api {
+            Api::OpenAI => {
+                let mut form = self.decoding_fields();
+                if format == ResponseFormat::VerboseJson {
+                    form = form
+                        .text("timestamp_granularities[]", "word")
+                        .text("timestamp_granularities[]", "segment");
+                }
+                if let Some(model) = self.request.model() {
+                    form = form.text("model", model.to_string());
+                }
+                client
+                    .post(self.endpoint(api))
+                    .multipart(form.part("file", part))
+            }
+            Api::WhisperCpp => client
+                .post(self.endpoint(api))
+                .multipart(self.decoding_fields().part("file", part)),
+            Api::AsrWebservice => {
+                let mut query = vec![("output", "json"), ("word_timestamps", "true")];
+                if let Some(language) = self.request.language_code() {
+                    query.push(("language", language));
+                }
+                if let Some(prompt) = self.request.prompt() {
+                    query.push(("initial_prompt", prompt));
+                }
+                client
+                    .post(self.endpoint(api))
+                    .query(&query)
+                    .multipart(multipart::Form::new().part("audio_file", part))
+            }
+        };
+        request
+            .send()
+            .with_context(|| format!("Failed to send request to {}", self.url))
+    }
+
+    fn response_format(&self) -> ResponseFormat {
+        self.request
+            .response_format()
+            .unwrap_or(ResponseFormat::VerboseJson)
+    }
+
+    /// Form with the response format and the decoding options of the request
+    fn decoding_fields(&self) -> multipart::Form {
+        let mut form =
+            multipart::Form::new().text("response_format", self.response_format().as_str());
+        if let Some(beam_size) = self.request.beam_size() {
+            form = form.text("beam_size", beam_size.to_string());
+        }
+        if let Some(best_of) = self.request.best_of() {
+            form = form.text("best_of", best_of.to_string());
+        }
+        super::request_fields(form, &self.request)
+    }
+}
+
+impl Provider for WhisperServer {
+    fn model(&self) -> &str {
+        super::Backend::WhisperServer.model()
+    }
+
+    fn max_upload_bytes(&self) -> Option<usize> {
+        None
+    }
+
+    fn transcribe(&self, audio: Bytes, file_name: &str, mime: &str) -> Result<Transcription> {
+        if let Some(&api) = self.api.get() {
+            let response = self.send(api, audio, file_name, mime)?;
+            return parse(super::check_status(response, "Whisper server")?);
+        }
+
+        for api in Api::ALL {
+            let response = self.send(api, audio.clone(), file_name, mime)?;
+            if matches!(
+                response.status(),
+                StatusCode::NOT_FOUND | StatusCode::METHOD_NOT_ALLOWED
+            ) {
+                continue;
+            }
+            let response = super::check_status(response, "Whisper server")?;
+            let _ = self.api.set(api);
+            return parse(response);
+        }
+        anyhow::bail!(
+            "No transcription endpoint found at {} (tried /v1/audio/transcriptions, /inference and /asr)",
+            self.url
+        )
+    }
+
+    fn health_check(&self) -> Result<()> {
+        let client = HttpOptions {
+            timeout: HEALTH_TIMEOUT,
+            ..self.http
+        }
+        .client()?;
+        let mut health = reqwest::Url::parse(&self.url)?;
+        health.set_path("/health");
+        health.set_query(None);
+
+        let response = client
+            .get(health)
+            .send()
+            .with_context(|| format!("Whisper server at {} is not reachable", self.url))?;
+        if response.status().is_success() {
+            return Ok(());
+        }
+        // Not every server has /health; any answer from the root will do
+        let response = client
+            .get(&self.url)
+            .send()
+            .with_context(|| format!("Whisper server at {} is not reachable", self.url))?;
+        if response.status().is_server_error() {
+            anyhow::bail!("Whisper server returned {}", response.status());
+        }
+        Ok(())
+    }
+}
+
+fn parse(response: reqwest::blocking::Response) -> Result<Transcription> {
+    let body = response
+        .text()
+        .context("Failed to get Whisper server response")?;
+    parse_body(&body)
+}
+
+/// Read any of the response shapes the supported servers return
+fn parse_body(body: &str) -> Result<Transcription> {
+    let response: ServerResponse =
+        serde_json::from_str(body).context("Failed to parse Whisper server response")?;
+
+    let text = match response.text {
+        Some(text) => text.trim().to_string(),
+        None => response
+            .segments
+            .iter()
+            .map(|s| s.text.trim())
+            .collect::<Vec<_>>()
+            .join(" "),
+    };
+    let segments = response
+        .segments
+        .iter()
+        .filter_map(|s| {
+            Some(Segment {
+                text: s.text.trim().to_string(),
+                start: s.start,
+                end: s.end,
+                avg_logprob: s.avg_logprob?,
+                no_speech_prob: s.no_speech_prob?,
+            })
+        })
+        .collect();
+    let words = if response.words.is_empty() {
+        response
+            .segments
+            .into_iter()
+            .flat_map(|s| s.words)
+            .collect()
+    } else {
+        response.words
+    };
+
+    Ok(Transcription {
+        text,
+        words: words
+            .into_iter()
+            .map(|w| Word {
+                text: w.word.trim().to_string(),
+                start: w.start,
+                end: w.end,
+                confidence: w.probability,
+            })
+            .collect(),
+        segments,
+        language: response.language.as_deref().and_then(language::code),
+        model: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_openai_shape() {
+        let body = r#"{"text": " Hello there.", "words": [
+            {"word": " Hello", "start": 0.0, "end": 0.4, "probability": 0.97},
+            {"word": " there.", "start": 0.4, "end": 0.8, "probability": 0.52}
+        ]}"#;
+        let transcription = parse_body(body).unwrap();
+        assert_eq!(transcription.text, "Hello there.");
+        assert_eq!(transcription.words[1].text, "there.");
+        assert_eq!(transcription.words[1].confidence, Some(0.52));
+    }
+
+    #[test]
+    fn test_parse_segment_shape() {
+        let body = r#"{"segments": [
+            {"text": " First part.", "words": [{"word": "First", "start": 0.0, "end": 0.3}]},
+            {"text": " Second part."}
+        ]}"#;
+        let transcription = parse_body(body).unwrap();
+        assert_eq!(transcription.text, "First part. Second part.");
+        assert_eq!(transcription.words.len(), 1);
+        assert_eq!(transcription.words[0].confidence, None);
+        assert!(transcription.segments.is_empty());
+    }
+
+    #[test]
+    fn test_parse_segment_quality() {
+        let body = r#"{"text": "Hello. Thanks for watching!", "segments": [
+            {"text": " Hello.", "start": 0.0, "end": 1.2, "avg_logprob": -0.2, "no_speech_prob": 0.01},
+            {"text": " Thanks for watching!", "start": 1.2, "end": 4.0, "avg_logprob": -1.4, "no_speech_prob": 0.8}
+        ]}"#;
+        let transcription = parse_body(body).unwrap();
+        assert_eq!(transcription.segments.len(), 2);
+        assert_eq!(transcription.segments[1].text, "Thanks for watching!");
+        assert_eq!(transcription.segments[1].no_speech_prob, 0.8);
+        assert_eq!(transcription.language, None);
+    }
+
+    #[test]
+    fn test_parse_language() {
+        let body = r#"{"text": "Hallo.", "language": "de"}"#;
+        assert_eq!(parse_body(body).unwrap().language.as_deref(), Some("de"));
+        let body = r#"{"text": "Hallo.", "language": "german"}"#;
+        assert_eq!(parse_body(body).unwrap().language.as_deref(), Some("de"));
+    }
+
+    #[test]
+    fn test_api_from_path() {
+        assert_eq!(Api::from_path("/inference"), Api::WhisperCpp);
+        assert_eq!(Api::from_path("/asr"), Api::AsrWebservice);
+        assert_eq!(Api::from_path("/v1/audio/transcriptions"), Api::OpenAI);
+    }
+}
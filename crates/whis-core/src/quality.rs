@@ -0,0 +1,210 @@
+//! Quality of a transcript from the decoder's own signals
+//!
+//! Whisper backends report per segment how likely the decoded tokens were
+//! (`avg_logprob`) and how likely the audio held no speech at all
+//! (`no_speech_prob`). Segments past Whisper's own thresholds are usually
+//! misheard or made up, so they are reported after the transcription and can
+//! be marked in the text. Segments that almost certainly hold no speech are
+//! dropped altogether: on silence Whisper tends to invent a sentence.
+
+use crate::provider::{Segment, Transcription};
+
+/// Below this average log probability Whisper considers a decode failed
+const LOGPROB_THRESHOLD: f32 = -1.0;
+/// Above this probability Whisper considers a segment silence
+const NO_SPEECH_THRESHOLD: f32 = 0.6;
+/// Above this probability a segment is dropped as silence
+const SILENCE_THRESHOLD: f32 = 0.8;
+/// How much of an unreliable segment's text the report quotes
+const QUOTE_CHARS: usize = 40;
+
+/// Quality score from 0 (unusable) to 1: the average token probability,
+/// scaled down by the chance that there was no speech
+pub fn score(segment: &Segment) -> f32 {
+    (segment.avg_logprob.exp() * (1.0 - segment.no_speech_prob)).clamp(0.0, 1.0)
+}
+
+pub fn is_unreliable(segment: &Segment) -> bool {
+    segment.avg_logprob < LOGPROB_THRESHOLD || segment.no_speech_prob > NO_SPEECH_THRESHOLD
+}
+
+/// Whether the segment is most likely text made up for silence
+pub fn is_silence(segment: &Segment) -> bool {
+    segment.no_speech_prob > SILENCE_THRESHOLD
+}
+
+/// Remove the segments that are silence, with their text and words
+///
+/// The text is rebuilt from the remaining segments; it is left alone if none
+/// was dropped.
+pub fn drop_silence(mut transcription: Transcription) -> Transcription {
+    let (silent, kept): (Vec<Segment>, Vec<Segment>) =
+        transcription.segments.into_iter().partition(is_silence);
+    transcription.segments = kept;
+    if silent.is_empty() {
+        return transcription;
+    }
+
+    transcription.text = transcription
+        .segments
+        .iter()
+        .map(|s| s.text.trim())
+        .collect::<Vec<_>>()
+        .join(" ");
+    transcription.words.retain(|word| {
+        !silent
+            .iter()
+            .any(|s| word.start >= s.start && word.end <= s.end)
+    });
+    transcription
+}
+
+/// Warning listing the unreliable segments, `None` if all look fine
+///
+/// Silent segments are left out when they get dropped anyway. With
+/// `privacy`, the segments are listed without their text.
+pub fn report(transcription: &Transcription, drop_silence: bool, privacy: bool) -> Option<String> {
+    let unreliable: Vec<&Segment> = transcription
+        .segments
+        .iter()
+        .filter(|s| is_unreliable(s) && !(drop_silence && is_silence(s)))
+        .collect();
+    let mut report = match unreliable.len() {
+        0 => return None,
+        1 => "1 segment looks unreliable:".to_string(),
+        count => format!("{count} segments look unreliable:"),
+    };
+    for segment in unreliable {
+        report.push_str(&format!(
+            "\n  {}-{}  {:.0}%",
+            timestamp(segment.start),
+            timestamp(segment.end),
+            score(segment) * 100.0,
+        ));
+        if !privacy {
+            report.push_str(&format!("  \"{}\"", quote(&segment.text)));
+        }
+    }
+    Some(report)
+}
+
+/// The text rebuilt from the segments, with unreliable ones in [brackets]
+///
+/// Falls back to the plain text if the backend returned no segments.
+pub fn mark(transcription: &Transcription) -> String {
+    if transcription.segments.is_empty() {
+        return transcription.text.clone();
+    }
+    transcription
+        .segments
+        .iter()
+        .map(|segment| {
+            let text = segment.text.trim();
+            if is_unreliable(segment) {
+                format!("[{text}]")
+            } else {
+                text.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn timestamp(secs: f32) -> String {
+    let secs = secs as u64;
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+fn quote(text: &str) -> String {
+    let text = text.trim();
+    match text.char_indices().nth(QUOTE_CHARS) {
+        Some((idx, _)) => format!("{}…", &text[..idx]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(text: &str, start: f32, avg_logprob: f32, no_speech_prob: f32) -> Segment {
+        Segment {
+            text: text.to_string(),
+            start,
+            end: start + 2.0,
+            avg_logprob,
+            no_speech_prob,
+        }
+    }
+
+    fn with_segments(segments: Vec<Segment>) -> Transcription {
+        Transcription {
+            segments,
+            ..Transcription::from_text("unused")
+        }
+    }
+
+    #[test]
+    fn test_score() {
+        assert_eq!(score(&segment("a", 0.0, 0.0, 0.0)), 1.0);
+        assert!((score(&segment("a", 0.0, -0.5, 0.5)) - 0.303).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_report() {
+        let transcription = with_segments(vec![
+            segment("Hello there.", 0.0, -0.2, 0.01),
+            segment("Thanks for watching!", 62.0, -0.3, 0.9),
+            segment("mumble", 70.0, -1.6, 0.1),
+        ]);
+        let text = report(&transcription, false, false).unwrap();
+        assert!(text.starts_with("2 segments look unreliable:"));
+        assert!(text.contains("1:02-1:04"));
+        assert!(text.contains("\"Thanks for watching!\""));
+        let text = report(&transcription, true, false).unwrap();
+        assert!(text.starts_with("1 segment looks unreliable:"));
+
+        let fine = with_segments(vec![segment("Hello.", 0.0, -0.2, 0.0)]);
+        assert_eq!(report(&fine, false, false), None);
+
+        let text = report(&transcription, false, true).unwrap();
+        assert!(text.contains("1:02-1:04"));
+        assert!(!text.contains("Thanks"));
+    }
+
+    #[test]
+    fn test_drop_silence() {
+        let mut transcription = with_segments(vec![
+            segment(" Call me back.", 0.0, -0.2, 0.01),
+            segment(" Thanks for watching!", 2.0, -0.3, 0.95),
+        ]);
+        transcription.words = ["Call", "Thanks"]
+            .iter()
+            .zip([0.5, 2.5])
+            .map(|(&text, start)| crate::provider::Word {
+                text: text.to_string(),
+                start,
+                end: start + 0.3,
+                confidence: None,
+            })
+            .collect();
+
+        let guarded = drop_silence(transcription);
+        assert_eq!(guarded.text, "Call me back.");
+        assert_eq!(guarded.segments.len(), 1);
+        assert_eq!(guarded.words.len(), 1);
+
+        let plain = Transcription::from_text("Untouched.");
+        assert_eq!(drop_silence(plain.clone()), plain);
+    }
+
+    #[test]
+    fn test_mark() {
+        let transcription = with_segments(vec![
+            segment(" Hello there.", 0.0, -0.2, 0.01),
+            segment(" Thanks for watching!", 2.0, -0.3, 0.9),
+        ]);
+        assert_eq!(mark(&transcription), "Hello there. [Thanks for watching!]");
+        assert_eq!(mark(&Transcription::from_text("Plain.")), "Plain.");
+    }
+}
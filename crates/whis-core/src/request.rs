@@ -0,0 +1,356 @@
+//! Decoding options of a transcription request
+//!
+//! [`TranscriptionRequest`] holds the options a caller can pass to the
+//! backend: model, language, prompt, temperature, response format, the
+//! beam search options of local Whisper servers and whether to mask swear
+//! words. It can only be created
+//! through [`TranscriptionRequest::builder`], which validates the values once,
+//! so the providers can pass them on as they are. Backends ignore the options
+//! they don't support.
+//!
+//! ```
+//! use whis_core::TranscriptionRequest;
+//!
+//! let request = TranscriptionRequest::builder()
+//!     .model("whisper-1")
+//!     .language("de")
+//!     .prompt("Whis, Tauri, PipeWire")
+//!     .temperature(0.0)
+//!     .build()
+//!     .unwrap();
+//! assert_eq!(request.language_code(), Some("de"));
+//! ```
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Largest beam size / best-of count accepted, far beyond the useful range
+const MAX_CANDIDATES: u32 = 16;
+
+/// Response format of Whisper APIs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseFormat {
+    /// Text only; faster on some servers
+    Json,
+    /// Text with segments and word timings
+    VerboseJson,
+}
+
+impl ResponseFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ResponseFormat::Json => "json",
+            ResponseFormat::VerboseJson => "verbose_json",
+        }
+    }
+}
+
+impl fmt::Display for ResponseFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ResponseFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().replace('-', "_").as_str() {
+            "json" => Ok(ResponseFormat::Json),
+            "verbose_json" => Ok(ResponseFormat::VerboseJson),
+            _ => Err(format!(
+                "unknown response format '{s}' (use json or verbose_json)"
+            )),
+        }
+    }
+}
+
+/// Validated options for a transcription; the default leaves everything to
+/// the backend
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TranscriptionRequest {
+    model: Option<String>,
+    language: Option<String>,
+    prompt: Option<String>,
+    temperature: Option<f32>,
+    response_format: Option<ResponseFormat>,
+    beam_size: Option<u32>,
+    best_of: Option<u32>,
+    filter_profanity: bool,
+}
+
+impl TranscriptionRequest {
+    pub fn builder() -> TranscriptionRequestBuilder {
+        TranscriptionRequestBuilder::default()
+    }
+
+    /// Model name of the backend, e.g. "gpt-4o-transcribe"
+    pub fn model(&self) -> Option<&str> {
+        self.model.as_deref()
+    }
+
+    /// Spoken language as given: a code ("en") or a locale ("en-US")
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// The same request for another language, e.g. one the user asked for
+    /// by voice; `language` must be a known language code
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// The language without its region ("en" for "en-US")
+    pub fn language_code(&self) -> Option<&str> {
+        self.language()
+            .map(|language| language.split('-').next().unwrap_or(language))
+    }
+
+    /// Text that guides spelling and style, e.g. names and jargon
+    pub fn prompt(&self) -> Option<&str> {
+        self.prompt.as_deref()
+    }
+
+    /// Sampling temperature from 0 to 1
+    pub fn temperature(&self) -> Option<f32> {
+        self.temperature
+    }
+
+    /// Format to ask Whisper APIs for; unset picks the richest one the model
+    /// supports
+    pub fn response_format(&self) -> Option<ResponseFormat> {
+        self.response_format
+    }
+
+    /// Beam search width (local Whisper servers)
+    pub fn beam_size(&self) -> Option<u32> {
+        self.beam_size
+    }
+
+    /// Candidates sampled when decoding with temperature (local Whisper servers)
+    pub fn best_of(&self) -> Option<u32> {
+        self.best_of
+    }
+
+    /// Whether to mask swear words (AssemblyAI and Azure)
+    pub fn filter_profanity(&self) -> bool {
+        self.filter_profanity
+    }
+}
+
+/// Builder for [`TranscriptionRequest`]; unset options keep the backend default
+#[derive(Debug, Clone, Default)]
+pub struct TranscriptionRequestBuilder {
+    model: Option<String>,
+    language: Option<String>,
+    prompt: Option<String>,
+    temperature: Option<f32>,
+    response_format: Option<ResponseFormat>,
+    beam_size: Option<u32>,
+    best_of: Option<u32>,
+    filter_profanity: bool,
+}
+
+impl TranscriptionRequestBuilder {
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// ISO 639-1 code ("en") or locale ("en-US"); Azure needs a locale
+    pub fn language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    pub fn prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.prompt = Some(prompt.into());
+        self
+    }
+
+    pub fn temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    pub fn response_format(mut self, format: ResponseFormat) -> Self {
+        self.response_format = Some(format);
+        self
+    }
+
+    pub fn beam_size(mut self, beam_size: u32) -> Self {
+        self.beam_size = Some(beam_size);
+        self
+    }
+
+    pub fn best_of(mut self, best_of: u32) -> Self {
+        self.best_of = Some(best_of);
+        self
+    }
+
+    pub fn filter_profanity(mut self, filter: bool) -> Self {
+        self.filter_profanity = filter;
+        self
+    }
+
+    /// Check the options; blank strings count as unset
+    pub fn build(self) -> Result<TranscriptionRequest> {
+        let model = non_blank(self.model);
+        if let Some(model) = &model
+            && model.contains(char::is_whitespace)
+        {
+            anyhow::bail!("Invalid model name '{model}'");
+        }
+
+        let language = non_blank(self.language);
+        if let Some(language) = &language
+            && !is_language(language)
+        {
+            anyhow::bail!(
+                "Invalid language '{language}' (expected a code like \"en\" or a locale like \"en-US\")"
+            );
+        }
+
+        if let Some(temperature) = self.temperature
+            && !(0.0..=1.0).contains(&temperature)
+        {
+            anyhow::bail!("Temperature must be between 0 and 1, got {temperature}");
+        }
+
+        for (name, value) in [("Beam size", self.beam_size), ("Best-of", self.best_of)] {
+            if let Some(value) = value
+                && !(1..=MAX_CANDIDATES).contains(&value)
+            {
+                anyhow::bail!("{name} must be between 1 and {MAX_CANDIDATES}, got {value}");
+            }
+        }
+
+        Ok(TranscriptionRequest {
+            model,
+            language,
+            prompt: non_blank(self.prompt),
+            temperature: self.temperature,
+            response_format: self.response_format,
+            beam_size: self.beam_size,
+            best_of: self.best_of,
+            filter_profanity: self.filter_profanity,
+        })
+    }
+}
+
+fn non_blank(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// A two or three letter language code with an optional region ("pt-BR")
+fn is_language(value: &str) -> bool {
+    let (code, region) = match value.split_once('-') {
+        Some((code, region)) => (code, Some(region)),
+        None => (value, None),
+    };
+    (2..=3).contains(&code.len())
+        && code.chars().all(|c| c.is_ascii_lowercase())
+        && region.is_none_or(|r| {
+            (2..=4).contains(&r.len()) && r.chars().all(|c| c.is_ascii_alphanumeric())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_is_empty() {
+        let request = TranscriptionRequest::builder().build().unwrap();
+        assert_eq!(request, TranscriptionRequest::default());
+    }
+
+    #[test]
+    fn test_language() {
+        let request = TranscriptionRequest::builder()
+            .language("en-US")
+            .build()
+            .unwrap();
+        assert_eq!(request.language(), Some("en-US"));
+        assert_eq!(request.language_code(), Some("en"));
+
+        for invalid in ["english", "EN", "e", "en_US", "en-"] {
+            assert!(
+                TranscriptionRequest::builder()
+                    .language(invalid)
+                    .build()
+                    .is_err(),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_temperature_range() {
+        assert!(
+            TranscriptionRequest::builder()
+                .temperature(0.2)
+                .build()
+                .is_ok()
+        );
+        assert!(
+            TranscriptionRequest::builder()
+                .temperature(1.5)
+                .build()
+                .is_err()
+        );
+        assert!(
+            TranscriptionRequest::builder()
+                .temperature(f32::NAN)
+                .build()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_beam_options() {
+        let request = TranscriptionRequest::builder()
+            .beam_size(5)
+            .best_of(3)
+            .build()
+            .unwrap();
+        assert_eq!((request.beam_size(), request.best_of()), (Some(5), Some(3)));
+        assert!(
+            TranscriptionRequest::builder()
+                .beam_size(0)
+                .build()
+                .is_err()
+        );
+        assert!(TranscriptionRequest::builder().best_of(50).build().is_err());
+    }
+
+    #[test]
+    fn test_response_format_from_str() {
+        assert_eq!("verbose-json".parse(), Ok(ResponseFormat::VerboseJson));
+        assert_eq!("JSON".parse(), Ok(ResponseFormat::Json));
+        assert!("srt".parse::<ResponseFormat>().is_err());
+    }
+
+    #[test]
+    fn test_blank_values_are_unset() {
+        let request = TranscriptionRequest::builder()
+            .model(" ")
+            .prompt("")
+            .build()
+            .unwrap();
+        assert_eq!(request.model(), None);
+        assert_eq!(request.prompt(), None);
+        assert!(
+            TranscriptionRequest::builder()
+                .model("gpt 4o")
+                .build()
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,187 @@
+//! Find a query in transcripts
+//!
+//! The plain search looks for the query as a substring, ignoring case. The
+//! fuzzy search matches each query word against the words of the transcript
+//! with a few typos allowed, so "kubernets" still finds "Kubernetes".
+
+use std::ops::Range;
+
+/// Where a query was found in a text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match {
+    /// Byte range of the (first) matched part of the text
+    pub range: Range<usize>,
+    /// Number of typos needed to match, 0 for an exact match
+    pub distance: usize,
+}
+
+/// Find `query` in `text`, as a substring or word by word with typos
+pub fn find(text: &str, query: &str, fuzzy: bool) -> Option<Match> {
+    let query = query.trim();
+    if query.is_empty() {
+        return None;
+    }
+    if let Some(range) = substring(text, query) {
+        return Some(Match { range, distance: 0 });
+    }
+    if fuzzy {
+        fuzzy_words(text, query)
+    } else {
+        None
+    }
+}
+
+/// The text around `range`, on one line and about `width` characters either
+/// side, with "…" where it was cut
+pub fn snippet(text: &str, range: Range<usize>, width: usize) -> String {
+    let start = text[..range.start]
+        .char_indices()
+        .rev()
+        .nth(width.saturating_sub(1))
+        .map_or(0, |(i, _)| i);
+    let end = text[range.end..]
+        .char_indices()
+        .nth(width)
+        .map_or(text.len(), |(i, _)| range.end + i);
+
+    let mut out = String::new();
+    if start > 0 {
+        out.push('…');
+    }
+    out.push_str(
+        &text[start..end]
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" "),
+    );
+    if end < text.len() {
+        out.push('…');
+    }
+    out
+}
+
+/// Byte range of the first case-insensitive occurrence of `query`
+fn substring(text: &str, query: &str) -> Option<Range<usize>> {
+    text.char_indices().find_map(|(start, _)| {
+        let mut rest = text[start..].char_indices();
+        for q in query.chars() {
+            let (_, c) = rest.next()?;
+            if !c.to_lowercase().eq(q.to_lowercase()) {
+                return None;
+            }
+        }
+        let len = rest.next().map_or(text.len() - start, |(i, _)| i);
+        Some(start..start + len)
+    })
+}
+
+/// Every query word has to be close to some word of the text
+fn fuzzy_words(text: &str, query: &str) -> Option<Match> {
+    let words = words(text);
+    let mut first: Option<Range<usize>> = None;
+    let mut distance = 0;
+    for term in query.split_whitespace().map(normalize) {
+        let (range, d) = words
+            .iter()
+            .map(|(range, word)| (range, levenshtein(&term, word)))
+            .filter(|(_, d)| *d <= max_typos(&term))
+            .min_by_key(|(_, d)| *d)?;
+        distance += d;
+        if first.as_ref().is_none_or(|f| range.start < f.start) {
+            first = Some(range.clone());
+        }
+    }
+    first.map(|range| Match { range, distance })
+}
+
+/// One typo for every four characters, none for very short words
+fn max_typos(term: &str) -> usize {
+    term.chars().count() / 4
+}
+
+/// The words of `text` with their byte ranges, normalized for comparison
+fn words(text: &str) -> Vec<(Range<usize>, String)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                words.push((s..i, normalize(&text[s..i])));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    words
+}
+
+/// Lowercase letters and digits only
+fn normalize(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Edit distance between two words, by character
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut next = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = row[j] + usize::from(ca != *cb);
+            next.push(substitution.min(row[j + 1] + 1).min(next[j] + 1));
+        }
+        row = next;
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_substring_ignores_case() {
+        let text = "Deploy it to Kubernetes tomorrow";
+        let found = find(text, "kubernetes", false).unwrap();
+        assert_eq!(&text[found.range], "Kubernetes");
+        assert_eq!(found.distance, 0);
+    }
+
+    #[test]
+    fn test_fuzzy_allows_typos() {
+        let text = "Deploy it to Kubernetes tomorrow";
+        assert!(find(text, "kubernets", false).is_none());
+        let found = find(text, "kubernets deploi", true).unwrap();
+        assert_eq!(&text[found.range], "Deploy");
+        assert_eq!(found.distance, 2);
+    }
+
+    #[test]
+    fn test_fuzzy_rejects_short_words() {
+        assert!(find("the cat sat", "cut", true).is_none());
+        assert!(find("the cat sat", "", true).is_none());
+    }
+
+    #[test]
+    fn test_snippet() {
+        let text = "one two three\nfour five six seven";
+        let range = 14..18;
+        assert_eq!(&text[range.clone()], "four");
+        assert_eq!(snippet(text, range.clone(), 6), "…three four five…");
+        assert_eq!(
+            snippet(text, range, 100),
+            "one two three four five six seven"
+        );
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("über", "uber"), 1);
+    }
+}
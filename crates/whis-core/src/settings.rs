@@ -1,20 +1,452 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
 
+use crate::app_rules::AppRule;
+use crate::audio::{AudioCodec, AudioEncoding, ChunkConfig};
+use crate::postprocess::normalize::Locale;
+use crate::profile::Profile;
+use crate::provider::Backend;
+use crate::request::ResponseFormat;
+use crate::typing::TypingConfig;
+use crate::voice_command::VoiceCommand;
+
+/// What happens with a finished transcript
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputMode {
+    /// Copy the transcript to the clipboard
+    #[default]
+    Clipboard,
+    /// Match the transcript against `voice_commands` and run the command
+    Command,
+    /// Type the transcript into the focused window
+    Type,
+    /// Leave the transcript to sinks, history and the caller
+    None,
+}
+
+/// Where clipboard output goes on Linux (elsewhere there is only the clipboard)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClipboardSelection {
+    /// The clipboard, pasted with Ctrl+V
+    #[default]
+    Clipboard,
+    /// The primary selection, pasted with a middle click
+    Primary,
+    /// Both of them
+    Both,
+}
+
+impl ClipboardSelection {
+    pub fn includes_clipboard(self) -> bool {
+        self != ClipboardSelection::Primary
+    }
+
+    pub fn includes_primary(self) -> bool {
+        self != ClipboardSelection::Clipboard
+    }
+}
+
+/// How clipboard output reaches the clipboard
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClipboardBackend {
+    /// OSC 52 in SSH sessions without a display, the system clipboard otherwise
+    #[default]
+    Auto,
+    /// The clipboard of the desktop session
+    System,
+    /// An OSC 52 escape sequence to the terminal, which sets the clipboard of
+    /// the machine the terminal runs on (e.g. the local end of SSH)
+    Osc52,
+    /// Only the paste buffer of tmux or GNU screen, which `Auto` also
+    /// fills when running inside one of them
+    Multiplexer,
+}
+
+/// What reads transcripts aloud (`whis say`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TtsEngine {
+    /// The OpenAI speech API
+    #[default]
+    OpenAI,
+    /// espeak-ng on Linux, `say` on macOS
+    Local,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Settings {
     pub shortcut: String,
+    /// Transcription backend
+    #[serde(default)]
+    pub backend: Backend,
+    /// Backends to retry with, in order, when the primary one fails
+    #[serde(default)]
+    pub backend_fallback: Vec<Backend>,
     #[serde(default)]
     pub openai_api_key: Option<String>,
+    #[serde(default)]
+    pub assemblyai_api_key: Option<String>,
+    #[serde(default)]
+    pub azure_api_key: Option<String>,
+    /// Azure region of the Speech resource, e.g. "westeurope"
+    #[serde(default)]
+    pub azure_region: Option<String>,
+    /// Model directory for local backends (Vosk)
+    #[serde(default)]
+    pub model_path: Option<PathBuf>,
+    /// URL of a self-hosted Whisper server (whisper-server backend)
+    #[serde(default)]
+    pub server_url: Option<String>,
+    /// Model of the transcription backend, e.g. "gpt-4o-transcribe"
+    /// (backend default when unset)
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Spoken language, e.g. "de" or "de-DE" (detected when unset)
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Names and jargon that guide the transcription's spelling
+    #[serde(default)]
+    pub prompt: Option<String>,
+    /// Sampling temperature from 0 to 1 (backend default when unset)
+    #[serde(default)]
+    pub temperature: Option<f32>,
+    /// Response format of Whisper APIs (json, verbose_json)
+    #[serde(default)]
+    pub response_format: Option<ResponseFormat>,
+    /// Beam search width of local Whisper servers
+    #[serde(default)]
+    pub beam_size: Option<u32>,
+    /// Candidates sampled at non-zero temperature by local Whisper servers
+    #[serde(default)]
+    pub best_of: Option<u32>,
+    /// Wrap words recognized with less confidence than this (0-1) in
+    /// [brackets], for backends that report word confidence
+    #[serde(default)]
+    pub min_confidence: Option<f32>,
+    /// Wrap segments that Whisper backends flag as unreliable in [brackets]
+    #[serde(default)]
+    pub mark_unreliable: bool,
+    /// Drop segments that Whisper backends are fairly sure hold no speech,
+    /// where Whisper tends to make up text ("Thanks for watching!")
+    #[serde(default = "default_true")]
+    pub drop_silent_segments: bool,
+    /// Start a new paragraph where the speaker paused at least this many
+    /// seconds, for backends that report word timings
+    #[serde(default)]
+    pub paragraph_pause_secs: Option<f32>,
+    /// Put the start time of each segment ("[01:23]") in front of it, one
+    /// segment per line, for backends that report timings
+    #[serde(default)]
+    pub timestamps: bool,
+    /// Convert spoken commands ("new line", "comma", "scratch that") into formatting
+    #[serde(default)]
+    pub dictation_commands: bool,
+    /// Verbatim mode for code: no punctuation, lowercase, spoken symbols and
+    /// case formatters ("snake case", "dot", ...) instead of dictation commands
+    #[serde(default)]
+    pub code_mode: bool,
+    /// Remove filler words ("um", "uh", "you know") and stutters ("the the")
+    #[serde(default)]
+    pub remove_fillers: bool,
+    /// Filler words and phrases to remove instead of the built-in list
+    #[serde(default)]
+    pub fillers: Option<Vec<String>>,
+    /// Write spoken numbers, amounts, times and email addresses the usual
+    /// way ("twenty three dollars" → "$23")
+    #[serde(default)]
+    pub normalize: bool,
+    /// Language and conventions used by `normalize` ("en-US", "en-GB", "de")
+    #[serde(default)]
+    pub locale: Locale,
+    /// Acronyms and names always written with this casing, e.g.
+    /// ["PostgreSQL", "gRPC", "OAuth2"]
+    #[serde(default)]
+    pub terms: Vec<String>,
+    /// Spoken phrases replaced by the text they stand for, e.g.
+    /// {"sign off": "Best regards,\nFrank"}
+    #[serde(default)]
+    pub macros: BTreeMap<String, String>,
+    /// Mask email addresses, phone numbers and card numbers in transcripts
+    #[serde(default)]
+    pub redact: bool,
+    /// Regexes whose matches are masked as well when `redact` is on
+    #[serde(default)]
+    pub redact_patterns: Vec<String>,
+    /// Mask swear words ("f***"), asking the backend to as well where it can
+    #[serde(default)]
+    pub profanity_filter: bool,
+    /// Words to mask instead of the built-in list
+    #[serde(default)]
+    pub profanity_words: Option<Vec<String>>,
+    /// Output bullet-point minutes written by a chat model instead of the
+    /// verbatim transcript (uses the OpenAI API key)
+    #[serde(default)]
+    pub summarize: bool,
+    /// Chat model for summaries (default "gpt-4o-mini")
+    #[serde(default)]
+    pub summary_model: Option<String>,
+    /// Vision model `whis ocr` reads images with (default "gpt-4o-mini")
+    #[serde(default)]
+    pub ocr_model: Option<String>,
+    /// What `whis say` reads aloud with
+    #[serde(default)]
+    pub tts_engine: TtsEngine,
+    /// Voice to read with (default "alloy" for OpenAI, the system voice
+    /// for the local engine)
+    #[serde(default)]
+    pub tts_voice: Option<String>,
+    #[serde(default)]
+    pub output_mode: OutputMode,
+    /// Selection the clipboard output mode copies to
+    #[serde(default)]
+    pub clipboard_selection: ClipboardSelection,
+    /// How the clipboard output mode reaches the clipboard
+    #[serde(default)]
+    pub clipboard_backend: ClipboardBackend,
+    /// Template for the final text, e.g. "{{date}} — {{text}}" (see `template`)
+    #[serde(default)]
+    pub template: Option<String>,
+    /// Spoken patterns and the shell commands they run in `command` output mode
+    #[serde(default)]
+    pub voice_commands: Vec<VoiceCommand>,
+    /// POST every transcript as JSON to this URL
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    /// Bearer token sent with webhook requests
+    #[serde(default)]
+    pub webhook_token: Option<String>,
+    /// Append every transcript to this Markdown file (strftime patterns allowed)
+    #[serde(default)]
+    pub daily_note: Option<String>,
+    /// Write every transcript as a line of JSON to this FIFO or UNIX socket
+    #[serde(default)]
+    pub pipe_path: Option<String>,
+    /// File "front :: back" lines of transcripts as flashcards: appended to
+    /// this TSV file, or added through AnkiConnect if it's a URL
+    /// ("http://localhost:8765")
+    #[serde(default)]
+    pub anki_target: Option<String>,
+    /// Deck AnkiConnect adds cards to (default "Default")
+    #[serde(default)]
+    pub anki_deck: Option<String>,
+    /// Bearer token required by the HTTP control server (`whis serve`)
+    #[serde(default)]
+    pub http_token: Option<String>,
+    /// Address for the Prometheus metrics endpoint of `whis listen`, e.g.
+    /// "127.0.0.1:9477" (needs a build with the `metrics` feature)
+    #[serde(default)]
+    pub metrics_addr: Option<String>,
+    /// Stop `whis listen` after this many minutes without recording;
+    /// `whis toggle` starts it again
+    #[serde(default)]
+    pub idle_exit_mins: Option<u64>,
+    /// Shell command run with the new state ("idle", "recording",
+    /// "transcribing") as `$1` whenever the recording state changes
+    #[serde(default)]
+    pub on_state_change: Option<String>,
+    /// USB id ("vendor:product", as shown by `lsusb`) of a foot pedal or
+    /// dictation switch that toggles recording instead of the hotkey (Linux)
+    #[serde(default)]
+    pub trigger_device: Option<String>,
+    /// Put between the last transcript and a take appended to it with
+    /// `whis toggle --append`
+    #[serde(default = "default_append_separator")]
+    pub append_separator: String,
+    /// File `whis meeting` writes to: strftime patterns for the start of the
+    /// meeting and `{title}`, e.g. "~/meetings/%Y-%m-%d_%H%M_{title}.md"
+    #[serde(default = "default_meeting_file")]
+    pub meeting_file: String,
+    /// Switch a Bluetooth headset in A2DP mode (no microphone) to its
+    /// headset profile while recording (Linux, see `bluetooth`)
+    #[serde(default)]
+    pub bluetooth_switch: bool,
+    /// Keep the text of chunks that succeeded when others fail, with a
+    /// placeholder for each gap, instead of failing the whole recording
+    #[serde(default)]
+    pub partial_results: bool,
+    /// How long recordings are split for upload
+    #[serde(default)]
+    pub chunking: ChunkConfig,
+    /// Keystroke delay and input of non-ASCII characters for output mode "type"
+    #[serde(default)]
+    pub typing: TypingConfig,
+    /// Codec recordings are uploaded in ("mp3", "opus" or "wav"); backends that
+    /// can't decode Opus get MP3
+    #[serde(default)]
+    pub audio_codec: AudioCodec,
+    /// Encoder bitrate like "64k" (128k for MP3 and 32k for Opus when unset)
+    #[serde(default)]
+    pub audio_bitrate: Option<String>,
+    /// Record a single channel, averaging the channels of the microphone;
+    /// speech doesn't need stereo
+    #[serde(default)]
+    pub mono: bool,
+    /// Timeout for each API request in seconds
+    #[serde(default = "default_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+    /// Cap uploads to this many KB/s so other traffic on a slow link keeps working
+    #[serde(default)]
+    pub upload_limit_kbps: Option<u32>,
+    /// Warn when the estimated cost of this month's transcriptions reaches this many USD
+    #[serde(default)]
+    pub monthly_budget_usd: Option<f64>,
+    /// Move recorded audio to disk once the in-memory buffer exceeds this
+    /// many MB (0 keeps the whole recording in memory)
+    #[serde(default = "default_spill_threshold_mb")]
+    pub spill_threshold_mb: u32,
+    /// Keep this many seconds of microphone audio while the service is idle
+    /// and prepend it to the next recording (0 disables; keeps the
+    /// microphone open)
+    #[serde(default)]
+    pub pre_roll_secs: f32,
+    /// While `whis listen` records, transcribe each window of this many
+    /// seconds as soon as it is complete, for `whis status --partial`
+    /// (billed on top of the final transcription)
+    #[serde(default)]
+    pub live_window_secs: Option<u32>,
+    /// Name of the microphone to record from (system default when unset)
+    #[serde(default)]
+    pub input_device: Option<String>,
+    /// Without `input_device`, also move a running recording to a new
+    /// default microphone (it must support the recording's sample rate);
+    /// new recordings always use the current default
+    #[serde(default)]
+    pub follow_default_device: bool,
+    /// Overrides applied depending on the focused application (see `app_rules`)
+    #[serde(default)]
+    pub app_rules: Vec<AppRule>,
+    /// Named overrides selectable with `--profile`
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+    /// Name of the profile applied to these settings, if any (not saved)
+    #[serde(skip)]
+    pub active_profile: Option<String>,
+    /// Profile to apply per detected language code, e.g. {"de": "deutsch"}
+    /// (see `language`)
+    #[serde(default)]
+    pub language_profiles: BTreeMap<String, String>,
+    /// Let a recording start with "switch to German" to set its language
+    /// (see `language`)
+    #[serde(default)]
+    pub language_hotword: bool,
+    /// Keep transcripts and audio off disk: no history, no spill files and
+    /// no transcript text in logs
+    #[serde(default)]
+    pub privacy: bool,
+    /// Encrypt transcripts in the history file with a key kept in the
+    /// system keyring (see `vault`). Recordings then stay in memory instead
+    /// of spilling to disk as plain WAV.
+    #[serde(default)]
+    pub encrypt_history: bool,
+}
+
+fn default_request_timeout_secs() -> u64 {
+    crate::provider::DEFAULT_TIMEOUT_SECS
+}
+
+fn default_append_separator() -> String {
+    " ".to_string()
+}
+
+fn default_meeting_file() -> String {
+    "meeting-%Y-%m-%d-%H%M.md".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_spill_threshold_mb() -> u32 {
+    crate::spill::DEFAULT_THRESHOLD_MB
 }
 
 impl Default for Settings {
     fn default() -> Self {
         Self {
             shortcut: "Ctrl+Shift+R".to_string(),
+            backend: Backend::default(),
+            backend_fallback: Vec::new(),
             openai_api_key: None,
+            assemblyai_api_key: None,
+            azure_api_key: None,
+            azure_region: None,
+            model_path: None,
+            server_url: None,
+            model: None,
+            language: None,
+            prompt: None,
+            temperature: None,
+            response_format: None,
+            beam_size: None,
+            best_of: None,
+            min_confidence: None,
+            mark_unreliable: false,
+            drop_silent_segments: true,
+            paragraph_pause_secs: None,
+            timestamps: false,
+            dictation_commands: false,
+            code_mode: false,
+            remove_fillers: false,
+            fillers: None,
+            normalize: false,
+            redact: false,
+            redact_patterns: Vec::new(),
+            profanity_filter: false,
+            profanity_words: None,
+            locale: Locale::default(),
+            terms: Vec::new(),
+            macros: BTreeMap::new(),
+            summarize: false,
+            summary_model: None,
+            ocr_model: None,
+            tts_engine: TtsEngine::default(),
+            tts_voice: None,
+            output_mode: OutputMode::Clipboard,
+            clipboard_selection: ClipboardSelection::default(),
+            clipboard_backend: ClipboardBackend::default(),
+            template: None,
+            voice_commands: Vec::new(),
+            webhook_url: None,
+            webhook_token: None,
+            daily_note: None,
+            pipe_path: None,
+            anki_target: None,
+            anki_deck: None,
+            http_token: None,
+            metrics_addr: None,
+            idle_exit_mins: None,
+            on_state_change: None,
+            trigger_device: None,
+            append_separator: default_append_separator(),
+            meeting_file: default_meeting_file(),
+            bluetooth_switch: false,
+            partial_results: false,
+            chunking: ChunkConfig::default(),
+            typing: TypingConfig::default(),
+            audio_codec: AudioCodec::default(),
+            audio_bitrate: None,
+            mono: false,
+            request_timeout_secs: default_request_timeout_secs(),
+            upload_limit_kbps: None,
+            monthly_budget_usd: None,
+            spill_threshold_mb: default_spill_threshold_mb(),
+            pre_roll_secs: 0.0,
+            live_window_secs: None,
+            input_device: None,
+            follow_default_device: false,
+            app_rules: Vec::new(),
+            profiles: BTreeMap::new(),
+            active_profile: None,
+            language_profiles: BTreeMap::new(),
+            language_hotword: false,
+            privacy: false,
+            encrypt_history: false,
         }
     }
 }
@@ -31,14 +463,65 @@ impl Settings {
     /// Load settings from disk
     pub fn load() -> Self {
         let path = Self::path();
-        if let Ok(content) = fs::read_to_string(&path) {
-            if let Ok(settings) = serde_json::from_str(&content) {
-                return settings;
-            }
+        if let Ok(content) = fs::read_to_string(&path)
+            && let Ok(settings) = serde_json::from_str(&content)
+        {
+            return settings;
         }
         Self::default()
     }
 
+    /// Configured upload encoding, before negotiating with the backend
+    pub fn encoding(&self) -> AudioEncoding {
+        AudioEncoding {
+            codec: self.audio_codec,
+            bitrate: self.audio_bitrate.clone(),
+            mono: self.mono,
+        }
+    }
+
+    /// In-memory buffer size in MB before audio moves to disk (0 never
+    /// spills, which privacy mode and `encrypt_history` enforce)
+    pub fn spill_limit_mb(&self) -> u32 {
+        if self.privacy || self.encrypt_history {
+            0
+        } else {
+            self.spill_threshold_mb
+        }
+    }
+
+    /// API key configured for a backend
+    pub fn api_key(&self, backend: Backend) -> Option<&str> {
+        match backend {
+            Backend::OpenAI => self.openai_api_key.as_deref(),
+            Backend::AssemblyAI => self.assemblyai_api_key.as_deref(),
+            Backend::Azure => self.azure_api_key.as_deref(),
+            Backend::Vosk | Backend::WhisperServer => None,
+        }
+    }
+
+    /// Set the API key for a backend
+    pub fn set_api_key(&mut self, backend: Backend, key: String) {
+        match backend {
+            Backend::OpenAI => self.openai_api_key = Some(key),
+            Backend::AssemblyAI => self.assemblyai_api_key = Some(key),
+            Backend::Azure => self.azure_api_key = Some(key),
+            Backend::Vosk | Backend::WhisperServer => {}
+        }
+    }
+
+    /// Settings with the named profile's overrides applied
+    pub fn with_profile(&self, name: &str) -> Result<Self> {
+        let profile = self
+            .profiles
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown profile: {name}"))?;
+        Ok(Self {
+            active_profile: Some(name.to_string()),
+            ..profile.apply(self)
+        })
+    }
+
     /// Save settings to disk with 0600 permissions
     pub fn save(&self) -> Result<()> {
         let path = Self::path();
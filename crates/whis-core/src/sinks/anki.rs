@@ -0,0 +1,154 @@
+//! Flashcards from dictated "front :: back" pairs
+//!
+//! Language learners dictate vocabulary as "Hund :: dog", one pair per line
+//! ("new line" with dictation commands). Each pair becomes a Basic card,
+//! appended to a TSV file that Anki imports, or added straight to a deck
+//! through AnkiConnect when the target is its URL. Transcripts without a
+//! pair are left alone.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::json;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+/// Between the front and the back of a card; Whisper may space the colons
+static SEPARATOR: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\s*:\s*:\s*").unwrap());
+
+/// Deck cards are added to through AnkiConnect unless `anki_deck` is set
+pub const DEFAULT_DECK: &str = "Default";
+/// Request timeout for AnkiConnect
+const TIMEOUT_SECS: u64 = 10;
+
+/// A flashcard
+#[derive(Debug, Clone, PartialEq)]
+pub struct Card {
+    pub front: String,
+    pub back: String,
+}
+
+#[derive(Deserialize)]
+struct ConnectResponse {
+    result: Option<Vec<Option<u64>>>,
+    error: Option<String>,
+}
+
+/// The cards in a transcript: lines with a front and a back
+pub fn cards(text: &str) -> Vec<Card> {
+    text.lines()
+        .filter_map(|line| {
+            let mut parts = SEPARATOR.splitn(line, 2);
+            let (front, back) = (parts.next()?.trim(), parts.next()?.trim());
+            (!front.is_empty() && !back.is_empty()).then(|| Card {
+                front: front.to_string(),
+                back: back.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// File the cards of `text` at `target`: an AnkiConnect URL or a TSV file
+pub fn send(target: &str, deck: Option<&str>, text: &str) -> Result<()> {
+    let cards = cards(text);
+    if cards.is_empty() {
+        return Ok(());
+    }
+    if target.starts_with("http://") || target.starts_with("https://") {
+        add_notes(target, deck.unwrap_or(DEFAULT_DECK), &cards)
+    } else {
+        append_tsv(target, &cards)
+    }
+}
+
+fn append_tsv(path: &str, cards: &[Card]) -> Result<()> {
+    let path = super::pipe::expand_path(path);
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    file.write_all(tsv(cards).as_bytes())
+        .with_context(|| format!("Failed to write to {}", path.display()))
+}
+
+/// A line per card, front and back separated by a tab
+fn tsv(cards: &[Card]) -> String {
+    let field = |text: &str| text.replace(['\t', '\n'], " ");
+    cards
+        .iter()
+        .map(|card| format!("{}\t{}\n", field(&card.front), field(&card.back)))
+        .collect()
+}
+
+fn add_notes(url: &str, deck: &str, cards: &[Card]) -> Result<()> {
+    let notes: Vec<_> = cards
+        .iter()
+        .map(|card| {
+            json!({
+                "deckName": deck,
+                "modelName": "Basic",
+                "fields": { "Front": card.front, "Back": card.back },
+                "tags": ["whis"],
+            })
+        })
+        .collect();
+    let body = json!({
+        "action": "addNotes",
+        "version": 6,
+        "params": { "notes": notes },
+    });
+
+    let response: ConnectResponse = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(TIMEOUT_SECS))
+        .build()
+        .context("Failed to create HTTP client")?
+        .post(url)
+        .json(&body)
+        .send()
+        .context("Failed to reach AnkiConnect. Is Anki running?")?
+        .json()
+        .context("Failed to parse AnkiConnect response")?;
+    if let Some(error) = response.error {
+        anyhow::bail!("AnkiConnect: {error}");
+    }
+    // Notes that weren't added (duplicates, unknown deck) come back as null
+    let failed = response
+        .result
+        .unwrap_or_default()
+        .iter()
+        .filter(|id| id.is_none())
+        .count();
+    if failed > 0 {
+        anyhow::bail!("AnkiConnect didn't add {failed} card(s); are they duplicates?");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(front: &str, back: &str) -> Card {
+        Card {
+            front: front.to_string(),
+            back: back.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_cards() {
+        assert_eq!(
+            cards("Hund :: dog\nKatze: : cat.\nno card here\n :: empty"),
+            [card("Hund", "dog"), card("Katze", "cat.")]
+        );
+        assert!(cards("Just a normal transcript.").is_empty());
+    }
+
+    #[test]
+    fn test_tsv() {
+        assert_eq!(tsv(&[card("der Hund", "the\tdog")]), "der Hund\tthe dog\n");
+    }
+}
@@ -0,0 +1,66 @@
+use anyhow::{Context, Result};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+
+use super::Payload;
+use crate::template;
+
+/// Append the transcript under a timestamped heading to a Markdown file.
+///
+/// `path_template` may start with `~/` and contain strftime patterns,
+/// e.g. `~/notes/%Y-%m-%d.md` for one note per day (see `template::file_path`).
+pub fn append(path_template: &str, payload: &Payload) -> Result<PathBuf> {
+    let title = payload.title.as_deref().unwrap_or_default();
+    let path = template::file_path(path_template, payload.timestamp, title)?;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+
+    let is_empty = std::fs::metadata(&path).map_or(true, |m| m.len() == 0);
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+
+    let separator = if is_empty { "" } else { "\n" };
+    write!(
+        file,
+        "{separator}## {}\n\n{}\n",
+        payload.timestamp.format("%H:%M"),
+        payload.text.trim()
+    )
+    .with_context(|| format!("Failed to write {}", path.display()))?;
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append() {
+        let dir = tempfile::tempdir().unwrap();
+        let template = format!("{}/%Y.md", dir.path().display());
+        let path = append(&template, &Payload::new(" First ", 1.0)).unwrap();
+        append(&template, &Payload::new("Second", 1.0)).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("## "));
+        assert!(content.contains("\n\nFirst\n\n## "));
+        assert!(content.ends_with("\n\nSecond\n"));
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        let dir = tempfile::tempdir().unwrap();
+        for template in ["100%.md", "%Q.md"] {
+            let template = format!("{}/{template}", dir.path().display());
+            assert!(append(&template, &Payload::new("text", 1.0)).is_err());
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! Additional outputs that receive every finished transcript
+//!
+//! Sinks run after the primary output (clipboard or voice command) and are
+//! configured independently in `Settings`. A failing sink never discards the
+//! transcript; errors are returned for the caller to report.
+
+pub mod anki;
+pub mod markdown;
+pub mod pipe;
+pub mod webhook;
+
+use chrono::{DateTime, Local};
+use serde::Serialize;
+
+use crate::metadata::Metadata;
+use crate::settings::Settings;
+
+/// A finished transcript as delivered to sinks
+#[derive(Debug, Clone, Serialize)]
+pub struct Payload {
+    pub text: String,
+    pub timestamp: DateTime<Local>,
+    /// Recording duration in seconds
+    #[serde(rename = "duration")]
+    pub duration_secs: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Metadata::is_empty")]
+    pub metadata: Metadata,
+}
+
+impl Payload {
+    pub fn new(text: impl Into<String>, duration_secs: f32) -> Self {
+        Self {
+            text: text.into(),
+            timestamp: Local::now(),
+            duration_secs,
+            title: None,
+            tags: Vec::new(),
+            metadata: Metadata::default(),
+        }
+    }
+}
+
+/// Deliver a transcript to every configured sink (blocking).
+/// Returns the errors of sinks that failed.
+pub fn dispatch(settings: &Settings, payload: &Payload) -> Vec<anyhow::Error> {
+    let mut errors = Vec::new();
+
+    if let Some(url) = &settings.webhook_url
+        && let Err(e) = webhook::send(url, settings.webhook_token.as_deref(), payload)
+    {
+        errors.push(e);
+    }
+
+    if let Some(template) = &settings.daily_note
+        && let Err(e) = markdown::append(template, payload)
+    {
+        errors.push(e);
+    }
+
+    if let Some(target) = &settings.anki_target
+        && let Err(e) = anki::send(target, settings.anki_deck.as_deref(), &payload.text)
+    {
+        errors.push(e);
+    }
+
+    if let Some(path) = &settings.pipe_path
+        && let Err(e) = pipe::send(path, payload)
+    {
+        errors.push(e);
+    }
+
+    errors
+}
@@ -0,0 +1,190 @@
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::path::PathBuf;
+
+use super::Payload;
+
+/// How long a socket or FIFO consumer may take to accept a transcript
+#[cfg(unix)]
+const WRITE_TIMEOUT_SECS: u64 = 5;
+
+/// Write the transcript as one line of JSON to a FIFO or UNIX socket.
+///
+/// Nothing is delivered when no consumer is listening: a socket refuses the
+/// connection (an error), and a FIFO without a reader drops the line. A
+/// consumer that stops reading fails delivery after a timeout instead of
+/// blocking it.
+#[cfg(unix)]
+pub fn send(path: &str, payload: &Payload) -> Result<()> {
+    use std::fs::OpenOptions;
+    use std::os::unix::fs::{FileTypeExt, OpenOptionsExt};
+    use std::os::unix::net::UnixStream;
+    use std::time::Duration;
+
+    let path = expand_path(path);
+    let line = line(payload)?;
+    let file_type = std::fs::metadata(&path)
+        .with_context(|| format!("Cannot open {}", path.display()))?
+        .file_type();
+
+    let written = if file_type.is_socket() {
+        let mut stream = UnixStream::connect(&path)
+            .with_context(|| format!("Failed to connect to {}", path.display()))?;
+        stream.set_write_timeout(Some(Duration::from_secs(WRITE_TIMEOUT_SECS)))?;
+        stream.write_all(line.as_bytes())
+    } else if file_type.is_fifo() {
+        // Non-blocking, so opening without a reader can't hang delivery
+        let fifo = OpenOptions::new()
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(&path);
+        match fifo {
+            Ok(fifo) => write_fifo(fifo, line.as_bytes()),
+            // No reader
+            Err(e) if e.raw_os_error() == Some(libc::ENXIO) => return Ok(()),
+            Err(e) => Err(e),
+        }
+    } else {
+        anyhow::bail!("{} is not a FIFO or socket", path.display());
+    };
+    written.with_context(|| format!("Failed to write to {}", path.display()))
+}
+
+/// Write all of `data` to a FIFO opened non-blocking, waiting for the reader
+/// whenever the pipe is full, so a line longer than the pipe buffer arrives
+/// whole instead of failing halfway with `WouldBlock`
+#[cfg(unix)]
+fn write_fifo(mut fifo: std::fs::File, mut data: &[u8]) -> std::io::Result<()> {
+    use std::io::{Error, ErrorKind};
+    use std::os::fd::AsRawFd;
+
+    while !data.is_empty() {
+        match fifo.write(data) {
+            Ok(written) => data = &data[written..],
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                let mut writable = libc::pollfd {
+                    fd: fifo.as_raw_fd(),
+                    events: libc::POLLOUT,
+                    revents: 0,
+                };
+                let timeout_ms = (WRITE_TIMEOUT_SECS * 1000) as libc::c_int;
+                match unsafe { libc::poll(&mut writable, 1, timeout_ms) } {
+                    0 => return Err(Error::new(ErrorKind::TimedOut, "Reader stopped reading")),
+                    -1 => {
+                        let e = Error::last_os_error();
+                        if e.kind() != ErrorKind::Interrupted {
+                            return Err(e);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn send(_path: &str, _payload: &Payload) -> Result<()> {
+    anyhow::bail!("Pipe output is only supported on Unix")
+}
+
+/// The payload as a line of newline-delimited JSON
+fn line(payload: &Payload) -> Result<String> {
+    let json = serde_json::to_string(payload).context("Failed to serialize transcript")?;
+    Ok(json + "\n")
+}
+
+/// Expand a leading `~/`
+pub(super) fn expand_path(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(rest),
+        None => PathBuf::from(path),
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::os::unix::net::UnixListener;
+
+    #[test]
+    fn test_send_to_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("whis.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let payload = Payload::new("hello\nworld", 1.5);
+        send(path.to_str().unwrap(), &payload).unwrap();
+
+        let (stream, _) = listener.accept().unwrap();
+        let mut received = String::new();
+        BufReader::new(stream).read_line(&mut received).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&received).unwrap();
+        assert_eq!(json["text"], "hello\nworld");
+        assert_eq!(json["duration"], 1.5);
+    }
+
+    fn fifo(dir: &tempfile::TempDir) -> PathBuf {
+        let path = dir.path().join("whis.fifo");
+        let c_path = std::ffi::CString::new(path.to_str().unwrap()).unwrap();
+        assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+        path
+    }
+
+    #[test]
+    fn test_send_to_fifo_without_reader() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = fifo(&dir);
+        let payload = Payload::new("x".repeat(128 * 1024), 1.0);
+        send(path.to_str().unwrap(), &payload).unwrap();
+    }
+
+    #[test]
+    fn test_send_to_fifo_longer_than_its_buffer() {
+        use std::io::{ErrorKind, Read};
+        use std::os::unix::fs::OpenOptionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = fifo(&dir);
+        // Opened before the line is sent, read only once the pipe is full
+        let mut reader = std::fs::OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(&path)
+            .unwrap();
+        let reading = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            let mut received = Vec::new();
+            let mut buf = [0; 8192];
+            while !received.ends_with(b"\n") {
+                match reader.read(&mut buf) {
+                    Ok(read) => received.extend_from_slice(&buf[..read]),
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                        std::thread::sleep(std::time::Duration::from_millis(1));
+                    }
+                    Err(e) => panic!("{e}"),
+                }
+            }
+            received
+        });
+
+        let text = "x".repeat(256 * 1024);
+        send(path.to_str().unwrap(), &Payload::new(text.clone(), 1.0)).unwrap();
+        let received = reading.join().unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&received).unwrap();
+        assert_eq!(json["text"], text.as_str());
+    }
+
+    #[test]
+    fn test_send_to_regular_file() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let payload = Payload::new("hello", 1.0);
+        assert!(send(file.path().to_str().unwrap(), &payload).is_err());
+    }
+}
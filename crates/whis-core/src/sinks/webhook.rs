@@ -0,0 +1,131 @@
+use anyhow::{Context, Result};
+use reqwest::StatusCode;
+use std::time::Duration;
+
+use super::Payload;
+
+/// Number of delivery attempts before giving up
+const MAX_ATTEMPTS: u32 = 3;
+/// Delay before the first retry (doubled for each further retry)
+const RETRY_DELAY_MS: u64 = 500;
+/// Request timeout in seconds
+const WEBHOOK_TIMEOUT_SECS: u64 = 10;
+
+/// POST the transcript as JSON to a webhook URL, retrying when the webhook
+/// is unreachable, rate limited or has a server error
+pub fn send(url: &str, token: Option<&str>, payload: &Payload) -> Result<()> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+        .build()
+        .context("Failed to create HTTP client")?;
+
+    let body = serde_json::to_vec(payload).context("Failed to serialize webhook payload")?;
+
+    let mut last_error = None;
+    for attempt in 0..MAX_ATTEMPTS {
+        if attempt > 0 {
+            std::thread::sleep(Duration::from_millis(RETRY_DELAY_MS << (attempt - 1)));
+        }
+
+        let mut request = client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.clone());
+        if let Some(token) = token {
+            request = request.bearer_auth(token);
+        }
+
+        match request.send() {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response) => {
+                let status = response.status();
+                let error = anyhow::anyhow!("Webhook returned {status}");
+                if !is_transient(status) {
+                    return Err(error);
+                }
+                last_error = Some(error);
+            }
+            Err(e) => {
+                let retry = e.is_connect() || e.is_timeout();
+                let error = anyhow::Error::new(e).context("Failed to reach webhook");
+                if !retry {
+                    return Err(error);
+                }
+                last_error = Some(error);
+            }
+        }
+    }
+
+    Err(last_error
+        .unwrap_or_else(|| anyhow::anyhow!("Webhook delivery failed"))
+        .context(format!(
+            "Webhook delivery failed after {MAX_ATTEMPTS} attempts"
+        )))
+}
+
+/// Whether a request answered with `status` may succeed when sent again
+fn is_transient(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Answer requests with `statuses` in turn (the last one repeated),
+    /// returning the webhook URL and the number of requests received
+    fn serve(statuses: &'static [u16]) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+        let count = requests.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(&stream);
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':')
+                        && name.eq_ignore_ascii_case("content-length")
+                    {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+                reader.read_exact(&mut vec![0; length]).unwrap();
+
+                let n = count.fetch_add(1, Ordering::SeqCst);
+                let status = statuses[n.min(statuses.len() - 1)];
+                write!(
+                    stream,
+                    "HTTP/1.1 {status} X\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                )
+                .unwrap();
+            }
+        });
+        (url, requests)
+    }
+
+    #[test]
+    fn test_retries_transient_errors() {
+        let (url, requests) = serve(&[503, 429, 200]);
+        send(&url, None, &Payload::new("Hello", 1.0)).unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_client_error_is_not_retried() {
+        let (url, requests) = serve(&[404, 200]);
+        let error = send(&url, None, &Payload::new("Hello", 1.0)).unwrap_err();
+        assert_eq!(error.to_string(), "Webhook returned 404 Not Found");
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+}
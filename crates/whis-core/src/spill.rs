@@ -0,0 +1,215 @@
+//! Disk spill for long recordings
+//!
+//! A background thread moves captured samples from the in-memory buffer to a
+//! 16-bit WAV file once the buffer grows past a threshold, so multi-hour
+//! recordings don't exhaust RAM. The WAV header is flushed after every write,
+//! so the file stays readable if the process dies mid-recording.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "audio")]
+pub(crate) use worker::SpillWorker;
+
+/// Default in-memory buffer size before samples are moved to disk
+pub const DEFAULT_THRESHOLD_MB: u32 = 64;
+
+/// Directory holding spill files (~/.local/share/whis/spill)
+pub fn dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("whis")
+        .join("spill")
+}
+
+/// Spill files whose recording process is no longer running, oldest first.
+/// These are recordings interrupted by a crash and can be recovered.
+pub fn orphaned() -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir()) else {
+        return Vec::new();
+    };
+
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "wav"))
+        .filter(|path| owner_pid(path).is_none_or(|pid| !process_alive(pid)))
+        .collect();
+    // File names start with the recording's start time
+    files.sort();
+    files
+}
+
+/// PID of the process that wrote a spill file (`recording-<time>-<pid>.wav`)
+fn owner_pid(path: &Path) -> Option<u32> {
+    path.file_stem()?.to_str()?.rsplit('-').next()?.parse().ok()
+}
+
+pub(crate) fn process_alive(pid: u32) -> bool {
+    if pid == std::process::id() {
+        return true;
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        Path::new(&format!("/proc/{pid}")).exists()
+    }
+    #[cfg(all(unix, not(target_os = "linux")))]
+    {
+        std::process::Command::new("kill")
+            .args(["-0", &pid.to_string()])
+            .stderr(std::process::Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    }
+    #[cfg(windows)]
+    {
+        std::process::Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {pid}"), "/NH"])
+            .output()
+            .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).contains(&pid.to_string()))
+    }
+}
+
+/// Writing spill files while recording
+#[cfg(feature = "audio")]
+mod worker {
+    use anyhow::{Context, Result};
+    use std::fs::{self, File, OpenOptions};
+    use std::io::BufWriter;
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::thread::JoinHandle;
+    use std::time::Duration;
+
+    use super::dir;
+
+    /// How often the buffer size is checked
+    const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+    type Writer = hound::WavWriter<BufWriter<File>>;
+
+    /// Background thread moving samples from memory to a spill file
+    pub(crate) struct SpillWorker {
+        stop: Arc<AtomicBool>,
+        handle: JoinHandle<Option<PathBuf>>,
+    }
+
+    impl SpillWorker {
+        /// Start watching `samples`. `spilled` counts the samples moved to disk
+        /// and is only updated while the `samples` lock is held.
+        pub(crate) fn start(
+            samples: Arc<Mutex<Vec<f32>>>,
+            spilled: Arc<AtomicUsize>,
+            spec: hound::WavSpec,
+            threshold_mb: u32,
+        ) -> Self {
+            let threshold_samples = threshold_mb as usize * 1024 * 1024 / size_of::<f32>();
+            let stop = Arc::new(AtomicBool::new(false));
+            let stop_flag = stop.clone();
+
+            let handle = std::thread::spawn(move || {
+                let mut writer: Option<(PathBuf, Writer)> = None;
+
+                while !stop_flag.load(Ordering::SeqCst) {
+                    std::thread::park_timeout(POLL_INTERVAL);
+
+                    let batch = {
+                        let mut samples = samples.lock().unwrap();
+                        if samples.len() < threshold_samples {
+                            continue;
+                        }
+                        spilled.fetch_add(samples.len(), Ordering::SeqCst);
+                        std::mem::take(&mut *samples)
+                    };
+
+                    if let Err(e) = write_batch(&mut writer, spec, &batch) {
+                        // Keep the audio in memory rather than lose it
+                        eprintln!("Failed to spill audio to disk: {e:#}");
+                        let mut samples = samples.lock().unwrap();
+                        spilled.fetch_sub(batch.len(), Ordering::SeqCst);
+                        samples.splice(0..0, batch);
+                        break;
+                    }
+                }
+
+                let (path, writer) = writer?;
+                match writer.finalize() {
+                    Ok(()) => Some(path),
+                    Err(e) => {
+                        eprintln!("Failed to finalize spill file: {e}");
+                        Some(path)
+                    }
+                }
+            });
+
+            Self { stop, handle }
+        }
+
+        /// Stop the worker and return the spill file, if anything was spilled
+        pub(crate) fn finish(self) -> Option<PathBuf> {
+            self.stop.store(true, Ordering::SeqCst);
+            self.handle.thread().unpark();
+            self.handle.join().ok().flatten()
+        }
+    }
+
+    /// Append a batch of samples, creating the spill file on first use
+    fn write_batch(
+        writer: &mut Option<(PathBuf, Writer)>,
+        spec: hound::WavSpec,
+        batch: &[f32],
+    ) -> Result<()> {
+        if writer.is_none() {
+            let path = new_path()?;
+            let file = create_private(&path)?;
+            let wav = hound::WavWriter::new(BufWriter::new(file), spec)?;
+            *writer = Some((path, wav));
+        }
+
+        let (_, wav) = writer.as_mut().unwrap();
+        for &sample in batch {
+            wav.write_sample(crate::audio::to_i16(sample))?;
+        }
+        // Update the header so the file is valid even if we crash
+        wav.flush()?;
+        Ok(())
+    }
+
+    fn new_path() -> Result<PathBuf> {
+        let dir = dir();
+        fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        let name = format!(
+            "recording-{}-{}.wav",
+            chrono::Local::now().format("%Y%m%d-%H%M%S"),
+            std::process::id()
+        );
+        Ok(dir.join(name))
+    }
+
+    /// Create a file readable only by the current user
+    fn create_private(path: &Path) -> Result<File> {
+        let mut options = OpenOptions::new();
+        options.write(true).read(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        options
+            .open(path)
+            .with_context(|| format!("Failed to create {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_owner_pid() {
+        let path = Path::new("/tmp/spill/recording-20250101-093000-4242.wav");
+        assert_eq!(owner_pid(path), Some(4242));
+        assert_eq!(owner_pid(Path::new("/tmp/spill/notes.wav")), None);
+    }
+}
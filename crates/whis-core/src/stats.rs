@@ -0,0 +1,139 @@
+//! Dictation statistics that survive restarts
+//!
+//! Keeps the number of the last recording of the background service, so
+//! recordings stay numbered across restarts, and totals per day (recordings,
+//! words, audio length) in ~/.local/share/whis/stats.json.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::sinks::Payload;
+
+/// Format of the days totals are grouped by ("2025-01-31")
+const DAY_FORMAT: &str = "%Y-%m-%d";
+
+/// Totals for one day
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct DayStats {
+    pub recordings: u64,
+    pub words: u64,
+    /// Seconds of audio recorded
+    pub seconds: f64,
+}
+
+impl DayStats {
+    /// Average recording length in seconds
+    pub fn average_secs(&self) -> f64 {
+        match self.recordings {
+            0 => 0.0,
+            recordings => self.seconds / recordings as f64,
+        }
+    }
+
+    fn add(&mut self, other: &DayStats) {
+        self.recordings += other.recordings;
+        self.words += other.words;
+        self.seconds += other.seconds;
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Stats {
+    /// Number of the service's last recording
+    #[serde(default)]
+    pub last_recording: u32,
+    /// Totals per day ("2025-01-31")
+    #[serde(default)]
+    pub days: BTreeMap<String, DayStats>,
+}
+
+impl Stats {
+    /// Add a transcript of `seconds` of audio to the totals of `day`
+    pub fn add(&mut self, day: &str, text: &str, seconds: f32) {
+        self.days
+            .entry(day.to_string())
+            .or_default()
+            .add(&DayStats {
+                recordings: 1,
+                words: text.split_whitespace().count() as u64,
+                seconds: seconds as f64,
+            });
+    }
+
+    /// Totals over all days
+    pub fn total(&self) -> DayStats {
+        let mut total = DayStats::default();
+        for day in self.days.values() {
+            total.add(day);
+        }
+        total
+    }
+}
+
+/// Get the stats file path (~/.local/share/whis/stats.json)
+pub fn path() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("whis")
+        .join("stats.json")
+}
+
+/// Load the stats (empty if nothing was recorded yet)
+pub fn load() -> Result<Stats> {
+    let path = path();
+    if !path.exists() {
+        return Ok(Stats::default());
+    }
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+fn save(stats: &Stats) -> Result<()> {
+    let path = path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, serde_json::to_string_pretty(stats)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Remember the number of the service's current recording
+pub fn set_last_recording(number: u32) -> Result<()> {
+    let mut stats = load()?;
+    stats.last_recording = number;
+    save(&stats)
+}
+
+/// Add a finished transcript to the totals of its day
+pub fn record(payload: &Payload) -> Result<()> {
+    let mut stats = load()?;
+    let day = payload.timestamp.format(DAY_FORMAT).to_string();
+    stats.add(&day, &payload.text, payload.duration_secs);
+    save(&stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_and_total() {
+        let mut stats = Stats::default();
+        stats.add("2025-01-30", "one two three", 6.0);
+        stats.add("2025-01-31", "four five", 10.0);
+        stats.add("2025-01-31", "six", 20.0);
+
+        let day = stats.days["2025-01-31"];
+        assert_eq!((day.recordings, day.words), (2, 3));
+        assert_eq!(day.average_secs(), 15.0);
+
+        let total = stats.total();
+        assert_eq!((total.recordings, total.words), (3, 6));
+        assert_eq!(total.seconds, 36.0);
+        assert_eq!(DayStats::default().average_secs(), 0.0);
+    }
+}
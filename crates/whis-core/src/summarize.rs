@@ -0,0 +1,73 @@
+//! Meeting minutes instead of a verbatim transcript
+//!
+//! Summary mode sends the finished transcript to an OpenAI chat model and
+//! outputs bullet-point minutes. It always uses the OpenAI API key, whatever
+//! backend did the transcription.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::config::ApiConfig;
+use crate::provider::{Backend, check_status};
+use crate::settings::Settings;
+
+/// Chat model used unless `summary_model` is set
+pub const DEFAULT_MODEL: &str = "gpt-4o-mini";
+const API_URL: &str = "https://api.openai.com/v1/chat/completions";
+
+const PROMPT: &str = "Turn the transcript you are given into meeting minutes. \
+Reply only with concise Markdown bullet points: the topics discussed, decisions made \
+and action items (with owners when named). Write in the language of the transcript \
+and don't add anything that wasn't said.";
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<Choice>,
+}
+
+#[derive(Deserialize)]
+struct Choice {
+    message: Message,
+}
+
+#[derive(Deserialize)]
+struct Message {
+    content: String,
+}
+
+/// Summarize a transcript as bullet-point minutes (blocking)
+pub fn summarize(text: &str, settings: &Settings) -> Result<String> {
+    let config = ApiConfig::for_backend(settings, Backend::OpenAI)
+        .context("Summaries need an OpenAI API key")?;
+    let model = settings.summary_model.as_deref().unwrap_or(DEFAULT_MODEL);
+    let messages = json!([
+        { "role": "system", "content": PROMPT },
+        { "role": "user", "content": text },
+    ]);
+    chat(&config, model, messages)?.context("OpenAI returned no summary")
+}
+
+/// Send `messages` to an OpenAI chat model and return its answer, if it
+/// gave one (blocking)
+pub(crate) fn chat(config: &ApiConfig, model: &str, messages: Value) -> Result<Option<String>> {
+    let body = json!({ "model": model, "messages": messages });
+    let response = config
+        .http
+        .client()?
+        .post(API_URL)
+        .header("Authorization", format!("Bearer {}", config.api_key))
+        .json(&body)
+        .send()
+        .context("Failed to send request to OpenAI API")?;
+    let response = check_status(response, "OpenAI")?;
+
+    let reply: ChatResponse = response
+        .json()
+        .context("Failed to parse OpenAI chat response")?;
+    Ok(reply
+        .choices
+        .into_iter()
+        .next()
+        .map(|choice| choice.message.content.trim().to_string()))
+}
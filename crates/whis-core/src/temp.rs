@@ -0,0 +1,78 @@
+//! Temp files holding dictation audio or text
+//!
+//! Files are created readable only by the current user, with unpredictable
+//! names, and are deleted when dropped. Their names start with `whis_<pid>_`,
+//! so files left behind by a crashed process can be swept on the next start:
+//! dictation shouldn't linger in /tmp.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+use tempfile::NamedTempFile;
+
+use crate::spill;
+
+const PREFIX: &str = "whis_";
+
+/// Create a private temp file with the given extension, deleted when dropped
+pub fn file(extension: &str) -> Result<NamedTempFile> {
+    tempfile::Builder::new()
+        .prefix(&format!("{PREFIX}{}_", std::process::id()))
+        .suffix(&format!(".{extension}"))
+        .tempfile()
+        .context("Failed to create temp file")
+}
+
+/// Remove temp files of whis processes that are no longer running.
+/// Returns the number of files removed.
+pub fn sweep() -> usize {
+    let Ok(entries) = fs::read_dir(std::env::temp_dir()) else {
+        return 0;
+    };
+    entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| owner_pid(path).is_some_and(|pid| !spill::process_alive(pid)))
+        .filter(|path| fs::remove_file(path).is_ok())
+        .count()
+}
+
+/// PID of the process that created a temp file (`whis_<pid>_<random>.<ext>`)
+fn owner_pid(path: &Path) -> Option<u32> {
+    let name = path.file_name()?.to_str()?.strip_prefix(PREFIX)?;
+    name.split(['_', '.']).next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_owner_pid() {
+        assert_eq!(
+            owner_pid(Path::new("/tmp/whis_1234_a8Xk2q.mp3")),
+            Some(1234)
+        );
+        // Names of older versions: whis_<pid>_<nanos>_<suffix>.<ext>
+        assert_eq!(
+            owner_pid(Path::new("/tmp/whis_99_17000_main.wav")),
+            Some(99)
+        );
+        assert_eq!(owner_pid(Path::new("/tmp/whis-review-1.txt")), None);
+        assert_eq!(owner_pid(Path::new("/tmp/whisper_1.txt")), None);
+    }
+
+    #[test]
+    fn test_file_is_private_and_removed() {
+        let file = file("txt").unwrap();
+        let path = file.path().to_path_buf();
+        assert_eq!(owner_pid(&path), Some(std::process::id()));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        drop(file);
+        assert!(!path.exists());
+    }
+}
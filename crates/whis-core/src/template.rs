@@ -0,0 +1,130 @@
+//! Output templates
+//!
+//! Wraps the final transcript in a user-defined template before it is copied
+//! or sent to sinks, e.g. `"{{date}} — {{text}}"` or a code fence. Supported
+//! placeholders: `{{text}}`, `{{date}}`, `{{time}}` and `{{duration}}`.
+//! Unknown placeholders are left as-is.
+//!
+//! File name templates, as for `whis meeting`, take strftime patterns and
+//! `{title}` instead, e.g. `~/meetings/%Y-%m-%d_%H%M_{title}.md`.
+
+use anyhow::{Context, Result};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local};
+use std::path::PathBuf;
+
+use crate::settings::{OutputMode, Settings};
+
+/// Apply the configured template to a transcript.
+///
+/// Returns the text unchanged without a template or in command output mode,
+/// where voice commands must match the plain transcript.
+pub fn apply(settings: &Settings, text: &str, duration_secs: f32) -> String {
+    match &settings.template {
+        Some(template) if settings.output_mode != OutputMode::Command => {
+            render(template, text, Local::now(), duration_secs)
+        }
+        _ => text.to_string(),
+    }
+}
+
+/// Fill a template's placeholders
+pub fn render(template: &str, text: &str, now: DateTime<Local>, duration_secs: f32) -> String {
+    let secs = duration_secs.round() as u64;
+    // Substitute the transcript last so placeholders inside it stay literal
+    template
+        .replace("{{date}}", &now.format("%Y-%m-%d").to_string())
+        .replace("{{time}}", &now.format("%H:%M").to_string())
+        .replace("{{duration}}", &format!("{}:{:02}", secs / 60, secs % 60))
+        .replace("{{text}}", text)
+}
+
+/// The path a file name template stands for at `time`: strftime patterns
+/// filled in, `{title}` replaced by `title` (made safe for a file name) and
+/// a leading `~/` expanded
+pub fn file_path(template: &str, time: DateTime<Local>, title: &str) -> Result<PathBuf> {
+    if StrftimeItems::new(template).any(|item| item == Item::Error) {
+        anyhow::bail!("Invalid strftime pattern in {template:?}");
+    }
+    // The title goes in after formatting, so a "%" in it stays as it is
+    let path = time
+        .format(template)
+        .to_string()
+        .replace("{title}", &file_name_part(title));
+    Ok(match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()
+            .context("No home directory to expand \"~/\"")?
+            .join(rest),
+        None => PathBuf::from(path),
+    })
+}
+
+/// `text` usable in a file name: no path separators or control characters
+fn file_name_part(text: &str) -> String {
+    text.trim()
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' => '-',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2025, 3, 14, 9, 5, 0).unwrap()
+    }
+
+    #[test]
+    fn test_render_placeholders() {
+        assert_eq!(
+            render(
+                "{{date}} {{time}} ({{duration}}) — {{text}}",
+                "Hello",
+                now(),
+                75.4
+            ),
+            "2025-03-14 09:05 (1:15) — Hello"
+        );
+    }
+
+    #[test]
+    fn test_render_code_fence() {
+        assert_eq!(
+            render("```\n{{text}}\n```", "ls -la", now(), 1.0),
+            "```\nls -la\n```"
+        );
+    }
+
+    #[test]
+    fn test_transcript_placeholders_stay_literal() {
+        assert_eq!(
+            render("> {{text}}", "say {{date}}", now(), 1.0),
+            "> say {{date}}"
+        );
+    }
+
+    #[test]
+    fn test_no_template_in_command_mode() {
+        let settings = Settings {
+            template: Some("\"{{text}}\"".to_string()),
+            output_mode: OutputMode::Command,
+            ..Settings::default()
+        };
+        assert_eq!(apply(&settings, "open firefox", 1.0), "open firefox");
+    }
+
+    #[test]
+    fn test_file_path() {
+        assert_eq!(
+            file_path("meetings/%Y-%m-%d_%H%M_{title}.md", now(), "Q3: 50% / plan").unwrap(),
+            PathBuf::from("meetings/2025-03-14_0905_Q3- 50% - plan.md")
+        );
+        assert!(file_path("%Q.md", now(), "x").is_err());
+    }
+}
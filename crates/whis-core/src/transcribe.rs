@@ -1,197 +1,538 @@
 use anyhow::{Context, Result};
-use reqwest::blocking::multipart;
-use serde::Deserialize;
+use bytes::Bytes;
+use serde::Serialize;
+use std::path::Path;
 use std::sync::Arc;
-use tokio::sync::Semaphore;
-
-use crate::audio::AudioChunk;
-
-/// Maximum concurrent API requests to OpenAI
-const MAX_CONCURRENT_REQUESTS: usize = 3;
+use std::time::Duration;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::{Semaphore, SemaphorePermit};
+use tokio_util::sync::CancellationToken;
+
+use crate::audio::{AudioChunk, AudioCodec};
+use crate::config::ApiConfig;
+use crate::language;
+use crate::provider::{self, Provider, Segment, Transcription, UploadProgress, Word};
+
+/// Maximum concurrent API requests
+pub const MAX_CONCURRENT_REQUESTS: usize = 3;
 /// Maximum words to search for overlap between chunks
 const MAX_OVERLAP_WORDS: usize = 15;
-/// API request timeout in seconds
-const API_TIMEOUT_SECS: u64 = 300;
+/// Error of chunks that were not transcribed because of a cancellation
+const CANCELLED: &str = "Cancelled";
+/// An upload that sends nothing for this long is given up and sent again
+const STALL_TIMEOUT: Duration = Duration::from_secs(30);
+/// Times a stalled upload is sent again before the chunk fails
+const MAX_STALL_RETRIES: usize = 2;
+/// How often the upload of a chunk is checked for progress
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Progress of a single chunk of a parallel transcription
+///
+/// Sent on the channel passed to [`parallel_transcribe_partial`]. Every chunk
+/// ends with either `Done` or `Failed`; chunks cancelled before their turn
+/// only send `Failed`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ChunkEvent {
+    /// The chunk's request is being sent
+    Started {
+        index: usize,
+    },
+    /// Part of the chunk's audio was sent (HTTP backends only)
+    Sending {
+        index: usize,
+        sent: u64,
+        total: u64,
+    },
+    /// The backend received the chunk's audio
+    Uploaded {
+        index: usize,
+        bytes: usize,
+    },
+    /// The chunk was transcribed
+    Done {
+        index: usize,
+        words: usize,
+    },
+    Failed {
+        index: usize,
+        error: String,
+    },
+}
 
-#[derive(Deserialize, Debug)]
-struct TranscriptionResponse {
-    text: String,
+impl ChunkEvent {
+    /// Index of the chunk the event is about
+    pub fn index(&self) -> usize {
+        match self {
+            ChunkEvent::Started { index }
+            | ChunkEvent::Sending { index, .. }
+            | ChunkEvent::Uploaded { index, .. }
+            | ChunkEvent::Done { index, .. }
+            | ChunkEvent::Failed { index, .. } => *index,
+        }
+    }
 }
 
 /// Result of transcribing a single chunk
 pub struct ChunkTranscription {
     pub index: usize,
-    pub text: String,
+    pub transcription: Transcription,
+    /// Offset of the chunk in the recording, in seconds
+    pub start_secs: f32,
     pub has_leading_overlap: bool,
 }
 
-/// Transcribe a single audio file (blocking, for simple single-file case)
-pub fn transcribe_audio(api_key: &str, audio_data: Vec<u8>) -> Result<String> {
-    let client = reqwest::blocking::Client::builder()
-        .timeout(std::time::Duration::from_secs(API_TIMEOUT_SECS))
-        .build()
-        .context("Failed to create HTTP client")?;
-
-    let form = multipart::Form::new().text("model", "whisper-1").part(
-        "file",
-        multipart::Part::bytes(audio_data)
-            .file_name("audio.mp3")
-            .mime_str("audio/mpeg")?,
-    );
-
-    let response = client
-        .post("https://api.openai.com/v1/audio/transcriptions")
-        .header("Authorization", format!("Bearer {api_key}"))
-        .multipart(form)
-        .send()
-        .context("Failed to send request to OpenAI API")?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let error_text = response
-            .text()
-            .unwrap_or_else(|_| "Unknown error".to_string());
-        anyhow::bail!("OpenAI API error ({status}): {error_text}");
-    }
+/// Requests in flight in this process, shared by every transcription so
+/// several files transcribed at once stay within the provider's rate limits
+static REQUESTS: Semaphore = Semaphore::const_new(MAX_CONCURRENT_REQUESTS);
 
-    let text = response.text().context("Failed to get response text")?;
-    let transcription: TranscriptionResponse =
-        serde_json::from_str(&text).context("Failed to parse OpenAI API response")?;
+/// Wait for one of the [`MAX_CONCURRENT_REQUESTS`] request slots; the slot is
+/// freed when the permit is dropped
+pub async fn request_permit() -> Result<SemaphorePermit<'static>> {
+    Ok(REQUESTS.acquire().await?)
+}
 
-    Ok(transcription.text)
+/// Transcribe a single encoded recording (blocking, for simple single-file case)
+pub fn transcribe_audio(
+    provider: &dyn Provider,
+    audio_data: Bytes,
+    codec: AudioCodec,
+) -> Result<Transcription> {
+    let file_name = format!("audio.{}", codec.extension());
+    provider.transcribe(audio_data, &file_name, codec.mime())
 }
 
-/// Transcribe a single chunk asynchronously
-async fn transcribe_chunk_async(
-    client: &reqwest::Client,
-    api_key: &str,
-    chunk: AudioChunk, // Take ownership to avoid clone
-) -> Result<ChunkTranscription> {
-    let chunk_index = chunk.index;
-    let has_leading_overlap = chunk.has_leading_overlap;
-
-    let form = reqwest::multipart::Form::new()
-        .text("model", "whisper-1")
-        .part(
-            "file",
-            reqwest::multipart::Part::bytes(chunk.data) // No clone needed
-                .file_name(format!("audio_chunk_{chunk_index}.mp3"))
-                .mime_str("audio/mpeg")?,
+/// Transcribe an existing audio file in a format the API accepts (blocking)
+pub fn transcribe_file(provider: &dyn Provider, path: &Path) -> Result<Transcription> {
+    let audio_data =
+        std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    if let Some(limit) = provider.max_upload_bytes()
+        && audio_data.len() > limit
+    {
+        anyhow::bail!(
+            "{} is larger than the {} MB upload limit",
+            path.display(),
+            limit / (1024 * 1024)
         );
-
-    let response = client
-        .post("https://api.openai.com/v1/audio/transcriptions")
-        .header("Authorization", format!("Bearer {api_key}"))
-        .multipart(form)
-        .send()
-        .await
-        .context("Failed to send request to OpenAI API")?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let error_text = response
-            .text()
-            .await
-            .unwrap_or_else(|_| "Unknown error".to_string());
-        anyhow::bail!("OpenAI API error ({status}): {error_text}");
     }
 
-    let text = response
-        .text()
-        .await
-        .context("Failed to get response text")?;
-    let transcription: TranscriptionResponse =
-        serde_json::from_str(&text).context("Failed to parse OpenAI API response")?;
-
-    Ok(ChunkTranscription {
-        index: chunk_index,
-        text: transcription.text,
-        has_leading_overlap,
-    })
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("audio.mp3")
+        .to_string();
+    let mime = match path.extension().and_then(|e| e.to_str()) {
+        Some("mp3") => "audio/mpeg",
+        Some("wav") => "audio/wav",
+        Some("m4a" | "mp4") => "audio/mp4",
+        Some("ogg" | "oga") => "audio/ogg",
+        Some("webm") => "audio/webm",
+        Some("flac") => "audio/flac",
+        _ => "application/octet-stream",
+    };
+
+    provider.transcribe(Bytes::from(audio_data), &file_name, mime)
+}
+
+/// A chunk that could not be transcribed
+#[derive(Debug, Clone, Serialize)]
+pub struct FailedChunk {
+    pub index: usize,
+    /// Offset of the chunk in the recording, in seconds
+    pub start_secs: f32,
+    pub error: String,
+}
+
+/// Chunked transcription that may be missing some segments
+pub struct PartialTranscription {
+    /// Merged text, with a placeholder where segments failed
+    pub transcription: Transcription,
+    pub failed: Vec<FailedChunk>,
+    /// Number of chunks in the recording
+    pub chunks: usize,
+    /// The transcription was cancelled; unfinished chunks are in `failed`
+    pub cancelled: bool,
+}
+
+impl PartialTranscription {
+    /// The transcription, or an error listing the failed chunks
+    pub fn require_complete(self) -> Result<Transcription> {
+        if self.failed.is_empty() {
+            return Ok(self.transcription);
+        }
+        if self.cancelled {
+            anyhow::bail!("Transcription cancelled");
+        }
+        let error_msgs: Vec<&str> = self.failed.iter().map(|f| f.error.as_str()).collect();
+        anyhow::bail!(
+            "Failed to transcribe {} of {} chunks:\n{}",
+            self.failed.len(),
+            self.chunks,
+            error_msgs.join("\n")
+        );
+    }
 }
 
 /// Transcribe multiple chunks in parallel with rate limiting
+///
+/// Fails if any chunk fails; see `parallel_transcribe_partial` to keep the rest.
 pub async fn parallel_transcribe(
-    api_key: &str,
+    provider: Arc<dyn Provider>,
     chunks: Vec<AudioChunk>,
-    progress_callback: Option<Box<dyn Fn(usize, usize) + Send + Sync>>,
-) -> Result<String> {
+    events: Option<UnboundedSender<ChunkEvent>>,
+    cancel: Option<CancellationToken>,
+) -> Result<Transcription> {
+    parallel_transcribe_partial(provider, chunks, events, cancel)
+        .await?
+        .require_complete()
+}
+
+/// Transcribe multiple chunks in parallel, keeping the text of the chunks
+/// that succeeded when others fail
+///
+/// Progress of each chunk is sent to `events` as it happens.
+/// Cancelling `cancel` returns promptly with the chunks finished so far.
+/// Requests already sent can't be interrupted; their results are dropped.
+pub async fn parallel_transcribe_partial(
+    provider: Arc<dyn Provider>,
+    chunks: Vec<AudioChunk>,
+    events: Option<UnboundedSender<ChunkEvent>>,
+    cancel: Option<CancellationToken>,
+) -> Result<PartialTranscription> {
     let total_chunks = chunks.len();
+    let cancel = cancel.unwrap_or_default();
+    let (results, failed) = transcribe_chunks(provider, chunks, events, &cancel).await;
+    Ok(merge_partial(results, failed, total_chunks, &cancel))
+}
 
-    // Create shared HTTP client with timeout
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(API_TIMEOUT_SECS))
-        .build()
-        .context("Failed to create HTTP client")?;
+/// Transcribe chunks like [`parallel_transcribe_partial`], letting a spoken
+/// "switch to German" at the start of the recording set the language
+///
+/// The first chunk is transcribed on its own; if it starts with the phrase,
+/// the other chunks are sent with that language. The phrase itself is left
+/// in the text for [`language::apply_switch`], which also handles
+/// recordings that weren't chunked.
+pub async fn parallel_transcribe_switching(
+    config: &ApiConfig,
+    chunks: Vec<AudioChunk>,
+    events: Option<UnboundedSender<ChunkEvent>>,
+    cancel: Option<CancellationToken>,
+) -> Result<PartialTranscription> {
+    let total_chunks = chunks.len();
+    let cancel = cancel.unwrap_or_default();
+    let mut chunks = chunks.into_iter();
+    let first = chunks.by_ref().take(1).collect();
+    let (mut results, mut failed) =
+        transcribe_chunks(config.provider(), first, events.clone(), &cancel).await;
+
+    let switch = results
+        .first()
+        .and_then(|r| language::spoken_switch(&r.transcription.text));
+    let provider = match switch {
+        Some(switch) => config.with_language(&switch.code).provider(),
+        None => config.provider(),
+    };
+    let (rest, rest_failed) = transcribe_chunks(provider, chunks.collect(), events, &cancel).await;
+    results.extend(rest);
+    failed.extend(rest_failed);
+    Ok(merge_partial(results, failed, total_chunks, &cancel))
+}
 
-    // Semaphore to limit concurrent requests
-    let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_REQUESTS));
-    let client = Arc::new(client);
-    let api_key = Arc::new(api_key.to_string());
-    let completed = Arc::new(std::sync::atomic::AtomicUsize::new(0));
-    let progress_callback = progress_callback.map(Arc::new);
+/// Send one chunk, reporting how much of it was sent
+///
+/// Returns `None` if the upload stalled; the request is left running in the
+/// background and its result dropped.
+async fn upload_chunk(
+    provider: &Arc<dyn Provider>,
+    data: &Bytes,
+    file_name: &str,
+    codec: AudioCodec,
+    index: usize,
+    send: &(dyn Fn(ChunkEvent) + Sync),
+    cancel: &CancellationToken,
+) -> Result<Option<Transcription>> {
+    let progress = Arc::new(UploadProgress::default());
+    let mut upload = tokio::task::spawn_blocking({
+        let (provider, data, file_name) = (provider.clone(), data.clone(), file_name.to_string());
+        let progress = progress.clone();
+        move || {
+            provider::with_progress(progress, || {
+                provider.transcribe(data, &file_name, codec.mime())
+            })
+        }
+    });
+
+    let mut interval = tokio::time::interval(PROGRESS_INTERVAL);
+    let mut reported = 0;
+    loop {
+        tokio::select! {
+            result = &mut upload => return Ok(Some(result??)),
+            () = cancel.cancelled() => anyhow::bail!(CANCELLED),
+            _ = interval.tick() => {
+                let (sent, total) = progress.get();
+                if sent != reported {
+                    reported = sent;
+                    send(ChunkEvent::Sending { index, sent, total });
+                }
+                if progress.stalled_for().is_some_and(|idle| idle >= STALL_TIMEOUT) {
+                    return Ok(None);
+                }
+            }
+        }
+    }
+}
 
-    // Spawn ALL tasks immediately - they'll wait on semaphore inside
-    let mut handles = Vec::with_capacity(total_chunks);
+/// Transcribe the chunks with rate limiting, returning the chunks that
+/// succeeded and the ones that failed
+async fn transcribe_chunks(
+    provider: Arc<dyn Provider>,
+    chunks: Vec<AudioChunk>,
+    events: Option<UnboundedSender<ChunkEvent>>,
+    cancel: &CancellationToken,
+) -> (Vec<ChunkTranscription>, Vec<FailedChunk>) {
+    // Spawn ALL tasks immediately - they'll wait for a request slot inside
+    let mut handles = Vec::with_capacity(chunks.len());
 
     for chunk in chunks {
-        let semaphore = semaphore.clone();
-        let client = client.clone();
-        let api_key = api_key.clone();
-        let completed = completed.clone();
-        let progress_callback = progress_callback.clone();
+        let provider = provider.clone();
+        let events = events.clone();
+        let cancel = cancel.clone();
+        let (index, start_secs) = (chunk.index, chunk.start_secs);
 
         let handle = tokio::spawn(async move {
-            // Acquire permit INSIDE the task - this is the key fix!
-            // All tasks spawn immediately, then wait for permits
-            let _permit = semaphore.acquire_owned().await?;
-
-            // Transcribe this chunk (no retry - data is consumed by the request)
-            let result = transcribe_chunk_async(&client, &api_key, chunk).await;
-
-            let transcription = match result {
-                Ok(t) => t,
-                Err(e) => return Err(e),
+            let send = |event| {
+                if let Some(events) = &events {
+                    // The receiver may have gone away; progress is best effort
+                    let _ = events.send(event);
+                }
             };
 
-            let done = completed.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
-            if let Some(ref cb) = progress_callback {
-                cb(done, total_chunks);
+            let result = async {
+                if chunk.silent {
+                    return Ok(ChunkTranscription {
+                        index,
+                        transcription: Transcription::default(),
+                        start_secs,
+                        has_leading_overlap: chunk.has_leading_overlap,
+                    });
+                }
+
+                // Acquire permit INSIDE the task - this is the key fix!
+                // All tasks spawn immediately, then wait for permits
+                let _permit = tokio::select! {
+                    permit = request_permit() => permit?,
+                    () = cancel.cancelled() => anyhow::bail!(CANCELLED),
+                };
+
+                let AudioChunk {
+                    data,
+                    codec,
+                    index,
+                    start_secs,
+                    has_leading_overlap,
+                    silent: _,
+                } = chunk;
+                send(ChunkEvent::Started { index });
+                let bytes = data.len();
+                let file_name = format!("audio_chunk_{index}.{}", codec.extension());
+                let mut attempt = 0;
+                let transcription = loop {
+                    let upload =
+                        upload_chunk(&provider, &data, &file_name, codec, index, &send, &cancel);
+                    match upload.await? {
+                        Some(transcription) => break transcription,
+                        None if attempt < MAX_STALL_RETRIES => attempt += 1,
+                        None => anyhow::bail!(
+                            "Upload stalled for {}s, {} times",
+                            STALL_TIMEOUT.as_secs(),
+                            attempt + 1
+                        ),
+                    }
+                };
+                send(ChunkEvent::Uploaded { index, bytes });
+
+                Ok(ChunkTranscription {
+                    index,
+                    transcription,
+                    start_secs,
+                    has_leading_overlap,
+                })
             }
-            Ok(transcription)
+            .await;
+
+            send(match &result {
+                Ok(chunk) => ChunkEvent::Done {
+                    index,
+                    words: chunk.transcription.text.split_whitespace().count(),
+                },
+                Err(e) => ChunkEvent::Failed {
+                    index,
+                    error: e.to_string(),
+                },
+            });
+            result
         });
 
-        handles.push(handle);
+        handles.push((index, start_secs, handle));
     }
 
     // Collect results
-    let mut results = Vec::with_capacity(total_chunks);
-    let mut errors = Vec::new();
-
-    for handle in handles {
-        match handle.await {
-            Ok(Ok(transcription)) => results.push(transcription),
-            Ok(Err(e)) => errors.push(e),
-            Err(e) => errors.push(anyhow::anyhow!("Task panicked: {e}")),
-        }
-    }
-
-    // If any chunks failed, return error with details
-    if !errors.is_empty() {
-        let error_msgs: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
-        anyhow::bail!(
-            "Failed to transcribe {} of {} chunks:\n{}",
-            errors.len(),
-            total_chunks,
-            error_msgs.join("\n")
-        );
+    let mut results = Vec::with_capacity(handles.len());
+    let mut failed = Vec::new();
+
+    for (index, start_secs, handle) in handles {
+        let error = match handle.await {
+            Ok(Ok(transcription)) => {
+                results.push(transcription);
+                continue;
+            }
+            Ok(Err(e)) => e.to_string(),
+            Err(e) => {
+                // The task couldn't report its own failure
+                let error = format!("Task panicked: {e}");
+                if let Some(events) = &events {
+                    let _ = events.send(ChunkEvent::Failed {
+                        index,
+                        error: error.clone(),
+                    });
+                }
+                error
+            }
+        };
+        failed.push(FailedChunk {
+            index,
+            start_secs,
+            error,
+        });
     }
+    (results, failed)
+}
 
+/// Merge the chunks into one transcription, with placeholders for the
+/// chunks that failed
+fn merge_partial(
+    mut results: Vec<ChunkTranscription>,
+    failed: Vec<FailedChunk>,
+    chunks: usize,
+    cancel: &CancellationToken,
+) -> PartialTranscription {
     // Sort by index to ensure correct order
     results.sort_by_key(|r| r.index);
 
     // Merge transcriptions
-    Ok(merge_transcriptions(results))
+    let words = merge_words(&results);
+    let segments = merge_segments(&results);
+    // The language is detected on the first chunk that reports one
+    let language = results
+        .iter()
+        .find_map(|r| r.transcription.language.clone());
+    // Report a fallback backend if any chunk needed one
+    let model = results.iter().find_map(|r| r.transcription.model.clone());
+    let text = if failed.is_empty() {
+        merge_transcriptions(results)
+    } else {
+        merge_transcriptions(with_placeholders(results, &failed))
+    };
+
+    PartialTranscription {
+        transcription: Transcription {
+            text,
+            words,
+            segments,
+            language,
+            model,
+        },
+        failed,
+        chunks,
+        cancelled: cancel.is_cancelled(),
+    }
+}
+
+/// Insert one placeholder for each run of consecutive failed chunks
+fn with_placeholders(
+    results: Vec<ChunkTranscription>,
+    failed: &[FailedChunk],
+) -> Vec<ChunkTranscription> {
+    let mut merged = Vec::with_capacity(results.len() + failed.len());
+    let mut results = results.into_iter().peekable();
+    let mut failed = failed.iter().peekable();
+
+    loop {
+        let next_ok = results.peek().map(|r| r.index);
+        let next_failed = failed.peek().map(|f| f.index);
+        match (next_ok, next_failed) {
+            (Some(ok), Some(bad)) if ok < bad => merged.push(results.next().unwrap()),
+            (Some(_), None) => merged.push(results.next().unwrap()),
+            (_, Some(_)) => {
+                let first = failed.next().unwrap();
+                let mut count = 1;
+                let mut last = first.index;
+                while failed.peek().is_some_and(|f| f.index == last + 1) {
+                    last = failed.next().unwrap().index;
+                    count += 1;
+                }
+                merged.push(ChunkTranscription {
+                    index: first.index,
+                    transcription: Transcription::from_text(placeholder(count)),
+                    start_secs: first.start_secs,
+                    has_leading_overlap: false,
+                });
+                // The next chunk overlaps audio we have no text for
+                if let Some(next) = results.peek_mut() {
+                    next.has_leading_overlap = false;
+                }
+            }
+            (None, None) => break,
+        }
+    }
+    merged
+}
+
+fn placeholder(count: usize) -> String {
+    let segments = if count == 1 { "segment" } else { "segments" };
+    format!("[…transcription failed for {count} {segments}…]")
+}
+
+/// Merge word timings of all chunks into recording time, dropping words
+/// repeated in the overlap with the previous chunk
+fn merge_words(transcriptions: &[ChunkTranscription]) -> Vec<Word> {
+    let mut merged: Vec<Word> = Vec::new();
+    for chunk in transcriptions {
+        let last_end = merged.last().map(|w| w.end);
+        for word in &chunk.transcription.words {
+            let start = word.start + chunk.start_secs;
+            if last_end.is_some_and(|end| start < end) {
+                continue;
+            }
+            merged.push(Word {
+                start,
+                end: word.end + chunk.start_secs,
+                ..word.clone()
+            });
+        }
+    }
+    merged
+}
+
+/// Merge segments of all chunks into recording time, dropping segments
+/// that start inside the previous chunk's last segment
+fn merge_segments(transcriptions: &[ChunkTranscription]) -> Vec<Segment> {
+    let mut merged: Vec<Segment> = Vec::new();
+    for chunk in transcriptions {
+        let last_end = merged.last().map(|s| s.end);
+        for segment in &chunk.transcription.segments {
+            let start = segment.start + chunk.start_secs;
+            if last_end.is_some_and(|end| start < end) {
+                continue;
+            }
+            merged.push(Segment {
+                start,
+                end: segment.end + chunk.start_secs,
+                ..segment.clone()
+            });
+        }
+    }
+    merged
 }
 
 /// Merge transcription results, handling overlaps
@@ -201,13 +542,18 @@ fn merge_transcriptions(transcriptions: Vec<ChunkTranscription>) -> String {
     }
 
     if transcriptions.len() == 1 {
-        return transcriptions.into_iter().next().unwrap().text;
+        return transcriptions
+            .into_iter()
+            .next()
+            .unwrap()
+            .transcription
+            .text;
     }
 
     let mut merged = String::new();
 
     for (i, transcription) in transcriptions.into_iter().enumerate() {
-        let text = transcription.text.trim();
+        let text = transcription.transcription.text.trim();
 
         if i == 0 {
             // First chunk - use as-is
@@ -271,3 +617,181 @@ fn remove_overlap(existing: &str, new_text: &str) -> String {
         new_text.to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(index: usize, start_secs: f32, words: &[(&str, f32)]) -> ChunkTranscription {
+        ChunkTranscription {
+            index,
+            transcription: Transcription {
+                text: String::new(),
+                words: words
+                    .iter()
+                    .map(|&(text, start)| Word {
+                        text: text.to_string(),
+                        start,
+                        end: start + 0.5,
+                        confidence: None,
+                    })
+                    .collect(),
+                segments: Vec::new(),
+                language: None,
+                model: None,
+            },
+            start_secs,
+            has_leading_overlap: index > 0,
+        }
+    }
+
+    fn failed(index: usize) -> FailedChunk {
+        FailedChunk {
+            index,
+            start_secs: index as f32 * 10.0,
+            error: "timeout".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_placeholders_for_failed_runs() {
+        let ok = |index, text: &str| {
+            let mut c = chunk(index, index as f32 * 10.0, &[]);
+            c.transcription.text = text.to_string();
+            c
+        };
+        let results = vec![ok(0, "one two"), ok(3, "two five"), ok(5, "six")];
+        let merged = with_placeholders(results, &[failed(1), failed(2), failed(4)]);
+        assert_eq!(
+            merge_transcriptions(merged),
+            "one two […transcription failed for 2 segments…] two five \
+             […transcription failed for 1 segment…] six"
+        );
+    }
+
+    /// Fails on empty audio, otherwise returns one word per byte
+    struct FakeProvider;
+
+    impl Provider for FakeProvider {
+        fn model(&self) -> &str {
+            "fake"
+        }
+
+        fn max_upload_bytes(&self) -> Option<usize> {
+            None
+        }
+
+        fn transcribe(&self, audio: Bytes, _: &str, _: &str) -> Result<Transcription> {
+            anyhow::ensure!(!audio.is_empty(), "empty audio");
+            Ok(Transcription::from_text(
+                vec!["word"; audio.len()].join(" "),
+            ))
+        }
+    }
+
+    /// Answers one-byte chunks at once and takes a while for longer ones
+    struct SlowProvider;
+
+    impl Provider for SlowProvider {
+        fn model(&self) -> &str {
+            "slow"
+        }
+
+        fn max_upload_bytes(&self) -> Option<usize> {
+            None
+        }
+
+        fn transcribe(&self, audio: Bytes, _: &str, _: &str) -> Result<Transcription> {
+            if audio.len() > 1 {
+                std::thread::sleep(Duration::from_millis(500));
+            }
+            Ok(Transcription::from_text("word"))
+        }
+    }
+
+    fn audio_chunks<const N: usize>(data: [Vec<u8>; N]) -> Vec<AudioChunk> {
+        data.into_iter()
+            .enumerate()
+            .map(|(index, data)| AudioChunk {
+                data: data.into(),
+                codec: AudioCodec::Mp3,
+                index,
+                start_secs: index as f32 * 10.0,
+                has_leading_overlap: index > 0,
+                silent: false,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_chunk_events() {
+        let chunks = audio_chunks([vec![0; 3], vec![]]);
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let partial = parallel_transcribe_partial(Arc::new(FakeProvider), chunks, Some(tx), None)
+            .await
+            .unwrap();
+        assert_eq!(partial.failed.len(), 1);
+
+        let mut events = Vec::new();
+        while let Some(event) = rx.recv().await {
+            events.push(event);
+        }
+        let first: Vec<_> = events.iter().filter(|e| e.index() == 0).collect();
+        assert_eq!(
+            first,
+            [
+                &ChunkEvent::Started { index: 0 },
+                &ChunkEvent::Uploaded { index: 0, bytes: 3 },
+                &ChunkEvent::Done { index: 0, words: 3 },
+            ]
+        );
+        assert!(events.contains(&ChunkEvent::Failed {
+            index: 1,
+            error: "empty audio".to_string(),
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_keeps_finished_chunks() {
+        let chunks = audio_chunks([vec![0], vec![0; 2]]);
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let cancel = CancellationToken::new();
+        let transcription = tokio::spawn(parallel_transcribe_partial(
+            Arc::new(SlowProvider),
+            chunks,
+            Some(tx),
+            Some(cancel.clone()),
+        ));
+        while let Some(event) = rx.recv().await {
+            if event == (ChunkEvent::Done { index: 0, words: 1 }) {
+                cancel.cancel();
+                break;
+            }
+        }
+
+        let partial = transcription.await.unwrap().unwrap();
+        assert!(partial.cancelled);
+        assert_eq!(partial.failed.len(), 1);
+        assert_eq!(partial.failed[0].error, CANCELLED);
+        assert_eq!(
+            partial.transcription.text,
+            "word […transcription failed for 1 segment…]"
+        );
+        assert_eq!(
+            partial.require_complete().unwrap_err().to_string(),
+            "Transcription cancelled"
+        );
+    }
+
+    #[test]
+    fn test_merge_words_offsets_and_overlap() {
+        let chunks = [
+            chunk(0, 0.0, &[("one", 0.0), ("two", 9.0)]),
+            chunk(1, 8.0, &[("two", 1.0), ("three", 2.0)]),
+        ];
+        let words = merge_words(&chunks);
+        let texts: Vec<_> = words.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(texts, ["one", "two", "three"]);
+        assert_eq!(words[2].start, 10.0);
+    }
+}
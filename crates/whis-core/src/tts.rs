@@ -0,0 +1,129 @@
+//! Read transcripts aloud
+//!
+//! Hearing a long dictation is quicker than re-reading it. The OpenAI
+//! speech API sounds natural and uses the OpenAI API key; the local engine
+//! (espeak-ng on Linux, `say` on macOS) works offline and costs nothing.
+
+use anyhow::{Context, Result};
+use serde_json::json;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::config::ApiConfig;
+use crate::playback;
+use crate::provider::{Backend, check_status};
+use crate::settings::{Settings, TtsEngine};
+
+const API_URL: &str = "https://api.openai.com/v1/audio/speech";
+const MODEL: &str = "gpt-4o-mini-tts";
+/// OpenAI voice used unless `tts_voice` is set
+pub const DEFAULT_VOICE: &str = "alloy";
+/// Longest input the speech API accepts, in characters
+const MAX_INPUT: usize = 4096;
+/// Format of the `pcm` responses: 16-bit little-endian mono
+const PCM_SAMPLE_RATE: u32 = 24_000;
+
+/// Speak `text` with the engine of the settings and wait until it's done
+pub fn speak(text: &str, settings: &Settings) -> Result<()> {
+    let text = text.trim();
+    if text.is_empty() {
+        anyhow::bail!("Nothing to read");
+    }
+    match settings.tts_engine {
+        TtsEngine::OpenAI => speak_openai(text, settings),
+        TtsEngine::Local => speak_local(text, settings.tts_voice.as_deref()),
+    }
+}
+
+fn speak_openai(text: &str, settings: &Settings) -> Result<()> {
+    let config = ApiConfig::for_backend(settings, Backend::OpenAI)
+        .context("Reading aloud needs an OpenAI API key (or \"tts_engine\": \"local\")")?;
+    let client = config.http.client()?;
+    let voice = settings.tts_voice.as_deref().unwrap_or(DEFAULT_VOICE);
+    for part in split(text, MAX_INPUT) {
+        let body = json!({
+            "model": MODEL,
+            "input": part,
+            "voice": voice,
+            "response_format": "pcm",
+        });
+        let response = client
+            .post(API_URL)
+            .header("Authorization", format!("Bearer {}", config.api_key))
+            .json(&body)
+            .send()
+            .context("Failed to send request to OpenAI API")?;
+        let pcm = check_status(response, "OpenAI")?
+            .bytes()
+            .context("Failed to read OpenAI speech")?;
+        let samples = pcm
+            .chunks_exact(2)
+            .map(|pair| i16::from_le_bytes([pair[0], pair[1]]) as f32 / 32768.0)
+            .collect();
+        playback::play(samples, PCM_SAMPLE_RATE, 1)?;
+    }
+    Ok(())
+}
+
+fn speak_local(text: &str, voice: Option<&str>) -> Result<()> {
+    // Text goes through stdin, so it can't be taken for options
+    let (program, mut args) = if cfg!(target_os = "macos") {
+        ("say", vec!["-f", "-"])
+    } else {
+        ("espeak-ng", vec!["--stdin"])
+    };
+    if let Some(voice) = voice {
+        args.extend(["-v", voice]);
+    }
+    let mut child = Command::new(program)
+        .args(&args)
+        .stdin(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run {program}. Is it installed?"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        anyhow::bail!("{program} exited with {status}");
+    }
+    Ok(())
+}
+
+/// Split `text` into parts of at most `max` characters, after the last
+/// sentence end (or space) that fits, so speech pauses where it would anyway
+fn split(text: &str, max: usize) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut rest = text.trim();
+    while rest.chars().count() > max {
+        let limit = rest.char_indices().nth(max).map_or(rest.len(), |(i, _)| i);
+        let head = &rest[..limit];
+        let cut = head
+            .rfind(['.', '!', '?', '\n'])
+            .map(|i| i + 1)
+            .or_else(|| head.rfind(' '))
+            .filter(|&i| i > 0)
+            .unwrap_or(limit);
+        parts.push(rest[..cut].trim());
+        rest = rest[cut..].trim_start();
+    }
+    if !rest.is_empty() {
+        parts.push(rest);
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split() {
+        assert_eq!(split("Short.", 20), ["Short."]);
+        assert_eq!(
+            split("One two. Three four five. Six", 20),
+            ["One two.", "Three four five. Six"]
+        );
+        assert_eq!(split("abcdefghij klm", 5), ["abcde", "fghij", "klm"]);
+    }
+}
@@ -0,0 +1,245 @@
+//! Type text into the focused window
+//!
+//! Simulates keyboard input with the platform's tools: wtype on Wayland,
+//! xdotool on X11 and System Events (via osascript) on macOS.
+//!
+//! What was typed can be taken back with [`erase`], given the number of
+//! characters from [`typed_len`].
+//!
+//! Some applications drop characters that arrive too quickly, which a delay
+//! between keystrokes avoids. wtype and System Events type any character
+//! regardless of the keyboard layout; xdotool can produce the wrong ones on
+//! some X11 layouts, where entering them with Ctrl+Shift+U helps.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// How keystrokes are sent
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TypingConfig {
+    /// Pause between keystrokes in milliseconds (0 uses the default of the
+    /// typing tool)
+    pub delay_ms: u32,
+    /// How characters outside ASCII are entered
+    pub unicode_input: UnicodeInput,
+}
+
+/// How characters outside ASCII are entered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum UnicodeInput {
+    /// Typed like any other character
+    #[default]
+    Direct,
+    /// Entered as a code point after Ctrl+Shift+U, which GTK and IBus
+    /// understand (X11 only; wtype handles any layout itself)
+    CtrlShiftU,
+}
+
+/// Type `text` into the currently focused window
+pub fn type_text(text: &str, config: &TypingConfig) -> Result<()> {
+    if text.is_empty() {
+        return Ok(());
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let delay = config.delay_ms.to_string();
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            let mut args = Vec::new();
+            if config.delay_ms > 0 {
+                args.extend(["-d", delay.as_str()]);
+            }
+            args.extend(["--", text]);
+            return run("wtype", &args);
+        }
+
+        for segment in segments(text, config.unicode_input) {
+            match segment {
+                Segment::Text(text) => {
+                    let mut args = vec!["type", "--clearmodifiers"];
+                    if config.delay_ms > 0 {
+                        args.extend(["--delay", delay.as_str()]);
+                    }
+                    args.extend(["--", text]);
+                    run("xdotool", &args)?;
+                }
+                Segment::CodePoint(c) => {
+                    let hex = format!("{:x}", u32::from(c));
+                    run("xdotool", &["key", "--clearmodifiers", "ctrl+shift+u"])?;
+                    run("xdotool", &["type", "--", &hex])?;
+                    run("xdotool", &["key", "space"])?;
+                }
+            }
+        }
+        Ok(())
+    }
+    #[cfg(target_os = "macos")]
+    {
+        if config.delay_ms == 0 {
+            return run(
+                "osascript",
+                &[
+                    "-e",
+                    "on run argv",
+                    "-e",
+                    "tell application \"System Events\" to keystroke (item 1 of argv)",
+                    "-e",
+                    "end run",
+                    text,
+                ],
+            );
+        }
+        let delay = format!("delay {}", config.delay_ms as f32 / 1000.0);
+        run(
+            "osascript",
+            &[
+                "-e",
+                "on run argv",
+                "-e",
+                "tell application \"System Events\"",
+                "-e",
+                "repeat with c in characters of (item 1 of argv)",
+                "-e",
+                "keystroke c",
+                "-e",
+                &delay,
+                "-e",
+                "end repeat",
+                "-e",
+                "end tell",
+                "-e",
+                "end run",
+                text,
+            ],
+        )
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        let _ = config;
+        anyhow::bail!("Typing output is not supported on this platform")
+    }
+}
+
+/// Part of a text to type
+#[derive(Debug, PartialEq)]
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+enum Segment<'a> {
+    Text(&'a str),
+    /// A character entered by its code point
+    CodePoint(char),
+}
+
+/// Split `text` into runs typed as they are and characters entered by
+/// code point
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn segments(text: &str, unicode_input: UnicodeInput) -> Vec<Segment<'_>> {
+    if unicode_input == UnicodeInput::Direct {
+        return vec![Segment::Text(text)];
+    }
+    let mut segments = Vec::new();
+    let mut start = 0;
+    for (i, c) in text.char_indices().filter(|(_, c)| !c.is_ascii()) {
+        if start < i {
+            segments.push(Segment::Text(&text[start..i]));
+        }
+        segments.push(Segment::CodePoint(c));
+        start = i + c.len_utf8();
+    }
+    if start < text.len() {
+        segments.push(Segment::Text(&text[start..]));
+    }
+    segments
+}
+
+/// Number of backspaces that remove `text` once typed. Counts characters,
+/// so text with combining marks may need fewer in some applications.
+pub fn typed_len(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// Press backspace `count` times in the currently focused window
+pub fn erase(count: usize) -> Result<()> {
+    if count == 0 {
+        return Ok(());
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            let args: Vec<&str> = ["-k", "BackSpace"].repeat(count);
+            run("wtype", &args)
+        } else {
+            let count = count.to_string();
+            run(
+                "xdotool",
+                &["key", "--clearmodifiers", "--repeat", &count, "BackSpace"],
+            )
+        }
+    }
+    #[cfg(target_os = "macos")]
+    {
+        // Key code 51 is Delete (backspace)
+        let repeat = format!("repeat {count} times");
+        run(
+            "osascript",
+            &[
+                "-e",
+                "tell application \"System Events\"",
+                "-e",
+                &repeat,
+                "-e",
+                "key code 51",
+                "-e",
+                "end repeat",
+                "-e",
+                "end tell",
+            ],
+        )
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        anyhow::bail!("Typing output is not supported on this platform")
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn run(program: &str, args: &[&str]) -> Result<()> {
+    use anyhow::Context;
+
+    let status = std::process::Command::new(program)
+        .args(args)
+        .status()
+        .with_context(|| format!("Failed to run {program}. Is it installed?"))?;
+    if !status.success() {
+        anyhow::bail!("{program} exited with {status}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segments() {
+        assert_eq!(
+            segments("Grüße!", UnicodeInput::Direct),
+            [Segment::Text("Grüße!")]
+        );
+        assert_eq!(
+            segments("Grüße!", UnicodeInput::CtrlShiftU),
+            [
+                Segment::Text("Gr"),
+                Segment::CodePoint('ü'),
+                Segment::CodePoint('ß'),
+                Segment::Text("e!"),
+            ]
+        );
+        assert_eq!(
+            segments("é", UnicodeInput::CtrlShiftU),
+            [Segment::CodePoint('é')]
+        );
+    }
+}
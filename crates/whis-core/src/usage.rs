@@ -0,0 +1,181 @@
+//! Audio usage tracking and cost estimation
+//!
+//! Records how many seconds of audio were sent to each backend/model, grouped
+//! by month, in ~/.local/share/whis/usage.json. Costs are estimates based on
+//! list prices and may drift from what the provider actually bills.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::settings::Settings;
+
+/// Totals for one backend/model in one month
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct UsageTotals {
+    /// Seconds of audio transcribed
+    pub seconds: f64,
+    /// Number of transcriptions
+    pub requests: u64,
+}
+
+/// Usage per month ("2025-01") and backend/model ("openai/whisper-1")
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct UsageLog {
+    months: BTreeMap<String, BTreeMap<String, UsageTotals>>,
+}
+
+impl UsageLog {
+    /// Totals per backend/model for a month
+    pub fn month(&self, month: &str) -> BTreeMap<String, UsageTotals> {
+        self.months.get(month).cloned().unwrap_or_default()
+    }
+
+    /// Estimated cost of a month in USD
+    pub fn month_cost(&self, month: &str) -> f64 {
+        self.month(month)
+            .iter()
+            .map(|(model, totals)| estimate_cost(model, totals.seconds))
+            .sum()
+    }
+
+    /// Months with recorded usage, oldest first
+    pub fn months(&self) -> impl Iterator<Item = &str> {
+        self.months.keys().map(String::as_str)
+    }
+}
+
+/// Get the usage file path (~/.local/share/whis/usage.json)
+pub fn path() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("whis")
+        .join("usage.json")
+}
+
+/// The current month as used for grouping ("2025-01")
+pub fn current_month() -> String {
+    chrono::Local::now().format("%Y-%m").to_string()
+}
+
+/// Load the usage log (empty if none was recorded yet)
+pub fn load() -> Result<UsageLog> {
+    let path = path();
+    if !path.exists() {
+        return Ok(UsageLog::default());
+    }
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// Add a transcription to the current month and return the updated log
+pub fn record(model: &str, seconds: f32) -> Result<UsageLog> {
+    let mut log = load()?;
+    let totals = log
+        .months
+        .entry(current_month())
+        .or_default()
+        .entry(model.to_string())
+        .or_default();
+    totals.seconds += seconds as f64;
+    totals.requests += 1;
+
+    let path = path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, serde_json::to_string_pretty(&log)?)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+
+    Ok(log)
+}
+
+/// Record a transcription by a backend/model and return a budget warning
+/// if the month's spending is getting close to the configured limit
+pub fn track(settings: &Settings, model: &str, seconds: f32) -> Result<Option<String>> {
+    let log = record(model, seconds)?;
+    let spent = log.month_cost(&current_month());
+    Ok(budget_warning(settings.monthly_budget_usd, spent))
+}
+
+/// List price per minute of audio in USD, if known
+fn price_per_minute(model: &str) -> Option<f64> {
+    match model {
+        "openai/whisper-1" | "openai/gpt-4o-transcribe" => Some(0.006),
+        "openai/gpt-4o-mini-transcribe" => Some(0.003),
+        "assemblyai/universal" => Some(0.0025),
+        "azure/fast" => Some(0.006),
+        _ => None,
+    }
+}
+
+/// Estimated cost in USD (0 for unknown or local models)
+pub fn estimate_cost(model: &str, seconds: f64) -> f64 {
+    price_per_minute(model).map_or(0.0, |price| price * seconds / 60.0)
+}
+
+/// Short summary of a transcription, e.g. "~3.2 min, ≈$0.019"
+pub fn describe(model: &str, seconds: f32) -> String {
+    let minutes = seconds / 60.0;
+    match price_per_minute(model) {
+        Some(_) => format!(
+            "~{minutes:.1} min, ≈${:.3}",
+            estimate_cost(model, seconds as f64)
+        ),
+        None => format!("~{minutes:.1} min"),
+    }
+}
+
+/// Warning once spending reaches 80% and 100% of the monthly budget
+pub fn budget_warning(budget: Option<f64>, spent: f64) -> Option<String> {
+    let budget = budget.filter(|b| *b > 0.0)?;
+    if spent >= budget {
+        Some(format!(
+            "Monthly budget of ${budget:.2} exceeded (≈${spent:.2} this month)"
+        ))
+    } else if spent >= budget * 0.8 {
+        Some(format!(
+            "≈${spent:.2} of your ${budget:.2} monthly budget used"
+        ))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_cost() {
+        let cost = estimate_cost("openai/whisper-1", 600.0);
+        assert!((cost - 0.06).abs() < 1e-9);
+        assert_eq!(estimate_cost("local/base.en", 600.0), 0.0);
+    }
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(describe("openai/whisper-1", 192.0), "~3.2 min, ≈$0.019");
+        assert_eq!(describe("local/base.en", 30.0), "~0.5 min");
+    }
+
+    #[test]
+    fn test_budget_warning() {
+        assert_eq!(budget_warning(None, 100.0), None);
+        assert_eq!(budget_warning(Some(10.0), 5.0), None);
+        assert!(
+            budget_warning(Some(10.0), 8.5)
+                .unwrap()
+                .contains("of your $10.00")
+        );
+        assert!(
+            budget_warning(Some(10.0), 12.0)
+                .unwrap()
+                .contains("exceeded")
+        );
+    }
+}
@@ -0,0 +1,140 @@
+//! Energy-based voice activity detection
+//!
+//! Audio is measured in short frames; a frame counts as speech when its RMS
+//! level exceeds a fixed threshold. Crude compared to a trained model, but
+//! reliable enough to tell talking from room noise on a typical microphone.
+
+/// Frame length used for level measurement
+const FRAME_MS: u32 = 30;
+/// RMS level above which a frame counts as speech (about -40 dBFS)
+const SPEECH_RMS: f32 = 0.01;
+
+/// Tracks how long the input has been silent since the last speech
+///
+/// Samples are fed incrementally as they are captured. Silence before the
+/// first speech is ignored so a slow start doesn't end the recording.
+pub struct SilenceDetector {
+    frame_len: usize,
+    samples_per_sec: f32,
+    frame: Vec<f32>,
+    consumed: usize,
+    heard_speech: bool,
+    silent_samples: usize,
+}
+
+impl SilenceDetector {
+    pub fn new(sample_rate: u32, channels: u16) -> Self {
+        let frame_len = frame_len(sample_rate, channels);
+        Self {
+            frame_len,
+            samples_per_sec: sample_rate as f32 * channels as f32,
+            frame: Vec::with_capacity(frame_len),
+            consumed: 0,
+            heard_speech: false,
+            silent_samples: 0,
+        }
+    }
+
+    /// Feed newly captured (interleaved) samples
+    pub fn feed(&mut self, samples: &[f32]) {
+        self.consumed += samples.len();
+        for &sample in samples {
+            self.frame.push(sample);
+            if self.frame.len() == self.frame_len {
+                self.process_frame();
+            }
+        }
+    }
+
+    /// Total number of samples fed so far
+    pub fn consumed(&self) -> usize {
+        self.consumed
+    }
+
+    /// Seconds of silence since the last speech, or `None` before any speech
+    pub fn silence_secs(&self) -> Option<f32> {
+        self.heard_speech
+            .then(|| self.silent_samples as f32 / self.samples_per_sec)
+    }
+
+    fn process_frame(&mut self) {
+        if is_speech(&self.frame) {
+            self.heard_speech = true;
+            self.silent_samples = 0;
+        } else if self.heard_speech {
+            self.silent_samples += self.frame.len();
+        }
+        self.frame.clear();
+    }
+}
+
+/// Number of interleaved samples in one measurement frame
+pub fn frame_len(sample_rate: u32, channels: u16) -> usize {
+    ((sample_rate * FRAME_MS / 1000) as usize * channels as usize).max(1)
+}
+
+/// Whether a frame is loud enough to count as speech
+pub fn is_speech(frame: &[f32]) -> bool {
+    rms(frame) > SPEECH_RMS
+}
+
+fn rms(frame: &[f32]) -> f32 {
+    let sum: f32 = frame.iter().map(|s| s * s).sum();
+    (sum / frame.len() as f32).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16000;
+
+    fn silence(secs: f32) -> Vec<f32> {
+        vec![0.0; (RATE as f32 * secs) as usize]
+    }
+
+    fn speech(secs: f32) -> Vec<f32> {
+        (0..(RATE as f32 * secs) as usize)
+            .map(|i| if i % 2 == 0 { 0.2 } else { -0.2 })
+            .collect()
+    }
+
+    #[test]
+    fn test_leading_silence_ignored() {
+        let mut detector = SilenceDetector::new(RATE, 1);
+        detector.feed(&silence(3.0));
+        assert_eq!(detector.silence_secs(), None);
+    }
+
+    #[test]
+    fn test_silence_after_speech() {
+        let mut detector = SilenceDetector::new(RATE, 1);
+        detector.feed(&speech(0.5));
+        detector.feed(&silence(1.5));
+        let secs = detector.silence_secs().unwrap();
+        assert!((secs - 1.5).abs() < 0.05, "got {secs}");
+    }
+
+    #[test]
+    fn test_speech_resets_silence() {
+        let mut detector = SilenceDetector::new(RATE, 1);
+        detector.feed(&speech(0.5));
+        detector.feed(&silence(2.0));
+        detector.feed(&speech(0.5));
+        assert_eq!(detector.silence_secs(), Some(0.0));
+    }
+
+    #[test]
+    fn test_incremental_feeding() {
+        let mut detector = SilenceDetector::new(RATE, 2);
+        let mut samples = speech(0.2);
+        samples.extend(silence(2.0));
+        for chunk in samples.chunks(123) {
+            detector.feed(chunk);
+        }
+        assert_eq!(detector.consumed(), samples.len());
+        // Two channels: 2.0s of mono-rate samples is 1.0s of stereo audio
+        let secs = detector.silence_secs().unwrap();
+        assert!((secs - 1.0).abs() < 0.05, "got {secs}");
+    }
+}
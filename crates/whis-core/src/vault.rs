@@ -0,0 +1,174 @@
+//! Encryption of transcripts at rest
+//!
+//! With `encrypt_history`, the text, title and window title of history
+//! entries are sealed with ChaCha20-Poly1305 before they are written. Tags,
+//! timestamps and the rest of the metadata (app, host name, profile, model)
+//! stay in plain text, so filters work without the key. The key is generated on first use
+//! and kept in the system keyring (Secret Service, macOS Keychain, Windows
+//! Credential Manager), and only fetched when text is sealed or read, so
+//! nothing asks for it until a transcript is saved or the history is shown.
+//!
+//! Sealed text is stored as `sealed:v1:` followed by the base64 of the nonce
+//! and the ciphertext. Needs the `encryption` feature; without it, sealing
+//! and opening fail.
+
+#[cfg(feature = "encryption")]
+use anyhow::Context;
+use anyhow::Result;
+#[cfg(feature = "encryption")]
+use base64::Engine;
+#[cfg(feature = "encryption")]
+use base64::engine::general_purpose::STANDARD as BASE64;
+#[cfg(feature = "encryption")]
+use ring::aead::{Aad, CHACHA20_POLY1305, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+#[cfg(feature = "encryption")]
+use ring::rand::{SecureRandom, SystemRandom};
+#[cfg(feature = "encryption")]
+use std::sync::OnceLock;
+
+const PREFIX: &str = "sealed:v1:";
+#[cfg(feature = "encryption")]
+const KEYRING_SERVICE: &str = "whis";
+#[cfg(feature = "encryption")]
+const KEYRING_USER: &str = "history-key";
+#[cfg(feature = "encryption")]
+const KEY_LEN: usize = 32;
+
+/// Key from the keyring, fetched once per process
+#[cfg(feature = "encryption")]
+static KEY: OnceLock<LessSafeKey> = OnceLock::new();
+
+/// Whether the text was sealed by `seal`
+pub fn is_sealed(text: &str) -> bool {
+    text.starts_with(PREFIX)
+}
+
+/// Encrypt text with the key from the keyring
+#[cfg(feature = "encryption")]
+pub fn seal(text: &str) -> Result<String> {
+    seal_with(key()?, text)
+}
+
+#[cfg(not(feature = "encryption"))]
+pub fn seal(_text: &str) -> Result<String> {
+    anyhow::bail!("whis was built without the \"encryption\" feature")
+}
+
+/// Decrypt text sealed by `seal`
+#[cfg(feature = "encryption")]
+pub fn open(sealed: &str) -> Result<String> {
+    open_with(key()?, sealed)
+}
+
+#[cfg(not(feature = "encryption"))]
+pub fn open(_sealed: &str) -> Result<String> {
+    anyhow::bail!("whis was built without the \"encryption\" feature")
+}
+
+#[cfg(feature = "encryption")]
+fn seal_with(key: &LessSafeKey, text: &str) -> Result<String> {
+    let mut nonce = [0u8; NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut nonce)
+        .map_err(|_| anyhow::anyhow!("Failed to generate a nonce"))?;
+    let mut data = text.as_bytes().to_vec();
+    key.seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut data)
+        .map_err(|_| anyhow::anyhow!("Failed to encrypt"))?;
+
+    let mut sealed = nonce.to_vec();
+    sealed.extend_from_slice(&data);
+    Ok(format!("{PREFIX}{}", BASE64.encode(sealed)))
+}
+
+#[cfg(feature = "encryption")]
+fn open_with(key: &LessSafeKey, sealed: &str) -> Result<String> {
+    let encoded = sealed
+        .strip_prefix(PREFIX)
+        .context("Text is not encrypted")?;
+    let data = BASE64
+        .decode(encoded)
+        .context("Encrypted text is corrupt")?;
+    anyhow::ensure!(data.len() >= NONCE_LEN, "Encrypted text is corrupt");
+
+    let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce)
+        .map_err(|_| anyhow::anyhow!("Encrypted text is corrupt"))?;
+    let mut ciphertext = ciphertext.to_vec();
+    let plain = key
+        .open_in_place(nonce, Aad::empty(), &mut ciphertext)
+        .map_err(|_| anyhow::anyhow!("Failed to decrypt: wrong key or corrupt text"))?;
+    String::from_utf8(plain.to_vec()).context("Decrypted text is not UTF-8")
+}
+
+#[cfg(feature = "encryption")]
+fn key() -> Result<&'static LessSafeKey> {
+    if let Some(key) = KEY.get() {
+        return Ok(key);
+    }
+    let key = cipher_key(&keyring_key()?)?;
+    Ok(KEY.get_or_init(|| key))
+}
+
+#[cfg(feature = "encryption")]
+fn cipher_key(bytes: &[u8]) -> Result<LessSafeKey> {
+    let key = UnboundKey::new(&CHACHA20_POLY1305, bytes)
+        .map_err(|_| anyhow::anyhow!("Invalid history key"))?;
+    Ok(LessSafeKey::new(key))
+}
+
+/// The key stored in the keyring, created there on first use
+#[cfg(feature = "encryption")]
+fn keyring_key() -> Result<Vec<u8>> {
+    let entry = keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)
+        .context("Failed to open the system keyring")?;
+    match entry.get_password() {
+        Ok(encoded) => {
+            let key = BASE64
+                .decode(encoded.trim())
+                .context("History key in the keyring is corrupt")?;
+            anyhow::ensure!(
+                key.len() == KEY_LEN,
+                "History key in the keyring is corrupt"
+            );
+            Ok(key)
+        }
+        Err(keyring::Error::NoEntry) => {
+            let mut key = vec![0u8; KEY_LEN];
+            SystemRandom::new()
+                .fill(&mut key)
+                .map_err(|_| anyhow::anyhow!("Failed to generate a history key"))?;
+            entry
+                .set_password(&BASE64.encode(&key))
+                .context("Failed to store the history key in the system keyring")?;
+            Ok(key)
+        }
+        Err(e) => Err(e).context("Failed to read the history key from the system keyring"),
+    }
+}
+
+#[cfg(all(test, feature = "encryption"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_and_open() {
+        let key = cipher_key(&[7; KEY_LEN]).unwrap();
+        let sealed = seal_with(&key, "Meeting notes: ship on Friday").unwrap();
+        assert!(is_sealed(&sealed));
+        assert!(!sealed.contains("Friday"));
+        // A fresh nonce each time
+        assert_ne!(
+            sealed,
+            seal_with(&key, "Meeting notes: ship on Friday").unwrap()
+        );
+        assert_eq!(
+            open_with(&key, &sealed).unwrap(),
+            "Meeting notes: ship on Friday"
+        );
+
+        let other = cipher_key(&[8; KEY_LEN]).unwrap();
+        assert!(open_with(&other, &sealed).is_err());
+        assert!(open_with(&key, "sealed:v1:AAAA").is_err());
+        assert!(!is_sealed("plain text"));
+    }
+}
@@ -0,0 +1,199 @@
+//! Voice commands: transcripts that run a shell command
+//!
+//! In output mode "command", the transcript is matched against the spoken
+//! patterns of `voice_commands` instead of being typed or copied, and the
+//! first match runs with the words its wildcards caught as arguments. A
+//! transcript that matches nothing, or a command that fails, is reported
+//! and the transcript is delivered to the sinks and the history as usual.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+/// A spoken pattern mapped to a shell command
+///
+/// Patterns use `*` as a wildcard; the text each wildcard matched is
+/// substituted into the command as `{1}`, `{2}`, ... (shell-quoted).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoiceCommand {
+    /// Spoken pattern, e.g. "open *"
+    pub pattern: String,
+    /// Shell command to run, e.g. "xdg-open {1}"
+    pub command: String,
+    /// Ask for confirmation before running
+    #[serde(default)]
+    pub confirm: bool,
+}
+
+/// A voice command matched against a transcript, with placeholders filled in
+pub struct MatchedCommand<'a> {
+    pub voice_command: &'a VoiceCommand,
+    /// The shell command line to execute
+    pub command_line: String,
+}
+
+/// Find the first voice command whose pattern matches the transcript
+pub fn find_match<'a>(text: &str, commands: &'a [VoiceCommand]) -> Option<MatchedCommand<'a>> {
+    // Whisper adds sentence punctuation ("Open Firefox.") that isn't part of the command
+    let text = text.trim().trim_end_matches(['.', '!', '?', ',']);
+
+    commands.iter().find_map(|voice_command| {
+        let captures = match_pattern(voice_command.pattern.trim(), text)?;
+        Some(MatchedCommand {
+            voice_command,
+            command_line: fill_placeholders(&voice_command.command, &captures),
+        })
+    })
+}
+
+/// What became of a transcript in command mode
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    /// The command line that ran
+    Ran(String),
+    /// The matching command needs confirmation, which wasn't given
+    Declined,
+    /// No pattern matches the transcript
+    NoMatch,
+}
+
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Outcome::Ran(command_line) => write!(f, "Ran: {command_line}"),
+            Outcome::Declined => write!(f, "Cancelled"),
+            Outcome::NoMatch => write!(f, "No voice command matches the transcript"),
+        }
+    }
+}
+
+/// Run the voice command matching a transcript
+///
+/// Commands marked `confirm` only run if `confirm` approves their command
+/// line. Fails only if the command can't run or exits with an error.
+pub fn run_matching(
+    text: &str,
+    commands: &[VoiceCommand],
+    confirm: impl FnOnce(&str) -> bool,
+) -> Result<Outcome> {
+    let Some(matched) = find_match(text, commands) else {
+        return Ok(Outcome::NoMatch);
+    };
+    if matched.voice_command.confirm && !confirm(&matched.command_line) {
+        return Ok(Outcome::Declined);
+    }
+    run(&matched.command_line)?;
+    Ok(Outcome::Ran(matched.command_line))
+}
+
+/// Run a command line through the shell and wait for it to finish
+pub fn run(command_line: &str) -> Result<()> {
+    #[cfg(unix)]
+    let status = Command::new("sh").arg("-c").arg(command_line).status();
+    #[cfg(windows)]
+    let status = Command::new("cmd").arg("/C").arg(command_line).status();
+
+    let status = status.with_context(|| format!("Failed to run command: {command_line}"))?;
+    if !status.success() {
+        anyhow::bail!("Command exited with {status}: {command_line}");
+    }
+    Ok(())
+}
+
+/// Match a glob-style pattern (ASCII case-insensitive) and return wildcard captures
+pub(crate) fn match_pattern<'t>(pattern: &str, text: &'t str) -> Option<Vec<&'t str>> {
+    let mut captures = Vec::new();
+    match_from(pattern.as_bytes(), text, 0, &mut captures).then_some(captures)
+}
+
+fn match_from<'t>(pattern: &[u8], text: &'t str, pos: usize, captures: &mut Vec<&'t str>) -> bool {
+    let rest = &text.as_bytes()[pos..];
+    match pattern.first() {
+        None => rest.is_empty(),
+        Some(b'*') => {
+            // Try the shortest non-empty capture first so later wildcards get text too
+            let boundaries = (pos + 1..=text.len()).filter(|&end| text.is_char_boundary(end));
+            for end in boundaries {
+                captures.push(text[pos..end].trim());
+                if match_from(&pattern[1..], text, end, captures) {
+                    return true;
+                }
+                captures.pop();
+            }
+            false
+        }
+        Some(&p) => {
+            rest.first().is_some_and(|&t| t.eq_ignore_ascii_case(&p))
+                && match_from(&pattern[1..], text, pos + 1, captures)
+        }
+    }
+}
+
+/// Substitute `{n}` placeholders with shell-quoted captures
+fn fill_placeholders(command: &str, captures: &[&str]) -> String {
+    let mut result = command.to_string();
+    for (i, capture) in captures.iter().enumerate().rev() {
+        result = result.replace(&format!("{{{}}}", i + 1), &shell_quote(capture));
+    }
+    result
+}
+
+/// Quote a string so the shell treats it as a single literal argument
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(pattern: &str, command: &str) -> VoiceCommand {
+        VoiceCommand {
+            pattern: pattern.to_string(),
+            command: command.to_string(),
+            confirm: false,
+        }
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        let commands = vec![command("open *", "xdg-open {1}")];
+        let matched = find_match("Open Firefox.", &commands).unwrap();
+        assert_eq!(matched.command_line, "xdg-open 'Firefox'");
+    }
+
+    #[test]
+    fn test_multiple_wildcards() {
+        let commands = vec![command("move * to *", "mv {1} {2}")];
+        let matched = find_match("move notes.txt to archive", &commands).unwrap();
+        assert_eq!(matched.command_line, "mv 'notes.txt' 'archive'");
+    }
+
+    #[test]
+    fn test_no_match() {
+        let commands = vec![command("open *", "xdg-open {1}")];
+        assert!(find_match("close the window", &commands).is_none());
+    }
+
+    #[test]
+    fn test_run_matching_without_running() {
+        let commands = vec![VoiceCommand {
+            confirm: true,
+            ..command("delete *", "rm {1}")
+        }];
+        let outcome = run_matching("hello there", &commands, |_| true).unwrap();
+        assert_eq!(outcome, Outcome::NoMatch);
+        let outcome = run_matching("delete notes", &commands, |line| {
+            assert_eq!(line, "rm 'notes'");
+            false
+        });
+        assert_eq!(outcome.unwrap(), Outcome::Declined);
+    }
+
+    #[test]
+    fn test_captures_are_quoted() {
+        let commands = vec![command("say *", "echo {1}")];
+        let matched = find_match("say it's $HOME", &commands).unwrap();
+        assert_eq!(matched.command_line, r"echo 'it'\''s $HOME'");
+    }
+}
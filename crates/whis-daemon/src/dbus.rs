@@ -0,0 +1,92 @@
+//! Session D-Bus interface
+//!
+//! Exposes the service as `org.whis.Daemon` at `/org/whis/Daemon`, alongside
+//! the IPC socket, so desktop environments and scripts can control it with
+//! `busctl`/`gdbus` and react to `StateChanged` signals.
+
+use anyhow::Result;
+use std::sync::Arc;
+use zbus::{Connection, connection, fdo, interface, object_server::SignalEmitter};
+
+use crate::ipc::IpcResponse;
+use crate::service::Service;
+
+const BUS_NAME: &str = "org.whis.Daemon";
+const OBJECT_PATH: &str = "/org/whis/Daemon";
+
+struct Daemon {
+    service: Arc<Service>,
+}
+
+#[interface(name = "org.whis.Daemon")]
+impl Daemon {
+    /// Start or stop recording. Returns the new state.
+    async fn toggle(&self) -> fdo::Result<String> {
+        match self.service.handle_toggle().await {
+            IpcResponse::Error(e) => Err(fdo::Error::Failed(e)),
+            _ => Ok(self.service.state().as_str().to_string()),
+        }
+    }
+
+    /// Like `Toggle`, but the recording is summarized instead of transcribed verbatim
+    async fn toggle_summary(&self) -> fdo::Result<String> {
+        match self.service.handle_summary_toggle().await {
+            IpcResponse::Error(e) => Err(fdo::Error::Failed(e)),
+            _ => Ok(self.service.state().as_str().to_string()),
+        }
+    }
+
+    /// Like `Toggle`, but the recording is appended to the last transcript
+    async fn toggle_append(&self) -> fdo::Result<String> {
+        match self.service.handle_append_toggle().await {
+            IpcResponse::Error(e) => Err(fdo::Error::Failed(e)),
+            _ => Ok(self.service.state().as_str().to_string()),
+        }
+    }
+
+    /// Discard the active recording, or stop the transcription in progress
+    async fn cancel(&self) -> fdo::Result<()> {
+        self.service
+            .cancel_recording()
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Remove the transcript typed last. Returns the number of characters removed.
+    async fn undo(&self) -> fdo::Result<u32> {
+        self.service
+            .undo_typing()
+            .await
+            .map(|len| len as u32)
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Current state: "idle", "recording" or "transcribing"
+    async fn status(&self) -> String {
+        self.service.state().as_str().to_string()
+    }
+
+    #[zbus(signal)]
+    async fn state_changed(emitter: &SignalEmitter<'_>, state: &str) -> zbus::Result<()>;
+}
+
+/// Register the interface on the session bus and emit `StateChanged` on
+/// every state transition. The returned connection must be kept alive.
+pub async fn serve(service: Arc<Service>) -> Result<Connection> {
+    let mut state_rx = service.subscribe();
+
+    let conn = connection::Builder::session()?
+        .name(BUS_NAME)?
+        .serve_at(OBJECT_PATH, Daemon { service })?
+        .build()
+        .await?;
+
+    let emitter = SignalEmitter::new(&conn, OBJECT_PATH)?.into_owned();
+    tokio::spawn(async move {
+        while state_rx.changed().await.is_ok() {
+            let state = *state_rx.borrow_and_update();
+            let _ = Daemon::state_changed(&emitter, state.as_str()).await;
+        }
+    });
+
+    Ok(conn)
+}
@@ -0,0 +1,141 @@
+//! Triggers read directly from input devices
+//!
+//! The rdev keyboard grab only reports regular keys, so mouse side buttons
+//! and media keys are read from the evdev devices that can send them. These
+//! events aren't grabbed and still reach other applications.
+//!
+//! A dedicated trigger device (foot pedal, dictation switch) is picked by
+//! USB vendor/product id and grabbed, so its presses don't type anything.
+
+use anyhow::{Context, Result};
+use evdev_rs::enums::{EV_KEY, EventCode};
+use evdev_rs::{Device, GrabMode, ReadFlag};
+use std::fs::File;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Paths of the input devices that can send `key`
+pub fn devices_with(key: &EV_KEY) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir("/dev/input") else {
+        return Vec::new();
+    };
+    let code = EventCode::EV_KEY(key.clone());
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| is_event_device(path))
+        .filter(|path| open(path).is_ok_and(|device| device.has_event_code(&code)))
+        .collect()
+}
+
+/// Paths of the input devices with a USB id like "05f3:00ff" (vendor:product, hex)
+pub fn devices_with_id(id: &str) -> Result<Vec<PathBuf>> {
+    let (vendor, product) = parse_device_id(id)?;
+    let Ok(entries) = std::fs::read_dir("/dev/input") else {
+        return Ok(Vec::new());
+    };
+    Ok(entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| is_event_device(path))
+        .filter(|path| {
+            open(path).is_ok_and(|d| d.vendor_id() == vendor && d.product_id() == product)
+        })
+        .collect())
+}
+
+/// Call `on_press` whenever one of the devices sends `key`
+pub fn listen<F>(devices: Vec<PathBuf>, key: EV_KEY, on_press: F) -> Result<()>
+where
+    F: Fn() + Send + Sync + 'static,
+{
+    if devices.is_empty() {
+        anyhow::bail!("No input device can send {key:?}. Is your user in the 'input' group?");
+    }
+    let code = EventCode::EV_KEY(key);
+    read_presses(devices, false, move |c| *c == code, on_press);
+    Ok(())
+}
+
+/// Call `on_press` whenever any key or button of the devices is pressed
+///
+/// The devices are grabbed, so other applications don't see the presses.
+pub fn listen_grabbed<F>(devices: Vec<PathBuf>, on_press: F)
+where
+    F: Fn() + Send + Sync + 'static,
+{
+    read_presses(
+        devices,
+        true,
+        |code| matches!(code, EventCode::EV_KEY(_)),
+        on_press,
+    );
+}
+
+/// Read each device on its own thread; a thread ends when its device goes away
+fn read_presses<M, F>(devices: Vec<PathBuf>, grab: bool, matches: M, on_press: F)
+where
+    M: Fn(&EventCode) -> bool + Send + Sync + 'static,
+    F: Fn() + Send + Sync + 'static,
+{
+    let matches = Arc::new(matches);
+    let on_press = Arc::new(on_press);
+    for path in devices {
+        let matches = matches.clone();
+        let on_press = on_press.clone();
+        std::thread::spawn(move || {
+            let mut device = match open(&path) {
+                Ok(device) => device,
+                Err(e) => {
+                    eprintln!("Failed to open {}: {e}", path.display());
+                    return;
+                }
+            };
+            if grab && let Err(e) = device.grab(GrabMode::Grab) {
+                eprintln!("Failed to grab {}: {e}", path.display());
+            }
+            loop {
+                match device.next_event(ReadFlag::NORMAL | ReadFlag::BLOCKING) {
+                    // Value 1 is a press; 0 is a release and 2 a key repeat
+                    Ok((_, event)) if matches(&event.event_code) && event.value == 1 => on_press(),
+                    Ok(_) => {}
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                    Err(_) => break,
+                }
+            }
+        });
+    }
+}
+
+/// Parse "vendor:product" in hex, as shown by `lsusb`
+fn parse_device_id(id: &str) -> Result<(u16, u16)> {
+    let parse = |s: &str| u16::from_str_radix(s.trim().trim_start_matches("0x"), 16).ok();
+    id.split_once(':')
+        .and_then(|(vendor, product)| Some((parse(vendor)?, parse(product)?)))
+        .with_context(|| {
+            format!("Invalid device id '{id}' (expected vendor:product, e.g. 05f3:00ff)")
+        })
+}
+
+fn is_event_device(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with("event"))
+}
+
+fn open(path: &Path) -> std::io::Result<Device> {
+    Device::new_from_fd(File::open(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_device_id() {
+        assert_eq!(parse_device_id("05f3:00ff").unwrap(), (0x05f3, 0x00ff));
+        assert_eq!(parse_device_id("0x0B33:0x0020").unwrap(), (0x0b33, 0x0020));
+        assert!(parse_device_id("05f3").is_err());
+        assert!(parse_device_id("pedal:1").is_err());
+    }
+}
@@ -1,22 +1,31 @@
 use anyhow::{Context, Result};
+use evdev_rs::enums::EV_KEY;
 use rdev::{Event, EventType, Key, grab};
 use std::collections::HashSet;
 use std::sync::{Arc, Mutex};
-use std::sync::mpsc::Receiver;
+use tokio::sync::mpsc::{self, UnboundedReceiver};
 
 pub struct HotkeyGuard;
 
-pub fn setup(hotkey_str: &str) -> Result<(Receiver<()>, HotkeyGuard)> {
+pub fn setup(hotkey_str: &str) -> Result<(UnboundedReceiver<()>, HotkeyGuard)> {
     let hotkey = Hotkey::parse(hotkey_str)?;
-    let (tx, rx) = std::sync::mpsc::channel();
+    let (tx, rx) = mpsc::unbounded_channel();
+    let on_press = move || {
+        let _ = tx.send(());
+    };
 
-    std::thread::spawn(move || {
-        if let Err(e) = listen_for_hotkey(hotkey, move || {
-            let _ = tx.send(());
-        }) {
-            eprintln!("Hotkey error: {e}");
+    match hotkey.key.clone() {
+        Trigger::Key(key) => {
+            std::thread::spawn(move || {
+                if let Err(e) = listen_for_hotkey(hotkey, key, on_press) {
+                    eprintln!("Hotkey error: {e}");
+                }
+            });
+        }
+        Trigger::Device(key) => {
+            super::evdev::listen(super::evdev::devices_with(&key), key, on_press)?
         }
-    });
+    }
 
     Ok((rx, HotkeyGuard))
 }
@@ -28,7 +37,16 @@ pub struct Hotkey {
     pub shift: bool,
     pub alt: bool,
     pub super_key: bool,
-    pub key: Key,
+    pub key: Trigger,
+}
+
+/// What presses the hotkey
+#[derive(Debug, Clone, PartialEq)]
+pub enum Trigger {
+    /// A keyboard key, seen through the keyboard grab
+    Key(Key),
+    /// A mouse button or media key, read from the input devices
+    Device(EV_KEY),
 }
 
 impl Hotkey {
@@ -45,7 +63,7 @@ impl Hotkey {
         let mut shift = false;
         let mut alt = false;
         let mut super_key = false;
-        let mut main_key: Option<Key> = None;
+        let mut main_key: Option<Trigger> = None;
 
         for part in parts {
             match part {
@@ -54,12 +72,18 @@ impl Hotkey {
                 "alt" => alt = true,
                 "super" | "meta" | "win" | "cmd" => super_key = true,
                 key_str => {
-                    main_key = Some(parse_key(key_str)?);
+                    main_key = Some(match parse_device_key(key_str) {
+                        Some(key) => Trigger::Device(key),
+                        None => Trigger::Key(parse_key(key_str)?),
+                    });
                 }
             }
         }
 
         let key = main_key.context("No main key specified in hotkey")?;
+        if matches!(key, Trigger::Device(_)) && (ctrl || shift || alt || super_key) {
+            anyhow::bail!("Mouse buttons and media keys can't be combined with modifiers");
+        }
 
         Ok(Hotkey {
             ctrl,
@@ -71,6 +95,22 @@ impl Hotkey {
     }
 }
 
+/// Parse a mouse button or media key, which the keyboard grab doesn't see
+fn parse_device_key(s: &str) -> Option<EV_KEY> {
+    let key = match s {
+        "mouse4" | "mouseback" => EV_KEY::BTN_SIDE,
+        "mouse5" | "mouseforward" => EV_KEY::BTN_EXTRA,
+        "xf86audiorecord" | "record" => EV_KEY::KEY_RECORD,
+        "xf86audioplay" | "playpause" => EV_KEY::KEY_PLAYPAUSE,
+        "xf86audiostop" | "stop" => EV_KEY::KEY_STOPCD,
+        "xf86audionext" => EV_KEY::KEY_NEXTSONG,
+        "xf86audioprev" => EV_KEY::KEY_PREVIOUSSONG,
+        "xf86audiomicmute" | "micmute" => EV_KEY::KEY_MICMUTE,
+        _ => return None,
+    };
+    Some(key)
+}
+
 /// Parse a single key string into an rdev Key
 fn parse_key(s: &str) -> Result<Key> {
     let key = match s {
@@ -144,7 +184,7 @@ fn parse_key(s: &str) -> Result<Key> {
 
 /// Listen for a hotkey and call the callback when pressed
 /// This function blocks and runs until an error occurs
-pub fn listen_for_hotkey<F>(hotkey: Hotkey, on_press: F) -> Result<()>
+pub fn listen_for_hotkey<F>(hotkey: Hotkey, main_key: Key, on_press: F) -> Result<()>
 where
     F: Fn() + Send + 'static,
 {
@@ -168,7 +208,7 @@ where
                 let super_ok = !hotkey.super_key
                     || keys.contains(&Key::MetaLeft)
                     || keys.contains(&Key::MetaRight);
-                let key_ok = keys.contains(&hotkey.key);
+                let key_ok = keys.contains(&main_key);
 
                 if ctrl_ok && shift_ok && alt_ok && super_ok && key_ok {
                     on_press();
@@ -193,3 +233,28 @@ where
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_keyboard_hotkey() {
+        let hotkey = Hotkey::parse("ctrl+shift+r").unwrap();
+        assert!(hotkey.ctrl && hotkey.shift);
+        assert_eq!(hotkey.key, Trigger::Key(Key::KeyR));
+    }
+
+    #[test]
+    fn test_parse_device_trigger() {
+        assert_eq!(
+            Hotkey::parse("mouse4").unwrap().key,
+            Trigger::Device(EV_KEY::BTN_SIDE)
+        );
+        assert_eq!(
+            Hotkey::parse("XF86AudioRecord").unwrap().key,
+            Trigger::Device(EV_KEY::KEY_RECORD)
+        );
+        assert!(Hotkey::parse("ctrl+mouse5").is_err());
+    }
+}
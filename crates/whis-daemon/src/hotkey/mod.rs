@@ -0,0 +1,54 @@
+//! Cross-platform hotkey support
+//!
+//! - Linux: Uses rdev for keyboard grab (supports X11 and Wayland), and
+//!   reads input devices directly for mouse buttons and media keys
+//! - Windows/macOS: Uses global-hotkey crate (Tauri-maintained)
+
+use anyhow::Result;
+use tokio::sync::mpsc::UnboundedReceiver;
+
+#[cfg(target_os = "linux")]
+mod evdev;
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "linux")]
+use linux as platform;
+
+#[cfg(not(target_os = "linux"))]
+mod non_linux;
+#[cfg(not(target_os = "linux"))]
+use non_linux as platform;
+
+/// Opaque guard that keeps the hotkey listener alive
+pub struct HotkeyGuard(platform::HotkeyGuard);
+
+/// Setup the hotkey listener.
+/// Returns a receiver for hotkey events and a guard that must be kept alive.
+/// Presses are sent from the listener's thread, so the service can await them.
+pub fn setup(hotkey_str: &str) -> Result<(UnboundedReceiver<()>, HotkeyGuard)> {
+    let (rx, guard) = platform::setup(hotkey_str)?;
+    Ok((rx, HotkeyGuard(guard)))
+}
+
+/// Use a dedicated input device (foot pedal, dictation switch) as the trigger,
+/// selected by USB id "vendor:product". Any of its buttons toggles recording.
+#[cfg(target_os = "linux")]
+pub fn setup_device(id: &str) -> Result<(UnboundedReceiver<()>, HotkeyGuard)> {
+    let devices = evdev::devices_with_id(id)?;
+    if devices.is_empty() {
+        anyhow::bail!(
+            "Trigger device {id} not found. Is it plugged in and your user in the 'input' group?"
+        );
+    }
+
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    evdev::listen_grabbed(devices, move || {
+        let _ = tx.send(());
+    });
+    Ok((rx, HotkeyGuard(platform::HotkeyGuard)))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn setup_device(_id: &str) -> Result<(UnboundedReceiver<()>, HotkeyGuard)> {
+    anyhow::bail!("Trigger devices are only supported on Linux")
+}
@@ -4,13 +4,13 @@
 
 use anyhow::{Context, Result};
 use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, hotkey::HotKey};
-use std::sync::mpsc::Receiver;
+use tokio::sync::mpsc::{self, UnboundedReceiver};
 
 pub struct HotkeyGuard {
     _manager: GlobalHotKeyManager,
 }
 
-pub fn setup(hotkey_str: &str) -> Result<(Receiver<()>, HotkeyGuard)> {
+pub fn setup(hotkey_str: &str) -> Result<(UnboundedReceiver<()>, HotkeyGuard)> {
     let converted = convert_to_global_hotkey_format(hotkey_str)?;
     let hotkey: HotKey = converted
         .parse()
@@ -30,7 +30,7 @@ pub fn setup(hotkey_str: &str) -> Result<(Receiver<()>, HotkeyGuard)> {
 
     let receiver = GlobalHotKeyEvent::receiver().clone();
     let hotkey_id = hotkey.id();
-    let (tx, rx) = std::sync::mpsc::channel();
+    let (tx, rx) = mpsc::unbounded_channel();
 
     std::thread::spawn(move || {
         loop {
@@ -192,6 +192,28 @@ fn convert_to_global_hotkey_format(s: &str) -> Result<String> {
                 "ArrowRight".to_string()
             }
 
+            // Media keys
+            "xf86audioplay" | "playpause" => {
+                has_main_key = true;
+                "MediaPlayPause".to_string()
+            }
+            "xf86audiostop" | "stop" => {
+                has_main_key = true;
+                "MediaStop".to_string()
+            }
+            "xf86audionext" => {
+                has_main_key = true;
+                "MediaTrackNext".to_string()
+            }
+            "xf86audioprev" => {
+                has_main_key = true;
+                "MediaTrackPrevious".to_string()
+            }
+            "mouse4" | "mouseback" | "mouse5" | "mouseforward" | "xf86audiorecord" | "record"
+            | "xf86audiomicmute" | "micmute" => {
+                anyhow::bail!("'{}' is only supported as a hotkey on Linux", part)
+            }
+
             _ => anyhow::bail!("Unknown key: {}", part),
         };
         result.push(converted);
@@ -226,6 +248,10 @@ mod tests {
             convert_to_global_hotkey_format("ctrl+space").unwrap(),
             "Ctrl+Space"
         );
+        assert_eq!(
+            convert_to_global_hotkey_format("XF86AudioPlay").unwrap(),
+            "MediaPlayPause"
+        );
     }
 
     #[test]
@@ -0,0 +1,371 @@
+use anyhow::{Context, Result};
+use interprocess::local_socket::tokio::{Listener as AsyncListener, Stream as AsyncStream};
+use interprocess::local_socket::traits::tokio::Listener as _;
+use interprocess::local_socket::{GenericFilePath, ListenerOptions, ToFsName, prelude::*};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum IpcMessage {
+    Stop,
+    Status,
+    Toggle,
+    /// Toggle recording; the recording is summarized instead of transcribed verbatim
+    ToggleSummary,
+    /// Toggle recording; the recording is appended to the last transcript
+    ToggleAppend,
+    /// Discard the active recording, or stop the transcription in progress
+    Cancel,
+    /// Remove the transcript typed last (output mode "type")
+    UndoTyping,
+    /// State, audio level and chunk progress for dashboards
+    Snapshot,
+    /// Text of the windows of the recording transcribed so far (`live_window_secs`)
+    Partial,
+    /// Toggle recording for a client; the transcript goes to the subscribers
+    /// of `session` instead of the output mode, and only the same session can
+    /// stop the recording
+    ToggleSession {
+        session: String,
+    },
+    /// Keep the connection open and receive every transcript as a
+    /// `Transcript` response, or only those of `session`
+    Subscribe {
+        session: Option<String>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum IpcResponse {
+    Success,
+    Recording { elapsed_secs: f32 },
+    Idle,
+    Transcribing,
+    Snapshot(Snapshot),
+    Partial(String),
+    Transcript(SessionTranscript),
+    Error(String),
+}
+
+/// A finished transcript, as sent to subscribers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionTranscript {
+    /// Session of the client that started the recording, if any
+    pub session: Option<String>,
+    pub text: String,
+}
+
+impl SessionTranscript {
+    /// Whether a subscriber of `session` (all sessions if `None`) gets it
+    pub fn is_for(&self, session: Option<&str>) -> bool {
+        session.is_none() || self.session.as_deref() == session
+    }
+}
+
+/// What the service is doing right now
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// "idle", "recording" or "transcribing"
+    pub state: String,
+    pub elapsed_secs: f32,
+    /// Microphone level while recording (RMS, 0 to 1)
+    pub level: f32,
+    /// Chunks transcribed so far and total chunks of a long recording
+    pub chunks: (usize, usize),
+}
+
+/// Get the socket name for IPC communication
+#[cfg(unix)]
+fn socket_name() -> String {
+    std::env::var("XDG_RUNTIME_DIR")
+        .map(|dir| format!("{dir}/whis.sock"))
+        .unwrap_or_else(|_| "/tmp/whis.sock".to_string())
+}
+
+#[cfg(windows)]
+fn socket_name() -> String {
+    "whis".to_string()
+}
+
+/// Get the PID file path
+pub fn pid_file_path() -> PathBuf {
+    #[cfg(unix)]
+    {
+        let runtime_dir = std::env::var("XDG_RUNTIME_DIR").unwrap_or_else(|_| "/tmp".to_string());
+        PathBuf::from(runtime_dir).join("whis.pid")
+    }
+    #[cfg(windows)]
+    {
+        let local_app_data = std::env::var("LOCALAPPDATA").unwrap_or_else(|_| ".".to_string());
+        PathBuf::from(local_app_data).join("whis").join("whis.pid")
+    }
+}
+
+/// IPC Server for the background service
+pub struct IpcServer {
+    listener: AsyncListener,
+    #[cfg(unix)]
+    socket_path: PathBuf,
+}
+
+impl IpcServer {
+    pub fn new() -> Result<Self> {
+        let name_str = socket_name();
+
+        // On Unix, save socket path for cleanup and remove old socket if it exists
+        #[cfg(unix)]
+        let socket_path = PathBuf::from(&name_str);
+        #[cfg(unix)]
+        if socket_path.exists() {
+            std::fs::remove_file(&socket_path).context("Failed to remove old socket file")?;
+        }
+
+        let name = name_str
+            .to_fs_name::<GenericFilePath>()
+            .context("Failed to create socket name")?;
+
+        // Needs a Tokio runtime: connections are awaited with the other events
+        let listener = ListenerOptions::new()
+            .name(name)
+            .create_tokio()
+            .context("Failed to create IPC listener")?;
+
+        Ok(Self {
+            listener,
+            #[cfg(unix)]
+            socket_path,
+        })
+    }
+
+    /// Wait for the next connection
+    pub async fn accept(&self) -> Result<IpcConnection> {
+        let stream = self.listener.accept().await?;
+        Ok(IpcConnection { stream })
+    }
+}
+
+impl Drop for IpcServer {
+    fn drop(&mut self) {
+        // On Unix, clean up the socket file
+        #[cfg(unix)]
+        {
+            let _ = std::fs::remove_file(&self.socket_path);
+        }
+        // On Windows, named pipes are cleaned up automatically by the OS
+    }
+}
+
+/// IPC Connection for handling individual client connections
+pub struct IpcConnection {
+    stream: AsyncStream,
+}
+
+impl IpcConnection {
+    /// Receive a message from the client
+    pub async fn receive(&mut self) -> Result<IpcMessage> {
+        let mut reader = tokio::io::BufReader::new(&mut self.stream);
+        let mut line = String::new();
+        reader
+            .read_line(&mut line)
+            .await
+            .context("Failed to read from socket")?;
+
+        serde_json::from_str(line.trim()).context("Failed to deserialize message")
+    }
+
+    /// Send a response to the client
+    pub async fn send(&mut self, response: IpcResponse) -> Result<()> {
+        let json = serde_json::to_string(&response)?;
+        self.stream
+            .write_all(format!("{json}\n").as_bytes())
+            .await
+            .context("Failed to write to socket")?;
+        self.stream
+            .flush()
+            .await
+            .context("Failed to flush socket")?;
+        Ok(())
+    }
+}
+
+/// IPC Client for sending commands to the background service
+pub struct IpcClient {
+    stream: LocalSocketStream,
+}
+
+impl IpcClient {
+    pub fn connect() -> Result<Self> {
+        let name_str = socket_name();
+
+        // On Unix, check if socket file exists first for better error messages
+        #[cfg(unix)]
+        {
+            let path = PathBuf::from(&name_str);
+            if !path.exists() {
+                anyhow::bail!(
+                    "whis service is not running.\n\
+                    Start it with: whis listen"
+                );
+            }
+        }
+
+        let name = name_str
+            .to_fs_name::<GenericFilePath>()
+            .context("Failed to create socket name")?;
+
+        let stream = LocalSocketStream::connect(name).with_context(|| {
+            #[cfg(unix)]
+            {
+                "Failed to connect to whis service.\n\
+                The service may have crashed. Try removing stale files:\n\
+                  rm -f $XDG_RUNTIME_DIR/whis.*\n\
+                Then start the service again with: whis listen"
+            }
+            #[cfg(windows)]
+            {
+                "Failed to connect to whis service.\n\
+                The service may not be running. Start it with: whis listen"
+            }
+        })?;
+
+        Ok(Self { stream })
+    }
+
+    pub fn send_message(&mut self, message: IpcMessage) -> Result<IpcResponse> {
+        self.write(&message)?;
+        read_response(&mut BufReader::new(&mut self.stream))
+    }
+
+    /// Subscribe to transcripts (of `session` only, if given)
+    pub fn subscribe(mut self, session: Option<String>) -> Result<Subscription> {
+        self.write(&IpcMessage::Subscribe { session })?;
+        // Kept for the whole subscription: a read can buffer several events
+        let mut reader = BufReader::new(self.stream);
+        match read_response(&mut reader)? {
+            IpcResponse::Success => Ok(Subscription { reader }),
+            IpcResponse::Error(e) => anyhow::bail!(e),
+            response => anyhow::bail!("Unexpected response: {response:?}"),
+        }
+    }
+
+    fn write(&mut self, message: &IpcMessage) -> Result<()> {
+        let json = serde_json::to_string(message)?;
+        writeln!(self.stream, "{json}").context("Failed to send message")?;
+        self.stream.flush().context("Failed to flush stream")
+    }
+}
+
+/// Transcripts published by the service, in the order they finish
+pub struct Subscription {
+    reader: BufReader<LocalSocketStream>,
+}
+
+impl Iterator for Subscription {
+    type Item = Result<SessionTranscript>;
+
+    /// Wait for the next transcript; `None` once the service is gone
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let mut line = String::new();
+            match self.reader.read_line(&mut line) {
+                Ok(0) => return None,
+                Ok(_) => {}
+                Err(e) => return Some(Err(anyhow::Error::new(e).context("Failed to read event"))),
+            }
+            match serde_json::from_str(line.trim()) {
+                Ok(IpcResponse::Transcript(transcript)) => return Some(Ok(transcript)),
+                Ok(_) => {}
+                Err(e) => {
+                    return Some(Err(
+                        anyhow::Error::new(e).context("Failed to deserialize event")
+                    ));
+                }
+            }
+        }
+    }
+}
+
+fn read_response(reader: &mut impl BufRead) -> Result<IpcResponse> {
+    let mut line = String::new();
+    reader
+        .read_line(&mut line)
+        .context("Failed to read response")?;
+    serde_json::from_str(line.trim()).context("Failed to deserialize response")
+}
+
+/// Check if the service is already running
+pub fn is_service_running() -> bool {
+    let name_str = socket_name();
+
+    // On Unix, check if socket file exists first
+    #[cfg(unix)]
+    let socket_path = PathBuf::from(&name_str);
+
+    #[cfg(unix)]
+    if !socket_path.exists() {
+        return false;
+    }
+
+    // Try to connect to check if service is actually running
+    let name = match name_str.to_fs_name::<GenericFilePath>() {
+        Ok(n) => n,
+        Err(_) => return false,
+    };
+
+    match LocalSocketStream::connect(name) {
+        Ok(_) => {
+            // Successfully connected, service is running
+            true
+        }
+        Err(_) => {
+            // Can't connect - service is not running
+            // On Unix, clean up stale socket and PID files
+            #[cfg(unix)]
+            {
+                let _ = std::fs::remove_file(&socket_path);
+            }
+            remove_pid_file();
+            false
+        }
+    }
+}
+
+/// Write PID file
+pub fn write_pid_file() -> Result<()> {
+    let path = pid_file_path();
+
+    // Ensure parent directory exists (needed for Windows)
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).ok();
+    }
+
+    let pid = std::process::id();
+    std::fs::write(&path, pid.to_string()).context("Failed to write PID file")?;
+    Ok(())
+}
+
+/// Remove PID file
+pub fn remove_pid_file() {
+    let path = pid_file_path();
+    let _ = std::fs::remove_file(path);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_filter() {
+        let transcript = |session: Option<&str>| SessionTranscript {
+            session: session.map(str::to_string),
+            text: "Hello".to_string(),
+        };
+        assert!(transcript(Some("vim")).is_for(Some("vim")));
+        assert!(transcript(Some("vim")).is_for(None));
+        assert!(transcript(None).is_for(None));
+        assert!(!transcript(Some("vim")).is_for(Some("tray")));
+        assert!(!transcript(None).is_for(Some("vim")));
+    }
+}
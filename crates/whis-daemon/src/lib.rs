@@ -0,0 +1,16 @@
+//! Background service of whis
+//!
+//! The service records on a hotkey or trigger device, transcribes with
+//! whis-core and delivers the text, and is controlled over a local socket
+//! ([`ipc`]) and, on Linux, the session bus ([`dbus`]). Frontends (the CLI's
+//! `whis listen`, editor plugins, trays) link this crate instead of copying
+//! it; see the README for an example.
+
+#[cfg(target_os = "linux")]
+pub mod dbus;
+pub mod hotkey;
+pub mod ipc;
+pub mod metrics;
+pub mod service;
+
+pub use service::{Service, ServiceState, Transcript};
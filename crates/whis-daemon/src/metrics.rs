@@ -0,0 +1,149 @@
+//! Service metrics in the Prometheus text format
+//!
+//! Counters are always collected; with the `metrics` feature, `whis listen`
+//! serves them on `metrics_addr` so kiosk setups can be monitored.
+//!
+//! Endpoints:
+//! - `GET /metrics` — Prometheus metrics
+//! - `GET /health` — 200 while the service is running
+
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Upper bounds of the transcription latency histogram in seconds
+const LATENCY_BUCKETS: [f64; 9] = [0.5, 1.0, 2.0, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0];
+
+#[derive(Default)]
+pub struct Metrics {
+    inner: Mutex<Counters>,
+}
+
+#[derive(Default)]
+struct Counters {
+    recordings: u64,
+    failures: u64,
+    audio_secs: f64,
+    /// Transcriptions per latency bucket (not cumulative; the last is +Inf)
+    latency_buckets: [u64; LATENCY_BUCKETS.len() + 1],
+    latency_sum: f64,
+}
+
+impl Metrics {
+    /// Count a transcribed recording and how long the backend took
+    pub fn record_transcription(&self, audio_secs: f32, latency: Duration) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.recordings += 1;
+        inner.audio_secs += audio_secs as f64;
+
+        let latency = latency.as_secs_f64();
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|&bound| latency <= bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        inner.latency_buckets[bucket] += 1;
+        inner.latency_sum += latency;
+    }
+
+    /// Count a recording or transcription that failed
+    pub fn record_failure(&self) {
+        self.inner.lock().unwrap().failures += 1;
+    }
+
+    /// The metrics in the Prometheus text exposition format
+    #[cfg_attr(not(feature = "metrics"), allow(dead_code))]
+    pub fn render(&self) -> String {
+        let inner = self.inner.lock().unwrap();
+        let mut out = String::new();
+
+        let counters = [
+            (
+                "whis_recordings_total",
+                "Recordings transcribed",
+                inner.recordings as f64,
+            ),
+            (
+                "whis_failures_total",
+                "Recordings or transcriptions that failed",
+                inner.failures as f64,
+            ),
+            (
+                "whis_audio_seconds_total",
+                "Seconds of audio transcribed",
+                inner.audio_secs,
+            ),
+        ];
+        for (name, help, value) in counters {
+            let _ = writeln!(
+                out,
+                "# HELP {name} {help}\n# TYPE {name} counter\n{name} {value}"
+            );
+        }
+
+        let name = "whis_transcription_latency_seconds";
+        let help = "Time the backend took to transcribe a recording";
+        let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} histogram");
+        let mut cumulative = 0;
+        for (bound, count) in LATENCY_BUCKETS.iter().zip(&inner.latency_buckets) {
+            cumulative += count;
+            let _ = writeln!(out, "{name}_bucket{{le=\"{bound}\"}} {cumulative}");
+        }
+        let _ = writeln!(out, "{name}_bucket{{le=\"+Inf\"}} {}", inner.recordings);
+        let _ = writeln!(out, "{name}_sum {}", inner.latency_sum);
+        let _ = writeln!(out, "{name}_count {}", inner.recordings);
+
+        out
+    }
+}
+
+/// Serve the metrics over HTTP on a background thread
+#[cfg(feature = "metrics")]
+pub fn serve(addr: &str, metrics: std::sync::Arc<Metrics>) -> anyhow::Result<()> {
+    use tiny_http::{Header, Method, Response, Server};
+
+    let server =
+        Server::http(addr).map_err(|e| anyhow::anyhow!("Failed to bind metrics on {addr}: {e}"))?;
+    println!("Metrics on http://{addr}/metrics");
+
+    std::thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let (status, body, content_type) = match (request.method(), request.url()) {
+                (Method::Get, "/metrics") => (
+                    200,
+                    metrics.render(),
+                    "text/plain; version=0.0.4; charset=utf-8",
+                ),
+                (Method::Get, "/health") => (200, "ok\n".to_string(), "text/plain"),
+                _ => (404, "Not found\n".to_string(), "text/plain"),
+            };
+            let header = Header::from_bytes("Content-Type", content_type).unwrap();
+            let response = Response::from_string(body)
+                .with_status_code(status)
+                .with_header(header);
+            let _ = request.respond(response);
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let metrics = Metrics::default();
+        metrics.record_transcription(12.5, Duration::from_millis(1500));
+        metrics.record_transcription(3.0, Duration::from_secs(400));
+        metrics.record_failure();
+
+        let out = metrics.render();
+        assert!(out.contains("whis_recordings_total 2\n"));
+        assert!(out.contains("whis_failures_total 1\n"));
+        assert!(out.contains("whis_audio_seconds_total 15.5\n"));
+        assert!(out.contains("whis_transcription_latency_seconds_bucket{le=\"1\"} 0\n"));
+        assert!(out.contains("whis_transcription_latency_seconds_bucket{le=\"2\"} 1\n"));
+        assert!(out.contains("whis_transcription_latency_seconds_bucket{le=\"300\"} 1\n"));
+        assert!(out.contains("whis_transcription_latency_seconds_bucket{le=\"+Inf\"} 2\n"));
+    }
+}
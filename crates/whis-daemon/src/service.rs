@@ -0,0 +1,1149 @@
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::sync::{broadcast, mpsc, watch};
+use tokio::time::sleep;
+
+use crate::ipc::{IpcConnection, IpcMessage, IpcResponse, IpcServer, SessionTranscript, Snapshot};
+use crate::metrics::Metrics;
+use std::time::{Duration, Instant};
+use whis_core::metadata::Metadata;
+use whis_core::{
+    ApiConfig, AudioRecorder, CancellationToken, ChunkConfig, ChunkEvent, FailedChunk, OutputMode,
+    Provider, RecordingOutput, Settings, app_rules, bluetooth, copy_to_clipboard, events, focus,
+    history, hooks, language, parallel_transcribe_partial, parallel_transcribe_switching,
+    postprocess, quality, sinks, stats, summarize, template, transcribe, transcribe_audio,
+    type_text, typing, usage, voice_command,
+};
+
+/// How often a recording's microphone stream is checked for failures
+const STREAM_CHECK_INTERVAL: Duration = Duration::from_millis(250);
+/// How often the system default input device is checked for changes
+const DEVICE_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+/// How many typed transcripts can be undone one after the other
+const UNDO_DEPTH: usize = 20;
+/// How often a recording is checked for a complete live window
+const LIVE_WINDOW_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+/// Transcripts kept for subscribers that haven't read them yet
+const TRANSCRIPT_BACKLOG: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ServiceState {
+    Idle,
+    Recording,
+    Transcribing,
+}
+
+impl ServiceState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ServiceState::Idle => "idle",
+            ServiceState::Recording => "recording",
+            ServiceState::Transcribing => "transcribing",
+        }
+    }
+}
+
+/// What a toggle asks for besides starting or stopping a recording
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Take {
+    Transcribe,
+    /// Summarize instead of transcribing verbatim
+    Summary,
+    /// Continue the last transcript instead of replacing it
+    Append,
+}
+
+/// A finished transcription
+pub struct Transcript {
+    pub text: String,
+    /// Recording duration in seconds
+    pub duration_secs: f32,
+    /// Backend/model that produced the text
+    pub model: String,
+    /// Chunks missing from the text (with `partial_results`)
+    pub failed_chunks: Vec<FailedChunk>,
+    /// Session of the client that started the recording
+    pub session: Option<String>,
+}
+
+pub struct Service {
+    /// Current state; watchers are notified on every change
+    state: watch::Sender<ServiceState>,
+    /// Chunks transcribed so far and total chunks for the current recording
+    progress: Arc<watch::Sender<(usize, usize)>>,
+    /// Text of the live windows of the current recording (`live_window_secs`)
+    partial: Arc<Mutex<String>>,
+    recorder: Arc<Mutex<Option<AudioRecorder>>>,
+    provider: Arc<dyn Provider>,
+    /// Backends, for sending a recording in the language asked for by voice
+    config: ApiConfig,
+    settings: Settings,
+    recording_counter: Arc<Mutex<u32>>,
+    metrics: Arc<Metrics>,
+    /// Summarize the current recording (requested with a summary toggle)
+    summary_requested: AtomicBool,
+    /// Append the current recording to the last transcript (requested with
+    /// an append toggle)
+    append_requested: AtomicBool,
+    /// A recording is being started (set before the state becomes Recording)
+    starting: AtomicBool,
+    /// Session of the client that started the current recording
+    session: Mutex<Option<String>>,
+    /// Finished transcripts, for subscribed clients
+    transcripts: broadcast::Sender<SessionTranscript>,
+    /// Characters of each transcript typed, newest last, for undo
+    typed: Mutex<Vec<usize>>,
+    /// Bluetooth headset switched to its headset profile for the recording
+    headset: Mutex<Option<bluetooth::ProfileSwitch>>,
+    /// Stops the transcription in progress
+    cancel: Mutex<CancellationToken>,
+    /// Asks whether to run a voice command that needs confirmation
+    confirm: Arc<dyn Fn(&str) -> bool + Send + Sync>,
+}
+
+impl Service {
+    pub fn new(config: ApiConfig, settings: Settings) -> Result<Self> {
+        Ok(Self {
+            state: watch::Sender::new(ServiceState::Idle),
+            progress: Arc::new(watch::Sender::new((0, 0))),
+            partial: Arc::new(Mutex::new(String::new())),
+            recorder: Arc::new(Mutex::new(None)),
+            provider: config.provider(),
+            config,
+            settings,
+            // Recordings keep counting from the last run
+            recording_counter: Arc::new(Mutex::new(
+                stats::load().map_or(0, |stats| stats.last_recording),
+            )),
+            metrics: Arc::new(Metrics::default()),
+            summary_requested: AtomicBool::new(false),
+            append_requested: AtomicBool::new(false),
+            starting: AtomicBool::new(false),
+            session: Mutex::new(None),
+            transcripts: broadcast::channel(TRANSCRIPT_BACKLOG).0,
+            typed: Mutex::new(Vec::new()),
+            headset: Mutex::new(None),
+            cancel: Mutex::new(CancellationToken::new()),
+            confirm: Arc::new(|_| false),
+        })
+    }
+
+    /// Ask `confirm` with the command line before running a voice command
+    /// marked `confirm`; without it, such commands are declined
+    pub fn with_confirm(mut self, confirm: impl Fn(&str) -> bool + Send + Sync + 'static) -> Self {
+        self.confirm = Arc::new(confirm);
+        self
+    }
+
+    /// Run the service main loop
+    ///
+    /// The loop sleeps until a connection, a hotkey press or the idle timeout
+    /// arrives. Messages and hotkey presses are handled on their own tasks, so
+    /// status requests are answered while a recording is being transcribed.
+    pub async fn run(self: Arc<Self>, hotkey_rx: Option<UnboundedReceiver<()>>) -> Result<()> {
+        // Create IPC server
+        let ipc_server = IpcServer::new().context("Failed to create IPC server")?;
+        self.watch_stream();
+
+        println!("whis listening. Ctrl+C to stop.");
+
+        let mut hotkey_rx = hotkey_rx;
+        let mut state_rx = self.subscribe();
+        let idle_exit = self
+            .settings
+            .idle_exit_mins
+            .map(|mins| Duration::from_secs(mins * 60));
+        let mut active_at = Instant::now();
+        loop {
+            tokio::select! {
+                conn = ipc_server.accept() => {
+                    let mut conn = conn?;
+                    let service = self.clone();
+                    tokio::spawn(async move {
+                        match conn.receive().await {
+                            Ok(IpcMessage::Subscribe { session }) => {
+                                service.serve_subscriber(conn, session).await;
+                            }
+                            Ok(message) => {
+                                let response = service.handle_message(message).await;
+                                let _ = conn.send(response).await;
+                            }
+                            Err(e) => {
+                                eprintln!("Error receiving message: {e}");
+                                let _ = conn.send(IpcResponse::Error(e.to_string())).await;
+                            }
+                        }
+                    });
+                }
+                pressed = next_press(&mut hotkey_rx) => {
+                    if pressed.is_some() {
+                        let service = self.clone();
+                        tokio::spawn(async move { service.handle_toggle().await });
+                    } else {
+                        // The listener is gone; keep serving IPC
+                        hotkey_rx = None;
+                    }
+                }
+                // Recordings and transcriptions count as activity
+                changed = state_rx.changed() => {
+                    changed?;
+                    active_at = Instant::now();
+                }
+                // Exit after a while without recordings (restarted on demand)
+                () = idle_timeout(idle_exit, active_at) => {
+                    if self.state() == ServiceState::Idle {
+                        let mins = idle_exit.unwrap_or_default().as_secs() / 60;
+                        println!("Idle for {mins} min, exiting");
+                        return Ok(());
+                    }
+                    active_at = Instant::now();
+                }
+            }
+        }
+    }
+
+    /// Handle an IPC message
+    async fn handle_message(&self, message: IpcMessage) -> IpcResponse {
+        match message {
+            IpcMessage::Stop => {
+                println!("Stop signal received");
+                // Return Ok response before exiting
+                tokio::spawn(async {
+                    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+                    std::process::exit(0);
+                });
+                IpcResponse::Success
+            }
+            IpcMessage::Status => match self.state() {
+                ServiceState::Idle => IpcResponse::Idle,
+                ServiceState::Recording => IpcResponse::Recording {
+                    elapsed_secs: self.elapsed_secs(),
+                },
+                ServiceState::Transcribing => IpcResponse::Transcribing,
+            },
+            IpcMessage::Toggle => self.handle_toggle().await,
+            IpcMessage::ToggleSummary => self.handle_summary_toggle().await,
+            IpcMessage::ToggleAppend => self.handle_append_toggle().await,
+            IpcMessage::Cancel => match self.cancel_recording() {
+                Ok(()) => IpcResponse::Success,
+                Err(e) => IpcResponse::Error(e.to_string()),
+            },
+            IpcMessage::UndoTyping => match self.undo_typing().await {
+                Ok(_) => IpcResponse::Success,
+                Err(e) => IpcResponse::Error(e.to_string()),
+            },
+            IpcMessage::Snapshot => IpcResponse::Snapshot(self.snapshot()),
+            IpcMessage::Partial => IpcResponse::Partial(self.partial()),
+            IpcMessage::ToggleSession { session } => self.handle_session_toggle(session).await,
+            // Handled in `run`, which keeps the connection
+            IpcMessage::Subscribe { .. } => {
+                IpcResponse::Error("Subscribe needs a connection of its own".to_string())
+            }
+        }
+    }
+
+    /// Send the transcripts of `session` (all of them without one) to a
+    /// subscribed client until it disconnects
+    async fn serve_subscriber(&self, mut conn: IpcConnection, session: Option<String>) {
+        let mut transcripts = self.subscribe_transcripts();
+        if conn.send(IpcResponse::Success).await.is_err() {
+            return;
+        }
+        loop {
+            match transcripts.recv().await {
+                Ok(transcript) => {
+                    if !transcript.is_for(session.as_deref()) {
+                        continue;
+                    }
+                    // A failed write means the client is gone
+                    if conn
+                        .send(IpcResponse::Transcript(transcript))
+                        .await
+                        .is_err()
+                    {
+                        return;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return,
+            }
+        }
+    }
+
+    /// Watch the microphone: stop and transcribe the recording when it goes
+    /// away, so the audio captured until then isn't lost, and follow changes
+    /// of the system default device
+    pub fn watch_stream(self: &Arc<Self>) {
+        let service = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut device_checked = Instant::now();
+            loop {
+                sleep(STREAM_CHECK_INTERVAL).await;
+                let Some(service) = service.upgrade() else {
+                    break;
+                };
+                if device_checked.elapsed() >= DEVICE_CHECK_INTERVAL {
+                    device_checked = Instant::now();
+                    service.follow_default_device();
+                }
+                if service.state() != ServiceState::Recording {
+                    continue;
+                }
+                let failure = service
+                    .recorder
+                    .lock()
+                    .unwrap()
+                    .as_ref()
+                    .and_then(AudioRecorder::stream_failure);
+                let Some(reason) = failure else {
+                    continue;
+                };
+
+                let recording = service.recording_number();
+                println!("\r#{recording} {reason}, transcribing what was recorded");
+                log_event(events::Event::Error {
+                    recording,
+                    message: reason,
+                });
+                let session = service.session.lock().unwrap().clone();
+                service.toggle(Take::Transcribe, session).await;
+            }
+        });
+    }
+
+    /// Reopen the microphone on a new default input device: the pre-roll
+    /// while idle, the recording itself with `follow_default_device`
+    fn follow_default_device(&self) {
+        let state = self.state();
+        let mut slot = self.recorder.lock().unwrap();
+        let Some(recorder) = slot.as_mut() else {
+            return;
+        };
+        if state == ServiceState::Transcribing || !recorder.default_device_changed() {
+            return;
+        }
+
+        let result = if state == ServiceState::Idle {
+            recorder.start_pre_roll(self.settings.pre_roll_secs)
+        } else if self.settings.follow_default_device {
+            recorder.switch_device()
+        } else {
+            return;
+        };
+        match result {
+            Ok(()) => {
+                let name = recorder.device_name().unwrap_or("default device");
+                println!("\rSwitched to {name}");
+            }
+            Err(e) => eprintln!("\rFailed to switch input device: {e:#}"),
+        }
+    }
+
+    /// Current service state
+    pub fn state(&self) -> ServiceState {
+        *self.state.borrow()
+    }
+
+    /// Seconds recorded so far in the active recording (0 when not recording)
+    pub fn elapsed_secs(&self) -> f32 {
+        self.recorder
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map_or(0.0, AudioRecorder::elapsed_secs)
+    }
+
+    /// Current state with audio level and chunk progress
+    pub fn snapshot(&self) -> Snapshot {
+        let state = self.state();
+        // The pre-roll recorder runs while idle; only report an active recording
+        let (elapsed_secs, level) = match state {
+            ServiceState::Recording => self
+                .recorder
+                .lock()
+                .unwrap()
+                .as_ref()
+                .map_or((0.0, 0.0), |r| (r.elapsed_secs(), r.level())),
+            _ => (0.0, 0.0),
+        };
+        Snapshot {
+            state: state.as_str().to_string(),
+            elapsed_secs,
+            level,
+            chunks: *self.progress.borrow(),
+        }
+    }
+
+    /// Text of the live windows of the current (or last) recording
+    pub fn partial(&self) -> String {
+        self.partial.lock().unwrap().clone()
+    }
+
+    /// Counters for the metrics endpoint
+    #[cfg_attr(not(feature = "metrics"), allow(dead_code))]
+    pub fn metrics(&self) -> Arc<Metrics> {
+        self.metrics.clone()
+    }
+
+    /// Subscribe to state changes
+    pub fn subscribe(&self) -> watch::Receiver<ServiceState> {
+        self.state.subscribe()
+    }
+
+    /// Subscribe to chunk progress of long recordings as `(completed, total)`
+    pub fn subscribe_progress(&self) -> watch::Receiver<(usize, usize)> {
+        self.progress.subscribe()
+    }
+
+    /// Subscribe to finished transcripts
+    pub fn subscribe_transcripts(&self) -> broadcast::Receiver<SessionTranscript> {
+        self.transcripts.subscribe()
+    }
+
+    /// Handle toggle command (start/stop recording)
+    pub async fn handle_toggle(&self) -> IpcResponse {
+        self.toggle(Take::Transcribe, None).await
+    }
+
+    /// Like `handle_toggle`, but the recording is summarized instead of
+    /// transcribed verbatim
+    pub async fn handle_summary_toggle(&self) -> IpcResponse {
+        self.toggle(Take::Summary, None).await
+    }
+
+    /// Like `handle_toggle`, but the recording is appended to the last
+    /// transcript (in the history and the clipboard) instead of replacing it
+    pub async fn handle_append_toggle(&self) -> IpcResponse {
+        self.toggle(Take::Append, None).await
+    }
+
+    /// Like `handle_toggle`, but for a client's `session`: the transcript is
+    /// published to the session's subscribers instead of going to the output
+    /// mode, and only the same session can stop the recording (not even the
+    /// hotkey, whose user wouldn't get the text)
+    pub async fn handle_session_toggle(&self, session: String) -> IpcResponse {
+        self.toggle(Take::Transcribe, Some(session)).await
+    }
+
+    async fn toggle(&self, take: Take, session: Option<String>) -> IpcResponse {
+        let current_state = self.state();
+
+        match current_state {
+            ServiceState::Idle => {
+                let result = self.start(take, session).await;
+                let count = self.recording_number();
+                match result {
+                    Ok(_) => {
+                        print!("#{count} recording...");
+                        let _ = std::io::stdout().flush();
+                        IpcResponse::Recording { elapsed_secs: 0.0 }
+                    }
+                    Err(e) => {
+                        println!("#{count} error: {e}");
+                        IpcResponse::Error(e.to_string())
+                    }
+                }
+            }
+            ServiceState::Recording => {
+                // A session's recording is stopped by its client, and a
+                // session can't stop someone else's
+                if *self.session.lock().unwrap() != session {
+                    return IpcResponse::Error("Another client is recording".to_string());
+                }
+                match take {
+                    Take::Summary => self.summary_requested.store(true, Ordering::SeqCst),
+                    Take::Append => self.append_requested.store(true, Ordering::SeqCst),
+                    Take::Transcribe => {}
+                }
+                // Stop recording and transcribe
+                let count = self.recording_number();
+
+                // Show transcribing state (overwrite recording line)
+                print!("\r#{count} transcribing...");
+                let _ = std::io::stdout().flush();
+
+                match self.finish_recording().await {
+                    Ok(transcript) => {
+                        let usage = usage::describe(&transcript.model, transcript.duration_secs);
+                        if transcript.model == self.provider.model() {
+                            println!("\r#{count} done ({usage})");
+                        } else {
+                            println!("\r#{count} done via {} ({usage})", transcript.model);
+                        }
+                        for chunk in &transcript.failed_chunks {
+                            println!("  chunk {} failed: {}", chunk.index, chunk.error);
+                        }
+                        IpcResponse::Success
+                    }
+                    Err(e) => {
+                        println!("\r#{count} error: {e}");
+                        IpcResponse::Error(e.to_string())
+                    }
+                }
+            }
+            ServiceState::Transcribing => {
+                // Already transcribing, ignore
+                IpcResponse::Transcribing
+            }
+        }
+    }
+
+    /// Change the state, notifying watchers and the `on_state_change` hook
+    fn set_state(&self, state: ServiceState) {
+        self.state.send_replace(state);
+        hooks::state_changed(&self.settings, state.as_str());
+    }
+
+    /// Keep the microphone open while idle if `pre_roll_secs` is set
+    pub fn start_pre_roll(&self) -> Result<()> {
+        if self.settings.pre_roll_secs <= 0.0 {
+            return Ok(());
+        }
+        let mut recorder = AudioRecorder::new()?;
+        recorder.set_device(self.settings.input_device.clone());
+        recorder.set_mono(self.settings.mono);
+        recorder.start_pre_roll(self.settings.pre_roll_secs)?;
+        *self.recorder.lock().unwrap() = Some(recorder);
+        Ok(())
+    }
+
+    /// Number of the current (or last) recording
+    fn recording_number(&self) -> u32 {
+        *self.recording_counter.lock().unwrap()
+    }
+
+    /// Start recording audio
+    pub async fn start_recording(&self) -> Result<()> {
+        self.start(Take::Transcribe, None).await
+    }
+
+    /// Start a recording for `take`, of a client's `session` if given
+    async fn start(&self, take: Take, session: Option<String>) -> Result<()> {
+        // Toggles run concurrently: claimed before the state is checked and
+        // released once it changed, so two of them can't both start a recording
+        if self.starting.swap(true, Ordering::SeqCst) {
+            anyhow::bail!("A recording is already starting");
+        }
+        let result = if self.state() == ServiceState::Idle {
+            // Only the toggle that claimed the start says where its transcript goes
+            *self.session.lock().unwrap() = session;
+            self.summary_requested
+                .store(take == Take::Summary, Ordering::SeqCst);
+            self.append_requested
+                .store(take == Take::Append, Ordering::SeqCst);
+            self.open_recording().await
+        } else {
+            Err(anyhow::anyhow!("A recording is already in progress"))
+        };
+        self.starting.store(false, Ordering::SeqCst);
+        result
+    }
+
+    async fn open_recording(&self) -> Result<()> {
+        let recording = {
+            let mut c = self.recording_counter.lock().unwrap();
+            *c += 1;
+            *c
+        };
+        if let Err(e) = stats::set_last_recording(recording) {
+            eprintln!("Stats error: {e:#}");
+        }
+        // A switch would cut off the stream a pre-rolling recorder has open
+        let switch = self.settings.bluetooth_switch && self.settings.pre_roll_secs <= 0.0;
+        let headset = tokio::task::spawn_blocking(move || bluetooth::prepare(switch))
+            .await
+            .context("Failed to join task")?;
+        *self.headset.lock().unwrap() = headset;
+
+        // Reuse the pre-rolling recorder so its buffered audio is kept
+        let idle = self.recorder.lock().unwrap().take();
+        let recorder = idle
+            .map_or_else(AudioRecorder::new, Ok)
+            .and_then(|mut recorder| {
+                recorder.set_spill_threshold_mb(self.settings.spill_limit_mb());
+                recorder.set_device(self.settings.input_device.clone());
+                recorder.set_mono(self.settings.mono);
+                recorder.start_recording()?;
+                Ok(recorder)
+            });
+        let recorder = match recorder {
+            Ok(recorder) => recorder,
+            Err(e) => {
+                self.headset.lock().unwrap().take();
+                self.metrics.record_failure();
+                log_event(events::Event::Error {
+                    recording,
+                    message: format!("{e:#}"),
+                });
+                return Err(e);
+            }
+        };
+
+        *self.recorder.lock().unwrap() = Some(recorder);
+        self.progress.send_replace((0, 0));
+        self.partial.lock().unwrap().clear();
+        self.set_state(ServiceState::Recording);
+        log_event(events::Event::RecordingStarted { recording });
+
+        if let Some(window_secs) = self.settings.live_window_secs.filter(|&secs| secs > 0) {
+            let windows = LiveWindows {
+                recorder: self.recorder.clone(),
+                provider: self.provider.clone(),
+                settings: self.settings.clone(),
+                partial: self.partial.clone(),
+                recording_counter: self.recording_counter.clone(),
+                recording,
+                state: self.subscribe(),
+                window_secs: window_secs as f32,
+            };
+            tokio::spawn(windows.run());
+        }
+
+        Ok(())
+    }
+
+    /// Discard the active recording without transcribing it, or stop the
+    /// transcription in progress
+    pub fn cancel_recording(&self) -> Result<()> {
+        match self.state() {
+            ServiceState::Recording => {}
+            ServiceState::Transcribing => {
+                self.cancel.lock().unwrap().cancel();
+                return Ok(());
+            }
+            ServiceState::Idle => anyhow::bail!("No active recording"),
+        }
+
+        // Dropping the recorder stops the stream and discards captured audio
+        let mut slot = self.recorder.lock().unwrap();
+        if let Some(mut recorder) = slot.take()
+            && recorder.is_pre_rolling()
+        {
+            let _ = recorder.stop_recording();
+            *slot = Some(recorder);
+        }
+        drop(slot);
+        self.headset.lock().unwrap().take();
+        self.set_state(ServiceState::Idle);
+
+        Ok(())
+    }
+
+    /// Remove the transcript typed last by pressing backspace once for each
+    /// of its characters. Returns the number of characters removed.
+    pub async fn undo_typing(&self) -> Result<usize> {
+        let len = self
+            .typed
+            .lock()
+            .unwrap()
+            .pop()
+            .context("Nothing typed to undo")?;
+        tokio::task::spawn_blocking(move || typing::erase(len))
+            .await
+            .context("Failed to join task")??;
+        Ok(len)
+    }
+
+    /// Stop the active recording, transcribe it and deliver the result.
+    /// Returns the final transcript.
+    pub async fn finish_recording(&self) -> Result<Transcript> {
+        let started = self.state.send_if_modified(|state| {
+            let recording = *state == ServiceState::Recording;
+            if recording {
+                // Replaced before the state changes, so a cancel always hits this transcription
+                *self.cancel.lock().unwrap() = CancellationToken::new();
+                *state = ServiceState::Transcribing;
+            }
+            recording
+        });
+        if !started {
+            anyhow::bail!("No active recording");
+        }
+        hooks::state_changed(&self.settings, ServiceState::Transcribing.as_str());
+
+        let result = self.stop_and_transcribe().await;
+        self.set_state(ServiceState::Idle);
+        if let Ok(transcript) = &result {
+            // Fails only without subscribers
+            let _ = self.transcripts.send(SessionTranscript {
+                session: transcript.session.clone(),
+                text: transcript.text.clone(),
+            });
+        }
+
+        let recording = self.recording_number();
+        log_event(match &result {
+            Ok(transcript) => events::Event::TranscriptionDone {
+                recording,
+                duration_secs: transcript.duration_secs,
+                model: transcript.model.clone(),
+                chars: transcript.text.chars().count(),
+                failed_chunks: transcript.failed_chunks.len(),
+            },
+            Err(e) => {
+                self.metrics.record_failure();
+                // Errors of the output (e.g. a voice command) can quote the transcript
+                let message = if self.settings.privacy {
+                    "Transcription failed (details left out in privacy mode)".to_string()
+                } else {
+                    format!("{e:#}")
+                };
+                events::Event::Error { recording, message }
+            }
+        });
+        result
+    }
+
+    /// Stop recording and transcribe
+    async fn stop_and_transcribe(&self) -> Result<Transcript> {
+        // Get the recorder
+        let mut recorder = self
+            .recorder
+            .lock()
+            .unwrap()
+            .take()
+            .context("No active recording")?;
+
+        // Output rules depend on the application focused when the recording
+        // stops, which is also recorded with the transcript
+        let window = focus::focused_window();
+        let settings = app_rules::resolve(&self.settings, window.as_ref().map(|w| w.app.as_str()));
+        let summarize = settings.summarize || self.summary_requested.swap(false, Ordering::SeqCst);
+        let append = self.append_requested.swap(false, Ordering::SeqCst);
+        let session = self.session.lock().unwrap().take();
+
+        // Stop recording and get the Send-safe recording data
+        // (cpal::Stream is dropped here, making RecordingData movable across threads)
+        if let Some(warning) = recorder.capture_report().warning() {
+            eprintln!("\n{warning}");
+        }
+        let recording_data = recorder.stop_recording();
+        self.headset.lock().unwrap().take();
+        let recording_data = recording_data?;
+        let duration_secs = recording_data.duration_secs();
+        let audio = recording_data.stats();
+        if recorder.is_pre_rolling() {
+            *self.recorder.lock().unwrap() = Some(recorder);
+        }
+
+        // Finalize recording (blocking operation, run in tokio blocking task)
+        let chunking = settings.chunking;
+        let encoding = settings.encoding().negotiate(self.provider.as_ref());
+        let codec = encoding.codec;
+        let audio_result =
+            tokio::task::spawn_blocking(move || recording_data.finalize(&chunking, &encoding))
+                .await
+                .context("Failed to join task")??;
+
+        // Transcribe based on output type
+        let started = Instant::now();
+        let provider = self.provider.clone();
+        let cancel = self.cancel.lock().unwrap().clone();
+        let mut failed_chunks = Vec::new();
+        let transcription = match audio_result {
+            RecordingOutput::Single(audio_data) => {
+                // Small file - use simple blocking transcription
+                let upload = tokio::task::spawn_blocking(move || {
+                    transcribe_audio(provider.as_ref(), audio_data, codec)
+                });
+                tokio::select! {
+                    result = upload => result.context("Failed to join task")??,
+                    () = cancel.cancelled() => anyhow::bail!("Transcription cancelled"),
+                }
+            }
+            RecordingOutput::Chunked(chunks) => {
+                // Large file - use parallel async transcription
+                let (events_tx, events_rx) = mpsc::unbounded_channel();
+                tokio::spawn(track_chunks(
+                    events_rx,
+                    self.progress.clone(),
+                    self.recording_number(),
+                    chunks.len(),
+                ));
+                let events = Some(events_tx);
+                let partial = if settings.language_hotword {
+                    parallel_transcribe_switching(&self.config, chunks, events, Some(cancel))
+                        .await?
+                } else {
+                    parallel_transcribe_partial(provider, chunks, events, Some(cancel)).await?
+                };
+                // A cancelled transcription is discarded even with partial results
+                if settings.partial_results && !partial.cancelled {
+                    failed_chunks = partial.failed;
+                    partial.transcription
+                } else {
+                    partial.require_complete()?
+                }
+            }
+        };
+        self.metrics
+            .record_transcription(duration_secs, started.elapsed());
+        let model = transcription
+            .usage_model(self.provider.as_ref())
+            .to_string();
+        let transcription = if settings.language_hotword {
+            language::apply_switch(transcription)
+        } else {
+            transcription
+        };
+        if let Some(report) = quality::report(
+            &transcription,
+            settings.drop_silent_segments,
+            settings.privacy,
+        ) {
+            eprintln!("\n{report}");
+        }
+        let settings = language::resolve(&settings, transcription.language.as_deref());
+        let metadata = Metadata {
+            model: Some(model.clone()),
+            audio,
+            ..Metadata::capture(window, &settings)
+        };
+        let transcription = postprocess::to_text(transcription, &settings);
+
+        // Apply local post-processing (dictation commands, etc.)
+        let transcription = postprocess::process(&transcription, &settings);
+        // Without a summary the verbatim transcript is delivered, as it is billed already
+        let transcription = if summarize {
+            let (text, summary_settings) = (transcription.clone(), settings.clone());
+            let summary =
+                tokio::task::spawn_blocking(move || summarize::summarize(&text, &summary_settings))
+                    .await
+                    .context("Failed to join task")?;
+            match summary {
+                Ok(summary) => summary,
+                Err(e) => {
+                    eprintln!("\nSummary error: {e:#}");
+                    transcription
+                }
+            }
+        } else {
+            transcription
+        };
+        let transcription = template::apply(&settings, &transcription, duration_secs);
+
+        // An appended take continues the last transcript in the history;
+        // without a history (privacy mode) it is a transcript of its own
+        let previous = if append && !settings.privacy {
+            tokio::task::spawn_blocking(|| history::recent(1))
+                .await
+                .context("Failed to join task")??
+                .pop()
+                .map(|entry| entry.text)
+        } else {
+            None
+        };
+        let appending = previous.is_some();
+
+        // A client's session takes the transcript from its subscription
+        let output_mode = if session.is_some() {
+            OutputMode::None
+        } else {
+            settings.output_mode
+        };
+        match output_mode {
+            OutputMode::Clipboard => {
+                // Copy to clipboard (blocking operation), with the last
+                // transcript when appending
+                let text = match &previous {
+                    Some(previous) => {
+                        history::join(previous, &settings.append_separator, &transcription)
+                    }
+                    None => transcription.clone(),
+                };
+                let (selection, backend) =
+                    (settings.clipboard_selection, settings.clipboard_backend);
+                tokio::task::spawn_blocking(move || copy_to_clipboard(&text, selection, backend))
+                    .await
+                    .context("Failed to join task")??;
+            }
+            OutputMode::Command => {
+                // Run the matching voice command (may prompt for confirmation)
+                let text = transcription.clone();
+                let commands = settings.voice_commands.clone();
+                let confirm = self.confirm.clone();
+                let outcome = tokio::task::spawn_blocking(move || {
+                    voice_command::run_matching(&text, &commands, |line| confirm(line))
+                })
+                .await
+                .context("Failed to join task")?;
+                // Like a sink error, a failed command doesn't lose the transcript
+                match outcome {
+                    Ok(voice_command::Outcome::Ran(_)) => {}
+                    Ok(outcome) => eprintln!("\n{outcome}"),
+                    Err(e) => eprintln!("\nVoice command error: {e:#}"),
+                }
+            }
+            OutputMode::Type => {
+                // Type into the focused window (blocking operation); the last
+                // transcript is already there when appending
+                let text = if appending {
+                    format!(
+                        "{}{}",
+                        settings.append_separator,
+                        transcription.trim_start()
+                    )
+                } else {
+                    transcription.clone()
+                };
+                let len = typing::typed_len(&text);
+                let typing = settings.typing;
+                tokio::task::spawn_blocking(move || type_text(&text, &typing))
+                    .await
+                    .context("Failed to join task")??;
+                let mut typed = self.typed.lock().unwrap();
+                if typed.len() == UNDO_DEPTH {
+                    typed.remove(0);
+                }
+                typed.push(len);
+            }
+            OutputMode::None => {}
+        }
+
+        // Deliver to additional sinks (webhook, etc.) and record in history
+        let payload = sinks::Payload {
+            metadata,
+            ..sinks::Payload::new(transcription.clone(), duration_secs)
+        };
+        let usage_model = model.clone();
+        let errors = tokio::task::spawn_blocking(move || {
+            let mut errors = sinks::dispatch(&settings, &payload);
+            if !settings.privacy {
+                let (separator, encrypt) = (&settings.append_separator, settings.encrypt_history);
+                let saved = if appending {
+                    history::append_to_last(&payload, separator, encrypt)
+                } else {
+                    history::append(&payload, encrypt)
+                };
+                if let Err(e) = saved {
+                    errors.push(e);
+                }
+            }
+            if let Err(e) = stats::record(&payload) {
+                errors.push(e);
+            }
+            match usage::track(&settings, &usage_model, payload.duration_secs) {
+                Ok(Some(warning)) => eprintln!("\n{warning}"),
+                Ok(None) => {}
+                Err(e) => errors.push(e),
+            }
+            errors
+        })
+        .await
+        .context("Failed to join task")?;
+        for e in errors {
+            eprintln!("\nOutput error: {e:#}");
+        }
+
+        Ok(Transcript {
+            text: transcription,
+            duration_secs,
+            model,
+            failed_chunks,
+            session,
+        })
+    }
+}
+
+/// Transcribes the windows of a running recording as they complete
+struct LiveWindows {
+    recorder: Arc<Mutex<Option<AudioRecorder>>>,
+    provider: Arc<dyn Provider>,
+    settings: Settings,
+    partial: Arc<Mutex<String>>,
+    recording_counter: Arc<Mutex<u32>>,
+    recording: u32,
+    state: watch::Receiver<ServiceState>,
+    window_secs: f32,
+}
+
+impl LiveWindows {
+    /// Transcribe one window after the other until the recording ends.
+    /// A window that fails is left out; the final transcript has it anyway.
+    async fn run(mut self) {
+        let mut window = 0;
+        while self.recording() {
+            let start_secs = window as f32 * self.window_secs;
+            let complete = self
+                .recorder
+                .lock()
+                .unwrap()
+                .as_ref()
+                .is_some_and(|r| r.elapsed_secs() >= start_secs + self.window_secs);
+            if !complete {
+                tokio::select! {
+                    () = sleep(LIVE_WINDOW_CHECK_INTERVAL) => {}
+                    changed = self.state.changed() => if changed.is_err() { return },
+                }
+                continue;
+            }
+
+            match self.transcribe(start_secs).await {
+                // The recording may have ended while the window was sent
+                Ok(text) if self.recording() => {
+                    if !text.is_empty() {
+                        let mut partial = self.partial.lock().unwrap();
+                        if !partial.is_empty() {
+                            partial.push(' ');
+                        }
+                        partial.push_str(&text);
+                    }
+                    log_event(events::Event::PartialTranscript {
+                        recording: self.recording,
+                        window,
+                        text: (!self.settings.privacy).then_some(text),
+                    });
+                }
+                Ok(_) => return,
+                Err(e) => eprintln!("\r#{} window {} failed: {e:#}", self.recording, window + 1),
+            }
+            window += 1;
+        }
+    }
+
+    /// Whether the recording these windows belong to is still running
+    fn recording(&self) -> bool {
+        *self.state.borrow() == ServiceState::Recording
+            && *self.recording_counter.lock().unwrap() == self.recording
+    }
+
+    /// Transcribe the window starting at `start_secs`
+    async fn transcribe(&self, start_secs: f32) -> Result<String> {
+        let data = self
+            .recorder
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|r| r.window(start_secs, self.window_secs))
+            .context("The window was already moved to disk")?;
+        let encoding = self.settings.encoding().negotiate(self.provider.as_ref());
+        let codec = encoding.codec;
+        // Windows are short enough to send in one piece
+        let chunking = ChunkConfig {
+            enabled: false,
+            ..self.settings.chunking
+        };
+        let output = tokio::task::spawn_blocking(move || data.finalize(&chunking, &encoding))
+            .await
+            .context("Failed to join task")??;
+        let RecordingOutput::Single(audio) = output else {
+            anyhow::bail!("Window was split into chunks");
+        };
+
+        let _permit = transcribe::request_permit().await?;
+        let provider = self.provider.clone();
+        let transcription =
+            tokio::task::spawn_blocking(move || transcribe_audio(provider.as_ref(), audio, codec))
+                .await
+                .context("Failed to join task")??;
+        let model = transcription
+            .usage_model(self.provider.as_ref())
+            .to_string();
+        if let Err(e) = usage::track(&self.settings, &model, self.window_secs) {
+            eprintln!("\nUsage error: {e:#}");
+        }
+        let text = postprocess::to_text(transcription, &self.settings);
+        Ok(postprocess::process(&text, &self.settings))
+    }
+}
+
+/// The next hotkey press; never resolves without a hotkey
+async fn next_press(hotkey_rx: &mut Option<UnboundedReceiver<()>>) -> Option<()> {
+    match hotkey_rx {
+        Some(rx) => rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Resolves `limit` after `since`; never without a limit
+async fn idle_timeout(limit: Option<Duration>, since: Instant) {
+    match limit {
+        Some(limit) => tokio::time::sleep_until((since + limit).into()).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Turn chunk events into progress updates and event log entries
+async fn track_chunks(
+    mut chunk_events: mpsc::UnboundedReceiver<ChunkEvent>,
+    progress: Arc<watch::Sender<(usize, usize)>>,
+    recording: u32,
+    total: usize,
+) {
+    let mut done = 0;
+    while let Some(event) = chunk_events.recv().await {
+        match event {
+            ChunkEvent::Done { .. } => {
+                done += 1;
+                progress.send_replace((done, total));
+                log_event(events::Event::ChunkUploaded {
+                    recording,
+                    done,
+                    total,
+                });
+            }
+            ChunkEvent::Failed { index, error } => log_event(events::Event::ChunkFailed {
+                recording,
+                index,
+                error,
+            }),
+            ChunkEvent::Started { .. }
+            | ChunkEvent::Sending { .. }
+            | ChunkEvent::Uploaded { .. } => {}
+        }
+    }
+}
+
+/// Append to the event log; a failure there shouldn't stop the service
+fn log_event(event: events::Event) {
+    if let Err(e) = events::emit(event) {
+        eprintln!("\nEvent log error: {e:#}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use whis_core::Backend;
+
+    fn service() -> Service {
+        let settings = Settings {
+            openai_api_key: Some("sk-test".to_string()),
+            ..Settings::default()
+        };
+        let config = ApiConfig::for_backend(&settings, Backend::OpenAI).unwrap();
+        Service::new(config, settings).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_losing_toggle_keeps_the_winners_take() {
+        let service = service();
+        // Another toggle claimed the start for its session
+        service.starting.store(true, Ordering::SeqCst);
+        *service.session.lock().unwrap() = Some("vim".to_string());
+
+        let response = service
+            .toggle(Take::Summary, Some("tray".to_string()))
+            .await;
+        assert!(matches!(response, IpcResponse::Error(_)));
+        assert_eq!(service.session.lock().unwrap().as_deref(), Some("vim"));
+        assert!(!service.summary_requested.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_only_the_owner_stops_a_recording() {
+        let service = service();
+        service.state.send_replace(ServiceState::Recording);
+        *service.session.lock().unwrap() = Some("vim".to_string());
+
+        for session in [None, Some("tray".to_string())] {
+            let response = service.toggle(Take::Transcribe, session).await;
+            assert!(matches!(response, IpcResponse::Error(_)));
+        }
+        assert_eq!(service.state(), ServiceState::Recording);
+        assert_eq!(service.session.lock().unwrap().as_deref(), Some("vim"));
+    }
+}
@@ -2,7 +2,7 @@ use crate::settings::Settings;
 use crate::shortcuts::ShortcutBackendInfo;
 use crate::state::{AppState, RecordingState};
 use tauri::{AppHandle, State};
-use whis_core::ApiConfig;
+use whis_core::{ApiConfig, Profile};
 
 #[derive(serde::Serialize)]
 pub struct StatusResponse {
@@ -17,7 +17,10 @@ pub struct SaveSettingsResponse {
 
 #[tauri::command]
 pub async fn is_api_configured() -> Result<bool, String> {
-    ApiConfig::from_env().map(|_| true).map_err(|e| e.to_string())
+    let settings = whis_core::Settings::load();
+    ApiConfig::from_settings(&settings)
+        .map(|_| true)
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -26,8 +29,7 @@ pub async fn get_status(state: State<'_, AppState>) -> Result<StatusResponse, St
 
     // Check if API key is configured (either in settings or already loaded)
     let config_valid = state.api_config.lock().unwrap().is_some()
-        || state.settings.lock().unwrap().openai_api_key.is_some()
-        || std::env::var("OPENAI_API_KEY").is_ok();
+        || ApiConfig::from_settings(&state.settings.lock().unwrap()).is_ok();
 
     Ok(StatusResponse {
         state: match current_state {
@@ -105,8 +107,13 @@ pub async fn save_settings(
     };
 
     {
+        // The UI only edits the shortcut and API key; keep all other settings from disk
+        let mut merged = Settings::load();
+        merged.shortcut = settings.shortcut.clone();
+        merged.openai_api_key = settings.openai_api_key.clone();
+
         let mut state_settings = state.settings.lock().unwrap();
-        *state_settings = settings.clone();
+        *state_settings = merged;
         state_settings.save().map_err(|e| e.to_string())?;
     }
 
@@ -126,6 +133,56 @@ pub async fn save_settings(
     Ok(SaveSettingsResponse { needs_restart })
 }
 
+/// Names of the available microphones
+#[tauri::command]
+pub fn list_input_devices() -> Result<Vec<String>, String> {
+    whis_core::audio::input_devices().map_err(|e| e.to_string())
+}
+
+/// Set the microphone to record from (`None` uses the system default)
+#[tauri::command]
+pub fn save_input_device(
+    state: State<'_, AppState>,
+    device: Option<String>,
+) -> Result<(), String> {
+    update_settings(&state, |settings| settings.input_device = device)
+}
+
+/// Create or replace a profile
+#[tauri::command]
+pub fn save_profile(
+    state: State<'_, AppState>,
+    name: String,
+    profile: Profile,
+) -> Result<(), String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Profile name is required".to_string());
+    }
+    update_settings(&state, |settings| {
+        settings.profiles.insert(name, profile);
+    })
+}
+
+#[tauri::command]
+pub fn delete_profile(state: State<'_, AppState>, name: String) -> Result<(), String> {
+    update_settings(&state, |settings| {
+        settings.profiles.remove(&name);
+    })
+}
+
+/// Change settings on disk (keeping edits made elsewhere) and in the app state
+fn update_settings(
+    state: &AppState,
+    change: impl FnOnce(&mut Settings),
+) -> Result<(), String> {
+    let mut settings = Settings::load();
+    change(&mut settings);
+    settings.save().map_err(|e| e.to_string())?;
+    *state.settings.lock().unwrap() = settings;
+    Ok(())
+}
+
 #[tauri::command]
 pub fn validate_api_key(api_key: String) -> Result<bool, String> {
     // Validate format: OpenAI keys start with "sk-"
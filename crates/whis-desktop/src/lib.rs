@@ -11,6 +11,9 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_process::init())
         .setup(|app| {
+            // Remove audio and text left in the temp dir by a crashed run
+            whis_core::temp::sweep();
+
             // Load settings from disk
             let loaded_settings = settings::Settings::load();
 
@@ -49,6 +52,10 @@ pub fn run() {
             commands::configure_shortcut_with_trigger,
             commands::portal_shortcut,
             commands::validate_api_key,
+            commands::list_input_devices,
+            commands::save_input_device,
+            commands::save_profile,
+            commands::delete_profile,
             commands::reset_shortcut,
             commands::portal_bind_error,
             commands::get_toggle_command,
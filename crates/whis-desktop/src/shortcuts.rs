@@ -169,13 +169,12 @@ pub fn read_portal_shortcut_from_dconf() -> Option<String> {
     for line in dump.lines() {
         if line.contains("toggle-recording") && line.contains("shortcuts") {
             // Parse the GVariant format: <['<Control><Alt>m']>
-            if let Some(start) = line.find("<['") {
-                if let Some(end) = line[start..].find("']>") {
+            if let Some(start) = line.find("<['")
+                && let Some(end) = line[start..].find("']>") {
                     let raw = &line[start + 3..start + end];
                     // Convert <Control><Alt>m to Ctrl+Alt+M
                     return Some(convert_gvariant_shortcut(raw));
                 }
-            }
         }
     }
     None
@@ -228,8 +227,8 @@ where
     let session = shortcuts.create_session().await?;
 
     // Check for existing shortcuts first
-    if let Ok(list_request) = shortcuts.list_shortcuts(&session).await {
-        if let Ok(list_response) = list_request.response() {
+    if let Ok(list_request) = shortcuts.list_shortcuts(&session).await
+        && let Ok(list_response) = list_request.response() {
             let existing = list_response.shortcuts();
             if let Some(s) = existing.iter().find(|s| s.id() == "toggle-recording") {
                 let trigger = s.trigger_description().to_string();
@@ -247,7 +246,6 @@ where
                 return Ok(());
             }
         }
-    }
 
     // Define the toggle-recording shortcut
     let shortcut = NewShortcut::new("toggle-recording", "Toggle voice recording")
@@ -333,8 +331,8 @@ pub async fn bind_shortcut_with_trigger(
     let session = shortcuts.create_session().await?;
 
     // Check for existing shortcuts first (XDG spec: can only bind once per session)
-    if let Ok(list_request) = shortcuts.list_shortcuts(&session).await {
-        if let Ok(list_response) = list_request.response() {
+    if let Ok(list_request) = shortcuts.list_shortcuts(&session).await
+        && let Ok(list_response) = list_request.response() {
             let existing = list_response.shortcuts();
             if !existing.is_empty() {
                 println!("Found {} existing shortcut(s) in session", existing.len());
@@ -347,7 +345,6 @@ pub async fn bind_shortcut_with_trigger(
                 }
             }
         }
-    }
 
     // Bind our shortcut ID with optional preferred trigger
     let mut shortcut = NewShortcut::new("toggle-recording", "Toggle voice recording");
@@ -380,8 +377,8 @@ pub async fn bind_shortcut_with_trigger(
                         let _ = shortcuts.configure_shortcuts(&session, None, None).await;
 
                         // Re-query after configure in case user changed it
-                        if let Ok(list_request) = shortcuts.list_shortcuts(&session).await {
-                            if let Ok(list_response) = list_request.response() {
+                        if let Ok(list_request) = shortcuts.list_shortcuts(&session).await
+                            && let Ok(list_response) = list_request.response() {
                                 let updated_trigger = list_response
                                     .shortcuts()
                                     .iter()
@@ -395,7 +392,6 @@ pub async fn bind_shortcut_with_trigger(
                                     return Ok(updated_trigger);
                                 }
                             }
-                        }
                     }
 
                     // Update AppState with bound trigger
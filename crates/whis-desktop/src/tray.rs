@@ -6,8 +6,9 @@ use tauri::{
     AppHandle, Manager, WebviewWindowBuilder, WebviewUrl,
 };
 use whis_core::{
-    copy_to_clipboard, parallel_transcribe, transcribe_audio, AudioRecorder, RecordingOutput, ApiConfig,
+    app_rules, copy_to_clipboard, focus, history, language, parallel_transcribe_partial, parallel_transcribe_switching, postprocess, quality, sinks, stats, summarize, template, transcribe_audio, type_text, usage, voice_command, AudioRecorder, OutputMode, RecordingOutput, ApiConfig,
 };
+use whis_core::metadata::Metadata;
 
 // Static icons for each state (pre-loaded at compile time)
 const ICON_IDLE: &[u8] = include_bytes!("../icons/icon-idle.png");
@@ -65,14 +66,13 @@ pub fn setup_tray(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
         })
         .on_tray_icon_event(|tray, event| {
             use tauri::tray::TrayIconEvent;
-            if let TrayIconEvent::Click { button, .. } = event {
-                if button == tauri::tray::MouseButton::Left {
+            if let TrayIconEvent::Click { button, .. } = event
+                && button == tauri::tray::MouseButton::Left {
                     let app_handle = tray.app_handle().clone();
                     tauri::async_runtime::spawn(async move {
                         toggle_recording(app_handle);
                     });
                 }
-            }
         })
         .build(app)?;
 
@@ -144,22 +144,21 @@ fn start_recording_sync(app: &AppHandle, state: &AppState) -> Result<(), String>
         let mut config_guard = state.api_config.lock().unwrap();
         if config_guard.is_none() {
             // Try settings first, then environment variable
-            let api_key = {
-                let settings = state.settings.lock().unwrap();
-                settings.openai_api_key.clone()
-            }
-            .or_else(|| std::env::var("OPENAI_API_KEY").ok());
-
-            let api_key = api_key.ok_or(
-                "No API key configured. Add it in Settings > API Keys.",
-            )?;
+            let config = ApiConfig::from_settings(&state.settings.lock().unwrap())
+                .map_err(|_| "No API key configured. Add it in Settings > API Keys.")?;
 
-            *config_guard = Some(ApiConfig { openai_api_key: api_key });
+            *config_guard = Some(config);
         }
     }
 
     // Start recording
     let mut recorder = AudioRecorder::new().map_err(|e| e.to_string())?;
+    {
+        let settings = state.settings.lock().unwrap();
+        recorder.set_spill_threshold_mb(settings.spill_limit_mb());
+        recorder.set_device(settings.input_device.clone());
+        recorder.set_mono(settings.mono);
+    }
     recorder.start_recording().map_err(|e| e.to_string())?;
 
     *state.recorder.lock().unwrap() = Some(recorder);
@@ -190,43 +189,166 @@ async fn stop_and_transcribe(app: &AppHandle) -> Result<(), String> {
         .take()
         .ok_or("No active recording")?;
 
-    let api_key = state
+    let config = state
         .api_config
         .lock()
         .unwrap()
-        .as_ref()
-        .ok_or("API config not loaded")?
-        .openai_api_key
-        .clone();
+        .clone()
+        .ok_or("API config not loaded")?;
+    let provider = config.provider();
 
     // Finalize recording (synchronous file encoding)
     // Note: AudioRecorder might need to be Send to be moved into async block?
     // It is likely Send since it's in a Mutex.
-    let audio_result = recorder.finalize_recording().map_err(|e| e.to_string())?;
+    let recording = recorder.stop_recording().map_err(|e| e.to_string())?;
+    let duration_secs = recording.duration_secs();
+    let audio = recording.stats();
+    // Output rules depend on the application focused when the recording stops
+    let window = focus::focused_window();
+    let settings = app_rules::resolve(
+        &state.settings.lock().unwrap(),
+        window.as_ref().map(|w| w.app.as_str()),
+    );
+    let chunking = settings.chunking;
+    let encoding = settings.encoding().negotiate(provider.as_ref());
+    let codec = encoding.codec;
+    let audio_result = recording.finalize(&chunking, &encoding).map_err(|e| e.to_string())?;
 
     // Transcribe
     let transcription = match audio_result {
         // transcribe_audio is synchronous (blocking HTTP), so we should wrap it in spawn_blocking
         // to avoid blocking the async runtime
         RecordingOutput::Single(data) => {
-            let api_key = api_key.clone();
+            let provider = provider.clone();
             tauri::async_runtime::spawn_blocking(move || {
-                transcribe_audio(&api_key, data)
+                transcribe_audio(provider.as_ref(), data, codec)
             })
             .await
             .map_err(|e| e.to_string())?
             .map_err(|e| e.to_string())?
         },
         RecordingOutput::Chunked(chunks) => {
-            // parallel_transcribe is async, so we can await it directly
-            parallel_transcribe(&api_key, chunks, None)
-                .await
-                .map_err(|e| e.to_string())?
+            // parallel_transcribe_partial is async, so we can await it directly
+            let partial = if settings.language_hotword {
+                parallel_transcribe_switching(&config, chunks, None, None).await
+            } else {
+                parallel_transcribe_partial(provider.clone(), chunks, None, None).await
+            }
+            .map_err(|e| e.to_string())?;
+            if settings.partial_results {
+                for chunk in &partial.failed {
+                    eprintln!("Chunk {} failed: {}", chunk.index, chunk.error);
+                }
+                partial.transcription
+            } else {
+                partial.require_complete().map_err(|e| e.to_string())?
+            }
         }
     };
+    let model = transcription.usage_model(provider.as_ref()).to_string();
+    let transcription = if settings.language_hotword {
+        language::apply_switch(transcription)
+    } else {
+        transcription
+    };
+    if let Some(report) = quality::report(
+        &transcription,
+        settings.drop_silent_segments,
+        settings.privacy,
+    ) {
+        eprintln!("{report}");
+    }
+    let settings = language::resolve(&settings, transcription.language.as_deref());
+    let metadata = Metadata {
+        model: Some(model.clone()),
+        audio,
+        ..Metadata::capture(window, &settings)
+    };
+    let transcription = postprocess::to_text(transcription, &settings);
+
+    // Apply local post-processing (dictation commands, etc.) and the output template
+    let transcription = postprocess::process(&transcription, &settings);
+    let transcription = if settings.summarize {
+        // Blocking HTTP, which panics on the async runtime
+        let (text, summary_settings) = (transcription.clone(), settings.clone());
+        let summary = tauri::async_runtime::spawn_blocking(move || {
+            summarize::summarize(&text, &summary_settings)
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+        match summary {
+            Ok(summary) => summary,
+            Err(e) => {
+                eprintln!("Summary error: {e:#}");
+                transcription
+            }
+        }
+    } else {
+        transcription
+    };
+    let transcription = template::apply(&settings, &transcription, duration_secs);
+
+    match settings.output_mode {
+        OutputMode::Clipboard => {
+            copy_to_clipboard(
+                &transcription,
+                settings.clipboard_selection,
+                settings.clipboard_backend,
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        OutputMode::Command => {
+            // The tray can't ask, so commands marked `confirm` are declined
+            let text = transcription.clone();
+            let commands = settings.voice_commands.clone();
+            let outcome = tauri::async_runtime::spawn_blocking(move || {
+                voice_command::run_matching(&text, &commands, |_| false)
+            })
+            .await
+            .map_err(|e| e.to_string())?;
+            match outcome {
+                Ok(voice_command::Outcome::Declined) => {
+                    eprintln!("Voice command needs confirmation; run it from the terminal");
+                }
+                Ok(outcome) => println!("{outcome}"),
+                Err(e) => eprintln!("Voice command error: {e:#}"),
+            }
+        }
+        OutputMode::Type => {
+            type_text(&transcription, &settings.typing).map_err(|e| e.to_string())?;
+        }
+        // Left to the sinks and the history
+        OutputMode::None => {}
+    }
 
-    // Copy to clipboard
-    copy_to_clipboard(&transcription).map_err(|e| e.to_string())?;
+    // Deliver to additional sinks (webhook, etc.) and record in history
+    let privacy = settings.privacy;
+    let payload = sinks::Payload {
+        metadata,
+        ..sinks::Payload::new(transcription.clone(), duration_secs)
+    };
+    let errors = tauri::async_runtime::spawn_blocking(move || {
+        let mut errors = sinks::dispatch(&settings, &payload);
+        if !settings.privacy
+            && let Err(e) = history::append(&payload, settings.encrypt_history)
+        {
+            errors.push(e);
+        }
+        if let Err(e) = stats::record(&payload) {
+            errors.push(e);
+        }
+        match usage::track(&settings, &model, payload.duration_secs) {
+            Ok(Some(warning)) => eprintln!("{warning}"),
+            Ok(None) => {}
+            Err(e) => errors.push(e),
+        }
+        errors
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    for e in errors {
+        eprintln!("Output error: {e:#}");
+    }
 
     // Reset state
     {
@@ -234,14 +356,25 @@ async fn stop_and_transcribe(app: &AppHandle) -> Result<(), String> {
     }
     update_tray(app, RecordingState::Idle);
 
-    println!("Done: {}", &transcription[..transcription.len().min(50)]);
+    if privacy {
+        println!("Done ({} chars)", transcription.chars().count());
+    } else {
+        println!("Done: {}", transcription.chars().take(50).collect::<String>());
+    }
 
     Ok(())
 }
 
 fn update_tray(app: &AppHandle, new_state: RecordingState) {
-    // Update menu item text using stored reference
     let app_state = app.state::<AppState>();
+    let hook_state = match new_state {
+        RecordingState::Idle => "idle",
+        RecordingState::Recording => "recording",
+        RecordingState::Transcribing => "transcribing",
+    };
+    whis_core::hooks::state_changed(&app_state.settings.lock().unwrap(), hook_state);
+
+    // Update menu item text using stored reference
     if let Some(ref menu_item) = *app_state.record_menu_item.lock().unwrap() {
         let text = match new_state {
             RecordingState::Idle => "Start Recording",